@@ -0,0 +1,62 @@
+//! Compares finding the patterns matching an event name with a `PatternTrie` and by testing every pattern.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod bench {
+    use criterion::{black_box, BenchmarkId, Criterion};
+    use emitter_rs::id::ListenerId;
+    use emitter_rs::pattern::{self, PatternTrie};
+
+    /// Hierarchical patterns spread over 50 services, a tenth of them with a trailing `**`.
+    fn patterns(count: u64) -> Vec<(ListenerId, String)> {
+        (0..count)
+            .map(|index| {
+                let pattern = if index % 10 == 0 {
+                    format!("service{}.**", index % 50)
+                } else {
+                    format!("service{}.entity{}.*", index % 50, index)
+                };
+                (ListenerId(index), pattern)
+            })
+            .collect()
+    }
+
+    pub fn matching(c: &mut Criterion) {
+        let mut group = c.benchmark_group("pattern matching");
+        let event = "service7.entity107.updated";
+
+        for count in [100, 1000, 10000] {
+            let patterns = patterns(count);
+            let mut trie = PatternTrie::new();
+            for (id, pattern) in &patterns {
+                trie.insert(pattern, *id);
+            }
+
+            group.bench_with_input(BenchmarkId::new("trie", count), &trie, |b, trie| {
+                b.iter(|| trie.matches(black_box(event)))
+            });
+            group.bench_with_input(
+                BenchmarkId::new("linear", count),
+                &patterns,
+                |b, patterns| {
+                    b.iter(|| {
+                        patterns
+                            .iter()
+                            .filter(|(_, pattern)| pattern::matches(pattern, black_box(event)))
+                            .map(|(id, _)| *id)
+                            .collect::<Vec<_>>()
+                    })
+                },
+            );
+        }
+
+        group.finish();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+criterion::criterion_group!(benches, bench::matching);
+#[cfg(not(target_arch = "wasm32"))]
+criterion::criterion_main!(benches);
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
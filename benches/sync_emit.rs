@@ -0,0 +1,134 @@
+//! Compares `sync_emit` on the full `EventEmitter`, on the minimal `FastEmitter` and on the path `sync_emit`
+//! took before the emitter grew its optional features, and measures the cost of the payload depth limit.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod bench {
+    use criterion::{black_box, Criterion};
+    use emitter_rs::event_emitter::Callback;
+    use emitter_rs::fast::FastEmitter;
+    use emitter_rs::EventEmitter;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    /// A payload serialized to about 100 bytes of JSON.
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Order {
+        id: u64,
+        customer: String,
+        items: Vec<String>,
+        total: f64,
+    }
+
+    fn order() -> Order {
+        Order {
+            id: 4_294_967_296,
+            customer: "Ada Lovelace".to_string(),
+            items: vec![
+                "keyboard".to_string(),
+                "mouse".to_string(),
+                "monitor".to_string(),
+            ],
+            total: 1234.5,
+        }
+    }
+
+    /// The listeners of an emitter without any optional feature, emitting like `sync_emit` used to: serialize
+    /// once, then hand each callback its own copy of the bytes.
+    #[derive(Default)]
+    struct Baseline {
+        listeners: HashMap<String, Vec<Callback>>,
+    }
+
+    impl Baseline {
+        fn on<F, T>(&mut self, event: &str, callback: F)
+        where
+            for<'de> T: Deserialize<'de>,
+            F: Fn(T) + 'static + Sync + Send,
+        {
+            self.listeners
+                .entry(event.to_string())
+                .or_default()
+                .push(Arc::new(move |bytes: Vec<u8>| {
+                    if let Ok(value) = serde_json::from_slice(&bytes) {
+                        callback(value);
+                    }
+                }));
+        }
+
+        fn sync_emit<T>(&self, event: &str, value: T)
+        where
+            T: Serialize,
+        {
+            if let Some(listeners) = self.listeners.get(event) {
+                let bytes = serde_json::to_vec(&value).unwrap();
+
+                for listener in listeners {
+                    let callback = Arc::clone(listener);
+                    callback(bytes.clone());
+                }
+            }
+        }
+    }
+
+    pub fn sync_emit(c: &mut Criterion) {
+        let mut group = c.benchmark_group("sync_emit 4 listeners");
+
+        let mut event_emitter = EventEmitter::new();
+        for _ in 0..4 {
+            event_emitter.on("order.placed", |order: Order| {
+                black_box(order);
+            });
+        }
+        group.bench_function("EventEmitter", |b| {
+            b.iter(|| event_emitter.sync_emit("order.placed", black_box(order())))
+        });
+
+        let mut fast_emitter = FastEmitter::new();
+        for _ in 0..4 {
+            fast_emitter.on("order.placed", |order: Order| {
+                black_box(order);
+            });
+        }
+        group.bench_function("FastEmitter", |b| {
+            b.iter(|| fast_emitter.sync_emit("order.placed", black_box(order())))
+        });
+
+        let mut baseline = Baseline::default();
+        for _ in 0..4 {
+            baseline.on("order.placed", |order: Order| {
+                black_box(order);
+            });
+        }
+        group.bench_function("Baseline", |b| {
+            b.iter(|| baseline.sync_emit("order.placed", black_box(order())))
+        });
+
+        group.finish();
+    }
+
+    pub fn max_json_depth(c: &mut Criterion) {
+        let mut group = c.benchmark_group("sync_emit max_json_depth");
+
+        for limit in [None, Some(64)] {
+            let mut event_emitter = EventEmitter::new();
+            event_emitter.set_max_json_depth(limit);
+            event_emitter.on("order.placed", |order: Order| {
+                black_box(order);
+            });
+            group.bench_function(format!("{:?}", limit), |b| {
+                b.iter(|| event_emitter.sync_emit("order.placed", black_box(order())))
+            });
+        }
+
+        group.finish();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+criterion::criterion_group!(benches, bench::sync_emit, bench::max_json_depth);
+#[cfg(not(target_arch = "wasm32"))]
+criterion::criterion_main!(benches);
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,221 @@
+//! A channel for values that cannot be serialized, such as open sockets, oneshot senders or trait objects.
+//!
+//! `emit_any` moves a boxed value to a single listener added with `on_any` for its concrete type, without
+//! serializing it. The listeners of the channel share event names with the serde listeners but never receive
+//! their emits, and the other way around. They run like serde listeners: pooled on their own thread (or as
+//! scheduled on wasm) unless inline, honoring their limit, and removed with `remove_listener`.
+
+use crate::event_emitter::{report_emit_error, Execution, ListenerOptions};
+use crate::hooks::DecodeError;
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+/// A callback receiving a boxed value known to hold the type its listener expects.
+type AnyCallback = Arc<dyn Fn(Box<dyn Any + Send>) + Sync + Send + 'static>;
+
+/// A listener added with `on_any`, expecting values of a single concrete type.
+pub(crate) struct AnyListener {
+    id: ListenerId,
+    limit: Option<u64>,
+    execution: Execution,
+    expects: TypeId,
+    type_name: &'static str,
+    callback: AnyCallback,
+}
+
+impl EventEmitter {
+    /// Adds a listener receiving the values of type `T` emitted with `emit_any`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute with the value.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener, to be used with `remove_listener`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::sync::mpsc;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_any("reply.channel", |sender: mpsc::Sender<u32>| {
+    ///     sender.send(42).unwrap();
+    /// });
+    ///
+    /// let (sender, receiver) = mpsc::channel::<u32>();
+    /// assert!(event_emitter.emit_any("reply.channel", Box::new(sender)));
+    /// assert_eq!(42, receiver.recv().unwrap());
+    /// ```
+    pub fn on_any<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        T: Send + 'static,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        self.on_any_with_options(event, ListenerOptions::default(), callback)
+    }
+
+    /// Adds a listener receiving a single value of type `T` emitted with `emit_any`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute with the value.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    pub fn once_any<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        T: Send + 'static,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let options = ListenerOptions {
+            limit: Some(1),
+            ..ListenerOptions::default()
+        };
+        self.on_any_with_options(event, options, callback)
+    }
+
+    /// Adds a listener receiving the values of type `T` emitted with `emit_any`, with the given options.
+    ///
+    /// The execution and limit apply as for `on_with_options`. Tags do not, since `emit_any` carries none.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `options` - Where the callback runs and how many times.
+    /// * `callback` - The callback function to execute with the value.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    pub fn on_any_with_options<F, T>(
+        &mut self,
+        event: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> ListenerId
+    where
+        T: Send + 'static,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        if self.refuses_listeners(event) {
+            return id;
+        }
+        let callback: AnyCallback = Arc::new(move |value: Box<dyn Any + Send>| {
+            if let Ok(value) = value.downcast::<T>() {
+                callback(*value);
+            }
+        });
+
+        self.any_listeners
+            .entry(event.to_string())
+            .or_default()
+            .push(AnyListener {
+                id,
+                limit: options.limit,
+                execution: options.execution,
+                expects: TypeId::of::<T>(),
+                type_name: std::any::type_name::<T>(),
+                callback,
+            });
+        id
+    }
+
+    /// Moves a value to the first listener added with `on_any` for its concrete type, without serializing it.
+    ///
+    /// Listeners are tried in registration order. Those expecting another type are skipped, without counting
+    /// toward their limit, and reported to the decode error hooks. The value is delivered at most once, however
+    /// many listeners expect its type; listeners added with `on` never receive it. Types must match exactly,
+    /// so a `Sender<i32>` inferred from an integer literal never reaches a listener of `Sender<u32>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to move to the listener.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a listener received the value, `false` if it was dropped.
+    pub fn emit_any(&mut self, event: &str, value: Box<dyn Any + Send>) -> bool {
+        match self.admit_emit(event) {
+            Ok(true) => {}
+            Ok(false) => return false,
+            Err(e) => {
+                report_emit_error(event, &e);
+                return false;
+            }
+        }
+        let Some(listeners) = self.any_listeners.get_mut(event) else {
+            return false;
+        };
+
+        let mut mismatches = Vec::new();
+        let mut job = None;
+        for listener in listeners.iter_mut() {
+            if listener.limit == Some(0) {
+                continue;
+            }
+
+            if (*value).type_id() != listener.expects {
+                mismatches.push((listener.id, listener.type_name));
+                continue;
+            }
+
+            if let Some(limit) = listener.limit.as_mut() {
+                *limit -= 1;
+            }
+            job = Some((
+                listener.id,
+                listener.execution,
+                Arc::clone(&listener.callback),
+            ));
+            break;
+        }
+
+        listeners.retain(|listener| listener.limit != Some(0));
+        if listeners.is_empty() {
+            self.any_listeners.remove(event);
+        }
+        for (listener_id, type_name) in mismatches {
+            self.hooks.decode_errors.report(&DecodeError {
+                event: event.to_string(),
+                listener_id,
+                message: format!("the value is not a {}", type_name),
+            });
+        }
+
+        let Some((id, execution, callback)) = job else {
+            return false;
+        };
+        self.run_job(event, id, execution, Box::new(move || callback(value)));
+        true
+    }
+
+    /// Removes the listener added with `on_any` with the given ID.
+    pub(crate) fn remove_any(&mut self, id: ListenerId) -> bool {
+        let Some((event, index)) = self.any_listeners.iter().find_map(|(event, listeners)| {
+            listeners
+                .iter()
+                .position(|listener| listener.id == id)
+                .map(|index| (event.clone(), index))
+        }) else {
+            return false;
+        };
+
+        if let Some(listeners) = self.any_listeners.get_mut(&event) {
+            listeners.remove(index);
+            if listeners.is_empty() {
+                self.any_listeners.remove(&event);
+            }
+        }
+        true
+    }
+}
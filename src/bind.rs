@@ -0,0 +1,246 @@
+//! Listeners bound to the methods of a shared service, without a bespoke closure per registration.
+//!
+//! `bind_method` stores the service and a method pointer in the listener. A weak binding holds a `Weak`
+//! reference and removes its listener on the first emit after the service is dropped, as a `ListenerRemover`
+//! would; a strong binding keeps the service alive as long as the listener. `binder` wires several methods of
+//! the same service at once and returns their listeners as a `ListenerGroup`.
+
+use crate::event_emitter::Listener;
+use crate::id::ListenerId;
+use crate::removal::ListenerRemover;
+use crate::EventEmitter;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// How a listener bound with `bind_method_with` references its service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Binding {
+    /// The listener holds a `Weak` reference and removes itself once the service is dropped.
+    #[default]
+    Weak,
+    /// The listener holds an `Arc`, keeping the service alive until the listener is removed.
+    Strong,
+}
+
+/// The listeners wired by a `Binder`, removable together.
+#[derive(Debug, Clone, Default)]
+pub struct ListenerGroup {
+    removers: Vec<ListenerRemover>,
+}
+
+impl ListenerGroup {
+    /// Returns the IDs of the listeners of the group, in the order they were bound.
+    pub fn ids(&self) -> Vec<ListenerId> {
+        self.removers.iter().map(ListenerRemover::id).collect()
+    }
+
+    /// Marks every listener of the group as removed, like `ListenerRemover::remove`.
+    ///
+    /// # Returns
+    ///
+    /// The number of listeners that were live.
+    pub fn remove(&self) -> usize {
+        self.removers
+            .iter()
+            .filter(|remover| remover.remove())
+            .count()
+    }
+}
+
+/// A builder binding several methods of a service to events, returned by `EventEmitter::binder`.
+pub struct Binder<'a, S> {
+    emitter: &'a mut EventEmitter,
+    service: Arc<S>,
+    binding: Binding,
+    ids: Vec<ListenerId>,
+}
+
+impl<S> Binder<'_, S>
+where
+    S: Send + Sync + 'static,
+{
+    /// Makes the methods bound from now on hold a strong reference to the service.
+    pub fn strong(mut self) -> Self {
+        self.binding = Binding::Strong;
+        self
+    }
+
+    /// Binds a method of the service to an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `method` - The method to call on the service with each emitted value.
+    pub fn on<T>(mut self, event: &str, method: fn(&S, T)) -> Self
+    where
+        for<'de> T: Deserialize<'de>,
+        T: 'static,
+    {
+        let id = self
+            .emitter
+            .bind_method_with(event, &self.service, self.binding, method);
+        self.ids.push(id);
+        self
+    }
+
+    /// Returns the IDs of the bound listeners, in the order they were bound.
+    pub fn ids(self) -> Vec<ListenerId> {
+        self.ids
+    }
+
+    /// Returns the bound listeners as a group, to remove them together.
+    pub fn group(self) -> ListenerGroup {
+        ListenerGroup {
+            removers: self
+                .ids
+                .iter()
+                .filter_map(|id| self.emitter.remover(*id))
+                .collect(),
+        }
+    }
+}
+
+impl EventEmitter {
+    /// Binds a method of a shared service to an event, holding the service weakly.
+    ///
+    /// The listener removes itself on the first emit after the service is dropped, without calling the method.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `service` - The service the method is called on.
+    /// * `method` - The method to call with each emitted value.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::sync::Arc;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// struct Mailer;
+    ///
+    /// impl Mailer {
+    ///     fn welcome(&self, name: String) {
+    ///         println!("Welcome, {}!", name);
+    ///     }
+    /// }
+    ///
+    /// let mailer = Arc::new(Mailer);
+    /// event_emitter.bind_method("user.created", &mailer, Mailer::welcome);
+    /// event_emitter.sync_emit("user.created", "ada".to_string());
+    /// ```
+    pub fn bind_method<S, T>(
+        &mut self,
+        event: &str,
+        service: &Arc<S>,
+        method: fn(&S, T),
+    ) -> ListenerId
+    where
+        S: Send + Sync + 'static,
+        for<'de> T: Deserialize<'de>,
+        T: 'static,
+    {
+        self.bind_method_with(event, service, Binding::Weak, method)
+    }
+
+    /// Binds a method of a shared service to an event, referencing the service as `binding` says.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `service` - The service the method is called on.
+    /// * `binding` - Whether the listener holds the service weakly or keeps it alive.
+    /// * `method` - The method to call with each emitted value.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    pub fn bind_method_with<S, T>(
+        &mut self,
+        event: &str,
+        service: &Arc<S>,
+        binding: Binding,
+        method: fn(&S, T),
+    ) -> ListenerId
+    where
+        S: Send + Sync + 'static,
+        for<'de> T: Deserialize<'de>,
+        T: 'static,
+    {
+        let id = self.ids.next_id();
+        let removed = Arc::new(AtomicBool::new(false));
+        let callback = match binding {
+            Binding::Weak => {
+                let service = Arc::downgrade(service);
+                let removed = Arc::clone(&removed);
+                self.decoding_callback(event, id, move |value: T| match service.upgrade() {
+                    Some(service) => method(&service, value),
+                    None => removed.store(true, Ordering::Release),
+                })
+            }
+            Binding::Strong => {
+                let service = Arc::clone(service);
+                self.decoding_callback(event, id, move |value: T| method(&service, value))
+            }
+        };
+
+        let mut listener = Listener::new(id, None, callback);
+        listener.removed = removed;
+        self.add_listener(event, listener)
+    }
+
+    /// Returns a builder binding several methods of `service` to events at once, holding it weakly unless
+    /// `Binder::strong` is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `service` - The service the methods are called on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::sync::Arc;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// struct Audit;
+    ///
+    /// impl Audit {
+    ///     fn created(&self, name: String) {
+    ///         println!("Created {}", name);
+    ///     }
+    ///
+    ///     fn deleted(&self, name: String) {
+    ///         println!("Deleted {}", name);
+    ///     }
+    /// }
+    ///
+    /// let audit = Arc::new(Audit);
+    /// let group = event_emitter
+    ///     .binder(&audit)
+    ///     .on("user.created", Audit::created)
+    ///     .on("user.deleted", Audit::deleted)
+    ///     .group();
+    /// assert_eq!(2, group.ids().len());
+    ///
+    /// group.remove();
+    /// assert_eq!(0, event_emitter.listener_count("user.created"));
+    /// ```
+    pub fn binder<S>(&mut self, service: &Arc<S>) -> Binder<'_, S>
+    where
+        S: Send + Sync + 'static,
+    {
+        Binder {
+            emitter: self,
+            service: Arc::clone(service),
+            binding: Binding::Weak,
+            ids: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Bridges forwarding the values received on a channel as emits of a shared emitter.
+//!
+//! Each bridge runs on its own thread, which locks the emitter for every value it forwards. The thread stops
+//! once the channel is disconnected and drained, or when its `BridgeHandle` is stopped or dropped. Values that
+//! cannot be emitted are reported like other emit errors, and the bridge keeps forwarding. While it forwards,
+//! a bridge is listed by `EventEmitter::topology` as an edge from its channel to its event.
+
+use crate::event_emitter::report_emit_error;
+use crate::topology::{EdgeKind, EventEdge};
+use crate::EventEmitter;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+/// How often a bridge waiting for a value checks whether it was stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The outcome of waiting for a value on the channel of a bridge.
+enum Received<T> {
+    Value(T),
+    Timeout,
+    Disconnected,
+}
+
+/// A handle on the thread of a bridge, stopping and joining it when dropped.
+#[derive(Debug)]
+pub struct BridgeHandle {
+    stopped: Arc<AtomicBool>,
+    /// Cleared once the bridge no longer forwards, to drop its edge from the topology.
+    live: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl BridgeHandle {
+    /// Stops forwarding and waits for the bridge thread to finish.
+    ///
+    /// A value being emitted is delivered first; values still in the channel are not forwarded. Calling
+    /// `stop` again has no effect.
+    pub fn stop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            if let Err(e) = thread.join() {
+                eprintln!("Thread error: {:?}", e);
+            }
+        }
+        self.live.store(false, Ordering::Release);
+    }
+
+    /// Returns whether the bridge no longer forwards values, because it was stopped or its channel disconnected.
+    pub fn is_finished(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_none_or(thread::JoinHandle::is_finished)
+    }
+}
+
+impl Drop for BridgeHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Spawns the thread forwarding the values returned by `receive` as emits of `event`.
+fn spawn_bridge<T, R>(
+    emitter: &Arc<Mutex<EventEmitter>>,
+    event: &str,
+    mut receive: R,
+) -> BridgeHandle
+where
+    T: Serialize,
+    R: FnMut(Duration) -> Received<T> + Send + 'static,
+{
+    let live = {
+        let mut emitter = emitter.lock().unwrap_or_else(PoisonError::into_inner);
+        let channel = format!("channel {}", emitter.ids.next_id());
+        emitter.add_edge(EventEdge {
+            from: channel,
+            to: event.to_string(),
+            kind: EdgeKind::Bridge,
+        })
+    };
+    let cloned_live = Arc::clone(&live);
+    let emitter = Arc::clone(emitter);
+    let event = event.to_string();
+    let stopped = Arc::new(AtomicBool::new(false));
+    let cloned_stopped = Arc::clone(&stopped);
+
+    let spawned = thread::Builder::new()
+        .name(format!("emit-bridge-{}", event))
+        .spawn(move || {
+            while !cloned_stopped.load(Ordering::Acquire) {
+                match receive(POLL_INTERVAL) {
+                    Received::Value(value) => {
+                        let mut emitter = emitter.lock().unwrap_or_else(PoisonError::into_inner);
+                        if let Err(e) = emitter.try_emit(&event, value) {
+                            report_emit_error(&event, &e);
+                        }
+                    }
+                    Received::Timeout => {}
+                    Received::Disconnected => break,
+                }
+            }
+            cloned_live.store(false, Ordering::Release);
+        });
+
+    let thread = match spawned {
+        Ok(thread) => Some(thread),
+        Err(e) => {
+            eprintln!("Failed to spawn bridge thread: {}", e);
+            live.store(false, Ordering::Release);
+            None
+        }
+    };
+    BridgeHandle {
+        stopped,
+        live,
+        thread,
+    }
+}
+
+impl EventEmitter {
+    /// Forwards every value received on a channel as an emit of `event` on a shared emitter, from a managed thread.
+    ///
+    /// Values are emitted like `emit`, in the order they are received. The bridge stops once every sender is
+    /// dropped and the channel drained, or when the returned handle is stopped or dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `emitter` - The emitter to emit on, shared with the bridge thread.
+    /// * `event` - The name of the event to emit.
+    /// * `receiver` - The channel whose values are forwarded.
+    ///
+    /// # Returns
+    ///
+    /// The handle stopping the bridge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::sync::mpsc;
+    /// use std::sync::{Arc, Mutex};
+    /// let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    /// event_emitter.lock().unwrap().on("job.done", |job: u32| println!("Job {} done", job));
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let bridge = EventEmitter::attach_receiver(&event_emitter, "job.done", receiver);
+    /// sender.send(7_u32).unwrap();
+    /// drop(sender);
+    /// drop(bridge);
+    /// ```
+    pub fn attach_receiver<T>(
+        emitter: &Arc<Mutex<Self>>,
+        event: &str,
+        receiver: mpsc::Receiver<T>,
+    ) -> BridgeHandle
+    where
+        T: Serialize + Send + 'static,
+    {
+        spawn_bridge(emitter, event, move |timeout| {
+            match receiver.recv_timeout(timeout) {
+                Ok(value) => Received::Value(value),
+                Err(mpsc::RecvTimeoutError::Timeout) => Received::Timeout,
+                Err(mpsc::RecvTimeoutError::Disconnected) => Received::Disconnected,
+            }
+        })
+    }
+
+    /// Forwards every value received on a crossbeam channel as an emit of `event` on a shared emitter, like
+    /// `attach_receiver`.
+    ///
+    /// # Arguments
+    ///
+    /// * `emitter` - The emitter to emit on, shared with the bridge thread.
+    /// * `event` - The name of the event to emit.
+    /// * `receiver` - The channel whose values are forwarded.
+    ///
+    /// # Returns
+    ///
+    /// The handle stopping the bridge.
+    #[cfg(feature = "crossbeam")]
+    pub fn attach_crossbeam_receiver<T>(
+        emitter: &Arc<Mutex<Self>>,
+        event: &str,
+        receiver: crossbeam_channel::Receiver<T>,
+    ) -> BridgeHandle
+    where
+        T: Serialize + Send + 'static,
+    {
+        spawn_bridge(emitter, event, move |timeout| {
+            match receiver.recv_timeout(timeout) {
+                Ok(value) => Received::Value(value),
+                Err(crossbeam_channel::RecvTimeoutError::Timeout) => Received::Timeout,
+                Err(crossbeam_channel::RecvTimeoutError::Disconnected) => Received::Disconnected,
+            }
+        })
+    }
+}
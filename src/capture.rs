@@ -0,0 +1,189 @@
+//! Interception of the emits of an event, for golden tests and custom dispatch.
+//!
+//! While a [`CaptureHandle`] returned by `EventEmitter::capture` is alive, emits of its event are
+//! recorded instead of being delivered to the listeners. Dropping the handle restores normal delivery.
+
+use crate::EventEmitter;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use web_time::Instant;
+
+/// A single emit recorded while its event was captured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedEmit {
+    /// The position of the emit since the capture started, starting from 0.
+    pub sequence: u64,
+    /// The sequence number of the emit among all the emits of the emitter, as returned by `emit`.
+    pub emit_seq: u64,
+    /// When the emit happened.
+    pub timestamp: Instant,
+    /// The serialized value of the emit.
+    pub bytes: Vec<u8>,
+}
+
+impl CapturedEmit {
+    /// Deserializes the recorded value.
+    ///
+    /// # Returns
+    ///
+    /// The recorded value, or the error raised while deserializing it.
+    pub fn value<T>(&self) -> serde_json::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(&self.bytes)
+    }
+}
+
+/// An error returned by `EventEmitter::capture`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureError {
+    /// The event is already captured by a live `CaptureHandle`.
+    AlreadyCaptured(String),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::AlreadyCaptured(event) => write!(f, "{} is already captured", event),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+/// The emits recorded for a captured event.
+#[derive(Default)]
+pub(crate) struct Recorder {
+    next_sequence: u64,
+    emits: Vec<CapturedEmit>,
+}
+
+impl Recorder {
+    pub(crate) fn record(&mut self, bytes: Vec<u8>, emit_seq: u64) {
+        self.emits.push(CapturedEmit {
+            sequence: self.next_sequence,
+            emit_seq,
+            timestamp: Instant::now(),
+            bytes,
+        });
+        self.next_sequence += 1;
+    }
+}
+
+/// Keeps an event captured while alive; dropping it restores normal delivery.
+///
+/// Emits still recorded when the handle is dropped are discarded.
+pub struct CaptureHandle {
+    event: String,
+    recorder: Arc<Mutex<Recorder>>,
+}
+
+impl CaptureHandle {
+    /// Returns the name of the captured event.
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    /// Takes the emits recorded so far, in emit order.
+    ///
+    /// # Returns
+    ///
+    /// The recorded emits, leaving none behind.
+    pub fn drain(&self) -> Vec<CapturedEmit> {
+        std::mem::take(
+            &mut self
+                .recorder
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .emits,
+        )
+    }
+
+    /// Delivers the emits recorded so far to the listeners of the captured event, in emit order.
+    ///
+    /// The event stays captured afterwards. Coalescing settings do not apply to forwarded emits, and `on_seq`
+    /// listeners receive the sequence number of the recorded emit.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_emitter` - The emitter the capture was started on.
+    ///
+    /// # Returns
+    ///
+    /// The number of forwarded emits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("some_event", |value: String| println!("{}", value));
+    ///
+    /// let capture = event_emitter.capture("some_event").unwrap();
+    /// event_emitter.emit("some_event", "Hello, world!".to_string());
+    /// assert_eq!(1, capture.forward(&mut event_emitter));
+    /// ```
+    pub fn forward(&self, event_emitter: &mut EventEmitter) -> usize {
+        let emits = self.drain();
+        let forwarded = emits.len();
+        for emit in emits {
+            event_emitter.current_seq = emit.emit_seq;
+            event_emitter.deliver(&self.event, &[], emit.bytes.into());
+        }
+
+        forwarded
+    }
+}
+
+impl EventEmitter {
+    /// Captures the emits of an event: instead of running its listeners, each emit is recorded in the returned handle.
+    ///
+    /// Captures do not nest: an event can only be captured by one live handle at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to capture.
+    ///
+    /// # Returns
+    ///
+    /// The handle keeping the event captured, or `CaptureError::AlreadyCaptured` if another handle is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let capture = event_emitter.capture("some_event").unwrap();
+    /// event_emitter.emit("some_event", "Hello, world!".to_string());
+    ///
+    /// let emits = capture.drain();
+    /// assert_eq!("Hello, world!", emits[0].value::<String>().unwrap());
+    /// ```
+    pub fn capture(&mut self, event: &str) -> Result<CaptureHandle, CaptureError> {
+        if self.captured(event).is_some() {
+            return Err(CaptureError::AlreadyCaptured(event.to_string()));
+        }
+
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        self.captures
+            .insert(event.to_string(), Arc::downgrade(&recorder));
+
+        Ok(CaptureHandle {
+            event: event.to_string(),
+            recorder,
+        })
+    }
+
+    /// Returns the recorder of the event if a live handle captures it, forgetting captures whose handle was dropped.
+    pub(crate) fn captured(&mut self, event: &str) -> Option<Arc<Mutex<Recorder>>> {
+        let recorder = self.captures.get(event).map(Weak::upgrade)?;
+        if recorder.is_none() {
+            self.captures.remove(event);
+        }
+
+        recorder
+    }
+}
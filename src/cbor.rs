@@ -0,0 +1,146 @@
+//! Canonical CBOR encoding of event values, enabled by the `cbor` feature.
+//!
+//! Values emitted with `EventEmitter::emit_cbor` are encoded deterministically: map keys are sorted
+//! in the canonical order of RFC 8949, so semantically equal values always produce the same bytes,
+//! whatever the insertion order of their maps. This makes the payloads suitable for hashing.
+//!
+//! CBOR payloads can only be decoded by listeners registered with `EventEmitter::on_cbor`. Any other
+//! listener of the same event reports a decode error instead.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::error::EmitError;
+use crate::event_emitter::{report_emit_error, ListenerOptions};
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use ciborium::value::{CanonicalValue, Value};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Encodes `value` as canonical CBOR.
+///
+/// # Arguments
+///
+/// * `value` - The value to encode.
+///
+/// # Returns
+///
+/// The encoded bytes, identical for any two semantically equal values, or `EmitError::Serialize` if the value
+/// cannot be encoded.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::cbor::to_canonical_vec;
+/// use std::collections::HashMap;
+///
+/// let first = HashMap::from([("a", 1), ("b", 2)]);
+/// let second = HashMap::from([("b", 2), ("a", 1)]);
+/// assert_eq!(to_canonical_vec(&first).unwrap(), to_canonical_vec(&second).unwrap());
+/// ```
+pub fn to_canonical_vec<T>(value: &T) -> Result<Vec<u8>, EmitError>
+where
+    T: Serialize,
+{
+    let value = Value::serialized(value).map_err(|e| EmitError::Serialize(e.to_string()))?;
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&canonicalize(value), &mut bytes)
+        .map_err(|e| EmitError::Serialize(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Sorts the keys of every map nested in `value` in canonical order.
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Map(entries) => {
+            let mut entries: Vec<(Value, Value)> = entries
+                .into_iter()
+                .map(|(key, value)| (canonicalize(key), canonicalize(value)))
+                .collect();
+            entries.sort_by_cached_key(|(key, _)| CanonicalValue::from(key.clone()));
+            Value::Map(entries)
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+        Value::Tag(tag, value) => Value::Tag(tag, Box::new(canonicalize(*value))),
+        value => value,
+    }
+}
+
+impl EventEmitter {
+    /// Adds an event listener decoding values emitted with `emit_cbor`.
+    ///
+    /// Values that cannot be decoded are reported to the decode error hooks instead of reaching the callback.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_cbor("some_event", |value: String| {
+    ///     println!("Received event with value: {}", value);
+    /// });
+    /// ```
+    pub fn on_cbor<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let cbor_callback = move |bytes: Vec<u8>| {
+            let decoded = decode_errors.decode_with(&listener_event, id, &bytes, |bytes| {
+                ciborium::from_reader::<T, _>(bytes)
+            });
+            if let Some(value) = decoded {
+                callback(value);
+            }
+        };
+
+        self.insert_listener(
+            event,
+            id,
+            ListenerOptions::default(),
+            Arc::new(cbor_callback),
+            None,
+        )
+    }
+
+    /// Emits an event with its value encoded as canonical CBOR, see `to_canonical_vec`.
+    ///
+    /// Listeners run like with `emit`, and the event's capture and coalescing settings apply. A value that
+    /// cannot be encoded is printed and delivered to no listener.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.emit_cbor("some_event", "Hello, world!".to_string());
+    /// ```
+    pub fn emit_cbor<T>(&mut self, event: impl AsRef<str>, value: T)
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        if let Err(e) = self.emit_encoded(event, &[], || to_canonical_vec(&value)) {
+            report_emit_error(event, &e);
+        }
+    }
+}
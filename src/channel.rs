@@ -0,0 +1,84 @@
+//! Listeners forwarding the values of an event to a channel.
+//!
+//! A channel listener outlives its interest once the `Receiver` is dropped. The first delivery that finds the
+//! channel closed marks the listener as removed, so it is skipped from then on and dropped by the next emit of
+//! its event, firing the removal hooks. The IDs of such listeners are also reported by
+//! `EventEmitter::take_closed_channels`.
+
+use crate::event_emitter::Listener;
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, PoisonError};
+
+impl EventEmitter {
+    /// Adds an event listener sending each value to a channel, removed automatically once the receiver is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener and the receiving end of its channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let (_, receiver) = event_emitter.on_channel::<String>("user.created");
+    /// event_emitter.sync_emit("user.created", "ada".to_string());
+    /// assert_eq!("ada", receiver.recv().unwrap());
+    /// ```
+    pub fn on_channel<T>(&mut self, event: &str) -> (ListenerId, Receiver<T>)
+    where
+        for<'de> T: Deserialize<'de>,
+        T: Send + 'static,
+    {
+        let id = self.ids.next_id();
+        let (sender, receiver) = mpsc::channel();
+        let removed = Arc::new(AtomicBool::new(false));
+        let cloned_removed = Arc::clone(&removed);
+        let closed_channels = Arc::clone(&self.closed_channels);
+        let callback = self.decoding_callback(event, id, move |value: T| {
+            if sender.send(value).is_err() && !cloned_removed.swap(true, Ordering::AcqRel) {
+                closed_channels
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(id);
+            }
+        });
+
+        let listener = Listener {
+            removed,
+            ..Listener::new(id, None, callback)
+        };
+        (self.attach(event, listener), receiver)
+    }
+
+    /// Returns the IDs of the channel listeners found to have a dropped receiver since the last call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let (listener_id, receiver) = event_emitter.on_channel::<u32>("tick");
+    /// drop(receiver);
+    /// event_emitter.sync_emit("tick", 1_u32);
+    /// assert_eq!(vec![listener_id], event_emitter.take_closed_channels());
+    /// ```
+    pub fn take_closed_channels(&mut self) -> Vec<ListenerId> {
+        std::mem::take(
+            &mut self
+                .closed_channels
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+}
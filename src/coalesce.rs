@@ -0,0 +1,78 @@
+//! Per-event coalescing of high-frequency emits.
+
+use std::time::Duration;
+use web_time::Instant;
+
+/// Which payload a coalesced delivery carries when several emits are merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coalesce {
+    /// Deliver the payload of the most recent emit in the interval.
+    KeepLatest,
+    /// Deliver the payload of the first emit in the interval.
+    KeepFirst,
+}
+
+/// Merges the emits of a single event into one delivery per interval.
+pub(crate) struct Coalescer {
+    pub(crate) interval: Duration,
+    pub(crate) strategy: Coalesce,
+    window_start: Option<Instant>,
+    pending: Option<Vec<u8>>,
+    /// When the pending payload was buffered, checked against the event's buffer TTL.
+    pending_since: Option<Instant>,
+    /// The emitter's buffer clock at the last push, used to evict the least recently buffered event first.
+    pub(crate) last_push: u64,
+}
+
+impl Coalescer {
+    pub(crate) fn new(interval: Duration, strategy: Coalesce) -> Self {
+        Self {
+            interval,
+            strategy,
+            window_start: None,
+            pending: None,
+            pending_since: None,
+            last_push: 0,
+        }
+    }
+
+    /// Merges `bytes` into the pending payload, returning the merged payload once the interval has elapsed.
+    pub(crate) fn push(&mut self, bytes: Vec<u8>) -> Option<Vec<u8>> {
+        let now = Instant::now();
+        let window_start = *self.window_start.get_or_insert(now);
+
+        if self.strategy == Coalesce::KeepLatest || self.pending.is_none() {
+            self.pending = Some(bytes);
+            self.pending_since = Some(now);
+        }
+
+        if now.duration_since(window_start) >= self.interval {
+            self.take()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the size in bytes of the pending payload.
+    pub(crate) fn pending_len(&self) -> usize {
+        self.pending.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Takes the pending payload, if any, and closes the current interval.
+    pub(crate) fn take(&mut self) -> Option<Vec<u8>> {
+        self.window_start = None;
+        self.pending_since = None;
+        self.pending.take()
+    }
+
+    /// Drops the pending payload if it was buffered more than `ttl` ago, returning its size, or 0 if it was kept.
+    pub(crate) fn expire(&mut self, ttl: Duration) -> usize {
+        match self.pending_since {
+            Some(since) if since.elapsed() > ttl => {
+                self.pending_since = None;
+                self.pending.take().map_or(0, |bytes| bytes.len())
+            }
+            _ => 0,
+        }
+    }
+}
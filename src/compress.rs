@@ -0,0 +1,178 @@
+//! Compression of large payloads between emit and listeners, enabled by the `compress` feature.
+//!
+//! A payload of a compressed event larger than the event's threshold is compressed once with LZ4, after
+//! serialization, and shared by the invocations of its listeners as a frame. Each invocation decompresses it
+//! on its own thread just before running the callback, so listeners always receive the original bytes.
+//! Frames start with a byte that neither JSON nor CBOR payloads can start with, followed by the compression
+//! format and the size of the original payload.
+//!
+//! Compression applies to the deliveries of `emit`, `sync_emit` and the methods behaving like them, including
+//! released and coalesced payloads; payloads are buffered, captured and filtered uncompressed. `sync_emit`
+//! decompresses the shared frame on the calling thread as each callback's turn comes.
+
+use crate::EventEmitter;
+use std::fmt;
+
+/// The first byte of a frame: never the first byte of a JSON text nor of a well-formed CBOR item.
+const FRAME_MAGIC: u8 = 0xFF;
+
+/// The format byte of a frame holding an LZ4 block.
+const FORMAT_LZ4: u8 = 1;
+
+/// The size of the frame header: magic byte, format byte and original size.
+const HEADER_LEN: usize = 6;
+
+/// The largest ratio between the original and compressed sizes LZ4 can reach, used to reject forged sizes.
+const MAX_RATIO: usize = 255;
+
+/// An error returned when a frame cannot be decompressed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// The frame is shorter than its header.
+    Truncated,
+    /// The frame uses a compression format this version does not know.
+    UnknownFormat(u8),
+    /// The compressed data is corrupt or does not match the original size.
+    Corrupt(String),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Truncated => write!(f, "truncated compressed frame"),
+            FrameError::UnknownFormat(format) => {
+                write!(f, "unknown compression format {}", format)
+            }
+            FrameError::Corrupt(reason) => write!(f, "corrupt compressed frame: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// Compresses a payload into a frame.
+///
+/// # Arguments
+///
+/// * `bytes` - The payload, at most `u32::MAX` bytes long.
+///
+/// # Returns
+///
+/// The frame, or `None` if the payload is too large to be framed.
+pub fn frame(bytes: &[u8]) -> Option<Vec<u8>> {
+    let len = u32::try_from(bytes.len()).ok()?;
+    let compressed = lz4_flex::block::compress(bytes);
+
+    let mut frame = Vec::with_capacity(HEADER_LEN + compressed.len());
+    frame.extend_from_slice(&[FRAME_MAGIC, FORMAT_LZ4]);
+    frame.extend_from_slice(&len.to_le_bytes());
+    frame.extend_from_slice(&compressed);
+    Some(frame)
+}
+
+/// Returns whether `bytes` is a frame rather than a plain payload.
+pub fn is_frame(bytes: &[u8]) -> bool {
+    bytes.first() == Some(&FRAME_MAGIC)
+}
+
+/// Decompresses a frame back into the original payload, returning a copy of `bytes` if it is not a frame.
+///
+/// # Arguments
+///
+/// * `bytes` - The frame or plain payload.
+///
+/// # Returns
+///
+/// The original payload, or a `FrameError` if the frame is malformed.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::compress;
+///
+/// let payload = vec![b'7'; 1024];
+/// let frame = compress::frame(&payload).unwrap();
+/// assert!(frame.len() < payload.len());
+/// assert_eq!(payload, compress::unframe(&frame).unwrap());
+/// assert!(compress::unframe(&frame[..frame.len() / 2]).is_err());
+/// ```
+pub fn unframe(bytes: &[u8]) -> Result<Vec<u8>, FrameError> {
+    if !is_frame(bytes) {
+        return Ok(bytes.to_vec());
+    }
+
+    let header = bytes.get(..HEADER_LEN).ok_or(FrameError::Truncated)?;
+    if header[1] != FORMAT_LZ4 {
+        return Err(FrameError::UnknownFormat(header[1]));
+    }
+
+    let len = u32::from_le_bytes([header[2], header[3], header[4], header[5]]) as usize;
+    let compressed = &bytes[HEADER_LEN..];
+    if len > compressed.len().saturating_mul(MAX_RATIO) {
+        return Err(FrameError::Corrupt(format!(
+            "original size {} is impossible for {} compressed bytes",
+            len,
+            compressed.len()
+        )));
+    }
+
+    let payload = lz4_flex::block::decompress(compressed, len)
+        .map_err(|e| FrameError::Corrupt(e.to_string()))?;
+    if payload.len() != len {
+        return Err(FrameError::Corrupt(format!(
+            "expected {} bytes, got {}",
+            len,
+            payload.len()
+        )));
+    }
+    Ok(payload)
+}
+
+impl EventEmitter {
+    /// Compresses the payloads of an event larger than `threshold_bytes` before handing them to its listeners.
+    ///
+    /// Listeners still receive the original payload: each invocation decompresses the shared frame right
+    /// before running the callback. A frame that cannot be decompressed is reported to the decode error hooks
+    /// instead of reaching the callback. Payloads that do not shrink when compressed are delivered as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    /// * `threshold_bytes` - The size above which payloads are compressed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_compression("report.generated", 64 * 1024);
+    /// event_emitter.on("report.generated", |report: String| println!("{} bytes", report.len()));
+    /// event_emitter.emit("report.generated", "x".repeat(1024 * 1024));
+    /// ```
+    pub fn set_compression(&mut self, event: &str, threshold_bytes: usize) {
+        self.compression.insert(event.to_string(), threshold_bytes);
+    }
+
+    /// Stops compressing the payloads of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the compressed event.
+    pub fn clear_compression(&mut self, event: &str) {
+        self.compression.remove(event);
+    }
+
+    /// Returns the frame to hand to the listeners of `event` instead of `bytes`, if the event's compression
+    /// applies and the frame is smaller.
+    pub(crate) fn compressed(&mut self, event: &str, bytes: &[u8]) -> Option<Vec<u8>> {
+        let threshold = *self.compression.get(event)?;
+        if bytes.len() <= threshold {
+            return None;
+        }
+
+        let frame = frame(bytes).filter(|frame| frame.len() < bytes.len())?;
+        self.stats.record_compression(bytes.len() - frame.len());
+        Some(frame)
+    }
+}
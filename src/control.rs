@@ -0,0 +1,279 @@
+//! Changes to individual listeners: refreshing their limit, disabling them temporarily, and suspending them
+//! until resumed.
+//!
+//! Listeners are found through an index from listener ID to event, so these operations only scan the
+//! listeners of one event. The index is kept up to date by registrations and repaired on lookup, since
+//! `EventEmitter::listeners` can also be changed directly.
+
+use crate::event_emitter::Listener;
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use std::fmt;
+use std::sync::atomic::Ordering;
+
+/// A listener removed with `suspend`, holding what `resume` needs to add it back.
+pub struct ResumeToken {
+    event: String,
+    listener: Listener,
+}
+
+impl ResumeToken {
+    /// Returns the event the listener was suspended from.
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    /// Returns the number of times the listener can still be invoked once resumed, or `None` for no limit.
+    pub fn limit(&self) -> Option<u64> {
+        self.listener.limit
+    }
+}
+
+impl fmt::Debug for ResumeToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumeToken")
+            .field("event", &self.event)
+            .field("listener", &self.listener)
+            .finish()
+    }
+}
+
+impl EventEmitter {
+    /// Returns the live listener with the given ID, looking it up through the ID index if it is up to date.
+    pub(crate) fn listener(&self, id: ListenerId) -> Option<&Listener> {
+        let is_live = |listener: &&Listener| listener.id == id && !listener.is_removed();
+        self.listener_events
+            .get(&id)
+            .and_then(|event| self.listeners.get(event))
+            .and_then(|listeners| listeners.iter().find(is_live))
+            .or_else(|| self.listeners.values().flatten().find(is_live))
+    }
+
+    /// Returns the live listener with the given ID, looking it up through the ID index and repairing its entry.
+    ///
+    /// The snapshot kept for re-entrant emits is dropped, since the caller may change the listener.
+    pub(crate) fn listener_mut(&mut self, id: ListenerId) -> Option<&mut Listener> {
+        self.listeners_changed();
+        let indexed = self.listener_events.get(&id).is_some_and(|event| {
+            self.listeners.get(event).is_some_and(|listeners| {
+                listeners
+                    .iter()
+                    .any(|listener| listener.id == id && !listener.retired)
+            })
+        });
+
+        if !indexed {
+            self.listener_events.remove(&id);
+            let event = self.listeners.iter().find_map(|(event, listeners)| {
+                listeners
+                    .iter()
+                    .any(|listener| listener.id == id && !listener.retired)
+                    .then(|| event.clone())
+            })?;
+            self.listener_events.insert(id, event);
+        }
+
+        let event = self.listener_events.get(&id)?;
+        self.listeners
+            .get_mut(event)?
+            .iter_mut()
+            .find(|listener| listener.id == id && !listener.is_removed())
+    }
+
+    /// Sets the number of times a listener can still be invoked, for example to extend a limited listener.
+    ///
+    /// The emit using the last call of a listener removes it, so it cannot be revived afterwards, unless its limit
+    /// only counts successes: such a listener is dropped by the next emit of its event instead. Setting the
+    /// limit to `Some(0)` leaves the listener registered until the next emit of its event.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    /// * `limit` - The new number of remaining invocations, or `None` for no limit.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the listener was found, `false` if no live listener has this ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.on_limited("some_event", Some(2), |value: String| println!("{}", value));
+    ///
+    /// event_emitter.sync_emit("some_event", "first".to_string());
+    /// assert!(event_emitter.reset_limit(listener_id, Some(2)));
+    /// event_emitter.sync_emit("some_event", "second".to_string());
+    /// assert_eq!(Some(Some(1)), event_emitter.remaining_limit(listener_id));
+    /// ```
+    pub fn reset_limit(&mut self, id: ListenerId, limit: Option<u64>) -> bool {
+        let Some(listener) = self.listener_mut(id) else {
+            return false;
+        };
+
+        if let Some(refunds) = &listener.refunds {
+            refunds.store(0, Ordering::Release);
+        }
+        listener.limit = limit;
+        true
+    }
+
+    /// Lifts the limit of a listener while `scope` runs, then restores the limit it had before.
+    ///
+    /// Emits made by `scope` do not consume the limit, so a `once` listener keeps firing until the scope ends
+    /// and can still fire once afterwards. The limit is not restored if the listener was removed or detached
+    /// in the meantime, nor if `scope` panics.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    /// * `scope` - The code to run while the listener has no limit.
+    ///
+    /// # Returns
+    ///
+    /// The result of `scope`, or `None` without running it if no live listener has this ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.once("some_event", |value: u32| println!("{}", value));
+    ///
+    /// event_emitter.with_persistent(listener_id, |event_emitter| {
+    ///     for value in 0..3_u32 {
+    ///         event_emitter.sync_emit("some_event", value);
+    ///     }
+    /// });
+    /// assert_eq!(Some(Some(1)), event_emitter.remaining_limit(listener_id));
+    /// ```
+    pub fn with_persistent<F, R>(&mut self, id: ListenerId, scope: F) -> Option<R>
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let listener = self.listener_mut(id)?;
+        let limit = listener.limit.take();
+        let result = scope(self);
+
+        if let Some(listener) = self.listener_mut(id) {
+            listener.limit = limit;
+        }
+        Some(result)
+    }
+
+    /// Enables or disables a listener without changing its position among the listeners of its event.
+    ///
+    /// A disabled listener is skipped by every emit method and does not consume its limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    /// * `enabled` - Whether the listener is invoked by emits.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the listener was found, `false` if no live listener has this ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.on("some_event", |value: String| println!("{}", value));
+    ///
+    /// event_emitter.set_enabled(listener_id, false);
+    /// event_emitter.sync_emit("some_event", "skipped".to_string());
+    /// event_emitter.set_enabled(listener_id, true);
+    /// event_emitter.sync_emit("some_event", "delivered".to_string());
+    /// ```
+    pub fn set_enabled(&mut self, id: ListenerId, enabled: bool) -> bool {
+        let Some(listener) = self.listener_mut(id) else {
+            return false;
+        };
+
+        listener.disabled = !enabled;
+        true
+    }
+
+    /// Returns the number of times a listener can still be invoked.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    ///
+    /// # Returns
+    ///
+    /// `Some(Some(n))` for a limited listener, `Some(None)` for an unlimited one, or `None` if no live listener
+    /// has this ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.on_limited("some_event", Some(3), |_: String| {});
+    ///
+    /// event_emitter.sync_emit("some_event", "Hello, world!".to_string());
+    /// assert_eq!(Some(Some(2)), event_emitter.remaining_limit(listener_id));
+    /// ```
+    pub fn remaining_limit(&self, id: ListenerId) -> Option<Option<u64>> {
+        let listener = self.listener(id)?;
+        let refunded = listener
+            .refunds
+            .as_ref()
+            .map_or(0, |refunds| refunds.load(Ordering::Acquire));
+        Some(listener.limit.map(|limit| limit + refunded))
+    }
+
+    /// Removes a listener until it is added back with `resume`, keeping its callback, options and remaining limit.
+    ///
+    /// Unlike `set_enabled`, a suspended listener is no longer counted among the listeners of its event.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    ///
+    /// # Returns
+    ///
+    /// The token resuming the listener, or `None` if no live listener has this ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.on("some_event", |value: String| println!("{}", value));
+    ///
+    /// let token = event_emitter.suspend(listener_id).unwrap();
+    /// assert_eq!(0, event_emitter.listener_count("some_event"));
+    /// event_emitter.resume(token);
+    /// assert_eq!(1, event_emitter.listener_count("some_event"));
+    /// ```
+    pub fn suspend(&mut self, id: ListenerId) -> Option<ResumeToken> {
+        self.listener_mut(id)?;
+        let event = self.listener_events.get(&id)?.clone();
+        let listener = self.detach(id)?;
+        Some(ResumeToken { event, listener })
+    }
+
+    /// Adds a suspended listener back to its event, after the listeners already registered, under a fresh ID.
+    ///
+    /// Decode errors of the callback keep reporting the ID the listener had when it was registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token returned by `suspend`.
+    ///
+    /// # Returns
+    ///
+    /// The new ID of the listener.
+    pub fn resume(&mut self, token: ResumeToken) -> ListenerId {
+        let ResumeToken {
+            event,
+            mut listener,
+        } = token;
+        listener.id = self.ids.next_id();
+        self.attach(&event, listener)
+    }
+}
@@ -0,0 +1,175 @@
+//! Re-emits the failures of listener callbacks as `"deadletter"` events, once enabled with
+//! `EventEmitter::enable_dead_letter`.
+//!
+//! A failure is a callback that panics, an `on_result` listener returning `Err`, or an `on_with_retry`
+//! listener out of retries. Failures are queued as they happen, possibly on worker threads, and emitted once
+//! the dispatch they happened in completes. Failures outside any dispatch, such as retries running in the
+//! background or pooled callbacks scheduled on the JS event loop, are emitted at the end of the next one.
+//! Failures of the listeners of `"deadletter"` itself are only printed, so that they cannot loop.
+//!
+//! The emit of a dead letter derives from the emit whose delivery failed, when known: its `EmitMeta` carries
+//! the sequence number of that emit as its parent.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::event_emitter::{report_emit_error, to_json};
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The event the failures of listener callbacks are emitted on.
+pub const DEAD_LETTER_EVENT: &str = "deadletter";
+
+thread_local! {
+    /// The sequence number of the emit whose delivery the thread is running under `catching`, or 0.
+    static DELIVERING: Cell<u64> = const { Cell::new(0) };
+}
+
+/// The failure of a listener callback, emitted on `"deadletter"`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// The event the failed callback was invoked for.
+    pub event: String,
+    /// The ID of the failed listener.
+    pub listener_id: ListenerId,
+    /// The sequence number of the emit whose delivery failed, or 0 if the failure happened outside of a
+    /// delivery, as for a retry running in the background.
+    pub seq: u64,
+    /// The serialized value the callback received, empty for a value handed over unserialized, as by
+    /// `emit_typed` to `on_typed` listeners.
+    pub payload: Vec<u8>,
+    /// The panic message or the error returned by the callback.
+    pub error: String,
+}
+
+/// The failures waiting to be emitted, shared with the callbacks that may report them.
+#[derive(Debug, Default)]
+pub(crate) struct DeadLetters {
+    enabled: AtomicBool,
+    /// Whether the queue may hold failures, so that dispatches only lock it when it does.
+    queued: AtomicBool,
+    queue: Mutex<Vec<DeadLetter>>,
+}
+
+impl DeadLetters {
+    /// Returns whether the failures of the listeners of `event` are re-emitted.
+    pub(crate) fn catches(&self, event: &str) -> bool {
+        self.enabled.load(Ordering::Acquire) && event != DEAD_LETTER_EVENT
+    }
+
+    /// Queues a failure to be emitted, returning `false` if it is not re-emitted and should be printed instead.
+    pub(crate) fn post(
+        &self,
+        event: &str,
+        listener_id: ListenerId,
+        payload: &[u8],
+        error: String,
+    ) -> bool {
+        if !self.catches(event) {
+            return false;
+        }
+
+        let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        queue.push(DeadLetter {
+            event: event.to_string(),
+            listener_id,
+            seq: DELIVERING.with(Cell::get),
+            payload: payload.to_vec(),
+            error,
+        });
+        self.queued.store(true, Ordering::Release);
+        true
+    }
+
+    /// Wraps an invocation of a listener of `event` for the emit numbered `seq` to queue a panic as a failure
+    /// instead of unwinding. The failures the invocation posts itself are attributed to that emit as well.
+    pub(crate) fn catching(
+        self: &Arc<Self>,
+        event: &str,
+        listener_id: ListenerId,
+        seq: u64,
+        payload: &Arc<[u8]>,
+        run: Box<dyn FnOnce() + Send + 'static>,
+    ) -> Box<dyn FnOnce() + Send + 'static> {
+        let dead_letters = Arc::clone(self);
+        let event = event.to_string();
+        let payload = Arc::clone(payload);
+        Box::new(move || {
+            let outer = DELIVERING.with(|delivering| delivering.replace(seq));
+            if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(run)) {
+                dead_letters.post(&event, listener_id, &payload, panic_message(panic.as_ref()));
+            }
+            DELIVERING.with(|delivering| delivering.set(outer));
+        })
+    }
+
+    fn take(&self) -> Vec<DeadLetter> {
+        if !self.queued.load(Ordering::Acquire) {
+            return Vec::new();
+        }
+
+        let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        self.queued.store(false, Ordering::Release);
+        std::mem::take(&mut *queue)
+    }
+}
+
+/// Returns the message a callback panicked with.
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "the listener panicked".to_string()
+    }
+}
+
+impl EventEmitter {
+    /// Re-emits the failures of listener callbacks as `"deadletter"` events carrying a `DeadLetter`.
+    ///
+    /// Once enabled, a panicking callback no longer unwinds into the emit method that invoked it, `sync_emit`
+    /// included, nor into its worker thread, and the failures of `on_result` and `on_with_retry` listeners are
+    /// emitted instead of printed; listener failure hooks still run. A failure is emitted once the dispatch it
+    /// happened in completes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::deadletter::DeadLetter;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.enable_dead_letter();
+    /// event_emitter.on("deadletter", |letter: DeadLetter| {
+    ///     println!("Listener of {} failed: {}", letter.event, letter.error);
+    /// });
+    /// event_emitter.on_result("order.placed", |id: u32| Err(format!("order {} is invalid", id)));
+    /// event_emitter.emit("order.placed", 7_u32);
+    /// ```
+    pub fn enable_dead_letter(&mut self) {
+        self.dead_letters.enabled.store(true, Ordering::Release);
+    }
+
+    /// Emits the queued failures on `"deadletter"`, until none is left.
+    pub(crate) fn emit_dead_letters(&mut self) {
+        loop {
+            let letters = self.dead_letters.take();
+            if letters.is_empty() {
+                return;
+            }
+
+            for letter in letters {
+                let emitted = self.emit_derived(letter.seq, DEAD_LETTER_EVENT, || to_json(&letter));
+                if let Err(e) = emitted {
+                    report_emit_error(DEAD_LETTER_EVENT, &e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Emits deferred until the dispatch in progress completes, for emitting from code that may run inside a
+//! listener callback, such as a `Drop` impl.
+//!
+//! A callback of the global emitter cannot emit on it directly: the emitting thread holds the global lock
+//! until every callback of the emit has returned. `global::emit_deferred` instead queues the emit while the
+//! global emitter is dispatching, and the dispatch drains the queue once its callbacks are done, in enqueue
+//! order. Emits deferred while draining join the same queue, after those already in it.
+//!
+//! A deferred emit derives from the emit being dispatched when it was queued: its `EmitMeta` carries the
+//! sequence number of that emit as its parent. Since the queue is drained right after the callbacks of that
+//! emit, before the emitting method returns, a derived emit never waits behind emits made after it.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::event_emitter::report_emit_error;
+use crate::EventEmitter;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The dispatches in progress on an emitter and the emits deferred until they complete.
+#[derive(Debug, Default)]
+pub(crate) struct Deferred {
+    state: Mutex<DeferredState>,
+}
+
+#[derive(Debug, Default)]
+struct DeferredState {
+    /// The sequence numbers of the emits whose dispatches are in progress, nested when draining deferred emits.
+    dispatches: Vec<u64>,
+    /// The serialized deferred emits with the sequence number of the emit they derive from, in enqueue order.
+    queue: VecDeque<(String, Vec<u8>, u64)>,
+}
+
+impl Deferred {
+    /// Queues an emit if the emitter is dispatching, otherwise hands the payload back.
+    pub(crate) fn defer(&self, event: &str, bytes: Vec<u8>) -> Result<(), Vec<u8>> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(&parent) = state.dispatches.last() else {
+            return Err(bytes);
+        };
+
+        state.queue.push_back((event.to_string(), bytes, parent));
+        Ok(())
+    }
+
+    fn enter(self: &Arc<Self>, seq: u64) -> DispatchScope {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .dispatches
+            .push(seq);
+        DispatchScope {
+            deferred: Arc::clone(self),
+            left: false,
+        }
+    }
+}
+
+/// A dispatch in progress, left once its deferred emits are drained or when a callback panics.
+struct DispatchScope {
+    deferred: Arc<Deferred>,
+    left: bool,
+}
+
+impl DispatchScope {
+    /// Pops the next deferred emit, or leaves the dispatch if the queue is empty. Both happen under the same
+    /// lock, so an emit is never queued after the last check of the queue.
+    fn next_or_leave(&mut self) -> Option<(String, Vec<u8>, u64)> {
+        let mut state = self
+            .deferred
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let next = state.queue.pop_front();
+        if next.is_none() {
+            state.dispatches.pop();
+            self.left = true;
+        }
+        next
+    }
+}
+
+impl Drop for DispatchScope {
+    fn drop(&mut self) {
+        if !self.left {
+            let mut state = self
+                .deferred
+                .state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            state.dispatches.pop();
+        }
+    }
+}
+
+impl EventEmitter {
+    /// Runs a dispatch of the current emit, then emits the emits deferred during it and the failures of its
+    /// callbacks, as derived from the emits that caused them.
+    pub(crate) fn dispatching<R>(&mut self, dispatch: impl FnOnce(&mut Self) -> R) -> R {
+        let mut scope = self.deferred.enter(self.current_seq);
+        let result = dispatch(self);
+
+        while let Some((event, bytes, parent)) = scope.next_or_leave() {
+            if let Err(e) = self.emit_derived(parent, &event, || Ok(bytes)) {
+                report_emit_error(&event, &e);
+            }
+        }
+        self.emit_dead_letters();
+        result
+    }
+
+    /// Emits an event like `emit`, for code that may also run inside a listener callback of the global emitter.
+    ///
+    /// Holding `&mut self` means this emitter is not dispatching, so the emit happens right away, followed by
+    /// the emits its callbacks deferred. The deferral itself happens through `global::emit_deferred`, which
+    /// queues the emit while the global emitter is dispatching instead of deadlocking on its lock.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the emit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("resource.released", |name: String| println!("Released {}", name));
+    /// event_emitter.emit_deferred("resource.released", "socket".to_string());
+    /// ```
+    pub fn emit_deferred<T>(&mut self, event: impl AsRef<str>, value: T) -> u64
+    where
+        T: Serialize,
+    {
+        self.emit(event, value)
+    }
+}
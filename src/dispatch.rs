@@ -0,0 +1,114 @@
+//! The dispatch core shared by `EventEmitter` and `FastEmitter`: building listener callbacks and handing
+//! emitted values over to them.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::error::EmitError;
+use crate::event_emitter::{self, Callback, TypedCallback};
+use crate::hooks::DecodeErrorHooks;
+use crate::id::ListenerId;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::cell::OnceCell;
+use std::sync::Arc;
+
+/// A `TypedCallback<T>` stored without its type, as registered by `on_typed`.
+pub(crate) type AnyCallback = Arc<dyn Any + Sync + Send + 'static>;
+
+/// Wraps `callback` into a `Callback` deserializing the value, reporting failures on behalf of the listener.
+pub(crate) fn decoding_callback<F, T>(
+    decode_errors: &DecodeErrorHooks,
+    event: &str,
+    id: ListenerId,
+    callback: F,
+) -> Callback
+where
+    for<'de> T: Deserialize<'de>,
+    F: Fn(T) + 'static + Sync + Send,
+{
+    let decode_errors = decode_errors.clone();
+    let event = event.to_string();
+    Arc::new(move |bytes: Vec<u8>| {
+        if let Some(value) = decode_errors.decode(&event, id, &bytes) {
+            callback(value);
+        }
+    })
+}
+
+/// Builds the callbacks of a listener that receives values of type `T` directly and any other value deserialized.
+pub(crate) fn typed_callbacks<F, T>(
+    decode_errors: &DecodeErrorHooks,
+    event: &str,
+    id: ListenerId,
+    callback: F,
+) -> (Callback, AnyCallback)
+where
+    for<'de> T: Deserialize<'de>,
+    T: 'static,
+    F: Fn(T) + 'static + Sync + Send,
+{
+    let typed_callback: TypedCallback<T> = Arc::new(callback);
+    let cloned_typed_callback = Arc::clone(&typed_callback);
+    let parsed_callback = decoding_callback(decode_errors, event, id, move |value: T| {
+        cloned_typed_callback(value)
+    });
+
+    (parsed_callback, Arc::new(typed_callback))
+}
+
+/// A value being dispatched, serialized at most once and only if a listener needs the bytes.
+pub(crate) struct Payload<'a, T> {
+    value: &'a T,
+    bytes: OnceCell<Result<Vec<u8>, EmitError>>,
+}
+
+impl<'a, T> Payload<'a, T>
+where
+    T: Serialize + Clone + 'static,
+{
+    pub(crate) fn new(value: &'a T) -> Self {
+        Self {
+            value,
+            bytes: OnceCell::new(),
+        }
+    }
+
+    /// Returns the serialized value, serializing it on first use, or the error if it cannot be serialized.
+    pub(crate) fn bytes(&self) -> Result<&[u8], EmitError> {
+        self.bytes
+            .get_or_init(|| event_emitter::to_json(self.value))
+            .as_deref()
+            .map_err(Clone::clone)
+    }
+
+    /// Returns the size of the serialized value, or 0 if the value was not serialized.
+    pub(crate) fn serialized_len(&self) -> usize {
+        self.bytes
+            .get()
+            .and_then(|bytes| bytes.as_ref().ok())
+            .map_or(0, Vec::len)
+    }
+
+    /// Returns the callback taking `T` directly, if the listener was registered for `T`.
+    pub(crate) fn typed(typed_callback: Option<&AnyCallback>) -> Option<TypedCallback<T>> {
+        typed_callback
+            .and_then(|typed| typed.downcast_ref::<TypedCallback<T>>())
+            .cloned()
+    }
+
+    /// Invokes a listener on the calling thread, with a clone of the value or with the serialized value.
+    ///
+    /// Returns the serialization error, without invoking the listener, if it needs a value that cannot be serialized.
+    pub(crate) fn invoke(
+        &self,
+        callback: &Callback,
+        typed_callback: Option<&AnyCallback>,
+    ) -> Result<(), EmitError> {
+        match Self::typed(typed_callback) {
+            Some(typed) => typed(self.value.clone()),
+            None => callback(self.bytes()?.to_vec()),
+        }
+
+        Ok(())
+    }
+}
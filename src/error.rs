@@ -0,0 +1,108 @@
+//! Errors returned by the fallible emit methods.
+
+use crate::guard::GuardError;
+use std::fmt;
+
+/// An error returned when an event cannot be emitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EmitError {
+    /// Strict mode is enabled and no listener was ever registered for the event.
+    NoSuchEvent(String),
+    /// The value could not be serialized, so it was delivered to no listener.
+    Serialize(String),
+    /// The payload of the event failed the event's JSON Schema in `Validation::Enforce` mode, so it was
+    /// delivered to no listener. Holds the event and the validation errors.
+    SchemaViolation(String, Vec<String>),
+    /// The emitter of an `EmitterSender` was dropped, so the emit could not be queued.
+    Disconnected,
+    /// The event is a new name and the emitter already keeps state for the maximum number of event names set
+    /// with `set_max_event_names`.
+    TooManyEventNames {
+        /// The number of event names the emitter keeps state for.
+        current: usize,
+        /// The maximum number of event names.
+        limit: usize,
+    },
+    /// A guard added with `add_guard` or `add_global_guard` rejected the payload, so it was delivered to no
+    /// listener.
+    Rejected {
+        /// The position of the rejecting guard, counting the global guards first.
+        guard_index: usize,
+        /// The error returned by the guard.
+        source: GuardError,
+    },
+    /// The emitter was shut down with `shutdown`, so it delivers nothing anymore.
+    ShutDown,
+    /// The payload nests arrays and objects deeper than the limit set with `set_max_json_depth`, so it was
+    /// delivered to no listener.
+    PayloadTooDeep {
+        /// The maximum nesting depth.
+        limit: usize,
+    },
+    /// The serialized payload is longer than the limit set with `set_max_payload_bytes`, so it was delivered
+    /// to no listener.
+    PayloadTooLarge {
+        /// The size of the serialized payload in bytes.
+        size: usize,
+        /// The maximum payload size in bytes.
+        limit: usize,
+    },
+}
+
+impl fmt::Display for EmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmitError::NoSuchEvent(event) => {
+                write!(f, "no listener was ever registered for {}", event)
+            }
+            EmitError::Serialize(reason) => write!(f, "failed to serialize value: {}", reason),
+            EmitError::SchemaViolation(event, errors) => write!(
+                f,
+                "payload of {} does not match its schema: {}",
+                event,
+                errors.join("; ")
+            ),
+            EmitError::Disconnected => write!(f, "the emitter was dropped"),
+            EmitError::TooManyEventNames { current, limit } => write!(
+                f,
+                "too many event names: {} of at most {} already in use",
+                current, limit
+            ),
+            EmitError::Rejected {
+                guard_index,
+                source,
+            } => write!(f, "rejected by guard {}: {}", guard_index, source),
+            EmitError::ShutDown => write!(f, "the emitter was shut down"),
+            EmitError::PayloadTooDeep { limit } => {
+                write!(f, "payload nests deeper than {} levels", limit)
+            }
+            EmitError::PayloadTooLarge { size, limit } => write!(
+                f,
+                "payload of {} bytes exceeds the limit of {} bytes",
+                size, limit
+            ),
+        }
+    }
+}
+
+/// What the `Result`-returning emit methods do when the emitted value cannot be serialized, set with
+/// `set_serialize_failure`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SerializeFailure {
+    /// The failure is printed and the emit delivers nothing, but succeeds, so a loop emitting many events
+    /// carries on past a bad payload.
+    Skip,
+    /// The emit delivers nothing and returns `EmitError::Serialize`.
+    #[default]
+    Abort,
+}
+
+impl std::error::Error for EmitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EmitError::Rejected { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
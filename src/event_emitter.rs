@@ -1,49 +1,2945 @@
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::any::AnyListener;
+use crate::capture::Recorder;
+use crate::coalesce::{Coalesce, Coalescer};
+use crate::deadletter::DeadLetters;
+use crate::deferred::Deferred;
+use crate::dispatch::{self, AnyCallback, Payload};
+use crate::error::{EmitError, SerializeFailure};
+use crate::gather::AsyncReplier;
+use crate::guard::Guard;
+use crate::history::History;
+use crate::hold::{HeldEmits, HoldState};
+use crate::hooks::{DecodeErrorHooks, Hooks, ListenerInfo};
+use crate::id::{DuplicateId, IdGenerator, IdSource, ListenerId, ToListenerId};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::idle::InFlight;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::keyed::KeyedListener;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::local::LocalDispatcher;
+use crate::order::DispatchOrder;
+use crate::pattern::{self, PatternTrie};
+use crate::per_key::SeenKeys;
+use crate::query::Responder;
+use crate::reentrancy::{ReentrancyPolicy, Snapshot};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::request::Replies;
+use crate::retry::FallibleCallback;
+use crate::sampling::Sampler;
+use crate::sender::PendingEmits;
+use crate::spawner::Spawner;
+use crate::stats::EmitterStats;
+use crate::topology::EventEdge;
+use crate::tracking::EmitStamp;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
 #[cfg(not(target_arch = "wasm32"))]
 use std::thread;
-use uuid::Uuid;
+use std::time::Duration;
 
 #[cfg(target_arch = "wasm32")]
-use wasm_bindgen_futures::spawn_local;
+use crate::scheduling::Scheduling;
+
+/// A listener callback receiving the serialized event value.
+pub type Callback = Arc<dyn Fn(Vec<u8>) + Sync + Send + 'static>;
+
+/// A predicate deciding whether a listener receives a serialized event value.
+pub type Filter = Arc<dyn Fn(&[u8]) -> bool + Sync + Send + 'static>;
+
+/// Decides like a `Filter` whether a listener receives a serialized event value, but remembers the values it
+/// lets through, as the listeners added with `once_per_key` and `on_distinct_limited` do.
+pub(crate) trait Gate: Send + Sync {
+    /// Returns whether the value would be let through, without remembering anything.
+    fn accepts(&self, bytes: &[u8]) -> bool;
+
+    /// Lets the value through if it accepts it, remembering it and returning the callback to invoke with it.
+    ///
+    /// Only called for a listener about to be dispatched, so that a value is remembered only once delivered.
+    fn admit(&self, bytes: &[u8]) -> Option<Callback>;
+}
+
+/// Lets through the values that differ from the last one delivered, as used by `on_distinct_limited`.
+struct DistinctGate<T> {
+    /// The last value delivered, updated only once the listener is dispatched with a new one.
+    last_value: Mutex<Option<T>>,
+    callback: Callback,
+    decode_errors: DecodeErrorHooks,
+    event: String,
+    id: ListenerId,
+}
+
+impl<T> DistinctGate<T>
+where
+    for<'de> T: Deserialize<'de>,
+    T: PartialEq,
+{
+    /// Decodes the value, returning it with the lock of the last value delivered if the two differ.
+    fn distinct(&self, bytes: &[u8]) -> Option<(T, MutexGuard<'_, Option<T>>)> {
+        let value = self
+            .decode_errors
+            .decode::<T>(&self.event, self.id, bytes)?;
+        let last_value = self
+            .last_value
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        (last_value.as_ref() != Some(&value)).then_some((value, last_value))
+    }
+}
+
+impl<T> Gate for DistinctGate<T>
+where
+    for<'de> T: Deserialize<'de>,
+    T: PartialEq + Send,
+{
+    fn accepts(&self, bytes: &[u8]) -> bool {
+        self.distinct(bytes).is_some()
+    }
+
+    fn admit(&self, bytes: &[u8]) -> Option<Callback> {
+        let (value, mut last_value) = self.distinct(bytes)?;
+        *last_value = Some(value);
+        Some(Arc::clone(&self.callback))
+    }
+}
+
+/// A listener callback receiving the sequence number of the emit and the serialized event value, as registered
+/// by `on_seq`.
+pub(crate) type SeqCallback = Arc<dyn Fn(u64, Vec<u8>) + Sync + Send + 'static>;
+
+/// A listener callback receiving an owned value of its concrete type, as registered by `on_typed`.
+pub type TypedCallback<T> = Arc<dyn Fn(T) + Sync + Send + 'static>;
+
+/// An emitter shared between threads, as used by integrations that emit on their own.
+pub type SharedEventEmitter = Arc<Mutex<EventEmitter>>;
 
 /// Represents a single event listener.
+///
+/// Listeners are usually built by the `on*` methods, but can also be built with `Listener::new` and added with
+/// `EventEmitter::add_listener`, for example to wrap callbacks before registration.
 pub struct Listener {
-    pub callback: Arc<dyn Fn(Vec<u8>) + Sync + Send + 'static>,
+    /// Receives the serialized value of each delivered emit.
+    pub callback: Callback,
+    /// The number of deliveries left, or `None` for no limit. A listener at `Some(0)` is never invoked again:
+    /// the emit exhausting its limit removes it, though it stays among the listeners of its event until the
+    /// next emit of the event sweeps it, and a listener set to `Some(0)` otherwise is dropped by that emit.
+    pub limit: Option<u64>,
+    /// Identifies the listener for removal; must be unique among the listeners of an emitter.
+    pub id: ListenerId,
+    pub execution: Execution,
+    /// Values rejected by the filter are not delivered and do not count toward the limit.
+    pub filter: Option<Filter>,
+    /// A `TypedCallback<T>` used by `emit_typed` to skip serialization when the emitted type matches.
+    pub typed_callback: Option<Arc<dyn Any + Sync + Send + 'static>>,
+    /// If not empty, only emits carrying at least one of these tags are delivered.
+    pub include_tags: Vec<String>,
+    /// Emits carrying any of these tags are not delivered and do not count toward the limit.
+    pub exclude_tags: Vec<String>,
+    /// Set once the listener is removed through a `ListenerRemover`, before it is physically dropped.
+    pub(crate) removed: Arc<AtomicBool>,
+    /// The callback reporting failures, as registered by `on_result` and retried by `emit_with_retry`.
+    pub(crate) fallible_callback: Option<FallibleCallback>,
+    /// The number of panicked invocations to give back to the limit, if the limit only counts successes.
+    pub(crate) refunds: Option<Arc<AtomicU64>>,
+    /// Set by `set_enabled(id, false)`; a disabled listener is skipped without consuming its limit.
+    pub(crate) disabled: bool,
+    /// The callback receiving the emit sequence number, as registered by `on_seq`, used instead of `callback`.
+    pub(crate) seq_callback: Option<SeqCallback>,
+    /// The prefix of the tenant that added the listener through a `TenantEmitter`, if any.
+    pub(crate) tenant: Option<String>,
+    /// The keys remembered by a listener added with `once_per_key`.
+    pub(crate) seen_keys: Option<Arc<dyn SeenKeys>>,
+    /// Remembers the values delivered to the listener, used instead of `filter` when it is dispatched. The
+    /// filter of such a listener only checks values, for the emits that do not deliver them.
+    pub(crate) gate: Option<Arc<dyn Gate>>,
+    /// Whether the listener was added with `on_pattern`, so that its event is matched as a pattern.
+    pub(crate) pattern: bool,
+    /// Set once an emit exhausted the listener's limit and removed it, firing the removal hooks, before it is
+    /// swept from its event. Unlike `removed`, it leaves the deliveries of that emit running.
+    pub(crate) retired: bool,
+}
+
+impl Listener {
+    /// Creates a pooled listener without filter, tags or typed callback.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener, usually from `EventEmitter::next_listener_id`.
+    /// * `limit` - The number of times the listener should be executed, or `None` for no limit.
+    /// * `callback` - The callback receiving the serialized value of each delivered emit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::event_emitter::Listener;
+    /// use emitter_rs::EventEmitter;
+    /// use std::sync::Arc;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let id = event_emitter.next_listener_id();
+    /// let listener = Listener::new(id, Some(2), Arc::new(|bytes: Vec<u8>| {
+    ///     println!("Received {} bytes", bytes.len());
+    /// }));
+    /// assert_eq!(id, event_emitter.add_listener("some_event", listener));
+    /// ```
+    pub fn new(id: ListenerId, limit: Option<u64>, callback: Callback) -> Self {
+        Self {
+            callback,
+            limit,
+            id,
+            execution: Execution::default(),
+            filter: None,
+            typed_callback: None,
+            include_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+            removed: Arc::default(),
+            fallible_callback: None,
+            refunds: None,
+            disabled: false,
+            seq_callback: None,
+            tenant: None,
+            seen_keys: None,
+            gate: None,
+            pattern: false,
+            retired: false,
+        }
+    }
+
+    /// Returns whether the listener was removed and is only waiting to be swept from its event.
+    pub fn is_removed(&self) -> bool {
+        self.retired || self.removed.load(Ordering::Acquire)
+    }
+
+    /// Returns whether the listener can be dropped as soon as an emit exhausts its limit, which is not the case
+    /// if a panicking invocation could still give a call back to it.
+    fn is_spent(&self) -> bool {
+        self.limit == Some(0) && self.refunds.is_none()
+    }
+
+    /// Returns whether the listener is invoked by emits, which is the case unless it was disabled with `set_enabled`.
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
+    /// Returns whether an untagged emit of its event could still invoke the listener, whatever the value.
+    pub(crate) fn is_reachable(&self) -> bool {
+        !self.is_removed()
+            && self.is_enabled()
+            && self.limit != Some(0)
+            && self.include_tags.is_empty()
+    }
+
+    /// Gives the invocations that panicked since the last emit back to the limit.
+    fn apply_refunds(&mut self) {
+        let Some(refunds) = &self.refunds else {
+            return;
+        };
+        let refunded = refunds.swap(0, Ordering::AcqRel);
+        if let Some(limit) = self.limit.as_mut() {
+            *limit += refunded;
+        }
+    }
+
+    /// Returns the callback to invoke for the emit with sequence number `seq`.
+    fn bound_callback(&self, seq: u64) -> Callback {
+        match &self.seq_callback {
+            Some(seq_callback) => {
+                let seq_callback = Arc::clone(seq_callback);
+                Arc::new(move |bytes: Vec<u8>| seq_callback(seq, bytes))
+            }
+            None => Arc::clone(&self.callback),
+        }
+    }
+
+    /// Returns the dispatch running the listener for the emit `seq`, with `gated` as its callback if its gate
+    /// bound one.
+    fn dispatch(&self, seq: u64, gated: Option<Callback>) -> Dispatch {
+        Dispatch {
+            id: self.id,
+            execution: self.execution,
+            callback: gated.unwrap_or_else(|| self.bound_callback(seq)),
+            typed_callback: self.typed_callback.clone(),
+            removed: Arc::clone(&self.removed),
+            fallible_callback: self.fallible_callback.clone(),
+        }
+    }
+
+    /// Consumes one call from the limit for an emit of `bytes`, returning the callback its gate bound to them,
+    /// if any, or `None` without consuming anything if its filter or gate rejects them.
+    ///
+    /// A gate remembers the value it lets through and may bind the callback to the value it decoded, so that
+    /// the value is decoded only once.
+    fn take_call(&mut self, bytes: &[u8]) -> Option<Option<Callback>> {
+        let gated = match &self.gate {
+            Some(gate) => Some(gate.admit(bytes)?),
+            None => {
+                if self.filter.as_ref().is_some_and(|filter| !filter(bytes)) {
+                    return None;
+                }
+                None
+            }
+        };
+
+        if let Some(limit) = self.limit {
+            self.limit = Some(limit - 1);
+        }
+        Some(gated)
+    }
+
+    /// Consumes one call from the limit and returns the dispatch running the listener for the emit `seq` of
+    /// `bytes`, or `None` without consuming anything if its filter or gate rejects them.
+    fn dispatch_value(&mut self, seq: u64, bytes: &[u8]) -> Option<Dispatch> {
+        let gated = self.take_call(bytes)?;
+        Some(self.dispatch(seq, gated))
+    }
+
+    /// Invokes the listener on the calling thread for the emit `seq` of `bytes`, once `take_call` returned
+    /// `gated` for them.
+    fn invoke(&self, seq: u64, bytes: &[u8], gated: Option<Callback>) {
+        match (gated, &self.seq_callback) {
+            (Some(callback), _) => callback(bytes.to_vec()),
+            (None, Some(seq_callback)) => seq_callback(seq, bytes.to_vec()),
+            (None, None) => (self.callback)(bytes.to_vec()),
+        }
+    }
+
+    /// Returns whether an emit carrying `tags` should be delivered to this listener.
+    fn accepts_tags(&self, tags: &[&str]) -> bool {
+        let carries = |tag: &String| tags.contains(&tag.as_str());
+        !self.exclude_tags.iter().any(carries)
+            && (self.include_tags.is_empty() || self.include_tags.iter().any(carries))
+    }
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listener")
+            .field("id", &self.id)
+            .field("limit", &self.limit)
+            .field("execution", &self.execution)
+            .field("filtered", &self.filter.is_some())
+            .field("include_tags", &self.include_tags)
+            .field("exclude_tags", &self.exclude_tags)
+            .field("enabled", &self.is_enabled())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Where a listener's callback runs when its event is emitted with `emit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Execution {
+    /// The callback runs synchronously on the thread that called `emit`.
+    Inline,
+    /// The callback runs on its own worker thread (or as scheduled by `set_wasm_scheduling` on wasm).
+    #[default]
+    Pooled,
+}
+
+/// Options used when registering a listener with `on_with_options`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ListenerOptions {
+    /// Where the callback runs during `emit`.
+    pub execution: Execution,
+    /// The number of times the listener should be executed, or `None` for no limit.
     pub limit: Option<u64>,
-    pub id: String,
+    /// If not empty, the listener only receives emits tagged with at least one of these tags by `emit_tagged`.
+    pub include_tags: Vec<String>,
+    /// The listener skips emits tagged with any of these tags, without counting them toward its limit.
+    pub exclude_tags: Vec<String>,
+    /// If `true`, an invocation whose callback panics does not count toward the limit.
+    pub limit_on_success: bool,
+}
+
+/// A listener callback selected to run for a single emit.
+pub(crate) struct Dispatch {
+    pub(crate) id: ListenerId,
+    execution: Execution,
+    pub(crate) callback: Callback,
+    typed_callback: Option<AnyCallback>,
+    pub(crate) removed: Arc<AtomicBool>,
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub(crate) fallible_callback: Option<FallibleCallback>,
+}
+
+impl Dispatch {
+    /// Prepares the serialized callback invocation sharing `bytes`, copied for the callback only once it runs.
+    fn into_job(self, bytes: &Arc<[u8]>) -> Job {
+        let callback = Arc::clone(&self.callback);
+        let bytes = Arc::clone(bytes);
+        self.job(move || callback(bytes.to_vec()))
+    }
+
+    /// Prepares the invocation with a compressed frame shared by all invocations, decompressed when it runs.
+    #[cfg(feature = "compress")]
+    fn into_framed_job(
+        self,
+        event: &str,
+        frame: Arc<[u8]>,
+        decode_errors: DecodeErrorHooks,
+    ) -> Job {
+        let callback = Arc::clone(&self.callback);
+        let event = event.to_string();
+        let id = self.id;
+        self.job(move || {
+            if let Some(bytes) =
+                decode_errors.decode_with(&event, id, &frame, crate::compress::unframe)
+            {
+                callback(bytes);
+            }
+        })
+    }
+
+    /// Prepares the given invocation, skipped if the listener is removed before it starts.
+    fn job<F>(&self, run: F) -> Job
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let removed = Arc::clone(&self.removed);
+        Job {
+            id: self.id,
+            execution: self.execution,
+            run: Box::new(move || {
+                if !removed.load(Ordering::Acquire) {
+                    run();
+                }
+            }),
+        }
+    }
+}
+
+/// A callback invocation ready to run for a single emit.
+pub(crate) struct Job {
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    id: ListenerId,
+    execution: Execution,
+    run: Box<dyn FnOnce() + Send + 'static>,
+}
+
+/// The value of an emit on its way through `emit_admitted`, serialized by the first step needing its bytes.
+pub(crate) enum Encoded<E> {
+    /// The serialized value.
+    Bytes(Vec<u8>),
+    /// The serialization of the value, not run yet.
+    Lazy(E),
+}
+
+impl<E> Encoded<E>
+where
+    E: FnOnce() -> Result<Vec<u8>, EmitError>,
+{
+    /// Returns the serialized value, serializing it and counting its size in `stats` if it was not yet.
+    pub(crate) fn into_bytes(self, stats: &mut EmitterStats) -> Result<Vec<u8>, EmitError> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes),
+            Self::Lazy(encode) => {
+                let bytes = encode()?;
+                stats.record_bytes(bytes.len());
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// A value serialized before it is emitted, such as one released from a hold.
+pub(crate) type Serialized = Encoded<fn() -> Result<Vec<u8>, EmitError>>;
+
+/// Wraps `callback` to count the invocations that panic, so that they can be given back to the listener limit.
+fn refunding_callback(callback: Callback) -> (Callback, Arc<AtomicU64>) {
+    let refunds = Arc::new(AtomicU64::new(0));
+    let cloned_refunds = Arc::clone(&refunds);
+    let refunding_callback = move |bytes: Vec<u8>| {
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| callback(bytes))) {
+            cloned_refunds.fetch_add(1, Ordering::AcqRel);
+            panic::resume_unwind(payload);
+        }
+    };
+
+    (Arc::new(refunding_callback), refunds)
+}
+
+/// The number of characters of an event name kept in the name of a worker thread.
+#[cfg(not(target_arch = "wasm32"))]
+const THREAD_NAME_EVENT_CHARS: usize = 64;
+
+/// Builds the name of the worker thread running `listener_id` for `event`, e.g. `emit-order.placed-1a2b3c4d`.
+///
+/// The event name is cut to its first `THREAD_NAME_EVENT_CHARS` characters and its NUL characters are dropped,
+/// since `thread::Builder` refuses a name containing one.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn thread_name(event: &str, listener_id: ListenerId) -> String {
+    let event: String = event
+        .chars()
+        .filter(|c| *c != '\0')
+        .take(THREAD_NAME_EVENT_CHARS)
+        .collect();
+    let short_id: String = listener_id.to_string().chars().take(8).collect();
+    format!("emit-{}-{}", event, short_id)
+}
+
+/// Spawns a worker thread named after the event and listener it runs.
+///
+/// Returns `None`, after printing the error, if the thread could not be spawned; the callback is then skipped.
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_named<F>(event: &str, listener_id: ListenerId, f: F) -> Option<thread::JoinHandle<()>>
+where
+    F: FnOnce() + Send + 'static,
+{
+    match thread::Builder::new()
+        .name(thread_name(event, listener_id))
+        .spawn(f)
+    {
+        Ok(handle) => Some(handle),
+        Err(e) => {
+            eprintln!(
+                "Failed to spawn thread for listener {} of {}: {}",
+                listener_id, event, e
+            );
+            None
+        }
+    }
+}
+
+/// Serializes a value to JSON, returning `EmitError::Serialize` if it cannot be represented.
+pub(crate) fn to_json<T>(value: &T) -> Result<Vec<u8>, EmitError>
+where
+    T: Serialize + ?Sized,
+{
+    serde_json::to_vec(value).map_err(|e| EmitError::Serialize(e.to_string()))
+}
+
+/// Prints the error of an emit method that has no way to return it.
+pub(crate) fn report_emit_error(event: &str, error: &EmitError) {
+    eprintln!("Failed to emit {}: {}", event, error);
+}
+
+impl EventEmitter {
+    /// Spawns the worker thread of a pooled job, counting it as in flight until the job completes.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn_tracked(&self, event: &str, job: Job) -> Option<thread::JoinHandle<()>> {
+        let guard = self.in_flight.start();
+        spawn_named(event, job.id, move || {
+            let _guard = guard;
+            (job.run)();
+        })
+    }
+
+    /// Runs pooled jobs on their own named threads and inline jobs on the calling thread, then waits for all of them.
+    ///
+    /// Pooled jobs are handed to the injected spawner instead if there is one, and not waited for.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run_jobs(&self, event: &str, jobs: Vec<Job>) {
+        let mut callback_handlers = Vec::new();
+        let mut inline_jobs = Vec::new();
+
+        for job in jobs {
+            match (job.execution, &self.spawner) {
+                (Execution::Pooled, Some(spawner)) => spawner.spawn(job.run),
+                (Execution::Pooled, None) => {
+                    callback_handlers.extend(self.spawn_tracked(event, job))
+                }
+                (Execution::Inline, _) => inline_jobs.push(job.run),
+            }
+        }
+
+        for run in inline_jobs {
+            run();
+        }
+
+        for handler in callback_handlers {
+            if let Err(e) = handler.join() {
+                eprintln!("Thread error: {:?}", e);
+            }
+        }
+    }
+
+    /// Schedules pooled jobs with the emitter's spawner and runs inline jobs synchronously.
+    #[cfg(target_arch = "wasm32")]
+    fn run_jobs(&self, _event: &str, jobs: Vec<Job>) {
+        let mut inline_jobs = Vec::new();
+
+        for job in jobs {
+            match job.execution {
+                Execution::Pooled => self.spawner().spawn(job.run),
+                Execution::Inline => inline_jobs.push(job.run),
+            }
+        }
+
+        for run in inline_jobs {
+            run();
+        }
+    }
+}
+
+/// Manages event listeners and event emissions.
+#[derive(Default)]
+pub struct EventEmitter {
+    /// The listeners of each event, in registration order. The entry of an event is dropped with its last
+    /// listener, so no event maps to an empty list; listeners waiting to be swept still count.
+    pub listeners: HashMap<String, Vec<Listener>>,
+    /// Handler names of the listeners wired up by `configure_from_js`, keyed by listener ID.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) handler_names: HashMap<ListenerId, String>,
+    pub(crate) coalescers: HashMap<String, Coalescer>,
+    /// The sampling policies of the events, set with `set_sampling`.
+    pub(crate) samplers: HashMap<String, Sampler>,
+    /// The seed of the generators of new sampling policies, set with `set_sampling_seed`.
+    pub(crate) sampling_seed: Option<u64>,
+    /// The recorders of captured events, alive as long as their `CaptureHandle`.
+    pub(crate) captures: HashMap<String, Weak<Mutex<Recorder>>>,
+    /// The maximum number of bytes buffered across all events, or `None` for no limit.
+    buffer_limit: Option<usize>,
+    /// How long the buffered payloads of each event stay deliverable, set with `set_buffer_ttl`.
+    buffer_ttls: HashMap<String, Duration>,
+    /// The size above which the payloads of each event are compressed, set with `set_compression`.
+    #[cfg(feature = "compress")]
+    pub(crate) compression: HashMap<String, usize>,
+    /// Incremented on every buffered emit to order payloads from least to most recently buffered.
+    pub(crate) buffer_clock: u64,
+    pub(crate) hold_state: HoldState,
+    /// The emits buffered while holding, in emission order across all events.
+    pub(crate) held: HeldEmits,
+    pub(crate) hooks: Hooks,
+    pub(crate) ids: IdSource,
+    /// What `on_with_id` does with an ID already in use, set with `set_duplicate_id`.
+    pub(crate) duplicate_id: DuplicateId,
+    /// Every event that ever had a listener registered, used by `strict_emit`.
+    pub(crate) known_events: HashSet<String>,
+    /// The event names the emitter keeps state for, capped by `max_event_names`.
+    pub(crate) event_names: HashSet<String>,
+    /// The maximum number of event names, set with `set_max_event_names`, or `None` for no limit.
+    pub(crate) max_event_names: Option<usize>,
+    /// The maximum nesting depth of payloads, set with `set_max_json_depth`, or `None` for no limit.
+    pub(crate) max_json_depth: Option<usize>,
+    /// The maximum serialized size of payloads, set with `set_max_payload_bytes`, or `None` for no limit.
+    pub(crate) max_payload_bytes: Option<usize>,
+    /// The prefixes of the tenants with views of the emitter, created by `tenant`.
+    pub(crate) tenants: HashSet<String>,
+    /// The number of listeners per event above which a possible leak is reported, or 0 for no limit.
+    pub(crate) max_listeners: usize,
+    strict: bool,
+    /// Set by `shutdown`, after which emits fail and listeners are refused.
+    pub(crate) shut_down: bool,
+    /// The event names declared with `declare_events`, the only ones `strict_emit` accepts once any is declared.
+    declared_events: HashSet<String>,
+    /// What the `Result`-returning emit methods do with values that cannot be serialized.
+    serialize_failure: SerializeFailure,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) replies: Arc<Replies>,
+    /// The repliers added with `on_async_replier`, in registration order per event.
+    pub(crate) async_repliers: HashMap<String, Vec<(ListenerId, AsyncReplier)>>,
+    /// The listeners added with `on_any`, in registration order per event.
+    pub(crate) any_listeners: HashMap<String, Vec<AnyListener>>,
+    /// The responders added with `on_responder`, in registration order per event.
+    pub(crate) responders: HashMap<String, Vec<(ListenerId, Responder)>>,
+    /// The listeners added with `on_keyed`, in registration order per event.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) keyed: HashMap<String, Vec<KeyedListener>>,
+    /// The order in which listeners are invoked, set with `set_dispatch_order`.
+    pub(crate) dispatch_order: DispatchOrder,
+    /// The state of the generator drawing shuffled dispatch orders.
+    pub(crate) shuffle_state: u64,
+    /// The event of each attached listener by ID, used by `listener_mut`. Entries go stale when listeners are
+    /// dropped or moved, and are repaired on lookup.
+    pub(crate) listener_events: HashMap<ListenerId, String>,
+    /// The listeners added with `on_pattern`, stored in `listeners` under their pattern.
+    pub(crate) patterns: PatternTrie,
+    /// The JSON Schemas of the events, registered with `register_schema`.
+    #[cfg(all(feature = "schema", not(target_arch = "wasm32")))]
+    pub(crate) schemas: HashMap<String, Arc<jsonschema::JSONSchema>>,
+    /// What happens to payloads failing their schema, set with `set_validation`.
+    #[cfg(all(feature = "schema", not(target_arch = "wasm32")))]
+    pub(crate) validation: crate::schema::Validation,
+    /// The emits queued by the senders of the emitter, delivered by `process_pending`.
+    pub(crate) pending: PendingEmits,
+    /// The channel listeners found with a dropped receiver, reported by `take_closed_channels`.
+    pub(crate) closed_channels: Arc<Mutex<Vec<ListenerId>>>,
+    /// The edges of the bridges attached to the emitter, each with the flag telling whether it still forwards,
+    /// listed by `topology`.
+    pub(crate) edges: Vec<(EventEdge, Arc<AtomicBool>)>,
+    /// The most recent emit of each event, or `None` if tracking is disabled.
+    pub(crate) last_emits: Option<HashMap<String, EmitStamp>>,
+    /// The number of emits so far, which is also the sequence number of the last emit.
+    pub(crate) emit_count: AtomicU64,
+    /// The sequence number of the emit being dispatched, passed to `on_seq` listeners, or 0 before any emit.
+    pub(crate) current_seq: u64,
+    /// The sequence number of the emit the next stamped emit derives from, set by `emit_derived`.
+    pub(crate) emit_parent: Option<u64>,
+    /// The recent payloads of the events with history enabled, set with `enable_history`.
+    pub(crate) history: HashMap<String, History>,
+    /// The emits recorded by the innermost `with_recording_scope` in progress, if any.
+    pub(crate) recording: Option<Vec<(String, Vec<u8>)>>,
+    /// The emit statistics, collected once enabled with `collect_stats`.
+    pub(crate) stats: EmitterStats,
+    /// The dispatches in progress and the emits deferred until they complete.
+    pub(crate) deferred: Arc<Deferred>,
+    /// The guards of each event, in the order they were added with `add_guard`.
+    pub(crate) guards: HashMap<String, Vec<(String, Guard)>>,
+    /// The guards of every event, in the order they were added with `add_global_guard`.
+    pub(crate) global_guards: Vec<(String, Guard)>,
+    /// What an emit re-entering the emitter from its own dispatch does, set with `set_reentrancy_policy`.
+    pub(crate) reentrancy_policy: ReentrancyPolicy,
+    /// The listeners re-entrant emits see under `ReentrancyPolicy::Snapshot`, built on demand and dropped
+    /// whenever the listeners change.
+    pub(crate) reentrancy_snapshot: Option<Arc<Snapshot>>,
+    /// The failures of listener callbacks waiting to be emitted, once enabled with `enable_dead_letter`.
+    pub(crate) dead_letters: Arc<DeadLetters>,
+    /// The callbacks running on worker threads, awaited by `wait_until_idle`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) in_flight: Arc<InFlight>,
+    /// How pooled callbacks are scheduled on the JS event loop.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) scheduling: Scheduling,
+    /// Runs the pooled callbacks instead of worker threads or the JS event loop, as injected by tests.
+    pub(crate) spawner: Option<Arc<dyn Spawner>>,
+    /// The dispatcher thread of the listeners added with `on_local`, started by the first of them.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) local: Option<LocalDispatcher>,
 }
 
-/// Manages event listeners and event emissions.
-#[derive(Default)]
-pub struct EventEmitter {
-    pub listeners: HashMap<String, Vec<Listener>>,
-}
+impl EventEmitter {
+    /// Creates a new `EventEmitter` instance.
+    ///
+    /// # Returns
+    ///
+    /// A new `EventEmitter` instance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let event_emitter = EventEmitter::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new `EventEmitter` instance whose listener IDs are created by the given generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `generator` - The generator creating the listener IDs.
+    ///
+    /// # Returns
+    ///
+    /// A new `EventEmitter` instance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::id::{ListenerId, SequentialGenerator};
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::with_id_generator(SequentialGenerator::default());
+    ///
+    /// assert_eq!(ListenerId(1), event_emitter.on("some_event", |_: String| {}));
+    /// ```
+    pub fn with_id_generator<G>(generator: G) -> Self
+    where
+        G: IdGenerator + 'static,
+    {
+        Self {
+            ids: IdSource(Arc::new(generator)),
+            ..Self::default()
+        }
+    }
+
+    /// Adds an event listener with a callback that will be called whenever the given event is emitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener. After `shutdown`, or on a new event name beyond the cap set with
+    /// `set_max_event_names`, the listener is dropped and reported on stderr, yet an ID is still returned;
+    /// `try_on` returns the error instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("some_event", |value: String| {
+    ///     println!("Received event with value: {}", value);
+    /// });
+    /// ```
+    pub fn on<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        self.on_limited(event, None, callback)
+    }
+
+    /// Adds one event listener per event, all sharing the same callback.
+    ///
+    /// The callback is shared rather than cloned, so each listener invokes the same closure and its captured
+    /// state. Each listener can be removed on its own with `remove_listener`.
+    ///
+    /// # Arguments
+    ///
+    /// * `events` - The names of the events to listen for.
+    /// * `callback` - The callback function to execute when any of the events is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the newly added listeners, in the order of `events`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let listener_ids = event_emitter.on_many(&["user.created", "user.updated"], |name: String| {
+    ///     println!("User changed: {}", name);
+    /// });
+    /// assert_eq!(2, listener_ids.len());
+    /// ```
+    pub fn on_many<F, T>(&mut self, events: &[&str], callback: F) -> Vec<ListenerId>
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let callback = Arc::new(callback);
+        events
+            .iter()
+            .map(|event| {
+                let id = self.ids.next_id();
+                let shared_callback = Arc::clone(&callback);
+                let parsed_callback =
+                    self.decoding_callback(event, id, move |value: T| shared_callback(value));
+                self.insert_listener(event, id, ListenerOptions::default(), parsed_callback, None)
+            })
+            .collect()
+    }
+
+    /// Emits an event with the given parameters, executing each callback asynchronously by spawning a new thread for each callback.
+    ///
+    /// Each thread is named `emit-<event>-<shortid>`, where `<shortid>` is the first 8 characters of the listener ID,
+    /// so callbacks are easy to identify in stack traces and profilers.
+    ///
+    /// Listeners registered with `Execution::Inline` are not spawned; they run on the calling thread, in
+    /// registration order, after every pooled callback has been started and before `emit` waits for them.
+    ///
+    /// Pooled callbacks run concurrently, so they may start and finish in any order. Use `sync_emit` to run
+    /// every callback in registration order on the calling thread, or `emit_sequential` to run them in
+    /// registration order on their own threads, one at a time.
+    ///
+    /// On WebAssembly, pooled callbacks are scheduled on the JS event loop instead, as microtasks unless another
+    /// mode is set with `set_wasm_scheduling`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit, as a `&str`, `String` or any other `AsRef<str>`.
+    /// * `value` - The value to pass to the event listeners. It is only serialized, so it can be passed by
+    ///   reference to keep using it afterwards.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the emit, assigned per emitter across all events and passed to `on_seq` listeners,
+    /// or 0 if the emit failed or was sampled out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.emit("some_event", "Hello, world!".to_string());
+    /// ```
+    pub fn emit<T>(&mut self, event: impl AsRef<str>, value: T) -> u64
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        self.try_emit(event, value).unwrap_or_else(|e| {
+            report_emit_error(event, &e);
+            0
+        })
+    }
+
+    /// Emits an event like `emit`, returning the error instead of printing it if the value cannot be serialized.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the emit, 0 if it was sampled out, or `EmitError::Serialize` if the value could
+    /// not be serialized, in which case no listener is invoked. With `SerializeFailure::Skip`, such a failure is
+    /// printed instead, and 0 returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::collections::BTreeMap;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("some_event", |value: String| println!("{}", value));
+    ///
+    /// assert!(event_emitter.try_emit("some_event", "Hello, world!".to_string()).is_ok());
+    ///
+    /// // JSON object keys must be strings.
+    /// let grid = BTreeMap::from([((0_u32, 0_u32), 1_u32)]);
+    /// assert!(event_emitter.try_emit("some_event", grid).is_err());
+    /// ```
+    pub fn try_emit<T>(&mut self, event: impl AsRef<str>, value: T) -> Result<u64, EmitError>
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        match self.emit_encoded(event, &[], || to_json(&value)) {
+            Ok((seq, _)) => Ok(seq),
+            Err(e) => self.apply_serialize_failure(event, Err(e)).map(|()| 0),
+        }
+    }
+
+    /// Emits an event like `emit`, asserting that at least `min_listeners` listeners were invoked.
+    ///
+    /// The assertion is meant for critical emits, such as one an audit listener must receive. In debug builds,
+    /// a shortfall panics after the invoked listeners have run. In release builds, it is only reported on
+    /// stderr. Emits held back, captured or coalesced invoke no listener yet, so they count as a shortfall,
+    /// as do emits whose value cannot be serialized.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    /// * `min_listeners` - The number of listeners expected to be invoked, at least.
+    ///
+    /// # Returns
+    ///
+    /// The number of listeners invoked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("payment.captured", |amount: u32| println!("Audit: {}", amount));
+    ///
+    /// assert_eq!(1, event_emitter.emit_expect("payment.captured", 100_u32, 1));
+    /// ```
+    pub fn emit_expect<T>(
+        &mut self,
+        event: impl AsRef<str>,
+        value: T,
+        min_listeners: usize,
+    ) -> usize
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        let invoked = self
+            .emit_encoded(event, &[], || to_json(&value))
+            .map(|(_, invoked)| invoked)
+            .unwrap_or_else(|e| {
+                report_emit_error(event, &e);
+                0
+            });
+
+        if invoked < min_listeners {
+            let message = format!(
+                "{} invoked {} listeners, expected at least {}",
+                event, invoked, min_listeners
+            );
+            if cfg!(debug_assertions) {
+                panic!("{}", message);
+            }
+            eprintln!("{}", message);
+        }
+
+        invoked
+    }
+
+    /// Emits an event like `emit`, building its value only if a listener would receive it.
+    ///
+    /// Nothing is built if no listener that a plain emit would invoke is registered, as counted by
+    /// `effective_listener_count`, so the value of an event nobody listens to costs nothing. The check and the
+    /// emit happen under the same borrow of the emitter, so no listener can come or go in between. Emits that
+    /// are held back, captured or coalesced build the value but invoke no listener yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `make` - The function building the value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The number of listeners invoked, 0 if `make` was not called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let invoked = event_emitter.emit_lazy("diagnostics", || -> Vec<String> {
+    ///     unreachable!("Nobody listens to diagnostics")
+    /// });
+    /// assert_eq!(0, invoked);
+    /// ```
+    pub fn emit_lazy<T, F>(&mut self, event: &str, make: F) -> usize
+    where
+        T: Serialize,
+        F: FnOnce() -> T,
+    {
+        if self.effective_listener_count(event) == 0 {
+            return 0;
+        }
+
+        self.emit_encoded(event, &[], || to_json(&make()))
+            .map(|(_, invoked)| invoked)
+            .unwrap_or_else(|e| {
+                report_emit_error(event, &e);
+                0
+            })
+    }
+
+    /// Emits an event like `sync_emit`, building its value only if a listener would receive it, like
+    /// `emit_lazy`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `make` - The function building the value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The number of listeners invoked, 0 if `make` was not called.
+    pub fn sync_emit_lazy<T, F>(&mut self, event: &str, make: F) -> usize
+    where
+        T: Serialize,
+        F: FnOnce() -> T,
+    {
+        if self.effective_listener_count(event) == 0 {
+            return 0;
+        }
+
+        self.sync_emit_with(event, make)
+            .map(|(_, invoked)| invoked)
+            .unwrap_or_else(|e| {
+                report_emit_error(event, &e);
+                0
+            })
+    }
+
+    /// Enables or disables strict mode, in which `strict_emit` rejects events that never had a listener.
+    ///
+    /// Strict mode is disabled by default. It is meant to catch event name typos during development.
+    ///
+    /// # Arguments
+    ///
+    /// * `strict` - Whether strict mode is enabled.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Declares the event names the emitter is meant to emit, typically generated by the `events!` macro.
+    ///
+    /// Once any name is declared, `strict_emit` in strict mode rejects every undeclared name, whether it has
+    /// listeners or not, instead of the names that never had a listener. The other emit methods are unaffected.
+    ///
+    /// # Arguments
+    ///
+    /// * `events` - The names to declare, added to those already declared.
+    pub fn declare_events(&mut self, events: &[&str]) {
+        self.declared_events
+            .extend(events.iter().map(|event| event.to_string()));
+    }
+
+    /// Sets what `try_emit`, `try_sync_emit` and `strict_emit` do with values that cannot be serialized,
+    /// `SerializeFailure::Abort` by default.
+    ///
+    /// The other emit methods print the failure and deliver nothing whatever the policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Whether to skip such emits or fail with `EmitError::Serialize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::error::SerializeFailure;
+    /// use emitter_rs::EventEmitter;
+    /// use std::collections::BTreeMap;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_serialize_failure(SerializeFailure::Skip);
+    /// // JSON object keys must be strings.
+    /// let grid = BTreeMap::from([((0_u32, 0_u32), 1_u32)]);
+    /// assert!(event_emitter.try_emit("some_event", grid).is_ok());
+    /// ```
+    pub fn set_serialize_failure(&mut self, policy: SerializeFailure) {
+        self.serialize_failure = policy;
+    }
+
+    /// Returns what the `Result`-returning emit methods do with values that cannot be serialized.
+    pub fn serialize_failure(&self) -> SerializeFailure {
+        self.serialize_failure
+    }
+
+    /// Turns a serialization failure of a `Result`-returning emit into a printed, skipped emit if the policy
+    /// says so.
+    fn apply_serialize_failure(
+        &self,
+        event: &str,
+        result: Result<(), EmitError>,
+    ) -> Result<(), EmitError> {
+        match result {
+            Err(e @ EmitError::Serialize(_))
+                if self.serialize_failure == SerializeFailure::Skip =>
+            {
+                report_emit_error(event, &e);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Emits an event like `emit`, failing in strict mode if no listener was ever registered for it.
+    ///
+    /// An event stays known after its listeners are removed, so only names that were never listened to
+    /// are rejected. If names were declared with `declare_events`, undeclared names are rejected instead.
+    /// Outside strict mode, this never fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the event was emitted, `EmitError::NoSuchEvent` if it is unknown in strict mode, or
+    /// `EmitError::Serialize` if the value could not be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::error::EmitError;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.set_strict(true);
+    /// event_emitter.on("user.created", |name: String| println!("{}", name));
+    ///
+    /// assert!(event_emitter.strict_emit("user.created", "ada".to_string()).is_ok());
+    /// assert_eq!(
+    ///     Err(EmitError::NoSuchEvent("user.craeted".to_string())),
+    ///     event_emitter.strict_emit("user.craeted", "ada".to_string())
+    /// );
+    /// ```
+    pub fn strict_emit<T>(&mut self, event: impl AsRef<str>, value: T) -> Result<(), EmitError>
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        let known = if self.declared_events.is_empty() {
+            self.known_events.contains(event)
+        } else {
+            self.declared_events.contains(event)
+        };
+        if self.strict && !known {
+            return Err(EmitError::NoSuchEvent(event.to_string()));
+        }
+
+        let result = self.emit_encoded(event, &[], || to_json(&value));
+        self.apply_serialize_failure(event, result.map(drop))
+    }
+
+    /// Emits an event like `emit`, attaching a set of tags that listeners can include or exclude.
+    ///
+    /// Listeners registered with `include_tags` or `exclude_tags` in their `ListenerOptions` skip emits whose
+    /// tags do not match, without counting them toward their limit. Tags are matched as exact strings; a plain
+    /// `emit` carries no tags. Held emits keep their tags until released, a coalesced delivery carries the tags
+    /// of the emit completing it, and captured emits do not record their tags.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    /// * `tags` - The tags of the emit, such as the subsystem it originates from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::event_emitter::ListenerOptions;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let options = ListenerOptions {
+    ///     exclude_tags: vec!["sync".to_string()],
+    ///     ..ListenerOptions::default()
+    /// };
+    /// event_emitter.on_with_options("settings.changed", options, |value: String| {
+    ///     println!("Pushing {} to the server", value);
+    /// });
+    ///
+    /// // Settings received from the server are not pushed back.
+    /// event_emitter.emit_tagged("settings.changed", "dark mode".to_string(), &["sync"]);
+    /// ```
+    pub fn emit_tagged<T>(&mut self, event: impl AsRef<str>, value: T, tags: &[&str])
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        if let Err(e) = self.emit_encoded(event, tags, || to_json(&value)) {
+            report_emit_error(event, &e);
+        }
+    }
+
+    /// Emits an event like `emit`, with the value serialized by `encode` instead of the emitter's JSON encoding.
+    ///
+    /// Use it for the one-off encoding needs of a single emit, such as pretty-printing or skipping `None`
+    /// fields. The bytes must still be what the listeners decode: JSON for listeners added with `on`. `encode`
+    /// is only called if the value is going to be used, and its bytes are checked against the event's schema
+    /// like any payload.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    /// * `encode` - The function serializing the value.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the emit, or 0 if it failed or was sampled out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use serde_json::json;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("report.generated", |report: serde_json::Value| println!("{}", report));
+    ///
+    /// let report = json!({ "pages": 3 });
+    /// event_emitter.emit_raw_from("report.generated", report, |report| {
+    ///     serde_json::to_vec_pretty(report).unwrap_or_default()
+    /// });
+    /// ```
+    pub fn emit_raw_from<T, E>(&mut self, event: impl AsRef<str>, value: T, encode: E) -> u64
+    where
+        E: Fn(&T) -> Vec<u8>,
+    {
+        let event = event.as_ref();
+        self.emit_encoded(event, &[], || Ok(encode(&value)))
+            .map(|(seq, _)| seq)
+            .unwrap_or_else(|e| {
+                report_emit_error(event, &e);
+                0
+            })
+    }
+
+    /// Emits an event whose payload is `bytes` verbatim, without serializing them.
+    ///
+    /// Use it for payloads that are already encoded, such as protobuf messages: `emit` would serialize a
+    /// `Vec<u8>` into a JSON array of numbers instead. Listeners added with `on_bytes` receive the bytes
+    /// unchanged; listeners added with `on` decode them as JSON like any payload. The bytes are checked against
+    /// the event's guards like any payload.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `bytes` - The payload to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the emit, or 0 if it failed or was sampled out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on_bytes("frame.received", |frame| println!("{} bytes", frame.len()));
+    ///
+    /// event_emitter.emit_bytes("frame.received", vec![0x08, 0x96, 0x01]);
+    /// ```
+    pub fn emit_bytes(&mut self, event: impl AsRef<str>, bytes: Vec<u8>) -> u64 {
+        let event = event.as_ref();
+        self.emit_encoded(event, &[], || Ok(bytes))
+            .map(|(seq, _)| seq)
+            .unwrap_or_else(|e| {
+                report_emit_error(event, &e);
+                0
+            })
+    }
+
+    /// Serializes a value of `event` to JSON, validating it against the size and depth limits and the event's schema and
+    /// guards.
+    pub(crate) fn encode_json<T>(&mut self, event: &str, value: &T) -> Result<Vec<u8>, EmitError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let bytes = to_json(value)?;
+        self.stats.record_bytes(bytes.len());
+        let bytes = self.check_limits(bytes)?;
+        let bytes = self.check_schema(event, bytes)?;
+        self.check_guards(event, bytes)
+    }
+
+    /// Returns whether payloads of `event` are validated against a schema, so they must be serialized even if
+    /// no listener needs them. Always `false` without the `schema` feature.
+    #[cfg_attr(
+        not(all(feature = "schema", not(target_arch = "wasm32"))),
+        allow(unused_variables)
+    )]
+    pub(crate) fn validates(&self, event: &str) -> bool {
+        #[cfg(all(feature = "schema", not(target_arch = "wasm32")))]
+        return self.schema_check(event).is_some();
+        #[cfg(not(all(feature = "schema", not(target_arch = "wasm32"))))]
+        false
+    }
+
+    /// Validates a serialized payload of `event` against the event's schema, returning it unless rejected.
+    #[cfg_attr(
+        not(all(feature = "schema", not(target_arch = "wasm32"))),
+        allow(unused_variables)
+    )]
+    pub(crate) fn check_schema(&self, event: &str, bytes: Vec<u8>) -> Result<Vec<u8>, EmitError> {
+        #[cfg(all(feature = "schema", not(target_arch = "wasm32")))]
+        if let Some(check) = self.schema_check(event) {
+            check.apply(event, &bytes)?;
+        }
+
+        Ok(bytes)
+    }
+
+    /// Emits the value serialized by `encode`, honoring the emitter's hold and the event's capture and
+    /// coalescing settings. The value is only serialized if it is going to be used.
+    ///
+    /// Returns the sequence number of the emit, 0 if it was sampled out, and the number of listeners invoked,
+    /// or the error of `encode`, in which case nothing is delivered.
+    pub(crate) fn emit_encoded<E>(
+        &mut self,
+        event: &str,
+        tags: &[&str],
+        encode: E,
+    ) -> Result<(u64, usize), EmitError>
+    where
+        E: FnOnce() -> Result<Vec<u8>, EmitError>,
+    {
+        let (seq, jobs) = self.prepare_emit(event, tags, encode, |emitter, encoded| {
+            emitter.serialized_jobs(event, tags, encoded)
+        })?;
+
+        Ok((seq, self.run_prepared(event, jobs)))
+    }
+
+    /// Admits an emit of `event` and takes it through `emit_admitted`, the single path of every emit method
+    /// up to the invocations of its listeners, which the method runs its own way.
+    ///
+    /// Returns the sequence number of the emit and the jobs of its listeners, no sequence number and no job if
+    /// it was sampled out, or the error refusing it.
+    pub(crate) fn prepare_emit<E, P>(
+        &mut self,
+        event: &str,
+        tags: &[&str],
+        encode: E,
+        prepare: P,
+    ) -> Result<(u64, Vec<Job>), EmitError>
+    where
+        E: FnOnce() -> Result<Vec<u8>, EmitError>,
+        P: FnOnce(&mut Self, Encoded<E>) -> Result<Vec<Job>, EmitError>,
+    {
+        if !self.admit_emit(event)? {
+            return Ok((0, Vec::new()));
+        }
+
+        self.emit_admitted(event, tags, encode, prepare)
+    }
+
+    /// Admits an emit of `event` before any of its work, for every emit method: refuses it once the emitter is
+    /// shut down, applies the event's sampling policy, then counts its name against `max_event_names`.
+    ///
+    /// Returns `false` if the emit was sampled out and must be dropped, or the error refusing it.
+    pub(crate) fn admit_emit(&mut self, event: &str) -> Result<bool, EmitError> {
+        if self.shut_down {
+            return Err(EmitError::ShutDown);
+        }
+        if !self.sample(event) {
+            return Ok(false);
+        }
+
+        self.admit_emitted_name(event)?;
+        Ok(true)
+    }
+
+    /// Takes an emit admitted by `admit_emit` through validation, stamping, recording, the emitter's hold and
+    /// the event's capture and coalescing, in that order, serializing the value with `encode` at the first of
+    /// them needing its bytes. If none of them keeps the emit from its listeners, `prepare` prepares their
+    /// jobs, from the value as serialized so far or by then not serialized at all.
+    ///
+    /// Returns the sequence number of the emit and the jobs of its listeners, or the error refusing it.
+    pub(crate) fn emit_admitted<E, P>(
+        &mut self,
+        event: &str,
+        tags: &[&str],
+        encode: E,
+        prepare: P,
+    ) -> Result<(u64, Vec<Job>), EmitError>
+    where
+        E: FnOnce() -> Result<Vec<u8>, EmitError>,
+        P: FnOnce(&mut Self, Encoded<E>) -> Result<Vec<Job>, EmitError>,
+    {
+        let encoded = self.validate_emit(event, Encoded::Lazy(encode))?;
+        self.emit_validated(event, tags, encoded, prepare)
+    }
+
+    /// Checks the value of an emit of `event` against the payload limits, the event's schema and the guards,
+    /// serializing it if any of them applies.
+    ///
+    /// Returns the value, serialized or not, or the error rejecting it.
+    fn validate_emit<E>(
+        &mut self,
+        event: &str,
+        encoded: Encoded<E>,
+    ) -> Result<Encoded<E>, EmitError>
+    where
+        E: FnOnce() -> Result<Vec<u8>, EmitError>,
+    {
+        if !self.validates(event) && !self.guarded(event) && !self.limits_payloads() {
+            return Ok(encoded);
+        }
+
+        let bytes = encoded.into_bytes(&mut self.stats)?;
+        let bytes = self.check_limits(bytes)?;
+        let bytes = self.check_schema(event, bytes)?;
+        Ok(Encoded::Bytes(self.check_guards(event, bytes)?))
+    }
+
+    /// Takes an emit validated by `validate_emit` through the rest of `emit_admitted`.
+    fn emit_validated<E, P>(
+        &mut self,
+        event: &str,
+        tags: &[&str],
+        mut encoded: Encoded<E>,
+        prepare: P,
+    ) -> Result<(u64, Vec<Job>), EmitError>
+    where
+        E: FnOnce() -> Result<Vec<u8>, EmitError>,
+        P: FnOnce(&mut Self, Encoded<E>) -> Result<Vec<Job>, EmitError>,
+    {
+        // The sequence number is the one the emit is stamped with, rather than `current_seq` once it is
+        // dispatched, which the emits deferred during the dispatch and the dead letters emitted after it move on.
+        let seq = self.stamp_emit(event);
+        if self.records_payloads(event) {
+            let bytes = encoded.into_bytes(&mut self.stats)?;
+            self.record_history(event, &bytes);
+            encoded = Encoded::Bytes(bytes);
+        }
+        if self.is_holding() {
+            let bytes = encoded.into_bytes(&mut self.stats)?;
+            self.hold_emit(event, tags, bytes);
+            return Ok((seq, Vec::new()));
+        }
+
+        let jobs = self.dispatch_encoded(event, tags, encoded, prepare)?;
+        Ok((seq, jobs))
+    }
+
+    /// Prepares the delivery of a stamped emit to the event listeners, honoring the event's capture and
+    /// coalescing settings, with `prepare` unless a coalesced delivery merges the value with others.
+    ///
+    /// Returns the jobs of the listeners to invoke, or the error of the serialization, in which case nothing
+    /// is delivered.
+    pub(crate) fn dispatch_encoded<E, P>(
+        &mut self,
+        event: &str,
+        tags: &[&str],
+        encoded: Encoded<E>,
+        prepare: P,
+    ) -> Result<Vec<Job>, EmitError>
+    where
+        E: FnOnce() -> Result<Vec<u8>, EmitError>,
+        P: FnOnce(&mut Self, Encoded<E>) -> Result<Vec<Job>, EmitError>,
+    {
+        if let Some(recorder) = self.captured(event) {
+            let bytes = encoded.into_bytes(&mut self.stats)?;
+            recorder
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .record(bytes, self.current_seq);
+            return Ok(Vec::new());
+        }
+
+        if !self.coalescers.contains_key(event) {
+            if !self.has_listeners(event) {
+                return Ok(Vec::new());
+            }
+            return prepare(self, encoded);
+        }
+
+        let bytes = encoded.into_bytes(&mut self.stats)?;
+        self.expire_buffered();
+        let Some(coalescer) = self.coalescers.get_mut(event) else {
+            return Ok(self.jobs(event, tags, &bytes.into()));
+        };
+        self.buffer_clock += 1;
+        coalescer.last_push = self.buffer_clock;
+        match coalescer.push(bytes) {
+            Some(merged) => Ok(self.jobs(event, tags, &merged.into())),
+            None => {
+                self.enforce_buffer_limit();
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// Prepares the jobs delivering the value of an emit of `event`, serialized now if it was not yet, to the
+    /// listeners accepting `tags`.
+    pub(crate) fn serialized_jobs<E>(
+        &mut self,
+        event: &str,
+        tags: &[&str],
+        encoded: Encoded<E>,
+    ) -> Result<Vec<Job>, EmitError>
+    where
+        E: FnOnce() -> Result<Vec<u8>, EmitError>,
+    {
+        let bytes = encoded.into_bytes(&mut self.stats)?.into();
+        Ok(self.jobs(event, tags, &bytes))
+    }
+
+    /// Prepares the jobs of the listeners of `event` accepting `tags` with an already serialized value.
+    fn jobs(&mut self, event: &str, tags: &[&str], bytes: &Arc<[u8]>) -> Vec<Job> {
+        let dispatches = self.take_dispatches(event, bytes, tags);
+        self.prepare_jobs(event, dispatches, bytes)
+    }
+
+    /// Runs the jobs prepared for an emit of `event` like `emit`, returning the number of listeners invoked.
+    pub(crate) fn run_prepared(&mut self, event: &str, jobs: Vec<Job>) -> usize {
+        let invoked = jobs.len();
+        if invoked > 0 {
+            self.dispatching(|emitter| emitter.run_jobs(event, jobs));
+        }
+        invoked
+    }
+
+    /// Runs the listeners of `event` accepting `tags` with an already serialized value, returning the number
+    /// of listeners invoked.
+    pub(crate) fn deliver(&mut self, event: &str, tags: &[&str], bytes: Arc<[u8]>) -> usize {
+        let jobs = self.jobs(event, tags, &bytes);
+        // Each job shares the serialized value or a compressed frame by now.
+        drop(bytes);
+
+        self.run_prepared(event, jobs)
+    }
+
+    /// Runs a single invocation of the listener `id` of `event` according to its execution.
+    pub(crate) fn run_job(
+        &mut self,
+        event: &str,
+        id: ListenerId,
+        execution: Execution,
+        run: Box<dyn FnOnce() + Send + 'static>,
+    ) {
+        let job = self.catching(event, &Arc::from([]), Job { id, execution, run });
+        self.dispatching(|emitter| emitter.run_jobs(event, vec![job]));
+    }
+
+    /// Wraps a job of a listener of `event` to emit its panic as a dead letter carrying `payload`, if the
+    /// failures of the event's listeners are re-emitted.
+    fn catching(&self, event: &str, payload: &Arc<[u8]>, job: Job) -> Job {
+        if !self.dead_letters.catches(event) {
+            return job;
+        }
+
+        Job {
+            run: self
+                .dead_letters
+                .catching(event, job.id, self.current_seq, payload, job.run),
+            ..job
+        }
+    }
+
+    /// Prepares the invocations of `dispatches` with the serialized value, compressed if the event's
+    /// compression applies. Every emit path delivering serialized values goes through here, so that panics
+    /// become dead letters on all of them.
+    #[cfg_attr(not(feature = "compress"), allow(unused_variables))]
+    fn prepare_jobs(
+        &mut self,
+        event: &str,
+        dispatches: Vec<Dispatch>,
+        bytes: &Arc<[u8]>,
+    ) -> Vec<Job> {
+        #[cfg(feature = "compress")]
+        if !dispatches.is_empty() {
+            if let Some(frame) = self.compressed(event, bytes) {
+                let frame: Arc<[u8]> = frame.into();
+                return dispatches
+                    .into_iter()
+                    .map(|dispatch| {
+                        let job = dispatch.into_framed_job(
+                            event,
+                            Arc::clone(&frame),
+                            self.hooks.decode_errors.clone(),
+                        );
+                        self.catching(event, bytes, job)
+                    })
+                    .collect();
+            }
+        }
+
+        dispatches
+            .into_iter()
+            .map(|dispatch| self.catching(event, bytes, dispatch.into_job(bytes)))
+            .collect()
+    }
+
+    /// Emits an event like `emit`, but runs the callbacks one at a time, in registration order.
+    ///
+    /// Each pooled callback runs on its own named thread, which is joined before the next callback starts, so
+    /// callbacks never run concurrently yet stay off the calling thread. Inline listeners run on the calling
+    /// thread at their turn. Held, captured and coalesced emits are delivered later, like `emit`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("some_event", |value: String| println!("1: {}", value));
+    /// event_emitter.on("some_event", |value: String| println!("2: {}", value));
+    ///
+    /// event_emitter.emit_sequential("some_event", "Hello, world!".to_string());
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn emit_sequential<T>(&mut self, event: impl AsRef<str>, value: T)
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        let prepared = self.prepare_emit(
+            event,
+            &[],
+            || to_json(&value),
+            |emitter, encoded| emitter.serialized_jobs(event, &[], encoded),
+        );
+        let jobs = match prepared {
+            Ok((_, jobs)) => jobs,
+            Err(e) => return report_emit_error(event, &e),
+        };
+        self.dispatching(|emitter| {
+            for job in jobs {
+                match job.execution {
+                    Execution::Pooled => {
+                        let handle = emitter.spawn_tracked(event, job);
+                        if let Some(Err(e)) = handle.map(thread::JoinHandle::join) {
+                            eprintln!("Thread error: {:?}", e);
+                        }
+                    }
+                    Execution::Inline => (job.run)(),
+                }
+            }
+        });
+    }
+
+    /// Emits each value as a separate occurrence of the event, in order, to the same listeners.
+    ///
+    /// Each value consumes one call from limited listeners, exactly like calling `emit` in a loop. The callbacks
+    /// of the whole batch are started together and waited for once, rather than once per value. Each value is
+    /// held, captured or coalesced like by `emit`, and the coalesced deliveries it completes join the batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `values` - The values to pass to the event listeners, one emit each.
+    ///
+    /// # Returns
+    ///
+    /// The total number of listener invocations across the batch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("some_event", |value: u32| println!("{}", value));
+    /// assert_eq!(3, event_emitter.emit_each("some_event", vec![1_u32, 2, 3]));
+    /// ```
+    pub fn emit_each<T, I>(&mut self, event: impl AsRef<str>, values: I) -> usize
+    where
+        T: Serialize,
+        I: IntoIterator<Item = T>,
+    {
+        let event = event.as_ref();
+        let mut jobs = Vec::new();
+        for value in values {
+            let prepared = self.prepare_emit(
+                event,
+                &[],
+                || to_json(&value),
+                |emitter, encoded| emitter.serialized_jobs(event, &[], encoded),
+            );
+            match prepared {
+                Ok((_, value_jobs)) => jobs.extend(value_jobs),
+                Err(e) => report_emit_error(event, &e),
+            }
+        }
+
+        self.run_prepared(event, jobs)
+    }
+
+    /// Emits the same value to several events, serializing it only once.
+    ///
+    /// The events are emitted in slice order, each like `emit`; an event listed twice is emitted twice. The
+    /// listeners of every event share the serialized bytes. Every event is admitted and its payload checked
+    /// before any is emitted, so that if the value cannot be serialized or any of the emits is refused, no
+    /// event is emitted at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `events` - The names of the events to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The number of listeners invoked for each event, in slice order, or the error refusing the emit of one
+    /// of them, such as `EmitError::ShutDown`, `EmitError::Rejected` by a guard or `EmitError::Serialize` if
+    /// the value could not be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("config.changed", |value: String| println!("{}", value));
+    /// event_emitter.on("config.changed.db", |value: String| println!("db: {}", value));
+    ///
+    /// let counts = event_emitter
+    ///     .emit_shared(&["config.changed", "config.changed.db", "config.changed.net"], "v2".to_string())
+    ///     .unwrap();
+    /// assert_eq!(vec![1, 1, 0], counts);
+    /// ```
+    pub fn emit_shared<T>(&mut self, events: &[&str], value: T) -> Result<Vec<usize>, EmitError>
+    where
+        T: Serialize,
+    {
+        let mut admitted = Vec::with_capacity(events.len());
+        for event in events {
+            admitted.push(self.admit_emit(event)?);
+        }
+        if !admitted.contains(&true) {
+            return Ok(vec![0; events.len()]);
+        }
+
+        let bytes: Arc<[u8]> = to_json(&value)?.into();
+        let encode = || Ok(bytes.to_vec());
+        let mut validated = Vec::with_capacity(events.len());
+        for (event, admitted) in events.iter().zip(admitted) {
+            let encoded = if admitted {
+                Some(self.validate_emit(event, Encoded::Lazy(encode))?)
+            } else {
+                None
+            };
+            validated.push(encoded);
+        }
+
+        let mut invoked = Vec::with_capacity(events.len());
+        for (event, encoded) in events.iter().zip(validated) {
+            let Some(encoded) = encoded else {
+                invoked.push(0);
+                continue;
+            };
+            let (_, jobs) = self.emit_validated(event, &[], encoded, |emitter, encoded| {
+                emitter.shared_jobs(event, &bytes, encoded)
+            })?;
+            invoked.push(self.run_prepared(event, jobs));
+        }
+        Ok(invoked)
+    }
+
+    /// Prepares the jobs of an `emit_shared` of `event`, handing the shared bytes to the listeners. Events whose
+    /// payloads are held, validated, recorded, captured or coalesced get their own copy.
+    fn shared_jobs<E>(
+        &mut self,
+        event: &str,
+        bytes: &Arc<[u8]>,
+        encoded: Encoded<E>,
+    ) -> Result<Vec<Job>, EmitError>
+    where
+        E: FnOnce() -> Result<Vec<u8>, EmitError>,
+    {
+        match encoded {
+            Encoded::Lazy(_) => {
+                self.stats.record_bytes(bytes.len());
+                Ok(self.jobs(event, &[], bytes))
+            }
+            encoded => self.serialized_jobs(event, &[], encoded),
+        }
+    }
+
+    /// Delivers a value to a single listener, whatever its event, for addressed messaging.
+    ///
+    /// The listener runs like it would for `emit`, according to its `Execution`, and the call counts toward
+    /// its limit. Disabled and exhausted listeners are not invoked, nor are listeners whose filter rejects the
+    /// value; tags do not apply. The emitter's hold and the captures and coalescing of the listener's event are
+    /// bypassed, and other listeners of the event are not invoked. The emit is still refused after `shutdown`
+    /// and sampled like any emit of the listener's event.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    /// * `value` - The value to pass to the listener.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the listener was invoked, `false` if no live listener has this ID, it could not be invoked, or
+    /// the value could not be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let alice = event_emitter.on("inbox", |message: String| println!("alice: {}", message));
+    /// event_emitter.on("inbox", |message: String| println!("bob: {}", message));
+    ///
+    /// assert!(event_emitter.emit_to_id(alice, "Hello, Alice!".to_string()));
+    /// ```
+    pub fn emit_to_id<T>(&mut self, id: ListenerId, value: T) -> bool
+    where
+        T: Serialize,
+    {
+        if self.listener_mut(id).is_none() {
+            return false;
+        }
+        let event = self.listener_events.get(&id).cloned().unwrap_or_default();
+        match self.admit_emit(&event) {
+            Ok(true) => {}
+            Ok(false) => return false,
+            Err(e) => {
+                eprintln!("Failed to emit to listener {}: {}", id, e);
+                return false;
+            }
+        }
+        let bytes = match self.encode_json(&event, &value) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("Failed to emit to listener {}: {}", id, e);
+                return false;
+            }
+        };
+
+        let seq = self.next_seq();
+        let Some(listener) = self.listener_mut(id) else {
+            return false;
+        };
+        if listener.disabled || listener.limit == Some(0) {
+            return false;
+        }
+        let Some(dispatch) = listener.dispatch_value(seq, &bytes) else {
+            return false;
+        };
+        if listener.is_spent() {
+            self.retire(&event, &[id]);
+        }
+        self.stats.record_invocations(1);
+        let jobs = self.prepare_jobs(&event, vec![dispatch], &bytes.into());
+        self.dispatching(|emitter| emitter.run_jobs(&event, jobs));
+        true
+    }
+
+    /// Selects the listeners of `event` that should receive `bytes`, consuming one call from each limited listener.
+    ///
+    /// Listeners whose limit this selection exhausts are removed right away, and those whose limit was already
+    /// exhausted are removed instead of being selected. The selected listeners are returned in the emitter's
+    /// dispatch order.
+    pub(crate) fn take_dispatches(
+        &mut self,
+        event: &str,
+        bytes: &[u8],
+        tags: &[&str],
+    ) -> Vec<Dispatch> {
+        let mut dispatches = Vec::new();
+        self.take_listener_dispatches(event, bytes, tags, &mut dispatches);
+
+        let matching = self.matching_patterns(event);
+        let exact = dispatches.len();
+        let mut patterns: Vec<&str> = Vec::new();
+        for (_, pattern) in &matching {
+            if !patterns.contains(&pattern.as_str()) {
+                patterns.push(pattern);
+                self.take_listener_dispatches(pattern, bytes, tags, &mut dispatches);
+            }
+        }
+        // Pattern listeners run in registration order across patterns, like the listeners of an event.
+        dispatches[exact..].sort_by_key(|dispatch| {
+            matching
+                .iter()
+                .position(|(id, _)| *id == dispatch.id)
+                .unwrap_or(usize::MAX)
+        });
+
+        self.arrange(&mut dispatches);
+        self.stats.record_invocations(dispatches.len());
+        dispatches
+    }
+
+    /// Appends the dispatches of the listeners stored under `key`, the emitted event or a pattern matching it,
+    /// dropping those at the end of their limit.
+    fn take_listener_dispatches(
+        &mut self,
+        key: &str,
+        bytes: &[u8],
+        tags: &[&str],
+        dispatches: &mut Vec<Dispatch>,
+    ) {
+        let seq = self.current_seq;
+        self.select_listeners(key, bytes, tags, |listener, gated| {
+            dispatches.push(listener.dispatch(seq, gated));
+        });
+    }
+
+    /// Returns whether the listeners of an emit of `event` can run right as they are selected, with no pattern
+    /// listener to merge in, dispatch order to apply, failure to catch or payload to compress in between.
+    fn runs_in_place(&self, event: &str) -> bool {
+        #[cfg(feature = "compress")]
+        if self.compression.contains_key(event) {
+            return false;
+        }
+
+        self.patterns.is_empty()
+            && self.dispatch_order == DispatchOrder::Insertion
+            && !self.dead_letters.catches(event)
+    }
+
+    /// Runs the listeners of `event` accepting `bytes` on the calling thread as they are selected, without
+    /// preparing a job for each, for `sync_emit` once `runs_in_place` holds.
+    ///
+    /// Returns the number of listeners invoked.
+    fn run_in_place(&mut self, event: &str, bytes: &[u8]) -> usize {
+        let seq = self.current_seq;
+        let mut invoked = 0;
+        self.dispatching(|emitter| {
+            emitter.select_listeners(event, bytes, &[], |listener, gated| {
+                listener.invoke(seq, bytes, gated);
+                invoked += 1;
+            });
+        });
+
+        self.stats.record_invocations(invoked);
+        invoked
+    }
+
+    /// Hands each listener stored under `key` that accepts `bytes` and `tags` to `select`, once it consumed one
+    /// call from its limit, with the callback its gate bound, then drops the listeners at the end of their limit.
+    fn select_listeners<S>(&mut self, key: &str, bytes: &[u8], tags: &[&str], mut select: S)
+    where
+        S: FnMut(&mut Listener, Option<Callback>),
+    {
+        let Some(listeners) = self.listeners.get_mut(key) else {
+            return;
+        };
+
+        let mut listeners_to_remove = Vec::new();
+        let mut exhausted = Vec::new();
+        let mut limited = false;
+
+        for listener in listeners.iter_mut() {
+            listener.apply_refunds();
+            limited |= listener.limit.is_some();
+            if listener.limit == Some(0) || listener.is_removed() {
+                listeners_to_remove.push(listener.id);
+                continue;
+            }
+
+            if listener.disabled || !listener.accepts_tags(tags) {
+                continue;
+            }
+
+            let Some(gated) = listener.take_call(bytes) else {
+                continue;
+            };
+            select(listener, gated);
+            if listener.is_spent() {
+                exhausted.push(listener.id);
+            }
+        }
+
+        if limited || !listeners_to_remove.is_empty() {
+            self.reentrancy_snapshot = None;
+        }
+        self.retire(key, &exhausted);
+        if listeners_to_remove.is_empty() {
+            return;
+        }
+        let Some(listeners) = self.listeners.get_mut(key) else {
+            return;
+        };
+
+        // Removal goes by ID rather than by index, so that it never depends on positions observed earlier. A
+        // listener added again under the ID of a retired one is kept.
+        let (removed, kept): (Vec<Listener>, Vec<Listener>) =
+            std::mem::take(listeners).into_iter().partition(|listener| {
+                listeners_to_remove.contains(&listener.id)
+                    && (listener.limit == Some(0) || listener.is_removed())
+            });
+        *listeners = kept;
+        if listeners.is_empty() {
+            self.listeners.remove(key);
+        }
+        let removed: Vec<Listener> = removed
+            .into_iter()
+            .filter(|listener| !listener.retired)
+            .collect();
+        let mut listener_count = self.listener_count(key) + removed.len();
+        for listener in &removed {
+            self.listener_events.remove(&listener.id);
+            listener_count -= 1;
+            self.hooks
+                .fire_removed(key, &ListenerInfo::new(listener, listener_count));
+        }
+    }
+
+    /// Removes the listeners stored under `key` whose limit an emit just exhausted, firing the removal hooks,
+    /// while leaving them at `Some(0)` among the listeners of the event until its next emit sweeps them. Their
+    /// deliveries from that emit still run. Pattern listeners, which no emit of their pattern may ever sweep,
+    /// are dropped right away.
+    fn retire(&mut self, key: &str, ids: &[ListenerId]) {
+        for &id in ids {
+            let Some(listeners) = self.listeners.get_mut(key) else {
+                return;
+            };
+            let Some(index) = listeners
+                .iter()
+                .position(|listener| listener.id == id && !listener.is_removed())
+            else {
+                continue;
+            };
+            if listeners[index].pattern {
+                self.detach(id);
+                continue;
+            }
+
+            listeners[index].retired = true;
+            let listener_count = listeners
+                .iter()
+                .filter(|listener| !listener.is_removed())
+                .count();
+            let info = ListenerInfo::new(&listeners[index], listener_count);
+            self.listener_events.remove(&id);
+            self.listeners_changed();
+            self.hooks.fire_removed(key, &info);
+        }
+    }
+
+    /// Returns whether `emit_typed` would serialize a value of type `T` for the event right now.
+    ///
+    /// Serialization is needed if a listener that an untagged emit would invoke was not registered with
+    /// `on_typed` for type `T`, or if any listener of the event filters its values, since filters inspect the
+    /// serialized value. The pattern listeners matching the event count like those of the event. Listeners
+    /// registered with `on_typed` for `T` receive a clone instead. Held, captured and coalesced events always
+    /// need the serialized value, whatever their listeners, as do events validated against a schema, checked
+    /// by guards or recorded in a history.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_typed("count", |value: u32| println!("{}", value));
+    /// assert!(!event_emitter.needs_bytes::<u32>("count"));
+    /// assert!(event_emitter.needs_bytes::<u64>("count"));
+    ///
+    /// event_emitter.on("count", |value: u32| println!("{}", value));
+    /// assert!(event_emitter.needs_bytes::<u32>("count"));
+    /// ```
+    pub fn needs_bytes<T>(&self, event: &str) -> bool
+    where
+        T: Serialize + Clone + 'static,
+    {
+        if self.is_holding()
+            || self.captures.contains_key(event)
+            || self.coalescers.contains_key(event)
+            || self.must_serialize(event)
+        {
+            return true;
+        }
+
+        let listeners = self.typed_listeners(event);
+        listeners.iter().any(|listener| listener.filter.is_some())
+            || listeners.iter().any(|listener| {
+                !listener.is_removed()
+                    && !listener.disabled
+                    && listener.limit != Some(0)
+                    && listener.accepts_tags(&[])
+                    && Payload::<T>::typed(listener.typed_callback.as_ref()).is_none()
+            })
+    }
+
+    /// Returns the listeners an `emit_typed` of `event` may invoke: those of its name, then the pattern
+    /// listeners matching it.
+    fn typed_listeners(&self, event: &str) -> Vec<&Listener> {
+        let patterns = self.pattern_listeners(event);
+        self.listeners
+            .get(event)
+            .into_iter()
+            .flatten()
+            .chain(patterns.into_iter().map(|(_, listener)| listener))
+            .collect()
+    }
+
+    /// Emits an event, handing a clone of `value` directly to listeners registered with `on_typed` for the same type.
+    ///
+    /// The value is serialized at most once, and only if `needs_bytes::<T>` holds for the event: some selected
+    /// listener was not registered with `on_typed` for type `T`, or some listener filters its values. Held,
+    /// captured, coalesced, schema-validated, guarded and recorded events always go through the serialized path
+    /// of `emit`. Like `emit`, it reaches the pattern listeners matching the event after those of the event,
+    /// whether the event has listeners of its own or not.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_typed("count", |value: u32| println!("{}", value));
+    /// event_emitter.emit_typed("count", 10_u32);
+    /// ```
+    pub fn emit_typed<T>(&mut self, event: impl AsRef<str>, value: T)
+    where
+        T: Serialize + Clone + Send + 'static,
+    {
+        let event = event.as_ref();
+        let prepared = self.prepare_emit(
+            event,
+            &[],
+            || to_json(&value),
+            |emitter, encoded| match encoded {
+                Encoded::Lazy(_) => emitter.typed_jobs(event, &value),
+                encoded => emitter.serialized_jobs(event, &[], encoded),
+            },
+        );
+        match prepared {
+            Ok((_, jobs)) => {
+                self.run_prepared(event, jobs);
+            }
+            Err(e) => report_emit_error(event, &e),
+        }
+    }
+
+    /// Prepares the jobs of an `emit_typed` of `value` not serialized by the emit itself, handing a clone of it
+    /// to the listeners registered with `on_typed` for its type and serializing it at most once for the others.
+    fn typed_jobs<T>(&mut self, event: &str, value: &T) -> Result<Vec<Job>, EmitError>
+    where
+        T: Serialize + Clone + Send + 'static,
+    {
+        let payload = Payload::new(value);
+
+        let filtered = self
+            .typed_listeners(event)
+            .iter()
+            .any(|listener| listener.filter.is_some());
+        let filter_bytes = if filtered {
+            payload.bytes()?.to_vec()
+        } else {
+            Vec::new()
+        };
+
+        let mut shared: Option<Arc<[u8]>> = None;
+        let unserialized: Arc<[u8]> = Arc::from([]);
+        let jobs = self
+            .take_dispatches(event, &filter_bytes, &[])
+            .into_iter()
+            .filter_map(
+                |dispatch| match Payload::<T>::typed(dispatch.typed_callback.as_ref()) {
+                    Some(callback) => {
+                        let value = value.clone();
+                        let job = dispatch.job(move || callback(value));
+                        Some(self.catching(event, &unserialized, job))
+                    }
+                    None => match payload.bytes() {
+                        Ok(bytes) => {
+                            let bytes = shared.get_or_insert_with(|| bytes.into());
+                            Some(self.catching(event, bytes, dispatch.into_job(bytes)))
+                        }
+                        Err(e) => {
+                            report_emit_error(event, &e);
+                            None
+                        }
+                    },
+                },
+            )
+            .collect();
+
+        self.stats.record_bytes(payload.serialized_len());
+        Ok(jobs)
+    }
+
+    /// Coalesces the emits of the given event so that listeners receive at most one delivery per interval.
+    ///
+    /// Emits are buffered and merged according to `strategy`. The merged payload is delivered by the
+    /// first emit that happens once `interval` has elapsed since the buffering started, or earlier by
+    /// calling `flush_coalesced`. Limits are consumed once per delivery, not once per emit. This applies to
+    /// `sync_emit` as well, which runs the coalesced delivery an emit completes on the calling thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to coalesce.
+    /// * `interval` - The minimum time between two deliveries.
+    /// * `strategy` - Which of the merged payloads is delivered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::coalesce::Coalesce;
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_coalescing("mouse.move", Duration::from_millis(16), Coalesce::KeepLatest);
+    /// ```
+    pub fn set_coalescing(&mut self, event: &str, interval: Duration, strategy: Coalesce) {
+        self.coalescers
+            .entry(event.to_string())
+            .and_modify(|coalescer| {
+                coalescer.interval = interval;
+                coalescer.strategy = strategy;
+            })
+            .or_insert_with(|| Coalescer::new(interval, strategy));
+    }
+
+    /// Delivers the pending coalesced payload of the given event immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the coalesced event.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a pending payload was delivered, otherwise `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::coalesce::Coalesce;
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_coalescing("mouse.move", Duration::from_secs(1), Coalesce::KeepLatest);
+    /// event_emitter.emit("mouse.move", (10, 20));
+    /// assert!(event_emitter.flush_coalesced("mouse.move"));
+    /// ```
+    pub fn flush_coalesced(&mut self, event: &str) -> bool {
+        self.expire_buffered();
+        match self.coalescers.get_mut(event).and_then(Coalescer::take) {
+            Some(bytes) => {
+                self.deliver(event, &[], bytes.into());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops coalescing the given event, delivering its pending payload first.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the coalesced event.
+    pub fn clear_coalescing(&mut self, event: &str) {
+        self.flush_coalesced(event);
+        self.coalescers.remove(event);
+    }
+
+    /// Caps the total size of the payloads buffered by the emitter: pending coalesced payloads and held emits.
+    ///
+    /// Whenever the buffered payloads exceed `max_bytes`, the least recently buffered payload is dropped,
+    /// with a warning printed to stderr, until the total fits again. The limit is applied immediately to
+    /// the payloads already buffered.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_bytes` - The maximum number of buffered bytes, or `None` for no limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::coalesce::Coalesce;
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_buffer_limit(Some(64 * 1024));
+    /// event_emitter.set_coalescing("mouse.move", Duration::from_secs(1), Coalesce::KeepLatest);
+    /// event_emitter.emit("mouse.move", (10, 20));
+    /// assert_eq!(7, event_emitter.buffered_bytes());
+    /// ```
+    pub fn set_buffer_limit(&mut self, max_bytes: Option<usize>) {
+        self.buffer_limit = max_bytes;
+        self.enforce_buffer_limit();
+    }
+
+    /// Sets how long the buffered payloads of an event stay deliverable: held emits and pending coalesced payloads.
+    ///
+    /// Payloads buffered for longer than `ttl` are dropped when the emitter is released or the event's coalesced
+    /// payload is delivered, with a warning printed to stderr, and counted by `EmitterStats::expired`. The
+    /// surviving payloads keep their order.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    /// * `ttl` - The maximum age of a buffered payload, or `None` to keep payloads however old they get.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_buffer_ttl("price.update", Some(Duration::ZERO));
+    /// event_emitter.hold();
+    /// event_emitter.emit("price.update", 101.5);
+    /// event_emitter.emit("app.started", ());
+    /// std::thread::sleep(Duration::from_millis(1));
+    /// assert_eq!(1, event_emitter.release());
+    /// ```
+    pub fn set_buffer_ttl(&mut self, event: &str, ttl: Option<Duration>) {
+        match ttl {
+            Some(ttl) => {
+                self.buffer_ttls.insert(event.to_string(), ttl);
+            }
+            None => {
+                self.buffer_ttls.remove(event);
+            }
+        }
+    }
+
+    /// Drops the buffered payloads older than the buffer TTL of their event.
+    pub(crate) fn expire_buffered(&mut self) {
+        if self.buffer_ttls.is_empty() {
+            return;
+        }
+
+        let mut expired = Vec::new();
+        let ttls = &self.buffer_ttls;
+        self.held.retain(|emit| {
+            let is_expired = ttls
+                .get(&emit.event)
+                .is_some_and(|ttl| emit.held_at.elapsed() > *ttl);
+            if is_expired {
+                expired.push((emit.event.clone(), emit.bytes.len()));
+            }
+            !is_expired
+        });
+        for (event, coalescer) in self.coalescers.iter_mut() {
+            if let Some(ttl) = ttls.get(event) {
+                match coalescer.expire(*ttl) {
+                    0 => {}
+                    len => expired.push((event.clone(), len)),
+                }
+            }
+        }
+
+        self.stats.record_expired(expired.len());
+        for (event, len) in expired {
+            eprintln!(
+                "Buffer TTL elapsed, dropped {} buffered bytes of event '{}'",
+                len, event
+            );
+        }
+    }
+
+    /// Returns the total size in bytes of the payloads currently buffered by the emitter.
+    pub fn buffered_bytes(&self) -> usize {
+        let held_bytes: usize = self.held.iter().map(|emit| emit.bytes.len()).sum();
+        held_bytes
+            + self
+                .coalescers
+                .values()
+                .map(Coalescer::pending_len)
+                .sum::<usize>()
+    }
+
+    /// Drops the least recently buffered payloads until the buffered bytes fit within the buffer limit.
+    pub(crate) fn enforce_buffer_limit(&mut self) {
+        let Some(max_bytes) = self.buffer_limit else {
+            return;
+        };
+
+        let mut buffered_bytes = self.buffered_bytes();
+        while buffered_bytes > max_bytes {
+            let oldest_coalesced = self
+                .coalescers
+                .iter_mut()
+                .filter(|(_, coalescer)| coalescer.pending_len() > 0)
+                .min_by_key(|(_, coalescer)| coalescer.last_push);
+            let oldest_held = self.held.front().map(|emit| emit.buffered_at);
+
+            let evict_held = match (&oldest_coalesced, oldest_held) {
+                (Some((_, coalescer)), Some(held_at)) => held_at < coalescer.last_push,
+                (None, Some(_)) => true,
+                (_, None) => false,
+            };
+
+            let (event, evicted) =
+                if let Some(emit) = evict_held.then(|| self.held.pop_front()).flatten() {
+                    (emit.event, emit.bytes.len())
+                } else if let Some((event, coalescer)) = oldest_coalesced {
+                    (
+                        event.clone(),
+                        coalescer.take().map_or(0, |bytes| bytes.len()),
+                    )
+                } else {
+                    break;
+                };
+
+            buffered_bytes -= evicted;
+            eprintln!(
+                "Buffer limit of {} bytes exceeded, dropped {} buffered bytes of event '{}'",
+                max_bytes, evicted, event
+            );
+        }
+    }
+
+    /// Removes an event listener with the given ID.
+    ///
+    /// The remaining listeners of the event keep their relative order. Removing the last listener of an event
+    /// drops the event from `listeners` and `event_names`.
+    ///
+    /// # Arguments
+    ///
+    /// * `id_to_delete` - The ID of the listener to remove. Its string form is still accepted but deprecated, see
+    ///   `ToListenerId`.
+    ///
+    /// # Returns
+    ///
+    /// An option containing the ID of the removed listener if found, otherwise `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.on("some_event", |value: String| {
+    ///     println!("Received event with value: {}", value);
+    /// });
+    ///
+    /// event_emitter.remove_listener(&listener_id);
+    /// ```
+    pub fn remove_listener<I>(&mut self, id_to_delete: &I) -> Option<ListenerId>
+    where
+        I: ToListenerId + ?Sized,
+    {
+        let id_to_delete = id_to_delete.to_listener_id()?;
+        match self.detach(id_to_delete) {
+            Some(listener) => {
+                listener.removed.store(true, Ordering::Release);
+                Some(listener.id)
+            }
+            None => (self.remove_async_replier(id_to_delete)
+                || self.remove_responder(id_to_delete)
+                || self.remove_any(id_to_delete)
+                || self.remove_keyed(id_to_delete))
+            .then_some(id_to_delete),
+        }
+    }
+
+    /// Keyed listeners only exist on native targets.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn remove_keyed(&mut self, _id: ListenerId) -> bool {
+        false
+    }
+
+    /// Removes the listener with the given ID and returns it, so that it can be added back with `attach`.
+    ///
+    /// The remaining listeners of the event keep their relative order.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener to remove.
+    ///
+    /// # Returns
+    ///
+    /// The removed listener, with its callback and remaining limit, or `None` if no listener has this ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.on_limited("some_event", Some(3), |value: String| {
+    ///     println!("{}", value);
+    /// });
+    ///
+    /// let listener = event_emitter.detach(listener_id).unwrap();
+    /// assert_eq!(Some(3), listener.limit);
+    /// ```
+    pub fn detach(&mut self, id: ListenerId) -> Option<Listener> {
+        let event = self.listener_events.get(&id)?.clone();
+        let event_listeners = self.listeners.get_mut(&event)?;
+        let index = event_listeners
+            .iter()
+            .position(|listener| listener.id == id && !listener.retired)?;
+        let listener = event_listeners.remove(index);
+        let info = ListenerInfo::new(&listener, event_listeners.len());
+        if event_listeners.is_empty() {
+            self.listeners.remove(&event);
+        }
+
+        self.listeners_changed();
+        self.listener_events.remove(&id);
+        self.patterns.remove(id);
+        self.hooks.fire_removed(&event, &info);
+        Some(listener)
+    }
+
+    /// Removes every event listener whose ID is in `ids`, like `remove_listener` does for each of them.
+    ///
+    /// The remaining listeners of each event keep their relative order, and the removed listeners skip their
+    /// pending deliveries and retries.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The IDs of the listeners to remove.
+    ///
+    /// # Returns
+    ///
+    /// The number of listeners that were found and removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let first_id = event_emitter.on("some_event", |_: String| {});
+    /// let second_id = event_emitter.on("other_event", |_: String| {});
+    ///
+    /// assert_eq!(2, event_emitter.remove_listeners(&[first_id, second_id]));
+    /// ```
+    pub fn remove_listeners(&mut self, ids: &[ListenerId]) -> usize {
+        ids.iter()
+            .filter(|id| self.remove_listener(*id).is_some())
+            .count()
+    }
+
+    /// Replaces every listener of the emitter at once, returning the previous listeners.
+    ///
+    /// No emit can observe a partially updated set of listeners, which makes this suitable for reloading a
+    /// whole listener configuration. Listener lifecycle hooks are not fired for the swapped listeners.
+    ///
+    /// The previous listeners are taken out like by `take_listeners`. The new listeners are admitted like by
+    /// `attach`: after `shutdown` they are all dropped, and the listeners of a new event name beyond the cap
+    /// set with `set_max_event_names` are dropped, each refusal being reported on stderr.
+    ///
+    /// # Arguments
+    ///
+    /// * `listeners` - The new listeners, keyed by event name.
+    ///
+    /// # Returns
+    ///
+    /// The listeners that were registered before the swap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("some_event", |value: String| println!("blue: {}", value));
+    ///
+    /// let mut green = EventEmitter::new();
+    /// green.on("some_event", |value: String| println!("green: {}", value));
+    ///
+    /// let blue = event_emitter.swap_listeners(green.take_listeners());
+    /// assert_eq!(1, blue["some_event"].len());
+    /// ```
+    pub fn swap_listeners(
+        &mut self,
+        listeners: HashMap<String, Vec<Listener>>,
+    ) -> HashMap<String, Vec<Listener>> {
+        let previous = self.take_listeners();
+        for (event, mut event_listeners) in listeners {
+            if event_listeners.is_empty() {
+                continue;
+            }
+            let refusal = if self.shut_down {
+                Err(EmitError::ShutDown)
+            } else {
+                self.admit_event_name(&event)
+            };
+            if let Err(e) = refusal {
+                eprintln!(
+                    "Failed to add {} listeners to {}: {}",
+                    event_listeners.len(),
+                    event,
+                    e
+                );
+                continue;
+            }
+
+            if !self.known_events.contains(&event) {
+                self.known_events.insert(event.clone());
+            }
+            for listener in &mut event_listeners {
+                // Taken out of an emitter, the listener was marked removed until added again.
+                listener.removed.store(false, Ordering::Release);
+                self.listener_events.insert(listener.id, event.clone());
+                if listener.pattern {
+                    self.patterns.insert(&event, listener.id);
+                }
+            }
+            self.listeners.insert(event, event_listeners);
+        }
+
+        self.listeners_changed();
+        previous
+    }
+
+    /// Removes every listener of the emitter at once and returns them, without firing lifecycle hooks.
+    ///
+    /// The listeners are marked removed, so that their deliveries and retries still pending are skipped until
+    /// they are added to an emitter again with `swap_listeners` or `set_listeners`. Listeners already removed
+    /// but not dropped yet are left out.
+    ///
+    /// # Returns
+    ///
+    /// The listeners that were registered, keyed by event name.
+    pub fn take_listeners(&mut self) -> HashMap<String, Vec<Listener>> {
+        self.listeners_changed();
+        let mut listeners = std::mem::take(&mut self.listeners);
+        for listener in listeners.values().flatten() {
+            self.listener_events.remove(&listener.id);
+            self.patterns.remove(listener.id);
+        }
+        // Listeners removed but not swept yet must not come back with the others.
+        for event_listeners in listeners.values_mut() {
+            event_listeners.retain(|listener| !listener.removed.swap(true, Ordering::AcqRel));
+        }
+        listeners.retain(|_, event_listeners| !event_listeners.is_empty());
+
+        listeners
+    }
+
+    /// Replaces every listener of the emitter at once, dropping the previous listeners. See `swap_listeners`.
+    ///
+    /// # Arguments
+    ///
+    /// * `listeners` - The new listeners, keyed by event name.
+    pub fn set_listeners(&mut self, listeners: HashMap<String, Vec<Listener>>) {
+        self.swap_listeners(listeners);
+    }
+
+    /// Adds an event listener that will execute the callback a limited number of times.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `limit` - The number of times the listener should be executed.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_limited("some_event", Some(3), |value: String| {
+    ///     println!("Received event with value: {}", value);
+    /// });
+    /// ```
+    pub fn on_limited<F, T>(&mut self, event: &str, limit: Option<u64>, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let options = ListenerOptions {
+            limit,
+            ..ListenerOptions::default()
+        };
+        self.on_with_options(event, options, callback)
+    }
+
+    /// Adds an event listener that will execute the callback successfully a limited number of times.
+    ///
+    /// Unlike `on_limited`, an invocation whose callback panics does not count toward the limit: the call is
+    /// given back before the next emit of the event. The panic itself still propagates as usual.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `limit` - The number of times the callback should complete without panicking.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_limited_on_success("app.ready", 1, |value: String| {
+    ///     println!("Running migrations for {}", value);
+    /// });
+    /// ```
+    pub fn on_limited_on_success<F, T>(
+        &mut self,
+        event: &str,
+        limit: u64,
+        callback: F,
+    ) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let options = ListenerOptions {
+            limit: Some(limit),
+            limit_on_success: true,
+            ..ListenerOptions::default()
+        };
+        self.on_with_options(event, options, callback)
+    }
+
+    /// Adds an event listener that runs either on the emitting thread or on a worker thread during `emit`.
+    ///
+    /// Shorthand for `on_with_options` with only the execution mode set.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `execution` - `Execution::Inline` to run on the emitting thread, `Execution::Pooled` to run on a worker thread.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::event_emitter::Execution;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_with_execution("some_event", Execution::Inline, |value: String| {
+    ///     println!("Received event with value: {}", value);
+    /// });
+    /// ```
+    pub fn on_with_execution<F, T>(
+        &mut self,
+        event: &str,
+        execution: Execution,
+        callback: F,
+    ) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let options = ListenerOptions {
+            execution,
+            ..ListenerOptions::default()
+        };
+        self.on_with_options(event, options, callback)
+    }
+
+    /// Adds an event listener configured by the given `ListenerOptions`.
+    ///
+    /// Limits, removal and ordering behave the same regardless of the execution mode. `sync_emit`
+    /// always runs callbacks on the calling thread, whatever their execution mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `options` - The execution mode and call limit of the listener.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::event_emitter::{Execution, ListenerOptions};
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let options = ListenerOptions {
+    ///     execution: Execution::Inline,
+    ///     ..ListenerOptions::default()
+    /// };
+    /// event_emitter.on_with_options("some_event", options, |value: String| {
+    ///     println!("Received event with value on the emitting thread: {}", value);
+    /// });
+    /// ```
+    pub fn on_with_options<F, T>(
+        &mut self,
+        event: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        let parsed_callback = self.decoding_callback(event, id, callback);
+
+        self.insert_listener(event, id, options, parsed_callback, None)
+    }
+
+    /// Adds an event listener receiving any emitted value as a `serde_json::Value`.
+    ///
+    /// This is handy for debugging or exploratory code, where naming a concrete type is unnecessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_any_value("some_event", |value| println!("Received: {}", value));
+    /// ```
+    pub fn on_any_value<F>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        F: Fn(serde_json::Value) + 'static + Sync + Send,
+    {
+        self.on(event, callback)
+    }
+
+    /// Adds an event listener receiving `T::default()` in place of any value it cannot decode.
+    ///
+    /// Unlike `on`, which skips such values, the callback runs on every emit; unlike `on_result`, it cannot
+    /// tell a decoded value from a replaced one. Each replacement is still reported to the decode error hooks,
+    /// or printed to stderr if there are none, so use `on_decode_error` to be warned of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_or_default("retry.count", |count: u32| println!("Retrying {} times", count));
+    /// event_emitter.sync_emit("retry.count", "three");
+    /// ```
+    pub fn on_or_default<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        T: Default,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let parsed_callback = move |bytes: Vec<u8>| {
+            callback(
+                decode_errors
+                    .decode(&listener_event, id, &bytes)
+                    .unwrap_or_default(),
+            );
+        };
+
+        self.insert_listener(
+            event,
+            id,
+            ListenerOptions::default(),
+            Arc::new(parsed_callback),
+            None,
+        )
+    }
 
-impl EventEmitter {
-    /// Creates a new `EventEmitter` instance.
+    /// Adds an event listener receiving the payload of each emit as bytes, without decoding it.
+    ///
+    /// Payloads emitted with `emit_bytes` arrive exactly as emitted; those emitted with `emit` arrive as the
+    /// emitter's JSON encoding of the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute with the payload.
     ///
     /// # Returns
     ///
-    /// A new `EventEmitter` instance.
+    /// The ID of the newly added listener.
     ///
     /// # Examples
     ///
     /// ```
     /// use emitter_rs::EventEmitter;
-    /// let event_emitter = EventEmitter::new();
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_bytes("frame.received", |frame| println!("{:02x?}", frame));
     /// ```
-    pub fn new() -> Self {
-        Self::default()
+    pub fn on_bytes<F>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        F: Fn(Vec<u8>) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        self.insert_listener(
+            event,
+            id,
+            ListenerOptions::default(),
+            Arc::new(callback),
+            None,
+        )
     }
 
-    /// Adds an event listener with a callback that will be called whenever the given event is emitted.
+    /// Adds an event listener that filters and transforms each value before its callback, in one registration.
+    ///
+    /// `filter_map` receives each decoded value and returns what to pass to the callback, or `None` to skip
+    /// it. Values that cannot be decoded into `T` are reported to the decode error hooks and skipped, like for
+    /// `on`. Both functions run where the callback of `on` would.
     ///
     /// # Arguments
     ///
     /// * `event` - The name of the event to listen for.
-    /// * `callback` - The callback function to execute when the event is emitted.
+    /// * `filter_map` - The function transforming a value, or rejecting it with `None`.
+    /// * `callback` - The callback function to execute with each transformed value.
     ///
     /// # Returns
     ///
@@ -55,24 +2951,62 @@ impl EventEmitter {
     /// use emitter_rs::EventEmitter;
     /// let mut event_emitter = EventEmitter::new();
     ///
-    /// event_emitter.on("some_event", |value: String| {
-    ///     println!("Received event with value: {}", value);
-    /// });
+    /// event_emitter.on_filter_map(
+    ///     "sensor.reading",
+    ///     |celsius: f64| (celsius > 30.0).then(|| celsius * 9.0 / 5.0 + 32.0),
+    ///     |fahrenheit| println!("Heat warning: {}°F", fahrenheit),
+    /// );
     /// ```
-    pub fn on<F, T>(&mut self, event: &str, callback: F) -> String
+    pub fn on_filter_map<M, F, T, U>(
+        &mut self,
+        event: &str,
+        filter_map: M,
+        callback: F,
+    ) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        M: Fn(T) -> Option<U> + 'static + Sync + Send,
+        F: Fn(U) + 'static + Sync + Send,
+    {
+        self.on(event, move |value: T| {
+            if let Some(value) = filter_map(value) {
+                callback(value);
+            }
+        })
+    }
+
+    /// Wraps `callback` into a `Callback` that decodes the serialized value into `T` first.
+    ///
+    /// Values that cannot be decoded are reported to the decode error hooks instead of reaching the callback.
+    pub(crate) fn decoding_callback<F, T>(
+        &self,
+        event: &str,
+        id: ListenerId,
+        callback: F,
+    ) -> Callback
     where
         for<'de> T: Deserialize<'de>,
         F: Fn(T) + 'static + Sync + Send,
     {
-        self.on_limited(event, None, callback)
+        dispatch::decoding_callback(&self.hooks.decode_errors, event, id, callback)
     }
 
-    /// Emits an event with the given parameters, executing each callback asynchronously by spawning a new thread for each callback.
+    /// Adds an event listener that only fires on values that differ from the previously delivered one,
+    /// executing the callback a limited number of times.
+    ///
+    /// Consecutive duplicate values neither fire the callback nor count toward the limit. A value becomes the
+    /// one the next values are compared with only once delivered, so emits that do not invoke the listener,
+    /// such as `dry_emit`, leave it unchanged.
     ///
     /// # Arguments
     ///
-    /// * `event` - The name of the event to emit.
-    /// * `value` - The value to pass to the event listeners.
+    /// * `event` - The name of the event to listen for.
+    /// * `limit` - The number of distinct values the listener should be executed for.
+    /// * `callback` - The callback function to execute when a distinct value is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
     ///
     /// # Examples
     ///
@@ -80,60 +3014,53 @@ impl EventEmitter {
     /// use emitter_rs::EventEmitter;
     /// let mut event_emitter = EventEmitter::new();
     ///
-    /// event_emitter.emit("some_event", "Hello, world!".to_string());
+    /// event_emitter.on_distinct_limited("state", Some(3), |state: String| {
+    ///     println!("State changed to: {}", state);
+    /// });
     /// ```
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn emit<T>(&mut self, event: &str, value: T)
+    pub fn on_distinct_limited<F, T>(
+        &mut self,
+        event: &str,
+        limit: Option<u64>,
+        callback: F,
+    ) -> ListenerId
     where
-        T: Serialize,
+        for<'de> T: Deserialize<'de>,
+        T: PartialEq + Send + 'static,
+        F: Fn(T) + 'static + Sync + Send,
     {
-        let mut callback_handlers = Vec::new();
-
-        if let Some(listeners) = self.listeners.get_mut(event) {
-            let bytes = serde_json::to_vec(&value).unwrap();
-
-            let mut listeners_to_remove = Vec::new();
-            for (index, listener) in listeners.iter_mut().enumerate() {
-                let cloned_bytes = bytes.clone();
-                let callback = Arc::clone(&listener.callback);
-
-                match listener.limit {
-                    None => {
-                        callback_handlers.push(thread::spawn(move || {
-                            callback(cloned_bytes);
-                        }));
-                    }
-                    Some(limit) => {
-                        if limit != 0 {
-                            callback_handlers.push(thread::spawn(move || {
-                                callback(cloned_bytes);
-                            }));
-                            listener.limit = Some(limit - 1);
-                        } else {
-                            listeners_to_remove.push(index);
-                        }
-                    }
-                }
-            }
+        let id = self.ids.next_id();
+        let parsed_callback = self.decoding_callback(event, id, callback);
 
-            for index in listeners_to_remove.into_iter().rev() {
-                listeners.remove(index);
-            }
-        }
+        let gate = Arc::new(DistinctGate::<T> {
+            last_value: Mutex::new(None),
+            callback: Arc::clone(&parsed_callback),
+            decode_errors: self.hooks.decode_errors.clone(),
+            event: event.to_string(),
+            id,
+        });
+        let filter_gate = Arc::clone(&gate);
 
-        for handler in callback_handlers {
-            if let Err(e) = handler.join() {
-                eprintln!("Thread error: {:?}", e);
-            }
-        }
+        let listener = Listener {
+            filter: Some(Arc::new(move |bytes: &[u8]| filter_gate.accepts(bytes))),
+            gate: Some(gate),
+            ..Listener::new(id, limit, parsed_callback)
+        };
+        self.attach(event, listener)
     }
 
-    /// Emits an event with the given parameters, executing each callback asynchronously using `spawn_local` for WebAssembly.
+    /// Adds an event listener that receives values emitted with `emit_typed` without a serialization round-trip.
+    ///
+    /// Values emitted with `emit`, or with `emit_typed` for a different type, are still deserialized into `T`.
     ///
     /// # Arguments
     ///
-    /// * `event` - The name of the event to emit.
-    /// * `value` - The value to pass to the event listeners.
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
     ///
     /// # Examples
     ///
@@ -141,132 +3068,189 @@ impl EventEmitter {
     /// use emitter_rs::EventEmitter;
     /// let mut event_emitter = EventEmitter::new();
     ///
-    /// event_emitter.emit("some_event", "Hello, world!".to_string());
+    /// event_emitter.on_typed("some_event", |value: String| {
+    ///     println!("Received event with value: {}", value);
+    /// });
     /// ```
-    #[cfg(target_arch = "wasm32")]
-    pub fn emit<T>(&mut self, event: &str, value: T)
+    pub fn on_typed<F, T>(&mut self, event: &str, callback: F) -> ListenerId
     where
-        T: Serialize + 'static,
+        for<'de> T: Deserialize<'de>,
+        T: 'static,
+        F: Fn(T) + 'static + Sync + Send,
     {
-        if let Some(listeners) = self.listeners.get_mut(event) {
-            let bytes = serde_json::to_vec(&value).unwrap();
-            let mut listeners_to_remove = Vec::new();
+        let id = self.ids.next_id();
+        let (parsed_callback, typed_callback) =
+            dispatch::typed_callbacks(&self.hooks.decode_errors, event, id, callback);
 
-            for (index, listener) in listeners.iter_mut().enumerate() {
-                let cloned_bytes = bytes.clone();
-                let callback = Arc::clone(&listener.callback);
+        let listener = Listener {
+            typed_callback: Some(typed_callback),
+            ..Listener::new(id, None, parsed_callback)
+        };
+        self.attach(event, listener)
+    }
 
-                match listener.limit {
-                    None => {
-                        let future = async move {
-                            callback(cloned_bytes);
-                        };
-                        spawn_local(future);
-                    }
-                    Some(limit) => {
-                        if limit != 0 {
-                            let future = async move {
-                                callback(cloned_bytes);
-                            };
-                            spawn_local(future);
-                            listener.limit = Some(limit - 1);
-                        } else {
-                            listeners_to_remove.push(index);
-                        }
-                    }
-                }
-            }
+    /// Inserts a listener built from its parts under the given event and returns its ID.
+    pub(crate) fn insert_listener(
+        &mut self,
+        event: &str,
+        id: ListenerId,
+        options: ListenerOptions,
+        callback: Callback,
+        filter: Option<Filter>,
+    ) -> ListenerId {
+        let (callback, refunds) = if options.limit_on_success {
+            let (callback, refunds) = refunding_callback(callback);
+            (callback, Some(refunds))
+        } else {
+            (callback, None)
+        };
 
-            for &index in listeners_to_remove.iter().rev() {
-                listeners.remove(index);
-            }
-        }
+        let listener = Listener {
+            execution: options.execution,
+            filter,
+            include_tags: options.include_tags,
+            exclude_tags: options.exclude_tags,
+            refunds,
+            ..Listener::new(id, options.limit, callback)
+        };
+
+        self.attach(event, listener)
     }
 
-    /// Removes an event listener with the given ID.
+    /// Returns a fresh ID from the emitter's ID generator, for listeners built with `Listener::new`.
+    pub fn next_listener_id(&self) -> ListenerId {
+        self.ids.next_id()
+    }
+
+    /// Adds a listener built outside the emitter, placing it after the existing listeners of the event.
+    ///
+    /// The listener's ID must not be used by another listener of the emitter; take it from
+    /// `next_listener_id`. Listener hooks fire as for any other registration.
     ///
     /// # Arguments
     ///
-    /// * `id_to_delete` - The ID of the listener to remove.
+    /// * `event` - The name of the event to listen for.
+    /// * `listener` - The listener to add.
     ///
     /// # Returns
     ///
-    /// An option containing the ID of the removed listener if found, otherwise `None`.
+    /// The ID of the listener, even if it was refused like by `attach`.
     ///
     /// # Examples
     ///
     /// ```
+    /// use emitter_rs::event_emitter::{Callback, Listener};
     /// use emitter_rs::EventEmitter;
+    /// use std::sync::Arc;
     /// let mut event_emitter = EventEmitter::new();
-    /// let listener_id = event_emitter.on("some_event", |value: String| {
-    ///     println!("Received event with value: {}", value);
-    /// });
     ///
-    /// event_emitter.remove_listener(&listener_id);
+    /// // An adapter logging every delivery before running the wrapped callback.
+    /// let logged = |callback: Callback| -> Callback {
+    ///     Arc::new(move |bytes: Vec<u8>| {
+    ///         println!("Delivering {}", String::from_utf8_lossy(&bytes));
+    ///         callback(bytes)
+    ///     })
+    /// };
+    ///
+    /// let callback: Callback = Arc::new(|bytes: Vec<u8>| println!("{} bytes", bytes.len()));
+    /// let listener = Listener::new(event_emitter.next_listener_id(), None, logged(callback));
+    /// event_emitter.add_listener("some_event", listener);
+    /// event_emitter.sync_emit("some_event", "Hello, world!".to_string());
     /// ```
-    pub fn remove_listener(&mut self, id_to_delete: &str) -> Option<String> {
-        for (_, event_listeners) in self.listeners.iter_mut() {
-            if let Some(index) = event_listeners
-                .iter()
-                .position(|listener| listener.id == id_to_delete)
-            {
-                event_listeners.remove(index);
-                return Some(id_to_delete.to_string());
-            }
-        }
-
-        None
+    pub fn add_listener(&mut self, event: &str, listener: Listener) -> ListenerId {
+        self.attach(event, listener)
     }
 
-    /// Adds an event listener that will execute the callback a limited number of times.
+    /// Adds a listener previously removed with `detach`, keeping its callback and remaining limit.
+    ///
+    /// The listener can be attached to a different event than the one it was detached from. It is placed
+    /// after the existing listeners of the event. Decode errors of its callback keep reporting the event
+    /// it was originally registered for.
     ///
     /// # Arguments
     ///
     /// * `event` - The name of the event to listen for.
-    /// * `limit` - The number of times the listener should be executed.
-    /// * `callback` - The callback function to execute when the event is emitted.
+    /// * `listener` - The listener to add.
     ///
     /// # Returns
     ///
-    /// The ID of the newly added listener.
+    /// The ID of the listener, unchanged. A listener refused after `shutdown` or by the cap set with
+    /// `set_max_event_names` is reported on stderr and dropped, yet its ID is still returned; use `try_attach`
+    /// to tell.
     ///
     /// # Examples
     ///
     /// ```
     /// use emitter_rs::EventEmitter;
     /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.on("some_event", |value: String| println!("{}", value));
     ///
-    /// event_emitter.on_limited("some_event", Some(3), |value: String| {
-    ///     println!("Received event with value: {}", value);
-    /// });
+    /// let listener = event_emitter.detach(listener_id).unwrap();
+    /// assert_eq!(listener_id, event_emitter.attach("other_event", listener));
     /// ```
-    pub fn on_limited<F, T>(&mut self, event: &str, limit: Option<u64>, callback: F) -> String
-    where
-        for<'de> T: Deserialize<'de>,
-        F: Fn(T) + 'static + Sync + Send,
-    {
-        let id = Uuid::new_v4().to_string();
-        let parsed_callback = move |bytes: Vec<u8>| {
-            let value: T = serde_json::from_slice(&bytes).unwrap();
-            callback(value);
-        };
+    pub fn attach(&mut self, event: &str, listener: Listener) -> ListenerId {
+        let id = listener.id;
+        if let Err(e) = self.try_attach(event, listener) {
+            eprintln!("Failed to add listener to {}: {}", event, e);
+        }
+        id
+    }
 
-        let listener = Listener {
-            id: id.clone(),
-            limit,
-            callback: Arc::new(parsed_callback),
-        };
+    /// Adds a listener like `attach`, returning the reason instead of reporting it if the listener is refused.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `listener` - The listener to add.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the listener, or `EmitError::ShutDown` after `shutdown` and `EmitError::TooManyEventNames` if
+    /// the event is a new name beyond the cap set with `set_max_event_names`, in which case the listener is
+    /// dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::error::EmitError;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.on("some_event", |value: String| println!("{}", value));
+    ///
+    /// let listener = event_emitter.detach(listener_id).unwrap();
+    /// event_emitter.shutdown();
+    /// assert_eq!(Err(EmitError::ShutDown), event_emitter.try_attach("some_event", listener));
+    /// ```
+    pub fn try_attach(&mut self, event: &str, listener: Listener) -> Result<ListenerId, EmitError> {
+        let id = listener.id;
+        if self.shut_down {
+            return Err(EmitError::ShutDown);
+        }
+        self.admit_event_name(event)?;
+        if !self.known_events.contains(event) {
+            self.known_events.insert(event.to_string());
+        }
 
-        match self.listeners.get_mut(event) {
-            Some(callbacks) => {
-                callbacks.push(listener);
-            }
-            None => {
-                self.listeners.insert(event.to_string(), vec![listener]);
-            }
+        self.listeners_changed();
+        self.listener_events.insert(id, event.to_string());
+        if listener.pattern && pattern::is_pattern(event) {
+            self.patterns.insert(event, id);
+        }
+        let callbacks = self.listeners.entry(event.to_string()).or_default();
+        callbacks.push(listener);
+        if self.max_listeners > 0 && callbacks.len() == self.max_listeners + 1 {
+            eprintln!(
+                "Possible listener leak: {} listeners added to event '{}', above the limit of {} set with set_max_listeners",
+                callbacks.len(),
+                event,
+                self.max_listeners
+            );
         }
 
-        id
+        let info = ListenerInfo::new(&callbacks[callbacks.len() - 1], callbacks.len());
+        self.hooks.fire_added(event, &info);
+
+        Ok(id)
     }
 
     /// Adds an event listener that will execute the callback only once.
@@ -290,7 +3274,7 @@ impl EventEmitter {
     ///     println!("Received event with value: {}", value);
     /// });
     /// ```
-    pub fn once<F, T>(&mut self, event: &str, callback: F) -> String
+    pub fn once<F, T>(&mut self, event: &str, callback: F) -> ListenerId
     where
         for<'de> T: Deserialize<'de>,
         F: Fn(T) + 'static + Sync + Send,
@@ -300,10 +3284,19 @@ impl EventEmitter {
 
     /// Emits an event with the given parameters synchronously, executing each callback in the order they were inserted.
     ///
+    /// Limits are honored exactly like `emit`: each delivery consumes one call, and exhausted listeners are
+    /// removed. This is a breaking change: `sync_emit` used to take `&self` and ignore limits altogether.
+    ///
     /// # Arguments
     ///
-    /// * `event` - The name of the event to emit.
-    /// * `value` - The value to pass to the event listeners.
+    /// * `event` - The name of the event to emit, as a `&str`, `String` or any other `AsRef<str>`.
+    /// * `value` - The value to pass to the event listeners. It is only serialized, so it can be passed by
+    ///   reference to keep using it afterwards.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the emit, assigned per emitter across all events and passed to `on_seq` listeners,
+    /// or 0 if the emit failed or was sampled out.
     ///
     /// # Examples
     ///
@@ -320,17 +3313,221 @@ impl EventEmitter {
     ///
     /// event_emitter.sync_emit("some_event", "Hello, world!".to_string());
     /// ```
-    pub fn sync_emit<T>(&self, event: &str, value: T)
+    pub fn sync_emit<T>(&mut self, event: impl AsRef<str>, value: T) -> u64
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        self.try_sync_emit(event, value).unwrap_or_else(|e| {
+            report_emit_error(event, &e);
+            0
+        })
+    }
+
+    /// Emits an event like `sync_emit`, returning the error instead of printing it if the value cannot be serialized.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the emit, 0 if it was sampled out, or `EmitError::Serialize` if the value could
+    /// not be serialized, in which case no listener is invoked. With `SerializeFailure::Skip`, such a failure is
+    /// printed instead, and 0 returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("some_event", |value: String| println!("{}", value));
+    ///
+    /// assert!(event_emitter.try_sync_emit("some_event", "Hello, world!".to_string()).is_ok());
+    /// ```
+    pub fn try_sync_emit<T>(&mut self, event: impl AsRef<str>, value: T) -> Result<u64, EmitError>
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        match self.sync_emit_with(event, || value) {
+            Ok((seq, _)) => Ok(seq),
+            Err(e) => self.apply_serialize_failure(event, Err(e)).map(|()| 0),
+        }
+    }
+
+    /// Emits the value built by `make` synchronously like `try_sync_emit`, before the serialize failure policy
+    /// applies. The value is only built if it is going to be used.
+    ///
+    /// Returns the sequence number of the emit and the number of listeners invoked.
+    fn sync_emit_with<T, F>(&mut self, event: &str, make: F) -> Result<(u64, usize), EmitError>
+    where
+        T: Serialize,
+        F: FnOnce() -> T,
+    {
+        let mut in_place = None;
+        let (seq, jobs) = self.prepare_emit(
+            event,
+            &[],
+            || to_json(&make()),
+            |emitter, encoded| {
+                if !emitter.runs_in_place(event) {
+                    return emitter.serialized_jobs(event, &[], encoded);
+                }
+                let bytes = encoded.into_bytes(&mut emitter.stats)?;
+                in_place = Some(emitter.run_in_place(event, &bytes));
+                Ok(Vec::new())
+            },
+        )?;
+        if let Some(invoked) = in_place {
+            return Ok((seq, invoked));
+        }
+
+        let invoked = jobs.len();
+        if invoked > 0 {
+            self.dispatching(|_| {
+                for job in jobs {
+                    (job.run)();
+                }
+            });
+        }
+
+        Ok((seq, invoked))
+    }
+
+    /// Emits a dynamic `serde_json::Value` synchronously, without defining a type for it.
+    ///
+    /// Typed listeners receive the value converted into their own type; listeners whose type is
+    /// incompatible skip it and report a decode error instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use serde_json::json;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("some_event", |value: u32| println!("{}", value));
+    /// event_emitter.sync_emit_value("some_event", json!(10));
+    /// ```
+    pub fn sync_emit_value(&mut self, event: impl AsRef<str>, value: serde_json::Value) {
+        let event = event.as_ref();
+        self.sync_emit(event, value);
+    }
+
+    /// Invokes the listeners of an event synchronously, without consuming their limits, for probing them in tests.
+    ///
+    /// The listeners of the patterns matching the event are invoked after those of the event, like by `emit`.
+    ///
+    /// This deliberately bypasses limit accounting: limits are not decremented and no listener is removed,
+    /// so `once` and limited listeners can be probed repeatedly. Listeners whose limit is already exhausted
+    /// are skipped, as are values rejected by a listener's filter. Captures and coalescing do not apply.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The number of listeners invoked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.once("some_event", |value: String| println!("{}", value));
+    ///
+    /// assert_eq!(1, event_emitter.dry_emit("some_event", "probe".to_string()));
+    /// assert_eq!(1, event_emitter.dry_emit("some_event", "probe".to_string()));
+    /// ```
+    pub fn dry_emit<T>(&self, event: impl AsRef<str>, value: T) -> usize
     where
         T: Serialize,
     {
-        if let Some(listeners) = self.listeners.get(event) {
-            let bytes = serde_json::to_vec(&value).unwrap();
+        let event = event.as_ref();
+        let patterns = self.pattern_listeners(event);
+        let listeners: Vec<&Listener> = self
+            .listeners
+            .get(event)
+            .into_iter()
+            .flatten()
+            .chain(patterns.into_iter().map(|(_, listener)| listener))
+            .collect();
+        if listeners.is_empty() {
+            return 0;
+        }
 
-            for listener in listeners {
-                let callback = Arc::clone(&listener.callback);
-                callback(bytes.clone());
+        let bytes = match to_json(&value) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                report_emit_error(event, &e);
+                return 0;
             }
+        };
+        let mut invoked = 0;
+        for listener in listeners {
+            if !listener.is_reachable()
+                || listener
+                    .filter
+                    .as_ref()
+                    .is_some_and(|filter| !filter(&bytes))
+            {
+                continue;
+            }
+
+            (listener.bound_callback(self.current_seq))(bytes.clone());
+            invoked += 1;
         }
+
+        invoked
+    }
+}
+
+/// Lists the events of the emitter in name order, with the ID and limit of each of their listeners.
+impl fmt::Debug for EventEmitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let listeners: BTreeMap<&str, Vec<&Listener>> = self
+            .listeners
+            .iter()
+            .map(|(event, listeners)| {
+                let live = listeners.iter().filter(|listener| !listener.is_removed());
+                (event.as_str(), live.collect())
+            })
+            .collect();
+
+        f.debug_struct("EventEmitter")
+            .field("listeners", &listeners)
+            .field("holding", &self.is_holding())
+            .field("coalesced_events", &self.coalescers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Summarizes the emitter as the number of events with listeners and the total number of listeners,
+/// e.g. `EventEmitter{3 events, 7 listeners}`.
+impl fmt::Display for EventEmitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts: Vec<usize> = self
+            .listeners
+            .keys()
+            .map(|event| self.listener_count(event))
+            .filter(|count| *count > 0)
+            .collect();
+        let events = counts.len();
+        let listeners: usize = counts.iter().sum();
+        write!(
+            f,
+            "EventEmitter{{{} events, {} listeners}}",
+            events, listeners
+        )
     }
 }
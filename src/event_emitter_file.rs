@@ -1,7 +1,14 @@
+use crate::deferred::Deferred;
 use crate::EventEmitter;
 use lazy_static::lazy_static;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 lazy_static! {
-    pub static ref EVENT_EMITTER: Mutex<EventEmitter> = Mutex::new(EventEmitter::new());
+    pub static ref EVENT_EMITTER: Mutex<EventEmitter> = {
+        let mut emitter = EventEmitter::new();
+        emitter.deferred = Arc::clone(&GLOBAL_DEFERRED);
+        Mutex::new(emitter)
+    };
+    /// The deferred emits of `EVENT_EMITTER`, reachable without its lock.
+    pub(crate) static ref GLOBAL_DEFERRED: Arc<Deferred> = Arc::default();
 }
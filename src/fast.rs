@@ -0,0 +1,147 @@
+//! A minimal emitter for hot paths that only need to add listeners, emit synchronously and remove listeners.
+
+use crate::dispatch::{self, AnyCallback, Payload};
+use crate::event_emitter::{report_emit_error, Callback};
+use crate::hooks::DecodeErrorHooks;
+use crate::id::{IdGenerator, ListenerId, SequentialGenerator};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A listener of a `FastEmitter`.
+struct FastListener {
+    id: ListenerId,
+    callback: Callback,
+    typed_callback: AnyCallback,
+}
+
+/// A stripped-down emitter without limits, filters, threads or hooks.
+///
+/// Listeners receive a clone of the emitted value when its type matches theirs, so the value is only
+/// serialized for listeners of another type. Events are kept in a vector sorted by name. Decode errors
+/// are printed to stderr. Use `EventEmitter` for everything else.
+#[derive(Default)]
+pub struct FastEmitter {
+    events: Vec<(Arc<str>, Vec<FastListener>)>,
+    ids: SequentialGenerator,
+}
+
+impl FastEmitter {
+    /// Creates a new `FastEmitter` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an event listener with a callback that will be called whenever the given event is emitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::fast::FastEmitter;
+    /// let mut fast_emitter = FastEmitter::new();
+    ///
+    /// fast_emitter.on("some_event", |value: String| {
+    ///     println!("Received event with value: {}", value);
+    /// });
+    /// ```
+    pub fn on<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        T: 'static,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        let (callback, typed_callback) =
+            dispatch::typed_callbacks(&DecodeErrorHooks::default(), event, id, callback);
+        let listener = FastListener {
+            id,
+            callback,
+            typed_callback,
+        };
+
+        match self.position(event) {
+            Ok(index) => self.events[index].1.push(listener),
+            Err(index) => self
+                .events
+                .insert(index, (Arc::from(event), vec![listener])),
+        }
+
+        id
+    }
+
+    /// Emits an event synchronously, executing each callback on the calling thread in the order they were inserted.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::fast::FastEmitter;
+    /// let mut fast_emitter = FastEmitter::new();
+    ///
+    /// fast_emitter.on("some_event", |value: String| println!("{}", value));
+    /// fast_emitter.sync_emit("some_event", "Hello, world!".to_string());
+    /// ```
+    pub fn sync_emit<T>(&self, event: &str, value: T)
+    where
+        T: Serialize + Clone + 'static,
+    {
+        let Ok(index) = self.position(event) else {
+            return;
+        };
+
+        let payload = Payload::new(&value);
+        for listener in &self.events[index].1 {
+            if let Err(e) = payload.invoke(&listener.callback, Some(&listener.typed_callback)) {
+                report_emit_error(event, &e);
+            }
+        }
+    }
+
+    /// Removes an event listener with the given ID.
+    ///
+    /// No index from IDs to events is kept, so this scans the listeners of every event and costs
+    /// O(total listeners). An event is dropped once its last listener is removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id_to_delete` - The ID of the listener to remove.
+    ///
+    /// # Returns
+    ///
+    /// An option containing the ID of the removed listener if found, otherwise `None`.
+    pub fn remove_listener(&mut self, id_to_delete: ListenerId) -> Option<ListenerId> {
+        for event_index in 0..self.events.len() {
+            let listeners = &mut self.events[event_index].1;
+            if let Some(index) = listeners
+                .iter()
+                .position(|listener| listener.id == id_to_delete)
+            {
+                listeners.remove(index);
+                if listeners.is_empty() {
+                    self.events.remove(event_index);
+                }
+                return Some(id_to_delete);
+            }
+        }
+
+        None
+    }
+
+    /// Finds the event by binary search, returning where to insert it if it is missing.
+    fn position(&self, event: &str) -> Result<usize, usize> {
+        self.events
+            .binary_search_by(|(name, _)| name.as_ref().cmp(event))
+    }
+}
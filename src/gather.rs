@@ -0,0 +1,334 @@
+//! Asynchronous scatter-gather requests.
+//!
+//! Listeners added with `on_async_replier` answer an event with a future. `EventEmitter::request_all` emits
+//! a value to every replier of the event and returns a [`Gather`] future resolving with the replies that
+//! arrived before the timeout. No async runtime is required: the futures are polled by whichever executor
+//! awaits the `Gather`.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::event_emitter::{report_emit_error, to_json};
+use crate::hooks::DecodeErrorHooks;
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use web_time::Instant;
+
+/// The future returned by an async replier, resolving with its serialized reply, or `None` if the request
+/// could not be decoded or the reply encoded.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) type ReplyFuture = Pin<Box<dyn Future<Output = Option<Vec<u8>>> + Send>>;
+
+/// The future returned by an async replier, resolving with its serialized reply, or `None` if the request
+/// could not be decoded or the reply encoded.
+#[cfg(target_arch = "wasm32")]
+pub(crate) type ReplyFuture = Pin<Box<dyn Future<Output = Option<Vec<u8>>>>>;
+
+/// An async replier receiving the serialized request.
+pub(crate) type AsyncReplier = Arc<dyn Fn(Vec<u8>) -> ReplyFuture + Sync + Send + 'static>;
+
+/// A reply that has not arrived yet.
+struct PendingReply {
+    listener_id: ListenerId,
+    future: ReplyFuture,
+}
+
+/// A future resolving with the replies to a `request_all`, in the registration order of their repliers.
+///
+/// Replies still pending once the timeout elapses are dropped, as are replies that cannot be decoded into
+/// `R`, which are reported to the decode error hooks.
+pub struct Gather<R> {
+    event: String,
+    pending: Vec<Option<PendingReply>>,
+    replies: Vec<Option<R>>,
+    deadline: Deadline,
+    decode_errors: DecodeErrorHooks,
+}
+
+// The replies are never pinned, so the `Gather` can move whether `R` is `Unpin` or not.
+impl<R> Unpin for Gather<R> {}
+
+impl<R> Future for Gather<R>
+where
+    R: DeserializeOwned,
+{
+    type Output = Vec<R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Vec<R>> {
+        let gather = self.get_mut();
+        for (slot, reply) in gather.pending.iter_mut().zip(gather.replies.iter_mut()) {
+            let Some(pending) = slot else {
+                continue;
+            };
+            if let Poll::Ready(bytes) = pending.future.as_mut().poll(cx) {
+                *reply = bytes.and_then(|bytes| {
+                    gather
+                        .decode_errors
+                        .decode(&gather.event, pending.listener_id, &bytes)
+                });
+                *slot = None;
+            }
+        }
+
+        let done = gather.pending.iter().all(Option::is_none);
+        if done || gather.deadline.poll_elapsed(cx) {
+            gather.pending.clear();
+            return Poll::Ready(gather.replies.drain(..).flatten().collect());
+        }
+
+        Poll::Pending
+    }
+}
+
+/// The timeout of a `Gather`, waking the awaiting task once it elapses.
+struct Deadline {
+    at: Instant,
+    waker: Arc<Mutex<Option<Waker>>>,
+    armed: bool,
+}
+
+impl Deadline {
+    fn after(timeout: Duration) -> Self {
+        Self {
+            at: Instant::now() + timeout,
+            waker: Arc::new(Mutex::new(None)),
+            armed: false,
+        }
+    }
+
+    /// Returns whether the deadline has passed, otherwise arranges for the task to be woken when it does.
+    fn poll_elapsed(&mut self, cx: &mut Context<'_>) -> bool {
+        let Some(remaining) = self.at.checked_duration_since(Instant::now()) else {
+            return true;
+        };
+        if remaining.is_zero() {
+            return true;
+        }
+
+        *self.waker.lock().unwrap_or_else(PoisonError::into_inner) = Some(cx.waker().clone());
+        if !self.armed {
+            self.armed = true;
+            wake_after(remaining, Arc::clone(&self.waker));
+        }
+        false
+    }
+}
+
+/// Wakes the stored waker, if any, once `delay` has elapsed.
+#[cfg(not(target_arch = "wasm32"))]
+fn wake_after(delay: Duration, waker: Arc<Mutex<Option<Waker>>>) {
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        if let Some(waker) = waker.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            waker.wake();
+        }
+    });
+}
+
+/// Wakes the stored waker, if any, once `delay` has elapsed.
+#[cfg(target_arch = "wasm32")]
+fn wake_after(delay: Duration, waker: Arc<Mutex<Option<Waker>>>) {
+    let handler = wasm_bindgen::closure::Closure::once_into_js(move || {
+        if let Some(waker) = waker.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            waker.wake();
+        }
+    });
+    let millis = i32::try_from(delay.as_millis()).unwrap_or(i32::MAX);
+    crate::scheduling::set_timeout(&handler, millis);
+}
+
+impl EventEmitter {
+    /// Adds a replier answering `request_all` on the given event with the output of an async callback.
+    ///
+    /// Repliers are not invoked by the other emit methods. A reply that cannot be serialized is reported to the
+    /// decode error hooks and left out of the gathered replies.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to answer.
+    /// * `callback` - The async function computing the reply to a request.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added replier, which `remove_listener` accepts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_async_replier("price.quote", |item: String| async move { item.len() as u32 * 10 });
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_async_replier<F, Fut, T, R>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) -> Fut + 'static + Sync + Send,
+        Fut: Future<Output = R> + Send + 'static,
+        R: Serialize,
+    {
+        let id = self.ids.next_id();
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let replier = move |bytes: Vec<u8>| -> ReplyFuture {
+            let future = decode_errors
+                .decode(&listener_event, id, &bytes)
+                .map(&callback);
+            let decode_errors = decode_errors.clone();
+            let listener_event = listener_event.clone();
+            Box::pin(async move { decode_errors.encode_reply(&listener_event, id, &future?.await) })
+        };
+
+        self.add_async_replier(event, id, Arc::new(replier))
+    }
+
+    /// Adds a replier answering `request_all` with the output of an async callback, see the native
+    /// `on_async_replier`. The returned future does not have to be `Send`.
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_async_replier<F, Fut, T, R>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) -> Fut + 'static + Sync + Send,
+        Fut: Future<Output = R> + 'static,
+        R: Serialize,
+    {
+        let id = self.ids.next_id();
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let replier = move |bytes: Vec<u8>| -> ReplyFuture {
+            let future = decode_errors
+                .decode(&listener_event, id, &bytes)
+                .map(&callback);
+            let decode_errors = decode_errors.clone();
+            let listener_event = listener_event.clone();
+            Box::pin(async move { decode_errors.encode_reply(&listener_event, id, &future?.await) })
+        };
+
+        self.add_async_replier(event, id, Arc::new(replier))
+    }
+
+    fn add_async_replier(
+        &mut self,
+        event: &str,
+        id: ListenerId,
+        replier: AsyncReplier,
+    ) -> ListenerId {
+        if self.refuses_listeners(event) {
+            return id;
+        }
+        self.async_repliers
+            .entry(event.to_string())
+            .or_default()
+            .push((id, replier));
+        id
+    }
+
+    /// Removes the async replier with the given ID, returning whether it was found.
+    pub(crate) fn remove_async_replier(&mut self, id: ListenerId) -> bool {
+        for repliers in self.async_repliers.values_mut() {
+            if let Some(index) = repliers
+                .iter()
+                .position(|(replier_id, _)| *replier_id == id)
+            {
+                repliers.remove(index);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Emits a request to the async repliers of an event and gathers their replies.
+    ///
+    /// The value is also emitted to the regular listeners of the event, like `emit`. Every replier is
+    /// invoked immediately; the returned future then waits for their replies until all have arrived or the
+    /// timeout elapses, so a slow replier cannot hang the request.
+    ///
+    /// The request is admitted like `emit`: once the emitter is shut down or if the request is sampled out, no
+    /// replier is invoked. Neither are they if the value cannot be serialized; the error is printed then.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The request passed to the repliers.
+    /// * `timeout` - How long to wait for the replies.
+    ///
+    /// # Returns
+    ///
+    /// A future resolving with the replies that arrived in time, in the registration order of their repliers,
+    /// or right away with no reply if the request was refused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_async_replier("price.quote", |item: String| async move { item.len() as u32 * 10 });
+    /// event_emitter.on_async_replier("price.quote", |_: String| async move { 25_u32 });
+    ///
+    /// let quotes = event_emitter.request_all::<_, u32>("price.quote", "apple".to_string(), Duration::from_secs(1));
+    /// # #[cfg(not(target_arch = "wasm32"))]
+    /// assert_eq!(vec![50, 25], futures::executor::block_on(quotes));
+    /// ```
+    pub fn request_all<T, R>(&mut self, event: &str, value: T, timeout: Duration) -> Gather<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let mut gather = Gather {
+            event: event.to_string(),
+            pending: Vec::new(),
+            replies: Vec::new(),
+            deadline: Deadline::after(timeout),
+            decode_errors: self.hooks.decode_errors.clone(),
+        };
+        let admitted = self
+            .admit_emit(event)
+            .and_then(|admitted| admitted.then(|| to_json(&value)).transpose());
+        let bytes = match admitted {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return gather,
+            Err(e) => {
+                report_emit_error(event, &e);
+                return gather;
+            }
+        };
+
+        gather.pending = self
+            .async_repliers
+            .get(event)
+            .into_iter()
+            .flatten()
+            .map(|(listener_id, replier)| {
+                Some(PendingReply {
+                    listener_id: *listener_id,
+                    future: replier(bytes.clone()),
+                })
+            })
+            .collect();
+        gather.replies = gather.pending.iter().map(|_| None).collect();
+        let prepared = self.emit_admitted(
+            event,
+            &[],
+            || Ok(bytes),
+            |emitter, encoded| emitter.serialized_jobs(event, &[], encoded),
+        );
+        match prepared {
+            Ok((_, jobs)) => {
+                self.run_prepared(event, jobs);
+            }
+            Err(e) => report_emit_error(event, &e),
+        }
+
+        gather
+    }
+}
@@ -0,0 +1,157 @@
+//! Convenience functions operating on the process-wide [`EVENT_EMITTER`].
+//!
+//! Unlike calling `EVENT_EMITTER.lock().unwrap()` directly, these functions never fail on a
+//! poisoned mutex: a poisoned lock is recovered and cleared before use, and synchronous callback
+//! invocation is guarded so that a panicking listener does not poison the lock in the first place.
+
+use crate::event_emitter::{report_emit_error, to_json};
+use crate::event_emitter_file::{EVENT_EMITTER, GLOBAL_DEFERRED};
+use crate::id::ListenerId;
+use crate::reentrancy::emit_locked;
+use crate::EventEmitter;
+use serde::{Deserialize, Serialize};
+use std::sync::MutexGuard;
+
+/// Locks the global emitter, recovering from a poisoned mutex instead of panicking.
+///
+/// # Returns
+///
+/// A guard giving exclusive access to the global `EventEmitter`.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::global;
+///
+/// global::lock().on("some_event", |value: String| println!("{}", value));
+/// ```
+pub fn lock() -> MutexGuard<'static, EventEmitter> {
+    EVENT_EMITTER.lock().unwrap_or_else(|poisoned| {
+        EVENT_EMITTER.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// Adds a listener to the global emitter. See [`EventEmitter::on`].
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::global;
+///
+/// global::on("some_event", |value: String| println!("{}", value));
+/// ```
+pub fn on<F, T>(event: &str, callback: F) -> ListenerId
+where
+    for<'de> T: Deserialize<'de>,
+    F: Fn(T) + 'static + Sync + Send,
+{
+    lock().on(event, callback)
+}
+
+/// Emits an event on the global emitter. See [`EventEmitter::emit`].
+///
+/// Called from a listener of the global emitter on the same thread, it applies the emitter's
+/// `ReentrancyPolicy` instead of deadlocking, and returns 0.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::global;
+///
+/// global::emit("some_event", "Hello, world!".to_string());
+/// ```
+pub fn emit<T>(event: impl AsRef<str>, value: T) -> u64
+where
+    T: Serialize,
+{
+    emit_locked(
+        &EVENT_EMITTER,
+        event.as_ref(),
+        value,
+        |emitter, event, value| emitter.emit(event, value),
+    )
+}
+
+/// Emits an event synchronously on the global emitter. See [`EventEmitter::sync_emit`].
+///
+/// A panic raised by a listener is propagated to the caller only after the lock has been
+/// released, so it does not poison the global emitter. Called from a listener of the global emitter
+/// on the same thread, it applies the emitter's `ReentrancyPolicy` instead of deadlocking, and
+/// returns 0.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::global;
+///
+/// global::sync_emit("some_event", "Hello, world!".to_string());
+/// ```
+pub fn sync_emit<T>(event: impl AsRef<str>, value: T) -> u64
+where
+    T: Serialize,
+{
+    emit_locked(
+        &EVENT_EMITTER,
+        event.as_ref(),
+        value,
+        |emitter, event, value| emitter.sync_emit(event, value),
+    )
+}
+
+/// Emits an event on the global emitter, or defers it if the global emitter is dispatching.
+///
+/// Use it to emit from code that may run inside a listener callback, such as a `Drop` impl, where
+/// `global::emit` would deadlock on the lock held by the emit in progress. A deferred emit runs once every
+/// listener of the triggering emit has returned, before the triggering `emit` or `sync_emit` returns.
+/// Deferred emits run in enqueue order, and those deferred by their own listeners run after them.
+///
+/// # Returns
+///
+/// The sequence number of the emit if it happened right away, or `None` if it was deferred or the value
+/// could not be serialized or emitted.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::global;
+///
+/// struct Resource(&'static str);
+///
+/// impl Drop for Resource {
+///     fn drop(&mut self) {
+///         global::emit_deferred("resource.released", self.0);
+///     }
+/// }
+///
+/// global::on("job.done", |_: ()| drop(Resource("socket")));
+/// global::on("resource.released", |name: String| println!("Released {}", name));
+/// global::emit("job.done", ());
+/// ```
+pub fn emit_deferred<T>(event: impl AsRef<str>, value: T) -> Option<u64>
+where
+    T: Serialize,
+{
+    let event = event.as_ref();
+    let bytes = match to_json(&value) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            report_emit_error(event, &e);
+            return None;
+        }
+    };
+
+    let bytes = GLOBAL_DEFERRED.defer(event, bytes).err()?;
+    match lock().emit_encoded(event, &[], || Ok(bytes)) {
+        Ok((seq, _)) => Some(seq),
+        Err(e) => {
+            report_emit_error(event, &e);
+            None
+        }
+    }
+}
+
+/// Removes a listener from the global emitter. See [`EventEmitter::remove_listener`].
+pub fn remove_listener(id_to_delete: ListenerId) -> Option<ListenerId> {
+    lock().remove_listener(&id_to_delete)
+}
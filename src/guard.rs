@@ -0,0 +1,172 @@
+//! Guards deciding whether an emit is delivered at all, for business rules that reject emits.
+//!
+//! Guards see the serialized payload, so they work whatever the codec, and run before the emit is held,
+//! captured, coalesced or delivered, so a rejected emit consumes no listener limit. The global guards run
+//! first, then the guards of the event, each in the order they were added; the first rejection stops the
+//! others from running.
+
+use crate::error::EmitError;
+use crate::EventEmitter;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+/// A guard called with the event name and the serialized payload of an emit, rejecting it with an error.
+pub type Guard = Arc<dyn Fn(&str, &[u8]) -> Result<(), GuardError> + Sync + Send + 'static>;
+
+/// The error returned by a guard to reject an emit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardError(String);
+
+impl GuardError {
+    /// Creates an error with the given message.
+    pub fn new(message: impl Display) -> Self {
+        Self(message.to_string())
+    }
+
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for GuardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for GuardError {}
+
+impl EventEmitter {
+    /// Adds a guard deciding whether the emits of an event are delivered.
+    ///
+    /// When a guard returns `Err`, the emit is rejected: no listener runs, `try_emit` and `try_sync_emit`
+    /// return `EmitError::Rejected`, and the other emit methods report the rejection on stderr. Payloads of
+    /// guarded events are always serialized, even if no listener needs them.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to guard.
+    /// * `guard` - The function to call with the event name and the serialized payload of each emit.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the guard, to be used with `remove_guard`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::guard::GuardError;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("order.placed", |amount: u32| println!("Order of {}", amount));
+    ///
+    /// event_emitter.add_guard("order.placed", |_, payload| {
+    ///     let amount: u32 = serde_json::from_slice(payload).map_err(GuardError::new)?;
+    ///     if amount > 1000 {
+    ///         return Err(GuardError::new("orders above 1000 need approval"));
+    ///     }
+    ///     Ok(())
+    /// });
+    ///
+    /// assert!(event_emitter.try_emit("order.placed", 10_u32).is_ok());
+    /// assert!(event_emitter.try_emit("order.placed", 5000_u32).is_err());
+    /// ```
+    pub fn add_guard<F>(&mut self, event: &str, guard: F) -> String
+    where
+        F: Fn(&str, &[u8]) -> Result<(), GuardError> + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id().to_string();
+        self.guards
+            .entry(event.to_string())
+            .or_default()
+            .push((id.clone(), Arc::new(guard)));
+        id
+    }
+
+    /// Adds a guard deciding whether the emits of every event are delivered, run before the guards of the event.
+    ///
+    /// Once a global guard is added, every payload is serialized, even if no listener needs it.
+    ///
+    /// # Arguments
+    ///
+    /// * `guard` - The function to call with the event name and the serialized payload of each emit.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the guard, to be used with `remove_guard`.
+    pub fn add_global_guard<F>(&mut self, guard: F) -> String
+    where
+        F: Fn(&str, &[u8]) -> Result<(), GuardError> + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id().to_string();
+        self.global_guards.push((id.clone(), Arc::new(guard)));
+        id
+    }
+
+    /// Removes a guard added with `add_guard` or `add_global_guard`.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the guard to remove.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a guard was removed.
+    pub fn remove_guard(&mut self, id: &str) -> bool {
+        if let Some(index) = self
+            .global_guards
+            .iter()
+            .position(|(guard_id, _)| guard_id == id)
+        {
+            self.global_guards.remove(index);
+            return true;
+        }
+
+        let Some((event, index)) = self.guards.iter().find_map(|(event, guards)| {
+            guards
+                .iter()
+                .position(|(guard_id, _)| guard_id == id)
+                .map(|index| (event.clone(), index))
+        }) else {
+            return false;
+        };
+        if let Some(guards) = self.guards.get_mut(&event) {
+            guards.remove(index);
+            if guards.is_empty() {
+                self.guards.remove(&event);
+            }
+        }
+        true
+    }
+
+    /// Returns whether any guard applies to `event`, so its payloads must be serialized and checked.
+    pub(crate) fn guarded(&self, event: &str) -> bool {
+        !self.global_guards.is_empty() || self.guards.contains_key(event)
+    }
+
+    /// Runs the guards of `event` on a serialized payload, returning it unless a guard rejects it.
+    pub(crate) fn check_guards(&self, event: &str, bytes: Vec<u8>) -> Result<Vec<u8>, EmitError> {
+        check_guards(&self.global_guards, &self.guards, event, bytes)
+    }
+}
+
+/// Runs the global guards, then the guards of `event` among `guards`, on a serialized payload, returning it
+/// unless a guard rejects it.
+pub(crate) fn check_guards(
+    global_guards: &[(String, Guard)],
+    guards: &HashMap<String, Vec<(String, Guard)>>,
+    event: &str,
+    bytes: Vec<u8>,
+) -> Result<Vec<u8>, EmitError> {
+    let event_guards = guards.get(event).map_or(&[][..], Vec::as_slice);
+    for (guard_index, (_, guard)) in global_guards.iter().chain(event_guards).enumerate() {
+        guard(event, &bytes).map_err(|source| EmitError::Rejected {
+            guard_index,
+            source,
+        })?;
+    }
+
+    Ok(bytes)
+}
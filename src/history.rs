@@ -0,0 +1,159 @@
+//! Opt-in history of the most recent payloads emitted on an event, for debugging.
+//!
+//! Each event with history enabled keeps its last payloads in a ring buffer of fixed capacity, along with
+//! the sequence number and time of their emit. Entries record emits, not deliveries: an emit is recorded
+//! whether or not any listener receives it, including held, captured and coalesced emits. Events without
+//! history cost a map lookup per emit.
+
+use crate::EventEmitter;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use web_time::SystemTime;
+
+/// A payload recorded in the history of an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    seq: u64,
+    at: SystemTime,
+    payload: Arc<[u8]>,
+}
+
+impl HistoryEntry {
+    /// Returns the sequence number of the emit, as returned by `emit`.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Returns the wall-clock time of the emit.
+    pub fn at(&self) -> SystemTime {
+        self.at
+    }
+
+    /// Returns the serialized payload of the emit.
+    pub fn bytes(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Decodes the payload as a `serde_json::Value`.
+    ///
+    /// # Returns
+    ///
+    /// The decoded payload, or the error if it is not JSON, as with `emit_cbor`.
+    pub fn value(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::from_slice(&self.payload)
+    }
+}
+
+/// The ring buffer of the recent payloads of an event.
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    capacity: usize,
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl History {
+    fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    fn record(&mut self, entry: HistoryEntry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+impl EventEmitter {
+    /// Starts recording the last `capacity` payloads emitted on an event, returned by `history`.
+    ///
+    /// Calling it again for the same event changes the capacity, dropping the oldest entries if it shrinks.
+    /// Recording forces the payloads of the event to be serialized, even when no listener needs them.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    /// * `capacity` - The number of payloads to keep, at least 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.enable_history("order.state", 20);
+    /// event_emitter.emit("order.state", "paid");
+    /// event_emitter.emit("order.state", "shipped");
+    ///
+    /// let states: Vec<_> = event_emitter
+    ///     .history("order.state")
+    ///     .iter()
+    ///     .map(|entry| entry.value().unwrap())
+    ///     .collect();
+    /// assert_eq!(vec!["paid", "shipped"], states);
+    /// ```
+    pub fn enable_history(&mut self, event: &str, capacity: usize) {
+        self.history
+            .entry(event.to_string())
+            .or_default()
+            .resize(capacity.max(1));
+    }
+
+    /// Stops recording the payloads of an event and frees its history.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    pub fn disable_history(&mut self, event: &str) {
+        self.history.remove(event);
+    }
+
+    /// Returns the recorded payloads of an event, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    ///
+    /// # Returns
+    ///
+    /// The recorded entries, sharing their payload with the history, or an empty list if the event has no
+    /// history.
+    pub fn history(&self, event: &str) -> Vec<HistoryEntry> {
+        self.history
+            .get(event)
+            .map(|history| history.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns whether the payloads of `event` must be serialized even if no listener needs them, because they
+    /// are validated against a schema, checked by guards or limits, or recorded.
+    pub(crate) fn must_serialize(&self, event: &str) -> bool {
+        self.validates(event)
+            || self.guarded(event)
+            || self.limits_payloads()
+            || self.records_payloads(event)
+    }
+
+    /// Returns whether the payloads of `event` are recorded, in its history or a recording scope.
+    pub(crate) fn records_payloads(&self, event: &str) -> bool {
+        self.history.contains_key(event) || self.recording.is_some()
+    }
+
+    /// Records the payload of the emit being stamped if the event has a history, and in the recording scope.
+    pub(crate) fn record_history(&mut self, event: &str, bytes: &[u8]) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.push((event.to_string(), bytes.to_vec()));
+        }
+
+        if let Some(history) = self.history.get_mut(event) {
+            history.record(HistoryEntry {
+                seq: self.current_seq,
+                at: SystemTime::now(),
+                payload: bytes.into(),
+            });
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Startup barrier deferring every emit until the emitter is released.
+//!
+//! While an emitter holds, emits of any event are appended to a single FIFO buffer instead of being
+//! delivered, so listeners registered later during bootstrap still receive them, in emission order.
+
+use crate::event_emitter::{report_emit_error, Serialized};
+use crate::EventEmitter;
+use std::collections::VecDeque;
+use web_time::Instant;
+
+/// Whether an emitter delivers emits or holds them back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum HoldState {
+    /// Emits are delivered immediately and `hold` has never been called.
+    #[default]
+    Live,
+    /// Emits are buffered until `release` is called.
+    Holding,
+    /// `release` has been called; emits are delivered and the emitter can no longer hold.
+    Released,
+}
+
+/// An emit buffered while the emitter was holding.
+pub(crate) struct HeldEmit {
+    pub(crate) event: String,
+    pub(crate) bytes: Vec<u8>,
+    /// The tags of the emit, as passed to `emit_tagged`.
+    pub(crate) tags: Vec<String>,
+    /// The emitter's buffer clock when the emit was held.
+    pub(crate) buffered_at: u64,
+    /// The sequence number of the emit.
+    pub(crate) seq: u64,
+    /// When the emit was held, checked against the event's buffer TTL.
+    pub(crate) held_at: Instant,
+}
+
+/// The emits held by an emitter, oldest first.
+pub(crate) type HeldEmits = VecDeque<HeldEmit>;
+
+impl EventEmitter {
+    /// Puts the emitter in a holding state where every emit is buffered until `release` is called.
+    ///
+    /// Held emits count toward the buffer limit set with `set_buffer_limit`: when it is exceeded, the
+    /// oldest buffered payloads are dropped first. An emitter can only hold once: after `release`,
+    /// `hold` has no effect.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the emitter is holding, or `false` if it was already released.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.hold();
+    /// event_emitter.emit("app.started", "early".to_string());
+    /// event_emitter.on("app.started", |value: String| println!("{}", value));
+    /// assert_eq!(1, event_emitter.release());
+    /// ```
+    pub fn hold(&mut self) -> bool {
+        if self.hold_state == HoldState::Released {
+            return false;
+        }
+
+        self.hold_state = HoldState::Holding;
+        true
+    }
+
+    /// Returns whether the emitter is holding emits back.
+    pub fn is_holding(&self) -> bool {
+        self.hold_state == HoldState::Holding
+    }
+
+    /// Delivers the held emits in their original order and switches the emitter to live mode permanently.
+    ///
+    /// Held emits are delivered like `emit`, whichever method emitted them, and the event's capture and
+    /// coalescing settings apply. Held emits older than the buffer TTL of their event are dropped instead.
+    ///
+    /// # Returns
+    ///
+    /// The number of held emits released, not counting the expired ones.
+    pub fn release(&mut self) -> usize {
+        self.hold_state = HoldState::Released;
+        self.expire_buffered();
+
+        let held = std::mem::take(&mut self.held);
+        let released = held.len();
+        for emit in held {
+            let tags: Vec<&str> = emit.tags.iter().map(String::as_str).collect();
+            self.current_seq = emit.seq;
+            let bytes = Serialized::Bytes(emit.bytes);
+            match self.dispatch_encoded(&emit.event, &tags, bytes, |emitter, encoded| {
+                emitter.serialized_jobs(&emit.event, &tags, encoded)
+            }) {
+                Ok(jobs) => {
+                    self.run_prepared(&emit.event, jobs);
+                }
+                Err(e) => report_emit_error(&emit.event, &e),
+            }
+        }
+
+        released
+    }
+
+    /// Appends an emit to the held emits, dropping the oldest buffered payloads if the buffer limit is exceeded.
+    ///
+    /// Callers check `is_holding` first.
+    pub(crate) fn hold_emit(&mut self, event: &str, tags: &[&str], bytes: Vec<u8>) {
+        self.buffer_clock += 1;
+        self.held.push_back(HeldEmit {
+            event: event.to_string(),
+            bytes,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            buffered_at: self.buffer_clock,
+            seq: self.current_seq,
+            held_at: Instant::now(),
+        });
+        self.enforce_buffer_limit();
+    }
+}
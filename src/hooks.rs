@@ -0,0 +1,439 @@
+//! Hooks notified whenever a listener is added to or removed from an emitter, cannot decode a value, or fails
+//! a delivery for good, and whenever an event is emitted.
+
+use crate::event_emitter::{to_json, Execution, Listener};
+use crate::id::ListenerId;
+use crate::tracking::EmitMeta;
+use crate::EventEmitter;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// A hook called with the event name and the listener that was added or removed.
+pub type ListenerHook = Arc<dyn Fn(&str, &ListenerInfo) + Sync + Send + 'static>;
+
+/// A hook called with the event name and the sequence numbers of each emit.
+pub type EmitHook = Arc<dyn Fn(&str, &EmitMeta) + Sync + Send + 'static>;
+
+/// A hook called when an emitted value cannot be decoded into the type a listener expects.
+pub type DecodeErrorHook = Arc<dyn Fn(&DecodeError) + Sync + Send + 'static>;
+
+/// A hook called when a listener added with `on_with_retry` fails a delivery on every attempt.
+pub type ListenerFailureHook = Arc<dyn Fn(&ListenerFailure) + Sync + Send + 'static>;
+
+/// Describes an emitted value that a listener could not decode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// The name of the emitted event.
+    pub event: String,
+    /// The ID of the listener that skipped the value.
+    pub listener_id: ListenerId,
+    /// The reason the value could not be decoded.
+    pub message: String,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to decode {} for listener {}: {}",
+            self.event, self.listener_id, self.message
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Describes a delivery that a listener added with `on_with_retry` failed on every attempt of its policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenerFailure {
+    /// The name of the emitted event.
+    pub event: String,
+    /// The ID of the failing listener.
+    pub listener_id: ListenerId,
+    /// The number of times the listener was called for the delivery.
+    pub attempts: u32,
+    /// The error of the last attempt.
+    pub message: String,
+}
+
+impl fmt::Display for ListenerFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "listener {} of {} failed after {} attempts: {}",
+            self.listener_id, self.event, self.attempts, self.message
+        )
+    }
+}
+
+impl std::error::Error for ListenerFailure {}
+
+/// Describes a listener passed to lifecycle hooks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenerInfo {
+    /// The ID of the listener.
+    pub id: ListenerId,
+    /// The remaining number of calls of the listener, or `None` for no limit.
+    pub limit: Option<u64>,
+    /// Where the listener's callback runs during `emit`.
+    pub execution: Execution,
+    /// The number of listeners of the event after the change.
+    pub listener_count: usize,
+}
+
+impl ListenerInfo {
+    pub(crate) fn new(listener: &Listener, listener_count: usize) -> Self {
+        Self {
+            id: listener.id,
+            limit: listener.limit,
+            execution: listener.execution,
+            listener_count,
+        }
+    }
+}
+
+/// Decode error hooks, shared with the listener callbacks that report to them from worker threads.
+#[derive(Default, Clone)]
+pub(crate) struct DecodeErrorHooks(Arc<RwLock<Vec<(String, DecodeErrorHook)>>>);
+
+impl DecodeErrorHooks {
+    /// Decodes `bytes` into `T`, reporting a failure on behalf of the given listener.
+    pub(crate) fn decode<T>(&self, event: &str, listener_id: ListenerId, bytes: &[u8]) -> Option<T>
+    where
+        for<'de> T: Deserialize<'de>,
+    {
+        self.decode_with(event, listener_id, bytes, |bytes| {
+            serde_json::from_slice(bytes)
+        })
+    }
+
+    /// Decodes `bytes` with the given decoder, reporting a failure on behalf of the given listener.
+    pub(crate) fn decode_with<T, E, D>(
+        &self,
+        event: &str,
+        listener_id: ListenerId,
+        bytes: &[u8],
+        decoder: D,
+    ) -> Option<T>
+    where
+        E: std::fmt::Display,
+        D: FnOnce(&[u8]) -> Result<T, E>,
+    {
+        match decoder(bytes) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.report(&DecodeError {
+                    event: event.to_string(),
+                    listener_id,
+                    message: e.to_string(),
+                });
+                None
+            }
+        }
+    }
+
+    /// Serializes the reply of a responding listener, reporting a failure on its behalf like a value it could
+    /// not decode.
+    pub(crate) fn encode_reply<R>(
+        &self,
+        event: &str,
+        listener_id: ListenerId,
+        reply: &R,
+    ) -> Option<Vec<u8>>
+    where
+        R: Serialize + ?Sized,
+    {
+        match to_json(reply) {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                self.report(&DecodeError {
+                    event: event.to_string(),
+                    listener_id,
+                    message: format!("the reply could not be encoded: {}", e),
+                });
+                None
+            }
+        }
+    }
+
+    /// Calls every decode error hook, or prints the error if there are none.
+    pub(crate) fn report(&self, error: &DecodeError) {
+        let hooks = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        if hooks.is_empty() {
+            eprintln!("{}", error);
+        }
+
+        for (_, hook) in hooks.iter() {
+            hook(error);
+        }
+    }
+}
+
+/// Listener failure hooks, shared with the retries that report to them from timer threads.
+#[derive(Default, Clone)]
+pub(crate) struct ListenerFailureHooks(Arc<RwLock<Vec<(String, ListenerFailureHook)>>>);
+
+impl ListenerFailureHooks {
+    /// Calls every listener failure hook, or prints the failure if there are none.
+    pub(crate) fn report(&self, failure: &ListenerFailure) {
+        if !self.notify(failure) {
+            eprintln!("{}", failure);
+        }
+    }
+
+    /// Calls every listener failure hook, returning `false` if there are none.
+    pub(crate) fn notify(&self, failure: &ListenerFailure) -> bool {
+        let hooks = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        for (_, hook) in hooks.iter() {
+            hook(failure);
+        }
+
+        !hooks.is_empty()
+    }
+}
+
+/// The lifecycle hooks registered on an emitter, each paired with its ID.
+#[derive(Default)]
+pub(crate) struct Hooks {
+    added: Vec<(String, ListenerHook)>,
+    removed: Vec<(String, ListenerHook)>,
+    emitted: Vec<(String, EmitHook)>,
+    pub(crate) decode_errors: DecodeErrorHooks,
+    pub(crate) failures: ListenerFailureHooks,
+}
+
+impl Hooks {
+    pub(crate) fn fire_added(&self, event: &str, info: &ListenerInfo) {
+        for (_, hook) in &self.added {
+            hook(event, info);
+        }
+    }
+
+    pub(crate) fn fire_removed(&self, event: &str, info: &ListenerInfo) {
+        for (_, hook) in &self.removed {
+            hook(event, info);
+        }
+    }
+
+    pub(crate) fn fire_emitted(&self, event: &str, meta: &EmitMeta) {
+        for (_, hook) in &self.emitted {
+            hook(event, meta);
+        }
+    }
+}
+
+impl EventEmitter {
+    /// Adds a hook called every time a listener is added, whichever method registered it.
+    ///
+    /// A `listener_count` of 1 means the first listener of the event was just added.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The function to call with the event name and the added listener.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the hook, to be used with `remove_hook`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_listener_added(|event, info| {
+    ///     if info.listener_count == 1 {
+    ///         println!("First listener of {} added, starting the poller", event);
+    ///     }
+    /// });
+    /// ```
+    pub fn on_listener_added<F>(&mut self, hook: F) -> String
+    where
+        F: Fn(&str, &ListenerInfo) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id().to_string();
+        self.hooks.added.push((id.clone(), Arc::new(hook)));
+        id
+    }
+
+    /// Adds a hook called every time a listener is removed, either explicitly or because its limit was reached.
+    ///
+    /// A `listener_count` of 0 means the last listener of the event was just removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The function to call with the event name and the removed listener.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the hook, to be used with `remove_hook`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_listener_removed(|event, info| {
+    ///     if info.listener_count == 0 {
+    ///         println!("Last listener of {} removed, stopping the poller", event);
+    ///     }
+    /// });
+    /// ```
+    pub fn on_listener_removed<F>(&mut self, hook: F) -> String
+    where
+        F: Fn(&str, &ListenerInfo) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id().to_string();
+        self.hooks.removed.push((id.clone(), Arc::new(hook)));
+        id
+    }
+
+    /// Adds a hook called every time a listener skips a value it cannot decode into its expected type.
+    ///
+    /// Without any decode error hook, such errors are printed to stderr. The hook may run on a worker thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The function to call with the decode error.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the hook, to be used with `remove_hook`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_decode_error(|error| eprintln!("Skipped value: {}", error));
+    /// ```
+    pub fn on_decode_error<F>(&mut self, hook: F) -> String
+    where
+        F: Fn(&DecodeError) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id().to_string();
+        self.hooks
+            .decode_errors
+            .0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((id.clone(), Arc::new(hook)));
+        id
+    }
+
+    /// Adds a hook called every time a listener added with `on_with_retry` fails a delivery on every attempt
+    /// of its retry policy.
+    ///
+    /// Without any listener failure hook, such failures are printed to stderr. The hook may run on a worker
+    /// or timer thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The function to call with the failure.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the hook, to be used with `remove_hook`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_listener_failure(|failure| {
+    ///     eprintln!("Gave up after {} attempts: {}", failure.attempts, failure.message)
+    /// });
+    /// ```
+    pub fn on_listener_failure<F>(&mut self, hook: F) -> String
+    where
+        F: Fn(&ListenerFailure) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id().to_string();
+        self.hooks
+            .failures
+            .0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((id.clone(), Arc::new(hook)));
+        id
+    }
+
+    /// Adds a hook called every time an event is emitted, with the sequence number of the emit and of the emit
+    /// it derives from, to trace chains of emits.
+    ///
+    /// The hook is called on the emitting thread once the emit is admitted, before its listeners run, so
+    /// sampled out emits and emits refused by `shutdown`, a guard or a payload limit are not traced.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The function to call with the event name and the sequence numbers.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the hook, to be used with `remove_hook`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_emitted(|event, meta| match meta.parent {
+    ///     Some(parent) => println!("#{} {} caused by #{}", meta.seq, event, parent),
+    ///     None => println!("#{} {}", meta.seq, event),
+    /// });
+    /// ```
+    pub fn on_emitted<F>(&mut self, hook: F) -> String
+    where
+        F: Fn(&str, &EmitMeta) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id().to_string();
+        self.hooks.emitted.push((id.clone(), Arc::new(hook)));
+        id
+    }
+
+    /// Removes a hook with the given ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID returned by `on_listener_added`, `on_listener_removed`, `on_emitted`, `on_decode_error`
+    ///   or `on_listener_failure`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a hook was removed, otherwise `false`.
+    pub fn remove_hook(&mut self, id: &str) -> bool {
+        let mut decode_errors = self
+            .hooks
+            .decode_errors
+            .0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut failures = self
+            .hooks
+            .failures
+            .0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let count = self.hooks.added.len()
+            + self.hooks.removed.len()
+            + self.hooks.emitted.len()
+            + decode_errors.len()
+            + failures.len();
+        self.hooks.added.retain(|(hook_id, _)| hook_id != id);
+        self.hooks.removed.retain(|(hook_id, _)| hook_id != id);
+        self.hooks.emitted.retain(|(hook_id, _)| hook_id != id);
+        decode_errors.retain(|(hook_id, _)| hook_id != id);
+        failures.retain(|(hook_id, _)| hook_id != id);
+        count
+            != self.hooks.added.len()
+                + self.hooks.removed.len()
+                + self.hooks.emitted.len()
+                + decode_errors.len()
+                + failures.len()
+    }
+}
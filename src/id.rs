@@ -0,0 +1,198 @@
+//! Listener IDs and the generators producing them.
+
+use crate::EventEmitter;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// A cheap, copyable handle identifying a listener.
+///
+/// It is displayed and parsed as a lowercase hexadecimal number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ListenerId(pub u64);
+
+impl fmt::Display for ListenerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:x}", self.0)
+    }
+}
+
+impl FromStr for ListenerId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(ListenerId)
+    }
+}
+
+/// A listener ID as accepted by `remove_listener`.
+///
+/// `ListenerId` is the key to remove listeners with. The string forms are deprecated and only kept for code
+/// written when listener IDs were strings: they are parsed as displayed by `ListenerId`, and a string that
+/// does not parse names no listener.
+pub trait ToListenerId {
+    /// Returns the listener ID this value stands for, if any.
+    fn to_listener_id(&self) -> Option<ListenerId>;
+}
+
+impl ToListenerId for ListenerId {
+    fn to_listener_id(&self) -> Option<ListenerId> {
+        Some(*self)
+    }
+}
+
+impl ToListenerId for str {
+    fn to_listener_id(&self) -> Option<ListenerId> {
+        self.parse().ok()
+    }
+}
+
+impl ToListenerId for String {
+    fn to_listener_id(&self) -> Option<ListenerId> {
+        self.as_str().to_listener_id()
+    }
+}
+
+/// Creates the IDs of the listeners registered on an emitter.
+///
+/// Implementations must be safe to call from several threads and must not return the same ID twice.
+pub trait IdGenerator: Send + Sync {
+    /// Returns a new, unique ID.
+    fn next_id(&self) -> ListenerId;
+}
+
+/// Generates random IDs from version 4 UUIDs, unique across emitters and processes.
+#[cfg(feature = "uuid")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UuidGenerator;
+
+#[cfg(feature = "uuid")]
+impl IdGenerator for UuidGenerator {
+    fn next_id(&self) -> ListenerId {
+        ListenerId(uuid::Uuid::new_v4().as_u64_pair().1)
+    }
+}
+
+/// Generates consecutive IDs starting from 1, unique within the generator.
+#[derive(Debug, Default)]
+pub struct SequentialGenerator {
+    last: AtomicU64,
+}
+
+impl IdGenerator for SequentialGenerator {
+    fn next_id(&self) -> ListenerId {
+        ListenerId(self.last.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+}
+
+/// The ID generator of an emitter: `UuidGenerator` with the `uuid` feature, `SequentialGenerator` otherwise.
+#[derive(Clone)]
+pub(crate) struct IdSource(pub(crate) Arc<dyn IdGenerator>);
+
+impl IdSource {
+    pub(crate) fn next_id(&self) -> ListenerId {
+        self.0.next_id()
+    }
+}
+
+impl Default for IdSource {
+    #[cfg(feature = "uuid")]
+    fn default() -> Self {
+        Self(Arc::new(UuidGenerator))
+    }
+
+    #[cfg(not(feature = "uuid"))]
+    fn default() -> Self {
+        Self(Arc::new(SequentialGenerator::default()))
+    }
+}
+
+/// What `on_with_id` does when its ID is already used by a live listener, set with `set_duplicate_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateId {
+    /// The registration fails with `ListenerIdTaken`, leaving the existing listener in place.
+    #[default]
+    Reject,
+    /// The existing listener is removed, and the new one added in its stead.
+    Replace,
+}
+
+/// The error of `on_with_id` when its ID is already used by a live listener and duplicates are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListenerIdTaken(pub ListenerId);
+
+impl fmt::Display for ListenerIdTaken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "listener ID {} is already in use", self.0)
+    }
+}
+
+impl std::error::Error for ListenerIdTaken {}
+
+impl EventEmitter {
+    /// Adds an event listener with an ID chosen by the caller instead of the emitter's generator.
+    ///
+    /// Use it for idempotent registration, with an ID derived from the subscription, or to reuse the stable
+    /// IDs of an external system. If a live listener added with `on` or the like already has the ID, the
+    /// duplicate ID policy decides: `DuplicateId::Reject`, the default, fails, while `DuplicateId::Replace`
+    /// removes that listener and adds the new one after the other listeners of the event. The ID must not
+    /// otherwise collide with the IDs of the emitter's generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `id` - The ID of the listener.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the listener, or `ListenerIdTaken` if the ID is in use and duplicates are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::id::ListenerId;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let subscription = ListenerId(0x5eed);
+    /// assert!(event_emitter.on_with_id("user.created", subscription, |name: String| println!("{}", name)).is_ok());
+    /// assert!(event_emitter.on_with_id("user.created", subscription, |name: String| println!("{}", name)).is_err());
+    /// assert_eq!(1, event_emitter.listener_count("user.created"));
+    /// ```
+    pub fn on_with_id<F, T>(
+        &mut self,
+        event: &str,
+        id: ListenerId,
+        callback: F,
+    ) -> Result<ListenerId, ListenerIdTaken>
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        if self.listener(id).is_some() {
+            match self.duplicate_id {
+                DuplicateId::Reject => return Err(ListenerIdTaken(id)),
+                DuplicateId::Replace => {
+                    self.remove_listener(&id);
+                }
+            }
+        }
+
+        let parsed_callback = self.decoding_callback(event, id, callback);
+        Ok(self.insert_listener(event, id, Default::default(), parsed_callback, None))
+    }
+
+    /// Sets what `on_with_id` does when its ID is already used by a live listener, `DuplicateId::Reject` by
+    /// default.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Whether duplicates are rejected or replace the existing listener.
+    pub fn set_duplicate_id(&mut self, policy: DuplicateId) {
+        self.duplicate_id = policy;
+    }
+}
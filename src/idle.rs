@@ -0,0 +1,100 @@
+//! Waiting for the callbacks running on worker threads to complete.
+//!
+//! Every worker thread started for a listener is counted as in flight until its callback returns or panics,
+//! as is every value queued for a keyed listener until processed. The other emit methods join the threads they
+//! start before returning, so only `emit_keyed` leaves callbacks in flight once it returns.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::EventEmitter;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::Duration;
+use web_time::Instant;
+
+/// The number of callbacks in flight on worker threads, signalled whenever it drops to zero.
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    count: Mutex<usize>,
+    idle: Condvar,
+}
+
+impl InFlight {
+    /// Counts a callback as in flight until the returned guard is dropped.
+    pub(crate) fn start(self: &Arc<Self>) -> InFlightGuard {
+        *self.count.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+        InFlightGuard(Arc::clone(self))
+    }
+
+    /// Blocks until no callback is in flight or `timeout` elapses, returning whether the count dropped to zero.
+    fn wait(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        while *count > 0 {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+            count = self
+                .idle
+                .wait_timeout(count, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+
+        true
+    }
+
+    /// Blocks until no callback is in flight.
+    pub(crate) fn wait_all(&self) {
+        let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        while *count > 0 {
+            count = self
+                .idle
+                .wait(count)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+/// Keeps a callback counted as in flight; dropped once the callback returns or unwinds.
+pub(crate) struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut count = self.0.count.lock().unwrap_or_else(PoisonError::into_inner);
+        *count -= 1;
+        if *count == 0 {
+            self.0.idle.notify_all();
+        }
+    }
+}
+
+impl EventEmitter {
+    /// Blocks until every callback running on a worker thread has completed, or until `timeout` elapses.
+    ///
+    /// This includes the values queued for keyed listeners by `emit_keyed`. Callbacks running inline and those
+    /// of `on_local` listeners are not tracked, since the emit invoking them waits for them.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The longest time to wait.
+    ///
+    /// # Returns
+    ///
+    /// `true` if no callback is in flight, `false` if some are still running when the timeout elapses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("some_event", |value: String| println!("{}", value));
+    /// event_emitter.emit("some_event", "Hello, world!".to_string());
+    /// assert!(event_emitter.wait_until_idle(Duration::from_secs(1)));
+    /// ```
+    pub fn wait_until_idle(&self, timeout: Duration) -> bool {
+        self.in_flight.wait(timeout)
+    }
+}
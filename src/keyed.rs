@@ -0,0 +1,198 @@
+//! Listeners processing emits in order per key, on a fixed set of worker threads.
+//!
+//! A keyed listener owns one worker thread per shard. `emit_keyed` hashes the key of the emit to pick a shard
+//! and queues the value there without waiting, so emits with the same key are processed one after the other,
+//! in emission order, while emits with different keys can be processed in parallel. The worker threads are
+//! stopped and joined when the listener is removed or the emitter dropped, after draining their queue.
+
+use crate::event_emitter::{report_emit_error, thread_name, to_json, Callback};
+use crate::id::ListenerId;
+use crate::idle::InFlightGuard;
+use crate::EventEmitter;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+/// A value queued on a shard, counted as in flight until processed.
+type Delivery = (Vec<u8>, InFlightGuard);
+
+/// The worker thread processing the queue of one shard.
+struct Shard {
+    sender: Option<Sender<Delivery>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Shard {
+    fn spawn(name: String, callback: Callback) -> Self {
+        let (sender, receiver) = mpsc::channel::<Delivery>();
+        let spawned = thread::Builder::new().name(name).spawn(move || {
+            for (bytes, _guard) in receiver {
+                if let Err(e) = panic::catch_unwind(AssertUnwindSafe(|| callback(bytes))) {
+                    eprintln!("Thread error: {:?}", e);
+                }
+            }
+        });
+
+        let thread = match spawned {
+            Ok(thread) => Some(thread),
+            Err(e) => {
+                eprintln!("Failed to spawn keyed listener thread: {}", e);
+                None
+            }
+        };
+        Self {
+            sender: Some(sender),
+            thread,
+        }
+    }
+}
+
+impl Drop for Shard {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            if let Err(e) = thread.join() {
+                eprintln!("Thread error: {:?}", e);
+            }
+        }
+    }
+}
+
+/// A listener added with `on_keyed`, with the worker threads of its shards.
+pub(crate) struct KeyedListener {
+    pub(crate) id: ListenerId,
+    shards: Vec<Shard>,
+}
+
+impl KeyedListener {
+    /// Queues a value on the shard of `key_hash`. A delivery that cannot be queued is dropped with its guard.
+    fn deliver(&self, key_hash: u64, delivery: Delivery) {
+        let index = (key_hash % self.shards.len() as u64) as usize;
+        if let Some(sender) = &self.shards[index].sender {
+            let _ = sender.send(delivery);
+        }
+    }
+}
+
+impl EventEmitter {
+    /// Adds an event listener receiving the values of `emit_keyed` in order per key, spread over `shards`
+    /// worker threads.
+    ///
+    /// All values emitted with the same key are processed by the same thread, in emission order. Values with
+    /// different keys may be processed in parallel, on different threads. Keyed listeners only receive
+    /// `emit_keyed` emits; other emit methods do not invoke them.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `shards` - The number of worker threads, at least 1.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_keyed("order.updated", 4, |status: String| println!("{}", status));
+    /// event_emitter.emit_keyed("order.updated", 42_u32, "paid".to_string());
+    /// event_emitter.emit_keyed("order.updated", 42_u32, "shipped".to_string());
+    /// assert!(event_emitter.wait_until_idle(Duration::from_secs(1)));
+    /// ```
+    pub fn on_keyed<F, T>(&mut self, event: &str, shards: usize, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        if self.refuses_listeners(event) {
+            return id;
+        }
+        let callback = self.decoding_callback(event, id, callback);
+        let name = thread_name(event, id);
+        let shards = (0..shards.max(1))
+            .map(|index| Shard::spawn(format!("{}-{}", name, index), callback.clone()))
+            .collect();
+
+        self.keyed
+            .entry(event.to_string())
+            .or_default()
+            .push(KeyedListener { id, shards });
+        id
+    }
+
+    /// Emits an event with a key, queuing the value on the keyed listeners by key and delivering it to the
+    /// other listeners like `emit`.
+    ///
+    /// Keyed listeners added with `on_keyed` process the value on the worker thread of the key's shard, after
+    /// every value queued before with a key of the same shard; `emit_keyed` does not wait for them, use
+    /// `wait_until_idle` for that. Listeners that don't care about keys are invoked exactly as by `emit`, and
+    /// the emitter's hold and the event's capture and coalescing settings only apply to them.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `key` - The key ordering the emit, such as an order ID.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the emit, or 0 if it failed.
+    pub fn emit_keyed<K, T>(&mut self, event: impl AsRef<str>, key: K, value: T) -> u64
+    where
+        K: Hash,
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        if !self.keyed.contains_key(event) {
+            return self.emit(event, value);
+        }
+
+        let bytes = match to_json(&value) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                report_emit_error(event, &e);
+                return 0;
+            }
+        };
+        let seq = match self.emit_encoded(event, &[], || Ok(bytes.clone())) {
+            Ok((seq, _)) => seq,
+            Err(e) => {
+                report_emit_error(event, &e);
+                return 0;
+            }
+        };
+
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let key_hash = hasher.finish();
+
+        let listeners = self.keyed.get(event).map_or(&[][..], Vec::as_slice);
+        for listener in listeners {
+            listener.deliver(key_hash, (bytes.clone(), self.in_flight.start()));
+        }
+        self.stats.record_invocations(listeners.len());
+
+        seq
+    }
+
+    /// Removes the keyed listener with the given ID, joining its worker threads once their queue is drained.
+    pub(crate) fn remove_keyed(&mut self, id: ListenerId) -> bool {
+        for listeners in self.keyed.values_mut() {
+            if let Some(index) = listeners.iter().position(|listener| listener.id == id) {
+                listeners.remove(index);
+                return true;
+            }
+        }
+
+        false
+    }
+}
@@ -1,6 +1,72 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
+// The README's `[!NOTE]` alert continues on an unquoted line, as GitHub renders it.
+#![allow(clippy::doc_lazy_continuation)]
 
+mod any;
+pub mod bind;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bridge;
+pub mod capture;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod channel;
+pub mod coalesce;
+#[cfg(feature = "compress")]
+pub mod compress;
+pub mod control;
+pub mod deadletter;
+mod deferred;
+mod dispatch;
+pub mod error;
 pub mod event_emitter;
 pub mod event_emitter_file;
+pub mod fast;
+pub mod gather;
+pub mod global;
+pub mod guard;
+pub mod history;
+pub mod hold;
+pub mod hooks;
+pub mod id;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod idle;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod keyed;
+mod limits;
+mod local;
+#[cfg(feature = "log")]
+pub mod logger;
+mod macros;
+mod names;
+pub mod node;
+pub mod order;
+pub mod pattern;
+mod per_key;
+pub mod query;
+pub mod reentrancy;
+pub mod removal;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod request;
+pub mod retry;
+pub mod sampling;
+#[cfg(target_arch = "wasm32")]
+pub mod scheduling;
+#[cfg(all(feature = "schema", not(target_arch = "wasm32")))]
+pub mod schema;
+pub mod sender;
+mod shutdown;
+mod spawner;
+pub mod state;
+pub mod stats;
+pub mod subscription;
+pub mod tenant;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod topology;
+pub mod tracking;
+#[cfg(target_arch = "wasm32")]
+pub mod wasm_config;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-workers"))]
+pub mod wasm_workers;
 pub use event_emitter::EventEmitter;
@@ -0,0 +1,149 @@
+//! Guards against oversized payloads, which every listener of their event would copy and decode.
+//!
+//! A size limit rejects payloads by their serialized length. A depth limit rejects deeply nested payloads,
+//! such as user-supplied JSON with thousands of nested arrays: listeners decode payloads with `serde_json`,
+//! whose recursion limit turns such a payload into a decode error reported to the `on_decode_error` hooks,
+//! while the emitter rejects it before any listener sees it, counting the nesting of brackets and braces in
+//! one pass over the bytes.
+
+use crate::error::EmitError;
+use crate::EventEmitter;
+
+impl EventEmitter {
+    /// Caps the serialized size of emitted payloads.
+    ///
+    /// Payloads longer than the limit fail with `EmitError::PayloadTooLarge` and are delivered to no listener:
+    /// `try_emit` and `strict_emit` return the error, the other methods report it on stderr.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum payload size in bytes, or `None` for no limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::error::EmitError;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_max_payload_bytes(Some(16));
+    /// assert!(event_emitter.try_emit("note.saved", "short").is_ok());
+    /// assert_eq!(
+    ///     Err(EmitError::PayloadTooLarge { size: 19, limit: 16 }),
+    ///     event_emitter.try_emit("note.saved", "somewhat too long"),
+    /// );
+    /// ```
+    pub fn set_max_payload_bytes(&mut self, limit: Option<usize>) {
+        self.max_payload_bytes = limit;
+    }
+
+    /// Returns the maximum serialized size of emitted payloads, set with `set_max_payload_bytes`.
+    pub fn max_payload_bytes(&self) -> Option<usize> {
+        self.max_payload_bytes
+    }
+
+    /// Caps the nesting depth of emitted JSON payloads.
+    ///
+    /// Payloads nesting arrays and objects deeper than the limit fail with `EmitError::PayloadTooDeep` and are
+    /// delivered to no listener: `try_emit` and `strict_emit` return the error, the other methods report it on
+    /// stderr. The check treats every payload as JSON, so leave it off for binary payloads emitted with
+    /// `emit_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum nesting depth, or `None` for no limit. A scalar payload has a depth of 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::error::EmitError;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_max_json_depth(Some(2));
+    /// assert!(event_emitter.try_emit("matrix.updated", vec![vec![1, 2]]).is_ok());
+    /// assert_eq!(
+    ///     Err(EmitError::PayloadTooDeep { limit: 2 }),
+    ///     event_emitter.try_emit("matrix.updated", vec![vec![vec![1, 2]]]),
+    /// );
+    /// ```
+    pub fn set_max_json_depth(&mut self, limit: Option<usize>) {
+        self.max_json_depth = limit;
+    }
+
+    /// Returns the maximum nesting depth of emitted JSON payloads, set with `set_max_json_depth`.
+    pub fn max_json_depth(&self) -> Option<usize> {
+        self.max_json_depth
+    }
+
+    /// Returns whether payloads are checked against a size or depth limit, so they must be serialized even if
+    /// no listener needs them.
+    pub(crate) fn limits_payloads(&self) -> bool {
+        self.max_payload_bytes.is_some() || self.max_json_depth.is_some()
+    }
+
+    /// Checks a serialized payload against the size and depth limits, returning it unless it exceeds either.
+    pub(crate) fn check_limits(&self, bytes: Vec<u8>) -> Result<Vec<u8>, EmitError> {
+        check_limits(bytes, self.max_payload_bytes, self.max_json_depth)
+    }
+}
+
+/// Checks a serialized payload against a size and a depth limit, returning it unless it exceeds either.
+pub(crate) fn check_limits(
+    bytes: Vec<u8>,
+    max_payload_bytes: Option<usize>,
+    max_json_depth: Option<usize>,
+) -> Result<Vec<u8>, EmitError> {
+    if let Some(limit) = max_payload_bytes.filter(|&limit| bytes.len() > limit) {
+        return Err(EmitError::PayloadTooLarge {
+            size: bytes.len(),
+            limit,
+        });
+    }
+
+    match max_json_depth {
+        Some(limit) if exceeds_depth(&bytes, limit) => Err(EmitError::PayloadTooDeep { limit }),
+        _ => Ok(bytes),
+    }
+}
+
+/// Returns whether the arrays and objects of a JSON document nest deeper than `limit`, skipping the
+/// brackets inside strings. Stops at the first level beyond the limit.
+fn exceeds_depth(bytes: &[u8], limit: usize) -> bool {
+    // Most payloads open fewer brackets than the limit overall, which a branchless count settles quickly.
+    let opening = bytes
+        .iter()
+        .map(|&byte| usize::from(byte == b'[' || byte == b'{'))
+        .sum::<usize>();
+    if opening <= limit {
+        return false;
+    }
+
+    let mut depth = 0_usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &byte in bytes {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > limit {
+                    return true;
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
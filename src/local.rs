@@ -0,0 +1,315 @@
+//! Listeners whose callbacks are not `Sync`, and on native targets not even `Send`.
+//!
+//! On native targets, these callbacks live on a single dispatcher thread owned by the emitter: they are
+//! created there by a setup closure, and emits send the serialized value to that thread over a channel.
+//! Data captured by the callbacks, such as an `Rc`, therefore never crosses threads. The dispatcher
+//! thread is joined when the emitter is dropped.
+//!
+//! On wasm, which is single-threaded, the callbacks are simply invoked on the emitting thread.
+
+use crate::event_emitter::{Execution, ListenerOptions};
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A callback living on the dispatcher thread, receiving the serialized event value.
+pub(crate) type LocalCallback = Box<dyn Fn(Vec<u8>)>;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{self, Receiver, Sender};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+
+/// A message processed by the dispatcher thread, in the order it was sent.
+#[cfg(not(target_arch = "wasm32"))]
+enum Message {
+    /// Creates the callback of a listener on the dispatcher thread.
+    Register(ListenerId, Box<dyn FnOnce() -> LocalCallback + Send>),
+    /// Runs the callback of a listener, then signals completion.
+    Deliver(ListenerId, Vec<u8>, Sender<()>),
+    /// Drops the callback of a listener.
+    Remove(ListenerId),
+    /// Drops every callback and stops the dispatcher thread.
+    Shutdown,
+}
+
+/// The dispatcher thread running the local listeners of an emitter.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) struct LocalDispatcher {
+    sender: Sender<Message>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LocalDispatcher {
+    fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("emit-local-dispatcher".to_string())
+            .spawn(move || dispatch(receiver))
+            .expect("failed to spawn local dispatcher thread");
+
+        Self {
+            sender,
+            thread: Some(thread),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for LocalDispatcher {
+    fn drop(&mut self) {
+        let _ = self.sender.send(Message::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            if let Err(e) = thread.join() {
+                eprintln!("Thread error: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Runs the dispatcher loop until shutdown, keeping a panicking callback from stopping the thread.
+#[cfg(not(target_arch = "wasm32"))]
+fn dispatch(receiver: Receiver<Message>) {
+    use std::panic::{self, AssertUnwindSafe};
+
+    let mut callbacks: HashMap<ListenerId, LocalCallback> = HashMap::new();
+    for message in receiver {
+        match message {
+            Message::Register(id, setup) => match panic::catch_unwind(AssertUnwindSafe(setup)) {
+                Ok(callback) => {
+                    callbacks.insert(id, callback);
+                }
+                Err(e) => eprintln!("Thread error: {:?}", e),
+            },
+            Message::Deliver(id, bytes, done) => {
+                if let Some(callback) = callbacks.get(&id) {
+                    if let Err(e) = panic::catch_unwind(AssertUnwindSafe(|| callback(bytes))) {
+                        eprintln!("Thread error: {:?}", e);
+                    }
+                }
+                let _ = done.send(());
+            }
+            Message::Remove(id) => {
+                callbacks.remove(&id);
+            }
+            Message::Shutdown => break,
+        }
+    }
+}
+
+/// The handle of a local listener held by its emitter-side callback; dropping it drops the local callback.
+#[cfg(not(target_arch = "wasm32"))]
+struct LocalHandle {
+    id: ListenerId,
+    sender: Sender<Message>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LocalHandle {
+    /// Runs the local callback on the dispatcher thread and waits for it to return.
+    fn deliver(&self, bytes: Vec<u8>) {
+        let (done, finished) = mpsc::channel();
+        if self
+            .sender
+            .send(Message::Deliver(self.id, bytes, done))
+            .is_ok()
+        {
+            let _ = finished.recv();
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for LocalHandle {
+    fn drop(&mut self) {
+        let _ = self.sender.send(Message::Remove(self.id));
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// The local callbacks, keyed by listener ID. Wasm has a single thread, which owns all of them.
+    static LOCAL_CALLBACKS: std::cell::RefCell<HashMap<ListenerId, std::rc::Rc<LocalCallback>>> =
+        std::cell::RefCell::new(HashMap::new());
+}
+
+/// The handle of a local listener held by its emitter-side callback; dropping it drops the local callback.
+#[cfg(target_arch = "wasm32")]
+struct LocalHandle {
+    id: ListenerId,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl LocalHandle {
+    /// Runs the local callback on the current thread.
+    fn deliver(&self, bytes: Vec<u8>) {
+        let callback = LOCAL_CALLBACKS.with(|callbacks| callbacks.borrow().get(&self.id).cloned());
+        if let Some(callback) = callback {
+            callback(bytes);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for LocalHandle {
+    fn drop(&mut self) {
+        LOCAL_CALLBACKS.with(|callbacks| callbacks.borrow_mut().remove(&self.id));
+    }
+}
+
+impl EventEmitter {
+    /// Adds an event listener whose callback is created by `setup` on the emitter's dispatcher thread.
+    ///
+    /// The callback needs to be neither `Send` nor `Sync`: it is created, invoked and dropped on the
+    /// dispatcher thread only. Emits, from any thread, hand the value over to that thread and wait for the
+    /// callback to return, so the callback must not wait on the emitter itself. On wasm, `setup` runs
+    /// immediately and the callback is invoked on the emitting thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `setup` - Creates the callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_local_with("some_event", || {
+    ///     let cache = Rc::new(RefCell::new(Vec::new()));
+    ///     move |value: String| cache.borrow_mut().push(value)
+    /// });
+    /// event_emitter.emit("some_event", "Hello, world!".to_string());
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_local_with<S, F, T>(&mut self, event: &str, setup: S) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        S: FnOnce() -> F + Send + 'static,
+        F: Fn(T) + 'static,
+    {
+        let id = self.ids.next_id();
+        let sender = self
+            .local
+            .get_or_insert_with(LocalDispatcher::spawn)
+            .sender
+            .clone();
+
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let local_setup = move || -> LocalCallback {
+            let callback = setup();
+            Box::new(move |bytes: Vec<u8>| {
+                if let Some(value) = decode_errors.decode(&listener_event, id, &bytes) {
+                    callback(value);
+                }
+            })
+        };
+        let _ = sender.send(Message::Register(id, Box::new(local_setup)));
+
+        self.add_local_listener(event, LocalHandle { id, sender })
+    }
+
+    /// Adds an event listener whose callback is created by `setup`, see the native `on_local_with`.
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_local_with<S, F, T>(&mut self, event: &str, setup: S) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        S: FnOnce() -> F + 'static,
+        F: Fn(T) + 'static,
+    {
+        let id = self.ids.next_id();
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let callback = setup();
+        let local_callback: LocalCallback = Box::new(move |bytes: Vec<u8>| {
+            if let Some(value) = decode_errors.decode(&listener_event, id, &bytes) {
+                callback(value);
+            }
+        });
+
+        self.add_local_callback(event, id, local_callback)
+    }
+
+    /// Adds an event listener invoking a callback that is neither `Send` nor `Sync` with the serialized value.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn add_local_callback(
+        &mut self,
+        event: &str,
+        id: ListenerId,
+        callback: LocalCallback,
+    ) -> ListenerId {
+        LOCAL_CALLBACKS.with(|callbacks| {
+            callbacks
+                .borrow_mut()
+                .insert(id, std::rc::Rc::new(callback))
+        });
+
+        self.add_local_listener(event, LocalHandle { id })
+    }
+
+    /// Adds an event listener whose callback is not `Sync`, running on the emitter's dispatcher thread.
+    ///
+    /// This is a shorthand for `on_local_with` when the callback can be moved to the dispatcher thread.
+    /// Callbacks capturing data that is not `Send` either must be created there with `on_local_with`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::cell::Cell;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let count = Cell::new(0);
+    /// event_emitter.on_local("some_event", move |_: String| count.set(count.get() + 1));
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_local<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + Send + 'static,
+    {
+        self.on_local_with(event, move || callback)
+    }
+
+    /// Adds an event listener whose callback is neither `Send` nor `Sync`, invoked on the emitting thread.
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_local<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static,
+    {
+        self.on_local_with(event, move || callback)
+    }
+
+    /// Registers the emitter-side listener forwarding deliveries to a local callback.
+    fn add_local_listener(&mut self, event: &str, handle: LocalHandle) -> ListenerId {
+        let id = handle.id;
+        let options = ListenerOptions {
+            execution: Execution::Inline,
+            ..ListenerOptions::default()
+        };
+        let callback = move |bytes: Vec<u8>| handle.deliver(bytes);
+
+        self.insert_listener(event, id, options, Arc::new(callback), None)
+    }
+}
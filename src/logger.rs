@@ -0,0 +1,162 @@
+//! A `log` crate logger emitting every record as an event, enabled by the `log` feature.
+//!
+//! Each record accepted by an [`EmitterLogger`] is emitted as a [`LogEvent`] on `log.<level>`, such as
+//! `log.error`, and on the catch-all `log.record`.
+
+use crate::event_emitter::SharedEventEmitter;
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError, TryLockError};
+
+/// The maximum number of records kept while the emitter is busy; older ones are dropped first.
+const MAX_PENDING: usize = 1024;
+
+/// A log record as emitted by `EmitterLogger`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEvent {
+    /// The lowercase level of the record, such as `error`.
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub module_path: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+impl LogEvent {
+    fn new(record: &Record) -> Self {
+        Self {
+            level: record.level().as_str().to_lowercase(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            module_path: record.module_path().map(str::to_string),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+        }
+    }
+}
+
+/// A logger emitting the records it accepts on a shared emitter.
+///
+/// Records are only converted into a `LogEvent` once they pass the level and target filters. If the emitter
+/// is locked when a record arrives, for example by a listener logging during an emit, the record is kept and
+/// emitted with the next record or `flush`. Records logged while the logger itself is emitting, by its
+/// listeners or by any other thread, are dropped so that a listener that logs cannot recurse.
+pub struct EmitterLogger {
+    emitter: SharedEventEmitter,
+    level: LevelFilter,
+    targets: Vec<String>,
+    pending: Mutex<Vec<LogEvent>>,
+    emitting: AtomicBool,
+}
+
+impl EmitterLogger {
+    /// Creates a logger emitting the records up to `level` on the given emitter.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The emitter to emit the records on.
+    /// * `level` - The most verbose level emitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::logger::EmitterLogger;
+    /// use emitter_rs::EventEmitter;
+    /// use log::{Log, Record};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let handle = Arc::new(Mutex::new(EventEmitter::new()));
+    /// handle.lock().unwrap().on("log.warn", |event: emitter_rs::logger::LogEvent| {
+    ///     println!("Warning: {}", event.message);
+    /// });
+    ///
+    /// let logger = EmitterLogger::new(handle, log::LevelFilter::Warn);
+    /// logger.log(&Record::builder().level(log::Level::Warn).args(format_args!("disk almost full")).build());
+    /// ```
+    pub fn new(handle: SharedEventEmitter, level: LevelFilter) -> Self {
+        Self {
+            emitter: handle,
+            level,
+            targets: Vec::new(),
+            pending: Mutex::new(Vec::new()),
+            emitting: AtomicBool::new(false),
+        }
+    }
+
+    /// Restricts the logger to the records whose target starts with `prefix`.
+    ///
+    /// Can be called several times to accept several prefixes. Without any, every target is accepted.
+    pub fn with_target(mut self, prefix: &str) -> Self {
+        self.targets.push(prefix.to_string());
+        self
+    }
+
+    /// Installs a logger emitting the records up to `level` as the global logger of the `log` crate.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The emitter to emit the records on.
+    /// * `level` - The most verbose level emitted, also set as the global maximum level.
+    ///
+    /// # Returns
+    ///
+    /// An error if a global logger was already installed.
+    pub fn install(handle: SharedEventEmitter, level: LevelFilter) -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::new(Self::new(handle, level)))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+
+    /// Emits the pending records if the emitter is not locked.
+    fn emit_pending(&self) {
+        let mut emitter = match self.emitter.try_lock() {
+            Ok(emitter) => emitter,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => return,
+        };
+        let events = std::mem::take(&mut *self.pending_events());
+
+        self.emitting.store(true, Ordering::SeqCst);
+        for event in events {
+            emitter.emit(format!("log.{}", event.level), &event);
+            emitter.emit("log.record", event);
+        }
+        self.emitting.store(false, Ordering::SeqCst);
+    }
+
+    fn pending_events(&self) -> std::sync::MutexGuard<'_, Vec<LogEvent>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Log for EmitterLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+            && (self.targets.is_empty()
+                || self
+                    .targets
+                    .iter()
+                    .any(|prefix| metadata.target().starts_with(prefix.as_str())))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) || self.emitting.load(Ordering::SeqCst) {
+            return;
+        }
+
+        {
+            let mut pending = self.pending_events();
+            if pending.len() == MAX_PENDING {
+                pending.remove(0);
+            }
+            pending.push(LogEvent::new(record));
+        }
+        self.emit_pending();
+    }
+
+    fn flush(&self) {
+        self.emit_pending();
+    }
+}
@@ -0,0 +1,83 @@
+//! Convenience macros operating on the global emitter.
+//!
+//! These macros are meant for application code that uses a single process-wide event bus.
+//! Libraries should take an `EventEmitter` from their caller instead of relying on global state.
+
+/// Adds a listener to the global emitter, handling the lock internally.
+///
+/// Expands to [`global::on`](crate::global::on), so a poisoned lock is recovered instead of panicking.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::on;
+///
+/// let listener_id = on!("Hello", |name: String| println!("Hello, {}!", name));
+/// ```
+#[macro_export]
+macro_rules! on {
+    ($event:expr, $callback:expr $(,)?) => {
+        $crate::global::on($event, $callback)
+    };
+}
+
+/// Emits an event on the global emitter, handling the lock internally.
+///
+/// Expands to [`global::emit`](crate::global::emit), so a poisoned lock is recovered instead of panicking.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::emit;
+///
+/// emit!("Hello", "world".to_string());
+/// ```
+#[macro_export]
+macro_rules! emit {
+    ($event:expr, $value:expr $(,)?) => {
+        $crate::global::emit($event, $value)
+    };
+}
+
+/// Declares event names as `&str` constants, so that typos become compile errors and names can be found and
+/// renamed by the IDE.
+///
+/// The constants work anywhere the API takes an event name. Prefixing the declarations with `NAME = { ... }`
+/// also declares `NAME` as the slice of every name, to pass to `EventEmitter::declare_events` so that
+/// `strict_emit` rejects any other name in strict mode.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::{events, EventEmitter};
+///
+/// events! {
+///     pub USER_EVENTS = {
+///         /// A user signed up.
+///         pub const CREATED = "user.created";
+///         pub const DELETED = "user.deleted";
+///     }
+/// }
+///
+/// let mut event_emitter = EventEmitter::new();
+/// event_emitter.set_strict(true);
+/// event_emitter.declare_events(USER_EVENTS);
+///
+/// event_emitter.on(CREATED, |name: String| println!("Welcome, {}!", name));
+/// assert!(event_emitter.strict_emit(DELETED, "ada").is_ok());
+/// assert!(event_emitter.strict_emit("user.craeted", "ada").is_err());
+/// ```
+#[macro_export]
+macro_rules! events {
+    ($($(#[$meta:meta])* $vis:vis const $name:ident = $value:expr;)*) => {
+        $($(#[$meta])* $vis const $name: &str = $value;)*
+    };
+    (
+        $(#[$all_meta:meta])* $all_vis:vis $all:ident = {
+            $($(#[$meta:meta])* $vis:vis const $name:ident = $value:expr;)*
+        }
+    ) => {
+        $($(#[$meta])* $vis const $name: &str = $value;)*
+        $(#[$all_meta])* $all_vis const $all: &[&str] = &[$($name),*];
+    };
+}
@@ -0,0 +1,178 @@
+//! A guard against runaway event names, such as names embedding user IDs.
+//!
+//! Every event name that gets an entry in the emitter's per-event state, by registering a listener or by an
+//! emit tracked with `track_last_emit` or counted with `collect_stats`, is counted once. With a cap set,
+//! names beyond it are rejected while the names already counted keep working, and unused names can be
+//! pruned to make room again.
+
+use crate::error::EmitError;
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::Deserialize;
+use std::time::Duration;
+use web_time::SystemTime;
+
+impl EventEmitter {
+    /// Caps the number of distinct event names the emitter keeps state for.
+    ///
+    /// Once the cap is reached, registering a listener on a new name or emitting a new name that would be
+    /// tracked or counted fails with `EmitError::TooManyEventNames`: `try_on` and `try_emit` return the error,
+    /// the other methods report it on stderr and drop the listener or the emit. Names already counted are not
+    /// affected, and lowering the cap below the current count forgets none of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of event names, or `None` for no limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_max_event_names(Some(1));
+    /// event_emitter.on("user.updated", |id: u32| println!("User {} updated", id));
+    /// assert!(event_emitter.try_on("user.12345.updated", |_: ()| {}).is_err());
+    /// assert!(event_emitter.try_emit("user.updated", 12345_u32).is_ok());
+    /// ```
+    pub fn set_max_event_names(&mut self, limit: Option<usize>) {
+        self.max_event_names = limit;
+    }
+
+    /// Returns the number of distinct event names the emitter keeps state for, as capped by
+    /// `set_max_event_names`.
+    pub fn event_name_count(&self) -> usize {
+        self.event_names.len()
+    }
+
+    /// Adds an event listener like `on`, unless the cap set with `set_max_event_names` rejects its event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener, or `EmitError::TooManyEventNames` if the event is a new name
+    /// beyond the cap.
+    pub fn try_on<F, T>(&mut self, event: &str, callback: F) -> Result<ListenerId, EmitError>
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        if self.is_shut_down() {
+            return Err(EmitError::ShutDown);
+        }
+        self.admit_event_name(event)?;
+        Ok(self.on(event, callback))
+    }
+
+    /// Forgets the event names that are no longer used, returning them to the cap set with
+    /// `set_max_event_names`.
+    ///
+    /// A name is unused when it has no listener, responder or replier, no buffered payload, coalescing, guard or
+    /// history, and was not emitted within `max_idle` as tracked by `track_last_emit`. Its last emit, its
+    /// emit count in the statistics and its registration for `strict_emit` are forgotten with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_idle` - How long since its last emit a name without listeners is still in use.
+    ///
+    /// # Returns
+    ///
+    /// The number of names forgotten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let listener_id = event_emitter.on("user.12345.updated", |_: ()| {});
+    /// event_emitter.remove_listener(&listener_id);
+    /// assert_eq!(1, event_emitter.prune_unused_event_names(Duration::from_secs(60)));
+    /// assert_eq!(0, event_emitter.event_name_count());
+    /// ```
+    pub fn prune_unused_event_names(&mut self, max_idle: Duration) -> usize {
+        let now = SystemTime::now();
+        let unused: Vec<String> = self
+            .event_names
+            .iter()
+            .filter(|event| {
+                let recently_emitted = self
+                    .last_emits
+                    .as_ref()
+                    .and_then(|last_emits| last_emits.get(*event))
+                    .is_some_and(|stamp| {
+                        now.duration_since(stamp.at)
+                            .map_or(true, |elapsed| elapsed <= max_idle)
+                    });
+                !recently_emitted && !self.uses_event_name(event)
+            })
+            .cloned()
+            .collect();
+
+        for event in &unused {
+            self.event_names.remove(event);
+            self.known_events.remove(event);
+            if let Some(last_emits) = &mut self.last_emits {
+                last_emits.remove(event);
+            }
+            self.stats.forget_event(event);
+        }
+
+        unused.len()
+    }
+
+    /// Returns whether anything but a past emit still refers to an event name.
+    fn uses_event_name(&self, event: &str) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.keyed.contains_key(event) {
+            return true;
+        }
+
+        self.listeners.contains_key(event)
+            || self.responders.contains_key(event)
+            || self.any_listeners.contains_key(event)
+            || self.async_repliers.contains_key(event)
+            || self.coalescers.contains_key(event)
+            || self.samplers.contains_key(event)
+            || self.history.contains_key(event)
+            || self.guards.contains_key(event)
+            || self.captures.contains_key(event)
+            || self.held.iter().any(|held| held.event == event)
+    }
+
+    /// Returns whether emits get an entry in the per-event state of the emitter, for tracking or statistics.
+    fn records_emits(&self) -> bool {
+        self.last_emits.is_some() || self.stats.is_enabled()
+    }
+
+    /// Counts `event` as a name the emitter keeps state for, unless it is a new name beyond the cap.
+    pub(crate) fn admit_event_name(&mut self, event: &str) -> Result<(), EmitError> {
+        if self.event_names.contains(event) {
+            return Ok(());
+        }
+
+        if let Some(limit) = self.max_event_names {
+            let current = self.event_names.len();
+            if current >= limit {
+                return Err(EmitError::TooManyEventNames { current, limit });
+            }
+        }
+
+        self.event_names.insert(event.to_string());
+        Ok(())
+    }
+
+    /// Counts the name of an emit recorded by tracking or statistics, reporting whether the cap admits it.
+    pub(crate) fn admit_emitted_name(&mut self, event: &str) -> Result<(), EmitError> {
+        if self.records_emits() {
+            self.admit_event_name(event)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Methods named after those of Node's `EventEmitter`, to ease porting JavaScript code.
+//!
+//! Node's `emit`, `once`, `removeListener` and `listenerCount` need no alias: `emit`, `once`,
+//! `remove_listener` and `listener_count` already match them. `addListener` maps to `on`, since
+//! `add_listener` adds a listener built with `Listener::new`. The remaining methods are:
+//!
+//! | Node                         | emitter-rs                     |
+//! |------------------------------|--------------------------------|
+//! | `off(event, listener)`       | `off(id)`                      |
+//! | `prependListener`            | `prepend_listener`             |
+//! | `prependOnceListener`        | `prepend_once_listener`        |
+//! | `eventNames()`               | `event_names()`                |
+//! | `setMaxListeners(n)`         | `set_max_listeners(n)`         |
+//! | `getMaxListeners()`          | `get_max_listeners()`          |
+
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::Deserialize;
+
+impl EventEmitter {
+    /// Removes an event listener with the given ID, like `remove_listener`.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener to remove.
+    ///
+    /// # Returns
+    ///
+    /// An option containing the ID of the removed listener if found, otherwise `None`.
+    pub fn off(&mut self, id: ListenerId) -> Option<ListenerId> {
+        self.remove_listener(&id)
+    }
+
+    /// Adds an event listener like `on`, placing it before the existing listeners of the event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("some_event", |value: String| println!("2: {}", value));
+    /// event_emitter.prepend_listener("some_event", |value: String| println!("1: {}", value));
+    /// event_emitter.sync_emit("some_event", "Hello, world!".to_string());
+    /// ```
+    pub fn prepend_listener<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.on(event, callback);
+        self.move_to_front(event);
+        id
+    }
+
+    /// Adds an event listener like `once`, placing it before the existing listeners of the event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    pub fn prepend_once_listener<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.once(event, callback);
+        self.move_to_front(event);
+        id
+    }
+
+    /// Moves the most recently added listener of `event` before the other listeners of the event.
+    fn move_to_front(&mut self, event: &str) {
+        if let Some(listeners) = self.listeners.get_mut(event) {
+            if let Some(listener) = listeners.pop() {
+                listeners.insert(0, listener);
+            }
+        }
+        self.listeners_changed();
+    }
+
+    /// Returns the names of the events that have listeners, in alphabetical order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("user.created", |_: String| {});
+    /// event_emitter.on("app.started", |_: ()| {});
+    /// assert_eq!(vec!["app.started", "user.created"], event_emitter.event_names());
+    /// ```
+    pub fn event_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .listeners
+            .keys()
+            .filter(|event| self.listener_count(event) > 0)
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Sets the number of listeners of a single event above which a possible leak is reported on stderr.
+    ///
+    /// The warning is printed when a registration takes an event past the limit. Unlike Node, where the
+    /// default is 10, there is no limit by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_listeners` - The number of listeners per event, or 0 for no limit.
+    pub fn set_max_listeners(&mut self, max_listeners: usize) {
+        self.max_listeners = max_listeners;
+    }
+
+    /// Returns the number of listeners per event above which a possible leak is reported, or 0 for no limit.
+    pub fn get_max_listeners(&self) -> usize {
+        self.max_listeners
+    }
+}
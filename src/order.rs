@@ -0,0 +1,76 @@
+//! The order in which the listeners of an event are dispatched, for hardening tests against hidden
+//! dependencies on registration order.
+
+use crate::EventEmitter;
+
+/// The order in which the listeners of an event are invoked by every emit method.
+///
+/// Limits, filters and removal behave the same in every order; only the invocation order changes. With
+/// `emit`, pooled callbacks run concurrently anyway, so the order mostly affects inline listeners and
+/// `sync_emit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DispatchOrder {
+    /// Listeners are invoked in registration order.
+    #[default]
+    Insertion,
+    /// Listeners are invoked in reverse registration order.
+    Reverse,
+    /// Listeners are invoked in a pseudo-random order, drawn from a deterministic generator seeded with
+    /// `seed`. The same seed and sequence of emits always produce the same orders.
+    Shuffled { seed: u64 },
+}
+
+/// Advances the SplitMix64 state and returns the next pseudo-random number.
+pub(crate) fn split_mix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl EventEmitter {
+    /// Sets the order in which the listeners of an event are invoked, `DispatchOrder::Insertion` by default.
+    ///
+    /// Setting `DispatchOrder::Shuffled` restarts its generator from the seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The dispatch order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::order::DispatchOrder;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_dispatch_order(DispatchOrder::Shuffled { seed: 42 });
+    /// println!("dispatch order: {:?}", event_emitter.dispatch_order());
+    /// ```
+    pub fn set_dispatch_order(&mut self, order: DispatchOrder) {
+        self.dispatch_order = order;
+        if let DispatchOrder::Shuffled { seed } = order {
+            self.shuffle_state = seed;
+        }
+    }
+
+    /// Returns the dispatch order, including the seed of a shuffled order so that it can be logged.
+    pub fn dispatch_order(&self) -> DispatchOrder {
+        self.dispatch_order
+    }
+
+    /// Reorders the listeners selected for an emit, given in registration order, according to the dispatch order.
+    pub(crate) fn arrange<T>(&mut self, dispatches: &mut [T]) {
+        match self.dispatch_order {
+            DispatchOrder::Insertion => {}
+            DispatchOrder::Reverse => dispatches.reverse(),
+            DispatchOrder::Shuffled { .. } => {
+                for index in (1..dispatches.len()).rev() {
+                    let other = split_mix64(&mut self.shuffle_state) % (index as u64 + 1);
+                    dispatches.swap(index, other as usize);
+                }
+            }
+        }
+    }
+}
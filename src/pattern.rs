@@ -0,0 +1,347 @@
+//! Listeners on patterns of hierarchical event names, such as `order.*` or `order.**`.
+//!
+//! Event names are split into dot-separated segments. In a pattern, `*` matches exactly one segment and `**`
+//! matches any number of segments, including none; every other segment matches itself. Patterns are kept in a
+//! `PatternTrie` keyed on their segments, so an emit finds the patterns matching its name by walking the trie
+//! along the name's segments instead of testing every pattern, while exact names keep their map lookup.
+
+use crate::event_emitter::Listener;
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The segment of a pattern matching exactly one segment of an event name.
+const ONE: &str = "*";
+
+/// The segment of a pattern matching any number of segments of an event name.
+const ANY: &str = "**";
+
+/// Returns whether `pattern` matches `event` by comparing their segments one pattern at a time.
+///
+/// This is the reference behavior of `PatternTrie`, in time linear in the number of patterns tested.
+///
+/// # Arguments
+///
+/// * `pattern` - The pattern, with `*` and `**` segments as wildcards.
+/// * `event` - The event name.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::pattern;
+///
+/// assert!(pattern::matches("order.*", "order.placed"));
+/// assert!(!pattern::matches("order.*", "order.item.added"));
+/// assert!(pattern::matches("order.**", "order.item.added"));
+/// assert!(pattern::matches("order.**", "order"));
+/// ```
+pub fn matches(pattern: &str, event: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    let event: Vec<&str> = event.split('.').collect();
+    segments_match(&pattern, &event)
+}
+
+fn segments_match(pattern: &[&str], event: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => event.is_empty(),
+        Some((&ANY, rest)) => {
+            (0..=event.len()).any(|skipped| segments_match(rest, &event[skipped..]))
+        }
+        Some((&ONE, rest)) => !event.is_empty() && segments_match(rest, &event[1..]),
+        Some((segment, rest)) => {
+            event.first() == Some(segment) && segments_match(rest, &event[1..])
+        }
+    }
+}
+
+/// Returns whether `pattern` has a wildcard segment, so that it may match other names than itself.
+pub fn is_pattern(pattern: &str) -> bool {
+    pattern
+        .split('.')
+        .any(|segment| segment == ONE || segment == ANY)
+}
+
+/// A node of the trie, holding the listeners whose pattern ends at it with their insertion sequence number.
+#[derive(Default, Clone)]
+struct Node {
+    literals: HashMap<String, Node>,
+    one: Option<Box<Node>>,
+    any: Option<Box<Node>>,
+    ids: Vec<(u64, ListenerId)>,
+}
+
+impl Node {
+    fn child_mut(&mut self, segment: &str) -> &mut Node {
+        match segment {
+            ONE => self.one.get_or_insert_with(Box::default),
+            ANY => self.any.get_or_insert_with(Box::default),
+            _ => self.literals.entry(segment.to_string()).or_default(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.literals.is_empty() && self.one.is_none() && self.any.is_none()
+    }
+
+    /// Removes `id` from the node at the end of `segments`, then prunes the nodes left empty.
+    fn remove(&mut self, segments: &[&str], id: ListenerId) -> bool {
+        let Some((segment, rest)) = segments.split_first() else {
+            let before = self.ids.len();
+            self.ids.retain(|(_, listener_id)| *listener_id != id);
+            return self.ids.len() < before;
+        };
+
+        let removed = match *segment {
+            ONE => self
+                .one
+                .as_mut()
+                .is_some_and(|child| child.remove(rest, id)),
+            ANY => self
+                .any
+                .as_mut()
+                .is_some_and(|child| child.remove(rest, id)),
+            _ => self
+                .literals
+                .get_mut(*segment)
+                .is_some_and(|child| child.remove(rest, id)),
+        };
+
+        if self.one.as_ref().is_some_and(|child| child.is_empty()) {
+            self.one = None;
+        }
+        if self.any.as_ref().is_some_and(|child| child.is_empty()) {
+            self.any = None;
+        }
+        if self.literals.get(*segment).is_some_and(Node::is_empty) {
+            self.literals.remove(*segment);
+        }
+        removed
+    }
+
+    /// Collects the listeners of the patterns below this node matching the event segments from `index` on.
+    fn collect(&self, segments: &[&str], index: usize, ids: &mut Vec<(u64, ListenerId)>) {
+        if let Some(any) = &self.any {
+            for skipped in index..=segments.len() {
+                any.collect(segments, skipped, ids);
+            }
+        }
+
+        let Some(segment) = segments.get(index) else {
+            ids.extend_from_slice(&self.ids);
+            return;
+        };
+        if let Some(child) = self.literals.get(*segment) {
+            child.collect(segments, index + 1, ids);
+        }
+        if let Some(one) = &self.one {
+            one.collect(segments, index + 1, ids);
+        }
+    }
+}
+
+/// The listeners of a set of patterns, found for an event name in time proportional to its number of segments
+/// rather than to the number of patterns.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::id::ListenerId;
+/// use emitter_rs::pattern::PatternTrie;
+///
+/// let (anything, placed) = (ListenerId(1), ListenerId(2));
+/// let mut trie = PatternTrie::new();
+/// trie.insert("order.*", placed);
+/// trie.insert("**", anything);
+///
+/// assert_eq!(vec![placed, anything], trie.matches("order.placed"));
+/// assert!(trie.remove(placed));
+/// assert_eq!(vec![anything], trie.matches("order.placed"));
+/// ```
+#[derive(Default, Clone)]
+pub struct PatternTrie {
+    root: Node,
+    /// The pattern of each listener, to find its node on removal.
+    patterns: HashMap<ListenerId, String>,
+    /// The sequence number of the next insertion, ordering the matches.
+    next_sequence: u64,
+}
+
+impl PatternTrie {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a listener on `pattern`, replacing the pattern it had if it was already in the trie.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern, with `*` and `**` segments as wildcards.
+    /// * `id` - The ID of the listener.
+    pub fn insert(&mut self, pattern: &str, id: ListenerId) {
+        self.remove(id);
+        let node = pattern
+            .split('.')
+            .fold(&mut self.root, |node, segment| node.child_mut(segment));
+        node.ids.push((self.next_sequence, id));
+        self.next_sequence += 1;
+        self.patterns.insert(id, pattern.to_string());
+    }
+
+    /// Removes a listener from the trie.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the listener was in the trie.
+    pub fn remove(&mut self, id: ListenerId) -> bool {
+        let Some(pattern) = self.patterns.remove(&id) else {
+            return false;
+        };
+        let segments: Vec<&str> = pattern.split('.').collect();
+        self.root.remove(&segments, id)
+    }
+
+    /// Returns the pattern of a listener in the trie.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    pub fn pattern(&self, id: ListenerId) -> Option<&str> {
+        self.patterns.get(&id).map(String::as_str)
+    }
+
+    /// Returns the listeners whose pattern matches `event`, each once, in insertion order.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event name.
+    pub fn matches(&self, event: &str) -> Vec<ListenerId> {
+        let segments: Vec<&str> = event.split('.').collect();
+        let mut ids = Vec::new();
+        self.root.collect(&segments, 0, &mut ids);
+        ids.sort_unstable();
+        ids.dedup();
+        ids.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Returns the number of listeners in the trie.
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Returns whether the trie has no listener.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+impl EventEmitter {
+    /// Adds an event listener on every event whose name matches `pattern`.
+    ///
+    /// In the pattern, `*` matches exactly one dot-separated segment of an event name and `**` matches any
+    /// number of segments, so `order.*` matches `order.placed` and `order.**` also matches `order` and
+    /// `order.item.added`. An emit is delivered to the listeners of its exact name first, then to the pattern
+    /// listeners matching it. The listener is counted among the listeners of its pattern, like by
+    /// `listener_count("order.*")`, and supports limits and removal like any other.
+    ///
+    /// Pattern listeners receive every emit of a matching event, `emit_typed` included, which serializes the
+    /// value for them. Only `emit_any`, which moves its value to a listener added with `on_any`, never reaches
+    /// them.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern of the event names to listen for.
+    /// * `callback` - The callback function to execute when a matching event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_pattern("order.*", |id: u32| println!("Order {} changed", id));
+    /// event_emitter.sync_emit("order.placed", 7_u32);
+    /// event_emitter.sync_emit("order.shipped", 7_u32);
+    /// ```
+    pub fn on_pattern<F, T>(&mut self, pattern: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        let parsed_callback = self.decoding_callback(pattern, id, callback);
+        let listener = Listener {
+            pattern: is_pattern(pattern),
+            ..Listener::new(id, None, parsed_callback)
+        };
+        self.attach(pattern, listener)
+    }
+
+    /// Returns whether an emit of `event` has listeners, on its exact name or on a pattern matching it.
+    pub(crate) fn has_listeners(&self, event: &str) -> bool {
+        self.listeners.contains_key(event)
+            || (!self.patterns.is_empty() && !self.patterns.matches(event).is_empty())
+    }
+
+    /// Returns the live pattern listeners matching `event` in registration order, with their pattern, other
+    /// than those on `event` itself, for the methods inspecting listeners without emitting.
+    pub(crate) fn pattern_listeners(&self, event: &str) -> Vec<(&str, &Listener)> {
+        if self.patterns.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matching = Vec::new();
+        for id in self.patterns.matches(event) {
+            let Some(pattern) = self.patterns.pattern(id) else {
+                continue;
+            };
+            if pattern == event
+                || self.listener_events.get(&id).map(String::as_str) != Some(pattern)
+            {
+                continue;
+            }
+            let listener = self
+                .listeners
+                .get(pattern)
+                .and_then(|listeners| listeners.iter().find(|listener| listener.id == id));
+            if let Some(listener) = listener.filter(|listener| !listener.is_removed()) {
+                matching.push((pattern, listener));
+            }
+        }
+        matching
+    }
+
+    /// Returns the pattern listeners matching `event` in registration order, with their pattern, other than
+    /// those on `event` itself, forgetting the listeners of the trie that were removed or moved to another
+    /// event since.
+    pub(crate) fn matching_patterns(&mut self, event: &str) -> Vec<(ListenerId, String)> {
+        if self.patterns.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matching = Vec::new();
+        for id in self.patterns.matches(event) {
+            let pattern = self.patterns.pattern(id);
+            match self.listener_events.get(&id) {
+                Some(listened) if Some(listened.as_str()) == pattern => {
+                    if listened != event {
+                        matching.push((id, listened.clone()));
+                    }
+                }
+                _ => {
+                    self.patterns.remove(id);
+                }
+            }
+        }
+        matching
+    }
+}
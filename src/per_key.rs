@@ -0,0 +1,293 @@
+//! Listeners invoked once per distinct key of their payloads, such as the ID of a user seen for the first time.
+//!
+//! Each such listener remembers the keys it was invoked for, optionally only the most recently seen ones, and
+//! skips the payloads whose key it already remembers without consuming its limit. A key is remembered once the
+//! listener is dispatched for it, so probing the listener with `dry_emit` remembers nothing.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::event_emitter::{Callback, Gate, Listener};
+use crate::hooks::DecodeErrorHooks;
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The keys a listener added with `once_per_key` was invoked for, type-erased to be stored on the listener.
+pub(crate) trait SeenKeys: Send + Sync {
+    /// Returns the number of keys remembered.
+    fn count(&self) -> usize;
+
+    /// Forgets every key.
+    fn clear(&self);
+}
+
+/// The remembered keys, evicting the least recently seen once there are more than `max_keys`.
+struct KeyLru<K> {
+    /// The recency stamp of each key.
+    stamps: HashMap<K, u64>,
+    /// The keys by recency stamp, least recently seen first.
+    order: BTreeMap<u64, K>,
+    next_stamp: u64,
+    max_keys: Option<usize>,
+}
+
+impl<K> KeyLru<K>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Returns whether the key is remembered, without marking it as seen.
+    fn contains(&self, key: &K) -> bool {
+        self.stamps.contains_key(key)
+    }
+
+    /// Marks the key as the most recently seen, returning whether it was not remembered yet.
+    fn see(&mut self, key: K) -> bool {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        let is_new = match self.stamps.insert(key.clone(), stamp) {
+            Some(previous) => {
+                self.order.remove(&previous);
+                false
+            }
+            None => true,
+        };
+        self.order.insert(stamp, key);
+
+        while self
+            .max_keys
+            .is_some_and(|max_keys| self.stamps.len() > max_keys)
+        {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.stamps.remove(&oldest);
+        }
+        is_new
+    }
+}
+
+impl<K> SeenKeys for Mutex<KeyLru<K>>
+where
+    K: Send,
+{
+    fn count(&self) -> usize {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .stamps
+            .len()
+    }
+
+    fn clear(&self) {
+        let mut keys = self.lock().unwrap_or_else(PoisonError::into_inner);
+        keys.stamps.clear();
+        keys.order.clear();
+    }
+}
+
+/// Lets through the values whose key is not remembered yet, remembering the key once the listener is dispatched.
+struct KeyGate<T, K, G, F> {
+    keys: Arc<Mutex<KeyLru<K>>>,
+    key: G,
+    callback: Arc<F>,
+    decode_errors: DecodeErrorHooks,
+    event: String,
+    id: ListenerId,
+    value: PhantomData<fn(T)>,
+}
+
+impl<T, K, G, F> KeyGate<T, K, G, F>
+where
+    for<'de> T: Deserialize<'de>,
+{
+    fn decode(&self, bytes: &[u8]) -> Option<T> {
+        self.decode_errors.decode(&self.event, self.id, bytes)
+    }
+}
+
+impl<T, K, G, F> Gate for KeyGate<T, K, G, F>
+where
+    for<'de> T: Deserialize<'de>,
+    T: Send + 'static,
+    K: Eq + Hash + Clone + Send,
+    G: Fn(&T) -> K + Sync + Send,
+    F: Fn(T) + 'static + Sync + Send,
+{
+    fn accepts(&self, bytes: &[u8]) -> bool {
+        self.decode(bytes).is_some_and(|value| {
+            !self
+                .keys
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .contains(&(self.key)(&value))
+        })
+    }
+
+    fn admit(&self, bytes: &[u8]) -> Option<Callback> {
+        let value = self.decode(bytes)?;
+        let is_new = self
+            .keys
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .see((self.key)(&value));
+        if !is_new {
+            return None;
+        }
+
+        // The decoded value is handed to the callback rather than decoded again from the bytes.
+        let callback = Arc::clone(&self.callback);
+        let value = Mutex::new(Some(value));
+        Some(Arc::new(move |_: Vec<u8>| {
+            if let Some(value) = value.lock().unwrap_or_else(PoisonError::into_inner).take() {
+                callback(value);
+            }
+        }))
+    }
+}
+
+impl EventEmitter {
+    /// Adds an event listener invoked at most once for each distinct key of the emitted values, ignoring the
+    /// values whose key it was already invoked for.
+    ///
+    /// Skipped values do not count toward the listener's limit. The listener remembers every key it was
+    /// dispatched for until `reset_keys`; use `once_per_key_bounded` to only remember a bounded number of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `key` - The function computing the key of a value.
+    /// * `callback` - The callback function to execute when a value with a new key is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use serde::Deserialize;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Visit {
+    ///     user_id: u32,
+    ///     page: String,
+    /// }
+    ///
+    /// event_emitter.once_per_key(
+    ///     "user.seen",
+    ///     |visit: &Visit| visit.user_id,
+    ///     |visit: Visit| println!("Welcome, user {}! You landed on {}", visit.user_id, visit.page),
+    /// );
+    /// ```
+    pub fn once_per_key<F, T, K, G>(&mut self, event: &str, key: G, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        T: Send + 'static,
+        K: Eq + Hash + Clone + Send + 'static,
+        G: Fn(&T) -> K + 'static + Sync + Send,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        self.once_per_key_bounded(event, None, key, callback)
+    }
+
+    /// Adds an event listener invoked at most once for each distinct key like `once_per_key`, remembering at
+    /// most `max_keys` keys.
+    ///
+    /// Beyond `max_keys`, the least recently seen key is forgotten, so a later value with that key is
+    /// delivered again. Seeing a remembered key again makes it the most recently seen.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `max_keys` - The maximum number of keys remembered, or `None` for no limit.
+    /// * `key` - The function computing the key of a value.
+    /// * `callback` - The callback function to execute when a value with a new key is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    pub fn once_per_key_bounded<F, T, K, G>(
+        &mut self,
+        event: &str,
+        max_keys: Option<usize>,
+        key: G,
+        callback: F,
+    ) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        T: Send + 'static,
+        K: Eq + Hash + Clone + Send + 'static,
+        G: Fn(&T) -> K + 'static + Sync + Send,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        let callback = Arc::new(callback);
+        let shared_callback = Arc::clone(&callback);
+        let parsed_callback =
+            self.decoding_callback(event, id, move |value: T| shared_callback(value));
+
+        let seen_keys = Arc::new(Mutex::new(KeyLru {
+            stamps: HashMap::new(),
+            order: BTreeMap::new(),
+            next_stamp: 0,
+            max_keys,
+        }));
+        let gate = Arc::new(KeyGate {
+            keys: Arc::clone(&seen_keys),
+            key,
+            callback,
+            decode_errors: self.hooks.decode_errors.clone(),
+            event: event.to_string(),
+            id,
+            value: PhantomData,
+        });
+        let filter_gate = Arc::clone(&gate);
+
+        let listener = Listener {
+            filter: Some(Arc::new(move |bytes: &[u8]| filter_gate.accepts(bytes))),
+            seen_keys: Some(seen_keys),
+            gate: Some(gate),
+            ..Listener::new(id, None, parsed_callback)
+        };
+        self.attach(event, listener)
+    }
+
+    /// Forgets the keys a listener added with `once_per_key` was invoked for, so that every key is delivered
+    /// again.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a listener added with `once_per_key` has this ID.
+    pub fn reset_keys(&mut self, id: ListenerId) -> bool {
+        let Some(seen_keys) = self
+            .listener(id)
+            .and_then(|listener| listener.seen_keys.as_ref())
+        else {
+            return false;
+        };
+        seen_keys.clear();
+        true
+    }
+
+    /// Returns the number of keys a listener added with `once_per_key` remembers, or `None` if no such listener
+    /// has this ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    pub fn seen_key_count(&self, id: ListenerId) -> Option<usize> {
+        self.listener(id)?
+            .seen_keys
+            .as_ref()
+            .map(|seen_keys| seen_keys.count())
+    }
+}
@@ -0,0 +1,255 @@
+//! Synchronous queries answered by responder listeners.
+//!
+//! Listeners added with `on_responder` return a value for each query. `EventEmitter::sync_query` collects the
+//! answers of every responder of an event, while `EventEmitter::sync_query_first` stops at the first one,
+//! which suits the chain of responsibility pattern. `EventEmitter::emit_iter` leaves the choice to the caller,
+//! invoking each responder only once its answer is asked for.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::event_emitter::{report_emit_error, to_json};
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A responder receiving the serialized query, returning its serialized answer or `None` if the query
+/// could not be decoded or the answer encoded.
+pub(crate) type Responder = Arc<dyn Fn(&[u8]) -> Option<Vec<u8>> + Sync + Send + 'static>;
+
+impl EventEmitter {
+    /// Adds a responder answering `sync_query` and `sync_query_first` on the given event.
+    ///
+    /// Responders are not invoked by the other emit methods. A responder returning an `Option` declines a
+    /// query by returning `None`, which `sync_query_first` skips. An answer that cannot be serialized counts as
+    /// no answer and is reported to the decode error hooks.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to answer.
+    /// * `callback` - The function computing the answer to a query.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added responder, which `remove_listener` accepts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_responder("file.handler", |extension: String| {
+    ///     (extension == "png").then(|| "image viewer".to_string())
+    /// });
+    /// ```
+    pub fn on_responder<F, T, R>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) -> R + 'static + Sync + Send,
+        R: Serialize,
+    {
+        let id = self.ids.next_id();
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let responder = move |bytes: &[u8]| {
+            let value = decode_errors.decode(&listener_event, id, bytes)?;
+            decode_errors.encode_reply(&listener_event, id, &callback(value))
+        };
+        if self.refuses_listeners(event) {
+            return id;
+        }
+
+        self.responders
+            .entry(event.to_string())
+            .or_default()
+            .push((id, Arc::new(responder)));
+        id
+    }
+
+    /// Removes the responder with the given ID, returning whether it was found.
+    pub(crate) fn remove_responder(&mut self, id: ListenerId) -> bool {
+        for responders in self.responders.values_mut() {
+            if let Some(index) = responders
+                .iter()
+                .position(|(responder_id, _)| *responder_id == id)
+            {
+                responders.remove(index);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Emits a query synchronously and collects the answers of the event responders.
+    ///
+    /// The value is first emitted to the regular listeners of the event like `sync_emit`; they fire normally
+    /// but contribute no answer. Answers that cannot be decoded into `R` are skipped and reported to the
+    /// decode error hooks. A query that cannot be serialized is printed and gets no answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The query passed to the responders.
+    ///
+    /// # Returns
+    ///
+    /// The decoded answers, in the registration order of their responders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_responder("file.handler", |_: String| "text editor".to_string());
+    /// event_emitter.on_responder("file.handler", |_: String| "hex viewer".to_string());
+    ///
+    /// let handlers: Vec<String> = event_emitter.sync_query("file.handler", "txt".to_string());
+    /// assert_eq!(vec!["text editor", "hex viewer"], handlers);
+    /// ```
+    pub fn sync_query<T, R>(&mut self, event: &str, value: T) -> Vec<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let Some(bytes) = self.emit_query(event, value) else {
+            return Vec::new();
+        };
+        self.query_responders(event, &bytes)
+            .filter_map(|(id, answer)| self.hooks.decode_errors.decode(event, id, &answer))
+            .collect()
+    }
+
+    /// Emits a query synchronously like `sync_query`, but stops at the first responder returning a value.
+    ///
+    /// Responders answering `None` decline the query and are skipped, as are answers that cannot be decoded
+    /// into `R`, which are reported to the decode error hooks. A query that cannot be serialized is printed and
+    /// gets no answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The query passed to the responders.
+    ///
+    /// # Returns
+    ///
+    /// The first decoded answer, or `None` if every responder declined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_responder("file.handler", |extension: String| {
+    ///     (extension == "png").then(|| "image viewer".to_string())
+    /// });
+    /// event_emitter.on_responder("file.handler", |_: String| Some("hex viewer".to_string()));
+    ///
+    /// let handler: Option<String> = event_emitter.sync_query_first("file.handler", "png".to_string());
+    /// assert_eq!(Some("image viewer".to_string()), handler);
+    /// ```
+    pub fn sync_query_first<T, R>(&mut self, event: &str, value: T) -> Option<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let bytes = self.emit_query(event, value)?;
+        let first = self
+            .query_responders(event, &bytes)
+            .filter(|(_, answer)| answer.as_slice() != b"null")
+            .find_map(|(id, answer)| self.hooks.decode_errors.decode(event, id, &answer));
+        first
+    }
+
+    /// Queries the responders of an event lazily, invoking each one only when the iterator is advanced.
+    ///
+    /// Unlike `sync_query`, the query is not emitted to the regular listeners of the event. Responders run on
+    /// the calling thread in registration order, and the caller can stop early, leaving the remaining ones
+    /// uninvoked. Responders declining with `None` yield `None` when `R` is an `Option`. Answers that cannot
+    /// be decoded into `R` are skipped and reported to the decode error hooks, as are queries that cannot be
+    /// serialized, which yield nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to query.
+    /// * `value` - The query passed to the responders.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the decoded answers, in the registration order of their responders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_responder("file.handler", |extension: String| {
+    ///     (extension == "png").then(|| "image viewer".to_string())
+    /// });
+    /// event_emitter.on_responder("file.handler", |_: String| Some("hex viewer".to_string()));
+    ///
+    /// let handler = event_emitter
+    ///     .emit_iter::<_, Option<String>>("file.handler", "txt".to_string())
+    ///     .flatten()
+    ///     .next();
+    /// assert_eq!(Some("hex viewer".to_string()), handler);
+    /// ```
+    pub fn emit_iter<T, R>(&self, event: &str, value: T) -> impl Iterator<Item = R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let (bytes, responders) = match to_json(&value) {
+            Ok(bytes) => (
+                bytes,
+                self.responders.get(event).cloned().unwrap_or_default(),
+            ),
+            Err(e) => {
+                report_emit_error(event, &e);
+                (Vec::new(), Vec::new())
+            }
+        };
+
+        let decode_errors = self.hooks.decode_errors.clone();
+        let event = event.to_string();
+        responders.into_iter().filter_map(move |(id, responder)| {
+            let answer = responder(&bytes)?;
+            decode_errors.decode(&event, id, &answer)
+        })
+    }
+
+    /// Emits a query to the regular listeners of the event, returning the serialized query, or `None` after
+    /// printing the error if it cannot be serialized.
+    fn emit_query<T>(&mut self, event: &str, value: T) -> Option<Vec<u8>>
+    where
+        T: Serialize,
+    {
+        let bytes = match to_json(&value) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                report_emit_error(event, &e);
+                return None;
+            }
+        };
+        self.sync_emit(event, value);
+        Some(bytes)
+    }
+
+    /// Lazily invokes the responders of an event in registration order, yielding the answer of each
+    /// responder that could decode the query.
+    fn query_responders<'a>(
+        &self,
+        event: &str,
+        bytes: &'a [u8],
+    ) -> impl Iterator<Item = (ListenerId, Vec<u8>)> + 'a {
+        let responders = self.responders.get(event).cloned().unwrap_or_default();
+        responders
+            .into_iter()
+            .filter_map(move |(id, responder)| Some((id, responder(bytes)?)))
+    }
+}
@@ -0,0 +1,372 @@
+//! Emits re-entering an emitter from its own dispatch, on the same thread.
+//!
+//! A listener running inline holds the lock of its emitter through the emit that invoked it, so emitting on
+//! the same emitter again from the same thread would deadlock; on wasm, where everything runs on one thread,
+//! that freezes the tab. The global facade and the functions of this module remember, per thread, which
+//! emitters they are dispatching on, and apply the emitter's `ReentrancyPolicy` to a nested emit instead of
+//! locking again. Emits from other threads are not re-entrant and wait for the lock as usual.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::deferred::Deferred;
+use crate::error::EmitError;
+use crate::event_emitter::{
+    report_emit_error, to_json, Callback, Filter, Gate, SharedEventEmitter,
+};
+use crate::guard::{self, Guard};
+use crate::id::ListenerId;
+use crate::limits;
+use crate::pattern::PatternTrie;
+use crate::EventEmitter;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// What a re-entrant emit does, set with `EventEmitter::set_reentrancy_policy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReentrancyPolicy {
+    /// The nested emit is queued and delivered once the outer dispatch completes, like `global::emit_deferred`.
+    #[default]
+    Defer,
+    /// The nested emit is delivered right away to the listeners the emitter had when the outer emit locked
+    /// it, those of its name then those of the patterns matching it. Their filters apply, but their limits are
+    /// not consumed, and listeners added with `on_seq` are left out, as the nested emit has no sequence number.
+    /// Listeners removed since are skipped. Changes made directly through the `listeners` field are only seen
+    /// once a method of the emitter changes its listeners too.
+    ///
+    /// The nested emit is refused if the emitter is shut down, and rejected by its guards and payload limits
+    /// like any emit. The rest of what an emit goes through needs the emitter itself, locked by the outer emit,
+    /// so the nested emit is neither sampled nor validated against a schema, counted in statistics or recorded
+    /// in a history, and the emitter's hold and the event's capture, coalescing and dead letters do not apply.
+    Snapshot,
+}
+
+/// A listener a re-entrant emit may invoke under `ReentrancyPolicy::Snapshot`.
+#[derive(Clone)]
+struct SnapshotListener {
+    id: ListenerId,
+    filter: Option<Filter>,
+    gate: Option<Arc<dyn Gate>>,
+    callback: Callback,
+    removed: Arc<AtomicBool>,
+}
+
+impl SnapshotListener {
+    /// Invokes the listener with `bytes` unless its filter or gate rejects them.
+    fn deliver(&self, bytes: &[u8]) {
+        match &self.gate {
+            Some(gate) => {
+                if let Some(callback) = gate.admit(bytes) {
+                    callback(bytes.to_vec());
+                }
+            }
+            None => {
+                if self.filter.as_ref().is_none_or(|filter| filter(bytes)) {
+                    (self.callback)(bytes.to_vec());
+                }
+            }
+        }
+    }
+}
+
+/// The listeners of the emitter as they were when the outer emit locked it.
+///
+/// The emitter keeps the snapshot it built until its listeners change, so that locking it again only clones
+/// the `Arc` holding it.
+#[derive(Default)]
+pub(crate) struct Snapshot {
+    /// The listeners stored under each key, an event name or a pattern, in registration order.
+    listeners: HashMap<String, Vec<SnapshotListener>>,
+    /// The pattern listeners, resolving the patterns matching the name of a nested emit.
+    patterns: PatternTrie,
+}
+
+impl Snapshot {
+    /// Returns the listeners an emit of `event` invokes: those of its name, then those of the patterns
+    /// matching it in registration order, like `take_dispatches`. Listeners of the trie that are not in the
+    /// snapshot under their pattern were removed or moved, and are skipped.
+    fn listeners_of(&self, event: &str) -> Vec<SnapshotListener> {
+        let mut listeners: Vec<&SnapshotListener> =
+            self.listeners.get(event).into_iter().flatten().collect();
+        for id in self.patterns.matches(event) {
+            let listener = self
+                .patterns
+                .pattern(id)
+                .filter(|pattern| *pattern != event)
+                .and_then(|pattern| self.listeners.get(pattern))
+                .and_then(|listeners| listeners.iter().find(|listener| listener.id == id));
+            listeners.extend(listener);
+        }
+
+        listeners
+            .into_iter()
+            .filter(|listener| !listener.removed.load(Ordering::Acquire))
+            .cloned()
+            .collect()
+    }
+}
+
+/// The checks of the emitter a re-entrant emit goes through under `ReentrancyPolicy::Snapshot`, as they were
+/// when the outer emit locked it.
+struct Checks {
+    shut_down: bool,
+    max_payload_bytes: Option<usize>,
+    max_json_depth: Option<usize>,
+    global_guards: Vec<(String, Guard)>,
+    guards: HashMap<String, Vec<(String, Guard)>>,
+}
+
+impl Checks {
+    fn of(emitter: &EventEmitter) -> Self {
+        Self {
+            shut_down: emitter.shut_down,
+            max_payload_bytes: emitter.max_payload_bytes,
+            max_json_depth: emitter.max_json_depth,
+            global_guards: emitter.global_guards.clone(),
+            guards: emitter.guards.clone(),
+        }
+    }
+
+    /// Returns the serialized value of a re-entrant emit of `event`, or the error refusing it.
+    fn apply(&self, event: &str, bytes: Vec<u8>) -> Result<Vec<u8>, EmitError> {
+        if self.shut_down {
+            return Err(EmitError::ShutDown);
+        }
+
+        let bytes = limits::check_limits(bytes, self.max_payload_bytes, self.max_json_depth)?;
+        guard::check_guards(&self.global_guards, &self.guards, event, bytes)
+    }
+}
+
+/// An emitter this thread is dispatching on, through the global facade or this module.
+struct Dispatch {
+    emitter: *const Mutex<EventEmitter>,
+    policy: ReentrancyPolicy,
+    deferred: Arc<Deferred>,
+    snapshot: Option<(Arc<Snapshot>, Checks)>,
+}
+
+thread_local! {
+    static DISPATCHES: RefCell<Vec<Dispatch>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Locks the emitter and runs `emit` on it, or applies its reentrancy policy if this thread is already
+/// dispatching on it.
+///
+/// A poisoned lock is recovered and cleared, and a panic raised by a listener is propagated only after the
+/// lock has been released, so that it does not poison the emitter.
+pub(crate) fn emit_locked<T, E>(
+    emitter: &Mutex<EventEmitter>,
+    event: &str,
+    value: T,
+    emit: E,
+) -> u64
+where
+    T: Serialize,
+    E: FnOnce(&mut EventEmitter, &str, T) -> u64,
+{
+    let key: *const Mutex<EventEmitter> = emitter;
+    if DISPATCHES.with(|dispatches| {
+        dispatches
+            .borrow()
+            .iter()
+            .any(|dispatch| dispatch.emitter == key)
+    }) {
+        reenter(key, event, &value);
+        return 0;
+    }
+
+    let mut guard = emitter.lock().unwrap_or_else(|poisoned| {
+        emitter.clear_poison();
+        poisoned.into_inner()
+    });
+    let policy = guard.reentrancy_policy;
+    let dispatch = Dispatch {
+        emitter: key,
+        policy,
+        deferred: Arc::clone(&guard.deferred),
+        snapshot: match policy {
+            ReentrancyPolicy::Defer => None,
+            ReentrancyPolicy::Snapshot => Some((guard.snapshot_listeners(), Checks::of(&guard))),
+        },
+    };
+
+    DISPATCHES.with(|dispatches| dispatches.borrow_mut().push(dispatch));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| emit(&mut guard, event, value)));
+    DISPATCHES.with(|dispatches| dispatches.borrow_mut().pop());
+    drop(guard);
+
+    match result {
+        Ok(seq) => seq,
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
+
+/// Applies the reentrancy policy of the emitter this thread is dispatching on to a nested emit.
+fn reenter<T>(key: *const Mutex<EventEmitter>, event: &str, value: &T)
+where
+    T: Serialize,
+{
+    let bytes = match to_json(value) {
+        Ok(bytes) => bytes,
+        Err(e) => return report_emit_error(event, &e),
+    };
+
+    // The callbacks run once the thread's dispatches are released, since they may re-enter again.
+    let delivery = DISPATCHES.with(|dispatches| {
+        let dispatches = dispatches.borrow();
+        let dispatch = dispatches
+            .iter()
+            .rev()
+            .find(|dispatch| dispatch.emitter == key)?;
+        match dispatch.policy {
+            ReentrancyPolicy::Defer => {
+                if dispatch.deferred.defer(event, bytes).is_err() {
+                    eprintln!(
+                        "Dropped the re-entrant emit of {}: the emitter is locked but not dispatching",
+                        event
+                    );
+                }
+                None
+            }
+            ReentrancyPolicy::Snapshot => {
+                let (snapshot, checks) = dispatch.snapshot.as_ref()?;
+                match checks.apply(event, bytes) {
+                    Ok(bytes) => Some((snapshot.listeners_of(event), bytes)),
+                    Err(e) => {
+                        report_emit_error(event, &e);
+                        None
+                    }
+                }
+            }
+        }
+    });
+
+    let Some((listeners, bytes)) = delivery else {
+        return;
+    };
+    for listener in listeners {
+        listener.deliver(&bytes);
+    }
+}
+
+/// Emits an event on a shared emitter like `EventEmitter::emit`, applying the emitter's reentrancy policy
+/// if the calling thread is already dispatching on it through this module or the global facade.
+///
+/// # Arguments
+///
+/// * `emitter` - The shared emitter.
+/// * `event` - The name of the event to emit.
+/// * `value` - The value to pass to the event listeners.
+///
+/// # Returns
+///
+/// The sequence number of the emit, or 0, which no emit is numbered with, for a re-entrant emit.
+pub fn emit<T>(emitter: &SharedEventEmitter, event: impl AsRef<str>, value: T) -> u64
+where
+    T: Serialize,
+{
+    emit_locked(emitter, event.as_ref(), value, |emitter, event, value| {
+        emitter.emit(event, value)
+    })
+}
+
+/// Emits an event synchronously on a shared emitter like `EventEmitter::sync_emit`, applying the emitter's
+/// reentrancy policy if the calling thread is already dispatching on it through this module or the global
+/// facade.
+///
+/// # Arguments
+///
+/// * `emitter` - The shared emitter.
+/// * `event` - The name of the event to emit.
+/// * `value` - The value to pass to the event listeners.
+///
+/// # Returns
+///
+/// The sequence number of the emit, or 0, which no emit is numbered with, for a re-entrant emit.
+///
+/// # Examples
+///
+/// ```
+/// use emitter_rs::reentrancy::{self, ReentrancyPolicy};
+/// use emitter_rs::EventEmitter;
+/// use std::sync::{Arc, Mutex};
+///
+/// let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+/// event_emitter.lock().unwrap().set_reentrancy_policy(ReentrancyPolicy::Defer);
+///
+/// let cloned_emitter = Arc::clone(&event_emitter);
+/// event_emitter.lock().unwrap().on("order.placed", move |id: u32| {
+///     reentrancy::sync_emit(&cloned_emitter, "order.audited", id);
+/// });
+/// event_emitter.lock().unwrap().on("order.audited", |id: u32| println!("Audited {}", id));
+///
+/// reentrancy::sync_emit(&event_emitter, "order.placed", 7_u32);
+/// ```
+pub fn sync_emit<T>(emitter: &SharedEventEmitter, event: impl AsRef<str>, value: T) -> u64
+where
+    T: Serialize,
+{
+    emit_locked(emitter, event.as_ref(), value, |emitter, event, value| {
+        emitter.sync_emit(event, value)
+    })
+}
+
+impl EventEmitter {
+    /// Sets what an emit re-entering the emitter from its own dispatch does, `ReentrancyPolicy::Defer` by
+    /// default.
+    ///
+    /// The policy applies to emits through the global facade and the functions of the `reentrancy` module; an
+    /// emit in progress keeps the policy it started with.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - What a re-entrant emit does.
+    pub fn set_reentrancy_policy(&mut self, policy: ReentrancyPolicy) {
+        self.reentrancy_policy = policy;
+    }
+
+    /// Returns what an emit re-entering the emitter from its own dispatch does.
+    pub fn reentrancy_policy(&self) -> ReentrancyPolicy {
+        self.reentrancy_policy
+    }
+
+    /// Returns the callbacks a plain emit could invoke, for re-entrant emits, building them again only if the
+    /// listeners changed since the last call.
+    fn snapshot_listeners(&mut self) -> Arc<Snapshot> {
+        if let Some(snapshot) = &self.reentrancy_snapshot {
+            return Arc::clone(snapshot);
+        }
+
+        let mut snapshot = Snapshot::default();
+        for (key, listeners) in &self.listeners {
+            let listeners: Vec<SnapshotListener> = listeners
+                .iter()
+                .filter(|listener| listener.is_reachable() && listener.seq_callback.is_none())
+                .map(|listener| SnapshotListener {
+                    id: listener.id,
+                    filter: listener.filter.clone(),
+                    gate: listener.gate.clone(),
+                    callback: Arc::clone(&listener.callback),
+                    removed: Arc::clone(&listener.removed),
+                })
+                .collect();
+            if !listeners.is_empty() {
+                snapshot.listeners.insert(key.clone(), listeners);
+            }
+        }
+        snapshot.patterns = self.patterns.clone();
+
+        let snapshot = Arc::new(snapshot);
+        self.reentrancy_snapshot = Some(Arc::clone(&snapshot));
+        snapshot
+    }
+
+    /// Drops the snapshot of the listeners kept for re-entrant emits, after a change to the listeners.
+    pub(crate) fn listeners_changed(&mut self) {
+        self.reentrancy_snapshot = None;
+    }
+}
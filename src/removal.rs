@@ -0,0 +1,228 @@
+//! Deferred listener removal.
+//!
+//! A [`ListenerRemover`] marks a listener as removed without access to the emitter, so it can be used from
+//! another thread while an emit is in progress, or from inside a callback while a shared emitter is locked.
+//! Removed listeners are skipped from then on and physically dropped by the next emit of their event, or
+//! by `EventEmitter::compact`.
+
+use crate::event_emitter::Listener;
+use crate::hooks::ListenerInfo;
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A handle removing a single listener, usable from any thread without locking the emitter.
+#[derive(Debug, Clone)]
+pub struct ListenerRemover {
+    id: ListenerId,
+    removed: Arc<AtomicBool>,
+}
+
+impl ListenerRemover {
+    /// Returns the ID of the listener this handle removes.
+    pub fn id(&self) -> ListenerId {
+        self.id
+    }
+
+    /// Marks the listener as removed and returns immediately.
+    ///
+    /// No invocation of the listener starts once this returns, even for an emit already in progress;
+    /// an invocation that had already started runs to completion.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the listener was live, `false` if it had already been removed.
+    pub fn remove(&self) -> bool {
+        !self.removed.swap(true, Ordering::AcqRel)
+    }
+
+    /// Returns whether the listener has been removed.
+    pub fn is_removed(&self) -> bool {
+        self.removed.load(Ordering::Acquire)
+    }
+}
+
+impl EventEmitter {
+    /// Returns a handle that removes the listener with the given ID without access to the emitter.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener.
+    ///
+    /// # Returns
+    ///
+    /// A `ListenerRemover`, or `None` if no live listener has this ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let listener_id = event_emitter.on("some_event", |value: String| println!("{}", value));
+    ///
+    /// let remover = event_emitter.remover(listener_id).unwrap();
+    /// std::thread::spawn(move || remover.remove()).join().unwrap();
+    ///
+    /// assert_eq!(0, event_emitter.listener_count("some_event"));
+    /// ```
+    pub fn remover(&self, id: ListenerId) -> Option<ListenerRemover> {
+        self.listeners
+            .values()
+            .flatten()
+            .find(|listener| listener.id == id && !listener.is_removed())
+            .map(|listener| ListenerRemover {
+                id,
+                removed: Arc::clone(&listener.removed),
+            })
+    }
+
+    /// Returns the number of live listeners of an event, leaving out removed listeners not yet swept.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    pub fn listener_count(&self, event: &str) -> usize {
+        self.listeners.get(event).map_or(0, |listeners| {
+            listeners
+                .iter()
+                .filter(|listener| !listener.is_removed())
+                .count()
+        })
+    }
+
+    /// Returns the number of listeners that a plain `emit` of the event would invoke right now.
+    ///
+    /// Unlike `listener_count`, this leaves out disabled listeners, listeners whose limit is exhausted and
+    /// listeners that only accept tagged emits, and counts the listeners of the patterns matching the event.
+    /// Listener filters depend on the emitted value and are not evaluated.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("user.created", |_: String| {});
+    /// let listener_id = event_emitter.on("user.created", |_: String| {});
+    ///
+    /// event_emitter.set_enabled(listener_id, false);
+    /// assert_eq!(2, event_emitter.listener_count("user.created"));
+    /// assert_eq!(1, event_emitter.effective_listener_count("user.created"));
+    /// ```
+    pub fn effective_listener_count(&self, event: &str) -> usize {
+        let exact = self.listeners.get(event).map_or(0, |listeners| {
+            listeners
+                .iter()
+                .filter(|listener| listener.is_reachable())
+                .count()
+        });
+        let patterns = self
+            .pattern_listeners(event)
+            .into_iter()
+            .filter(|(_, listener)| listener.is_reachable())
+            .count();
+        exact + patterns
+    }
+
+    /// Physically drops every listener removed through a `ListenerRemover`, firing the removal hooks, along with
+    /// the listeners an emit removed when it exhausted their limit, whose hooks already fired.
+    ///
+    /// Emits already sweep the removed listeners of the emitted event; this sweeps every event.
+    ///
+    /// # Returns
+    ///
+    /// The number of listeners dropped.
+    pub fn compact(&mut self) -> usize {
+        let mut swept = 0;
+        for (event, event_listeners) in self.listeners.iter_mut() {
+            let mut index = 0;
+            while index < event_listeners.len() {
+                if !event_listeners[index].is_removed() {
+                    index += 1;
+                    continue;
+                }
+
+                let listener = event_listeners.remove(index);
+                swept += 1;
+                if listener.retired {
+                    continue;
+                }
+                self.listener_events.remove(&listener.id);
+                let info = ListenerInfo::new(&listener, event_listeners.len());
+                self.hooks.fire_removed(event, &info);
+            }
+        }
+
+        self.listeners
+            .retain(|_, event_listeners| !event_listeners.is_empty());
+        if swept > 0 {
+            self.listeners_changed();
+        }
+        swept
+    }
+
+    /// Emits an event like `emit`, then removes the listeners of the event for which `should_remove` returns
+    /// `true`.
+    ///
+    /// The predicate sees each live listener once the emit has been dispatched, so listeners whose limit the
+    /// emit exhausted are already removed and limited listeners show their remaining calls. Removal happens
+    /// before any other emit of the event and fires the listener hooks like `remove_listener`; pooled callbacks
+    /// already started by the emit run to completion.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    /// * `should_remove` - The predicate selecting the listeners to remove.
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the removed listeners, in registration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("job.done", |id: u32| println!("Job {} done", id));
+    /// let listener_id = event_emitter.on_limited("job.done", Some(3), |id: u32| println!("Early: {}", id));
+    ///
+    /// let removed = event_emitter.emit_and_prune("job.done", 7_u32, |listener| {
+    ///     listener.limit.is_some()
+    /// });
+    /// assert_eq!(vec![listener_id], removed);
+    /// assert_eq!(1, event_emitter.listener_count("job.done"));
+    /// ```
+    pub fn emit_and_prune<T, P>(
+        &mut self,
+        event: impl AsRef<str>,
+        value: T,
+        should_remove: P,
+    ) -> Vec<ListenerId>
+    where
+        T: Serialize,
+        P: Fn(&Listener) -> bool,
+    {
+        let event = event.as_ref();
+        self.emit(event, value);
+        let ids: Vec<ListenerId> = self
+            .listeners
+            .get(event)
+            .map_or_else(Vec::new, |listeners| {
+                listeners
+                    .iter()
+                    .filter(|listener| !listener.is_removed() && should_remove(listener))
+                    .map(|listener| listener.id)
+                    .collect()
+            });
+
+        ids.into_iter()
+            .filter_map(|id| self.remove_listener(&id))
+            .collect()
+    }
+}
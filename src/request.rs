@@ -0,0 +1,248 @@
+//! Request/response on top of events, matched by correlation ID.
+//!
+//! `EventEmitter::request` emits a payload wrapped in an [`Envelope`] and blocks until a reply carrying
+//! the same correlation ID is sent through a [`Replier`], or until the timeout elapses.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::event_emitter::{report_emit_error, to_json};
+use crate::EventEmitter;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// A payload tagged with the correlation ID of the request it belongs to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    pub correlation_id: String,
+    pub payload: T,
+}
+
+/// The correlation ID of an envelope, read without decoding its payload.
+#[derive(Deserialize)]
+struct Correlation {
+    correlation_id: String,
+}
+
+/// An error returned by `EventEmitter::request`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    /// No reply with a matching correlation ID arrived before the timeout.
+    Timeout,
+    /// The matching reply could not be deserialized into the expected response type.
+    Decode(String),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Timeout => write!(f, "request timed out"),
+            RequestError::Decode(reason) => write!(f, "invalid response: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+/// Requests awaiting a reply and the replies not yet picked up by `request`.
+#[derive(Default)]
+struct RepliesState {
+    /// The response event of each pending request, keyed by correlation ID.
+    pending: HashMap<String, String>,
+    /// Serialized reply envelopes together with the response event they are emitted on.
+    queue: VecDeque<(String, Vec<u8>)>,
+}
+
+/// Replies shared between an emitter and its `Replier` handles.
+#[derive(Default)]
+pub(crate) struct Replies {
+    state: Mutex<RepliesState>,
+    ready: Condvar,
+}
+
+impl Replies {
+    /// Locks the replies, recovering them if a responder panicked while holding the lock.
+    fn lock(&self) -> MutexGuard<'_, RepliesState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A cloneable handle used by responders to answer requests, from any thread.
+#[derive(Clone)]
+pub struct Replier {
+    replies: Arc<Replies>,
+}
+
+impl Replier {
+    /// Answers the pending request with the given correlation ID.
+    ///
+    /// The reply is emitted on the request's response event as an `Envelope`, so regular
+    /// listeners of that event receive it as well. A response that cannot be serialized is printed and not
+    /// sent, so the request keeps waiting for another reply.
+    ///
+    /// # Arguments
+    ///
+    /// * `to_correlation` - The correlation ID of the request being answered.
+    /// * `value` - The response payload.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the reply was sent, `false` if the request is unknown, already timed out, or the response
+    /// cannot be serialized.
+    pub fn respond<T>(&self, to_correlation: &str, value: T) -> bool
+    where
+        T: Serialize,
+    {
+        // Serialized before locking, so that a panicking `Serialize` cannot poison the replies.
+        let envelope = Envelope {
+            correlation_id: to_correlation.to_string(),
+            payload: value,
+        };
+        let encoded = to_json(&envelope);
+
+        let mut state = self.replies.lock();
+        let Some(response_event) = state.pending.get(to_correlation).cloned() else {
+            return false;
+        };
+        let bytes = match encoded {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                report_emit_error(&response_event, &e);
+                return false;
+            }
+        };
+        state.queue.push_back((response_event, bytes));
+        self.replies.ready.notify_all();
+        true
+    }
+}
+
+impl EventEmitter {
+    /// Returns a handle that responders use to answer requests made with `request`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::request::Envelope;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let replier = event_emitter.replier();
+    /// event_emitter.on("query.weather", move |request: Envelope<String>| {
+    ///     replier.respond(&request.correlation_id, format!("sunny in {}", request.payload));
+    /// });
+    /// ```
+    pub fn replier(&self) -> Replier {
+        Replier {
+            replies: Arc::clone(&self.replies),
+        }
+    }
+
+    /// Emits a request and blocks until a reply with the same correlation ID arrives or the timeout elapses.
+    ///
+    /// The payload is emitted on `request_event` wrapped in an `Envelope` carrying a fresh correlation ID.
+    /// Responders answer with `Replier::respond`; replies are also emitted on `response_event` while waiting,
+    /// like by `emit`, so they are stamped, recorded and captured as any other emit.
+    /// Replies carrying another correlation ID are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_event` - The name of the event the request is emitted on.
+    /// * `response_event` - The name of the event replies are emitted on.
+    /// * `payload` - The request payload.
+    /// * `timeout` - How long to wait for a reply.
+    ///
+    /// # Returns
+    ///
+    /// The decoded response, or a `RequestError` if no valid reply arrived in time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::request::Envelope;
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let replier = event_emitter.replier();
+    /// event_emitter.on("query.weather", move |request: Envelope<String>| {
+    ///     replier.respond(&request.correlation_id, format!("sunny in {}", request.payload));
+    /// });
+    ///
+    /// let forecast: String = event_emitter
+    ///     .request("query.weather", "weather.result", "Paris".to_string(), Duration::from_secs(1))
+    ///     .unwrap();
+    /// assert_eq!("sunny in Paris", forecast);
+    /// ```
+    pub fn request<Req, Resp>(
+        &mut self,
+        request_event: &str,
+        response_event: &str,
+        payload: Req,
+        timeout: Duration,
+    ) -> Result<Resp, RequestError>
+    where
+        Req: Serialize,
+        Resp: DeserializeOwned,
+    {
+        let deadline = Instant::now() + timeout;
+        let correlation_id = self.ids.next_id().to_string();
+        self.replies
+            .lock()
+            .pending
+            .insert(correlation_id.clone(), response_event.to_string());
+
+        self.emit(
+            request_event,
+            Envelope {
+                correlation_id: correlation_id.clone(),
+                payload,
+            },
+        );
+
+        loop {
+            let (event, bytes) = match self.next_reply(deadline) {
+                Some(reply) => reply,
+                None => {
+                    self.replies.lock().pending.remove(&correlation_id);
+                    return Err(RequestError::Timeout);
+                }
+            };
+
+            let is_match = serde_json::from_slice::<Correlation>(&bytes)
+                .is_ok_and(|reply| reply.correlation_id == correlation_id);
+            let response = is_match.then(|| serde_json::from_slice::<Envelope<Resp>>(&bytes));
+            if let Err(e) = self.emit_encoded(&event, &[], || Ok(bytes)) {
+                report_emit_error(&event, &e);
+            }
+
+            if let Some(response) = response {
+                self.replies.lock().pending.remove(&correlation_id);
+                return response
+                    .map(|envelope| envelope.payload)
+                    .map_err(|e| RequestError::Decode(e.to_string()));
+            }
+        }
+    }
+
+    /// Waits for the next queued reply until the deadline.
+    fn next_reply(&self, deadline: Instant) -> Option<(String, Vec<u8>)> {
+        let mut state = self.replies.lock();
+        loop {
+            if let Some(reply) = state.queue.pop_front() {
+                return Some(reply);
+            }
+
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            state = self
+                .replies
+                .ready
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+}
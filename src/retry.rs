@@ -0,0 +1,477 @@
+//! Listeners that report failures, and emits retrying the failed ones.
+//!
+//! Listeners added with `on_result` return a `Result`. Plain emits ignore their errors beyond printing
+//! them, while `EventEmitter::emit_with_retry` calls them again after a delay until they succeed or run
+//! out of retries, and reports the outcome of every listener.
+//!
+//! Listeners added with `on_with_retry` retry on their own, whichever method emitted the value: a failed
+//! delivery is attempted again in the background, on a timer thread on native targets and with `setTimeout`
+//! on WebAssembly, following the listener's `RetryPolicy`.
+
+use crate::deadletter::DeadLetters;
+use crate::event_emitter::Listener;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::event_emitter::{report_emit_error, thread_name, to_json};
+use crate::hooks::{ListenerFailure, ListenerFailureHooks};
+use crate::id::ListenerId;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::idle::InFlightGuard;
+#[cfg(target_arch = "wasm32")]
+use crate::scheduling::set_timeout;
+use crate::EventEmitter;
+use serde::Deserialize;
+#[cfg(not(target_arch = "wasm32"))]
+use serde::Serialize;
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+use std::time::Duration;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::closure::Closure;
+
+/// A listener callback receiving the serialized event value and returning the error message on failure.
+pub(crate) type FallibleCallback = Arc<dyn Fn(&[u8]) -> Result<(), String> + Sync + Send + 'static>;
+
+/// Calls the callback of an `on_with_retry` listener with a serialized value, or returns `None` if the value
+/// cannot be decoded, which no retry would fix.
+type RetryAttempt =
+    Arc<dyn Fn(&[u8]) -> Option<Result<(), RetryableError>> + Sync + Send + 'static>;
+
+/// The final outcome of a listener invoked by `emit_with_retry`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenerOutcome {
+    /// The ID of the listener.
+    pub listener_id: ListenerId,
+    /// The number of times the listener was called.
+    pub attempts: u32,
+    /// The result of the last call, with the error message if it failed.
+    pub result: Result<(), String>,
+}
+
+/// How long an `on_with_retry` listener waits before retrying a failed delivery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// Waits the same delay before every retry.
+    Fixed(Duration),
+    /// Waits `base` before the first retry and doubles the delay before each following one, up to `cap`.
+    Exponential { base: Duration, cap: Duration },
+}
+
+/// How an `on_with_retry` listener retries the deliveries its callback fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of calls per delivery, including the first one. A policy of 0 or 1 never retries.
+    pub max_attempts: u32,
+    /// How long to wait before each retry.
+    pub backoff: Backoff,
+}
+
+impl RetryPolicy {
+    /// Returns the delay before the given retry of a delivery.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The number of the retry, 1 for the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::retry::{Backoff, RetryPolicy};
+    /// use std::time::Duration;
+    ///
+    /// let policy = RetryPolicy {
+    ///     max_attempts: 5,
+    ///     backoff: Backoff::Exponential {
+    ///         base: Duration::from_millis(100),
+    ///         cap: Duration::from_millis(300),
+    ///     },
+    /// };
+    /// assert_eq!(Duration::from_millis(100), policy.delay(1));
+    /// assert_eq!(Duration::from_millis(200), policy.delay(2));
+    /// assert_eq!(Duration::from_millis(300), policy.delay(3));
+    /// ```
+    pub fn delay(&self, retry: u32) -> Duration {
+        match self.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { base, cap } => base
+                .saturating_mul(2_u32.saturating_pow(retry.saturating_sub(1)))
+                .min(cap),
+        }
+    }
+}
+
+/// The error returned by the callback of an `on_with_retry` listener to have the delivery retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryableError(String);
+
+impl RetryableError {
+    /// Creates an error with the given message.
+    pub fn new(message: impl Display) -> Self {
+        Self(message.to_string())
+    }
+
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for RetryableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RetryableError {}
+
+/// A delivery of an `on_with_retry` listener, attempted until it succeeds, exhausts the listener's policy or the
+/// listener is removed.
+struct RetriedDelivery {
+    event: String,
+    listener_id: ListenerId,
+    attempt: RetryAttempt,
+    bytes: Vec<u8>,
+    policy: RetryPolicy,
+    removed: Arc<AtomicBool>,
+    failures: ListenerFailureHooks,
+    dead_letters: Arc<DeadLetters>,
+}
+
+impl RetriedDelivery {
+    /// Makes the given attempt, reporting the failure if it was the last one, and returns whether to retry.
+    fn attempt(&self, attempts: u32) -> bool {
+        match (self.attempt)(&self.bytes) {
+            None | Some(Ok(())) => false,
+            Some(Err(e)) if attempts >= self.policy.max_attempts => {
+                let failure = ListenerFailure {
+                    event: self.event.clone(),
+                    listener_id: self.listener_id,
+                    attempts,
+                    message: e.to_string(),
+                };
+                let posted = self.dead_letters.post(
+                    &self.event,
+                    self.listener_id,
+                    &self.bytes,
+                    failure.message.clone(),
+                );
+                if posted {
+                    self.failures.notify(&failure);
+                } else {
+                    self.failures.report(&failure);
+                }
+                false
+            }
+            Some(Err(_)) => true,
+        }
+    }
+
+    /// Retries the delivery on a timer thread, counted as in flight until the delivery is done.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn retry_later(self, guard: InFlightGuard) {
+        let name = format!("retry-{}", thread_name(&self.event, self.listener_id));
+        let spawned = thread::Builder::new().name(name).spawn(move || {
+            let _guard = guard;
+            let mut attempts = 1;
+            loop {
+                thread::sleep(self.policy.delay(attempts));
+                if self.removed.load(Ordering::Acquire) {
+                    return;
+                }
+
+                attempts += 1;
+                if !self.attempt(attempts) {
+                    return;
+                }
+            }
+        });
+
+        if let Err(e) = spawned {
+            eprintln!("Failed to spawn retry thread: {}", e);
+        }
+    }
+
+    /// Retries the delivery with `setTimeout`, after the given number of attempts.
+    #[cfg(target_arch = "wasm32")]
+    fn retry_later(self, attempts: u32) {
+        let delay = i32::try_from(self.policy.delay(attempts).as_millis()).unwrap_or(i32::MAX);
+        let retry = move || {
+            if self.removed.load(Ordering::Acquire) {
+                return;
+            }
+
+            if self.attempt(attempts + 1) {
+                self.retry_later(attempts + 1);
+            }
+        };
+        set_timeout(&Closure::once_into_js(retry), delay);
+    }
+}
+
+impl EventEmitter {
+    /// Adds an event listener whose failed deliveries are retried in the background according to `policy`.
+    ///
+    /// The first attempt runs like any listener callback. Each time the callback returns `Err`, the delivery is
+    /// attempted again after the policy's backoff, on a timer thread on native targets and with `setTimeout` on
+    /// WebAssembly, so the emit does not wait for the retries; `wait_until_idle` does on native targets. Once a
+    /// delivery fails its last attempt, the failure is reported to the listener failure hooks with the number
+    /// of attempts. A limited listener consumes one call per delivery, however many attempts it takes.
+    /// Removing the listener cancels the pending retries of its deliveries. A value that cannot be decoded is
+    /// reported to the decode error hooks and never retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `policy` - How many times and how often a failed delivery is attempted.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::retry::{Backoff, RetryPolicy, RetryableError};
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let policy = RetryPolicy {
+    ///     max_attempts: 3,
+    ///     backoff: Backoff::Fixed(Duration::from_millis(10)),
+    /// };
+    /// event_emitter.on_with_retry("order.placed", policy, |order_id: u32| {
+    ///     println!("Syncing order {}", order_id);
+    ///     Ok::<(), RetryableError>(())
+    /// });
+    /// event_emitter.emit("order.placed", 7_u32);
+    /// ```
+    pub fn on_with_retry<F, T>(
+        &mut self,
+        event: &str,
+        policy: RetryPolicy,
+        callback: F,
+    ) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) -> Result<(), RetryableError> + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let attempt: RetryAttempt = Arc::new(move |bytes: &[u8]| {
+            let value = decode_errors.decode(&listener_event, id, bytes)?;
+            Some(callback(value))
+        });
+
+        let removed = Arc::new(AtomicBool::new(false));
+        let cloned_removed = Arc::clone(&removed);
+        let failures = self.hooks.failures.clone();
+        let dead_letters = Arc::clone(&self.dead_letters);
+        #[cfg(not(target_arch = "wasm32"))]
+        let in_flight = Arc::clone(&self.in_flight);
+        let listener_event = event.to_string();
+        let parsed_callback = move |bytes: Vec<u8>| {
+            let delivery = RetriedDelivery {
+                event: listener_event.clone(),
+                listener_id: id,
+                attempt: Arc::clone(&attempt),
+                bytes,
+                policy,
+                removed: Arc::clone(&cloned_removed),
+                failures: failures.clone(),
+                dead_letters: Arc::clone(&dead_letters),
+            };
+            if delivery.attempt(1) {
+                #[cfg(not(target_arch = "wasm32"))]
+                delivery.retry_later(in_flight.start());
+                #[cfg(target_arch = "wasm32")]
+                delivery.retry_later(1);
+            }
+        };
+
+        let listener = Listener {
+            removed,
+            ..Listener::new(id, None, Arc::new(parsed_callback))
+        };
+        self.attach(event, listener)
+    }
+
+    /// Adds an event listener whose callback returns a `Result`, so that `emit_with_retry` can retry it.
+    ///
+    /// Other emit methods call it like any listener, printing the error if it fails, or emitting it on
+    /// `"deadletter"` once enabled with `enable_dead_letter`. A value that cannot be
+    /// decoded is reported to the decode error hooks and counts as a failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_result("order.placed", |order_id: u32| {
+    ///     if order_id == 0 {
+    ///         return Err("invalid order");
+    ///     }
+    ///     println!("Syncing order {}", order_id);
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn on_result<F, T, E>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) -> Result<(), E> + 'static + Sync + Send,
+        E: Display,
+    {
+        let id = self.ids.next_id();
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let fallible_callback: FallibleCallback = Arc::new(move |bytes: &[u8]| {
+            let value = decode_errors
+                .decode(&listener_event, id, bytes)
+                .ok_or_else(|| "the value could not be decoded".to_string())?;
+            callback(value).map_err(|e| e.to_string())
+        });
+
+        let cloned_fallible_callback = Arc::clone(&fallible_callback);
+        let dead_letters = Arc::clone(&self.dead_letters);
+        let listener_event = event.to_string();
+        let parsed_callback = move |bytes: Vec<u8>| {
+            if let Err(e) = cloned_fallible_callback(&bytes) {
+                if !dead_letters.post(&listener_event, id, &bytes, e.clone()) {
+                    eprintln!("Listener {} of {} failed: {}", id, listener_event, e);
+                }
+            }
+        };
+
+        let listener = Listener {
+            fallible_callback: Some(fallible_callback),
+            ..Listener::new(id, None, Arc::new(parsed_callback))
+        };
+        self.attach(event, listener)
+    }
+
+    /// Emits an event synchronously, retrying the `on_result` listeners that fail.
+    ///
+    /// Listeners run one at a time on the calling thread, in registration order, like `sync_emit`. A listener
+    /// added with `on_result` is called again after `backoff` each time it returns `Err`, up to `retries`
+    /// more times; other listeners are called once. Each emit consumes one call from a limited listener,
+    /// however many times it is retried. Held and captured emits are buffered like `sync_emit`, and report
+    /// no outcome, as does a value that cannot be serialized.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    /// * `retries` - The maximum number of additional calls of a failing listener.
+    /// * `backoff` - How long to wait before each retry.
+    ///
+    /// # Returns
+    ///
+    /// The outcome of each invoked listener, in registration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::sync::atomic::{AtomicU32, Ordering};
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let calls = AtomicU32::new(0);
+    /// event_emitter.on_result("order.placed", move |_: u32| {
+    ///     match calls.fetch_add(1, Ordering::SeqCst) {
+    ///         0 => Err("connection reset"),
+    ///         _ => Ok(()),
+    ///     }
+    /// });
+    ///
+    /// let outcomes = event_emitter.emit_with_retry("order.placed", 7_u32, 3, Duration::from_millis(10));
+    /// assert_eq!(2, outcomes[0].attempts);
+    /// assert_eq!(Ok(()), outcomes[0].result);
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn emit_with_retry<T>(
+        &mut self,
+        event: impl AsRef<str>,
+        value: T,
+        retries: u32,
+        backoff: Duration,
+    ) -> Vec<ListenerOutcome>
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        // The emit goes through the same steps as any other, up to the listeners it then retries itself.
+        let mut selected = None;
+        let prepared = self.prepare_emit(
+            event,
+            &[],
+            || to_json(&value),
+            |emitter, encoded| {
+                let bytes = encoded.into_bytes(&mut emitter.stats)?;
+                selected = Some((emitter.take_dispatches(event, &bytes, &[]), bytes));
+                Ok(Vec::new())
+            },
+        );
+        let jobs = match prepared {
+            Ok((_, jobs)) => jobs,
+            Err(e) => {
+                report_emit_error(event, &e);
+                return Vec::new();
+            }
+        };
+        // A coalesced delivery merges this value with others, so it runs like for `emit`, without retries.
+        let Some((dispatches, bytes)) = selected else {
+            self.run_prepared(event, jobs);
+            return Vec::new();
+        };
+
+        self.dispatching(|_| {
+            let mut outcomes = Vec::new();
+            for dispatch in dispatches {
+                if dispatch.removed.load(Ordering::Acquire) {
+                    continue;
+                }
+
+                let Some(fallible_callback) = &dispatch.fallible_callback else {
+                    (dispatch.callback)(bytes.clone());
+                    outcomes.push(ListenerOutcome {
+                        listener_id: dispatch.id,
+                        attempts: 1,
+                        result: Ok(()),
+                    });
+                    continue;
+                };
+
+                let mut attempts = 1;
+                let mut result = fallible_callback(&bytes);
+                while result.is_err() && attempts <= retries {
+                    std::thread::sleep(backoff);
+                    attempts += 1;
+                    result = fallible_callback(&bytes);
+                }
+
+                outcomes.push(ListenerOutcome {
+                    listener_id: dispatch.id,
+                    attempts,
+                    result,
+                });
+            }
+
+            outcomes
+        })
+    }
+}
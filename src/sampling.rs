@@ -0,0 +1,156 @@
+//! Emit-side sampling of noisy events, delivering a statistical sample of their emits to listeners.
+//!
+//! Sampling applies before anything else in an emit, so a sampled-out emit is not serialized, stamped,
+//! recorded, held or delivered, and costs little more than a map lookup. Sampled-out emits are counted by
+//! `EmitterStats::sampled_out` while statistics are collected.
+
+use crate::order::split_mix64;
+use crate::EventEmitter;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use web_time::{Instant, SystemTime};
+
+/// Which emits of an event are delivered, set with `EventEmitter::set_sampling`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sampling {
+    /// Delivers each emit with the given probability, from 0.0 for none to 1.0 for all. Probabilities out of
+    /// that range are clamped into it, and NaN delivers none. Draws come from a generator seeded with
+    /// `set_sampling_seed`, or randomly if no seed was set.
+    Ratio(f64),
+    /// Delivers every n-th emit, starting with the n-th. `OneIn(1)` delivers every emit and `OneIn(0)` none.
+    OneIn(u64),
+    /// Delivers at most the given number of emits per second, allowing bursts of up to that many.
+    PerSecond(u32),
+}
+
+/// The state of the sampling policy of an event.
+pub(crate) struct Sampler {
+    policy: Sampling,
+    /// The number of emits seen by a `OneIn` policy.
+    emits: u64,
+    /// The state of the generator drawing for a `Ratio` policy.
+    rng_state: u64,
+    /// The deliveries a `PerSecond` policy can still make, refilled continuously.
+    tokens: f64,
+    refilled_at: Option<Instant>,
+}
+
+impl Sampler {
+    fn new(policy: Sampling, seed: u64) -> Self {
+        Self {
+            policy,
+            emits: 0,
+            rng_state: seed,
+            tokens: 0.0,
+            refilled_at: None,
+        }
+    }
+
+    /// Returns whether the next emit is delivered, updating the state of the policy.
+    fn admit(&mut self) -> bool {
+        match self.policy {
+            Sampling::Ratio(ratio) => {
+                // The top 53 bits give a uniform float in [0, 1), so 1.0 always delivers and 0.0 never does.
+                let draw = (split_mix64(&mut self.rng_state) >> 11) as f64 / (1_u64 << 53) as f64;
+                draw < ratio.clamp(0.0, 1.0)
+            }
+            Sampling::OneIn(0) => false,
+            Sampling::OneIn(n) => {
+                self.emits += 1;
+                self.emits.is_multiple_of(n)
+            }
+            Sampling::PerSecond(rate) => {
+                let rate = f64::from(rate);
+                let now = Instant::now();
+                self.tokens = match self.refilled_at {
+                    Some(refilled_at) => {
+                        let elapsed = now.duration_since(refilled_at).as_secs_f64();
+                        (self.tokens + elapsed * rate).min(rate)
+                    }
+                    None => rate,
+                };
+                self.refilled_at = Some(now);
+                if self.tokens < 1.0 {
+                    return false;
+                }
+
+                self.tokens -= 1.0;
+                true
+            }
+        }
+    }
+}
+
+/// Draws a seed for the generators of the samplers when no seed was set.
+fn random_seed() -> u64 {
+    RandomState::new().hash_one(SystemTime::now())
+}
+
+impl EventEmitter {
+    /// Samples the emits of an event, delivering only those picked by `policy` and dropping the others.
+    ///
+    /// Setting a policy for an event that already has one replaces it and restarts its state.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to sample.
+    /// * `policy` - Which emits of the event are delivered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::sampling::Sampling;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("trace.span", |span: String| println!("{}", span));
+    ///
+    /// event_emitter.collect_stats(true);
+    /// event_emitter.set_sampling("trace.span", Sampling::OneIn(10));
+    /// for index in 0..100 {
+    ///     event_emitter.sync_emit("trace.span", format!("span {}", index));
+    /// }
+    /// assert_eq!(90, event_emitter.stats().sampled_out());
+    /// ```
+    pub fn set_sampling(&mut self, event: &str, policy: Sampling) {
+        let seed = self.sampling_seed.unwrap_or_else(random_seed);
+        self.samplers
+            .insert(event.to_string(), Sampler::new(policy, seed));
+    }
+
+    /// Seeds the generators drawing for `Sampling::Ratio` policies, so that the same seed and sequence of emits
+    /// always sample the same emits.
+    ///
+    /// The seed applies to the policies already set, restarting their generators, and to those set later.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the generators.
+    pub fn set_sampling_seed(&mut self, seed: u64) {
+        self.sampling_seed = Some(seed);
+        for sampler in self.samplers.values_mut() {
+            sampler.rng_state = seed;
+        }
+    }
+
+    /// Removes the sampling policy of an event, so that every emit is delivered again.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the sampled event.
+    pub fn clear_sampling(&mut self, event: &str) {
+        self.samplers.remove(event);
+    }
+
+    /// Returns whether an emit of `event` passes its sampling policy, counting it as sampled out otherwise.
+    pub(crate) fn sample(&mut self, event: &str) -> bool {
+        let Some(sampler) = self.samplers.get_mut(event) else {
+            return true;
+        };
+        if sampler.admit() {
+            return true;
+        }
+
+        self.stats.record_sampled_out();
+        false
+    }
+}
@@ -0,0 +1,108 @@
+//! Scheduling of pooled callbacks on WebAssembly.
+//!
+//! By default, `emit` runs pooled callbacks as microtasks, which all run before the browser gets back to
+//! rendering. A burst of emits with heavy listeners can therefore starve painting. Macrotask and idle
+//! scheduling queue each callback as its own task instead, so the event loop turns between callbacks.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::spawner::Spawner;
+use crate::EventEmitter;
+use serde::Serialize;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+
+/// How long an idle callback may wait for the browser to become idle before it runs anyway, in milliseconds.
+const IDLE_TIMEOUT_MS: u32 = 100;
+
+/// How `emit` schedules pooled callbacks on the JS event loop.
+///
+/// Inline callbacks and `sync_emit` are not affected. Limits and removal behave the same in every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scheduling {
+    /// Each callback runs as a microtask, before the browser renders or handles other events.
+    #[default]
+    Microtask,
+    /// Each callback runs as its own task queued with `setTimeout(0)`, letting the event loop turn in between.
+    Macrotask,
+    /// Each callback runs when the browser is idle, queued with `requestIdleCallback`, or after a short
+    /// timeout if the browser stays busy. Falls back to `Macrotask` where `requestIdleCallback` is missing.
+    Idle,
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    pub(crate) fn set_timeout(handler: &JsValue, millis: i32) -> i32;
+
+    #[wasm_bindgen(js_name = requestIdleCallback, catch)]
+    fn request_idle_callback(handler: &JsValue, options: &JsValue) -> Result<u32, JsValue>;
+}
+
+/// The options passed to `requestIdleCallback`.
+#[derive(Serialize)]
+struct IdleRequestOptions {
+    timeout: u32,
+}
+
+impl Spawner for Scheduling {
+    fn spawn(&self, run: Box<dyn FnOnce() + Send + 'static>) {
+        schedule(*self, run);
+    }
+}
+
+/// Schedules a pooled callback in the given mode.
+fn schedule(scheduling: Scheduling, run: Box<dyn FnOnce() + Send + 'static>) {
+    match scheduling {
+        Scheduling::Microtask => spawn_local(async move {
+            run();
+        }),
+        Scheduling::Macrotask => {
+            set_timeout(&Closure::once_into_js(run), 0);
+        }
+        Scheduling::Idle => {
+            let handler = Closure::once_into_js(run);
+            let options = serde_wasm_bindgen::to_value(&IdleRequestOptions {
+                timeout: IDLE_TIMEOUT_MS,
+            })
+            .unwrap_or(JsValue::UNDEFINED);
+            if request_idle_callback(&handler, &options).is_err() {
+                set_timeout(&handler, 0);
+            }
+        }
+    }
+}
+
+impl EventEmitter {
+    /// Sets how `emit` schedules pooled callbacks on the JS event loop, effective from the next emit.
+    ///
+    /// # Arguments
+    ///
+    /// * `scheduling` - The scheduling mode, `Scheduling::Microtask` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use emitter_rs::scheduling::Scheduling;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.set_wasm_scheduling(Scheduling::Macrotask);
+    /// event_emitter.on("frame.data", |value: Vec<u8>| println!("{} bytes", value.len()));
+    /// ```
+    pub fn set_wasm_scheduling(&mut self, scheduling: Scheduling) {
+        self.scheduling = scheduling;
+    }
+
+    /// Returns how `emit` schedules pooled callbacks.
+    pub fn wasm_scheduling(&self) -> Scheduling {
+        self.scheduling
+    }
+
+    /// Returns what runs the pooled callbacks: the injected spawner, or the emitter's scheduling.
+    pub(crate) fn spawner(&self) -> &dyn Spawner {
+        self.spawner.as_deref().unwrap_or(&self.scheduling)
+    }
+}
@@ -0,0 +1,150 @@
+//! Validation of emitted payloads against JSON Schemas, enabled by the `schema` feature.
+//!
+//! A schema registered for an event is checked once per emit, on the serialized JSON, before any listener
+//! runs, even if the event has no listeners. The feature is not available on WebAssembly. What happens to a payload failing validation depends on the emitter's [`Validation`] mode.
+//! Payloads that are not JSON, such as those of `emit_cbor`, are not validated.
+
+use crate::error::EmitError;
+use crate::event_emitter::report_emit_error;
+use crate::EventEmitter;
+use jsonschema::JSONSchema;
+use std::fmt;
+use std::sync::Arc;
+
+/// What an emitter does with payloads failing the schema of their event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Validation {
+    /// Payloads are not validated.
+    #[default]
+    Off,
+    /// Payloads failing validation are reported with their validation errors, then delivered anyway.
+    Warn,
+    /// Payloads failing validation are rejected with `EmitError::SchemaViolation` and delivered to no listener.
+    Enforce,
+}
+
+/// An error returned when a schema cannot be registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// The schema is not a valid JSON Schema.
+    InvalidSchema(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::InvalidSchema(reason) => write!(f, "invalid schema: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// The schema of an event together with the validation mode, checked on payloads once serialized.
+pub(crate) struct SchemaCheck {
+    schema: Arc<JSONSchema>,
+    validation: Validation,
+}
+
+impl SchemaCheck {
+    /// Validates a serialized payload of `event`, reporting it in `Warn` mode and rejecting it in `Enforce` mode.
+    pub(crate) fn apply(&self, event: &str, bytes: &[u8]) -> Result<(), EmitError> {
+        let Ok(instance) = serde_json::from_slice(bytes) else {
+            return Ok(());
+        };
+
+        let errors: Vec<String> = match self.schema.validate(&instance) {
+            Ok(()) => return Ok(()),
+            Err(errors) => errors.map(|error| error.to_string()).collect(),
+        };
+        let violation = EmitError::SchemaViolation(event.to_string(), errors);
+        match self.validation {
+            Validation::Enforce => Err(violation),
+            _ => {
+                report_emit_error(event, &violation);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl EventEmitter {
+    /// Registers the JSON Schema that payloads of an event must match, replacing any previous schema of the event.
+    ///
+    /// The schema takes effect from the next emit, provided validation is enabled with `set_validation`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    /// * `schema` - The JSON Schema of the event's payloads.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the schema was registered, or `SchemaError::InvalidSchema` if it could not be compiled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::error::EmitError;
+    /// use emitter_rs::schema::Validation;
+    /// use emitter_rs::EventEmitter;
+    /// use serde_json::json;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter
+    ///     .register_schema("user.created", json!({ "type": "string", "minLength": 1 }))
+    ///     .unwrap();
+    /// event_emitter.set_validation(Validation::Enforce);
+    ///
+    /// assert!(event_emitter.try_emit("user.created", "ada".to_string()).is_ok());
+    /// assert!(matches!(
+    ///     event_emitter.try_emit("user.created", 42),
+    ///     Err(EmitError::SchemaViolation(..))
+    /// ));
+    /// ```
+    pub fn register_schema(
+        &mut self,
+        event: &str,
+        schema: serde_json::Value,
+    ) -> Result<(), SchemaError> {
+        let compiled =
+            JSONSchema::compile(&schema).map_err(|e| SchemaError::InvalidSchema(e.to_string()))?;
+        self.schemas.insert(event.to_string(), Arc::new(compiled));
+        Ok(())
+    }
+
+    /// Removes the schema of an event, returning whether it had one.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    pub fn remove_schema(&mut self, event: &str) -> bool {
+        self.schemas.remove(event).is_some()
+    }
+
+    /// Sets what happens to payloads failing the schema of their event, `Validation::Off` by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `validation` - The validation mode.
+    pub fn set_validation(&mut self, validation: Validation) {
+        self.validation = validation;
+    }
+
+    /// Returns the validation mode.
+    pub fn validation(&self) -> Validation {
+        self.validation
+    }
+
+    /// Returns the check to apply to payloads of `event`, or `None` if they are not validated.
+    pub(crate) fn schema_check(&self, event: &str) -> Option<SchemaCheck> {
+        if self.validation == Validation::Off {
+            return None;
+        }
+
+        self.schemas.get(event).map(|schema| SchemaCheck {
+            schema: Arc::clone(schema),
+            validation: self.validation,
+        })
+    }
+}
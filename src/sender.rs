@@ -0,0 +1,106 @@
+//! Handles letting other threads queue emits for an emitter they do not own.
+//!
+//! An `EmitterSender` serializes values on the sending thread and appends them to a queue shared with its
+//! emitter. The owner of the emitter delivers the queued emits with `process_pending`, on its own thread,
+//! so the emitter never needs to be shared behind a mutex.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::error::EmitError;
+use crate::event_emitter::{report_emit_error, to_json};
+use crate::EventEmitter;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+/// The serialized emits queued by the senders of an emitter, in enqueue order.
+type Queue = Mutex<VecDeque<(String, Vec<u8>)>>;
+
+/// The queue of an emitter, shared with its senders.
+pub(crate) type PendingEmits = Arc<Queue>;
+
+/// A cheap, cloneable handle queuing emits for an emitter from any thread, returned by `sender`.
+#[derive(Debug, Clone)]
+pub struct EmitterSender {
+    pending: Weak<Queue>,
+}
+
+impl EmitterSender {
+    /// Serializes a value and queues its emit, to be delivered by the next `process_pending` of the emitter.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to emit.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` once queued, `EmitError::Serialize` if the value could not be serialized, or
+    /// `EmitError::Disconnected` if the emitter was dropped.
+    pub fn send<T>(&self, event: impl AsRef<str>, value: T) -> Result<(), EmitError>
+    where
+        T: Serialize,
+    {
+        let event = event.as_ref();
+        let pending = self.pending.upgrade().ok_or(EmitError::Disconnected)?;
+        let bytes = to_json(&value)?;
+        pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back((event.to_string(), bytes));
+        Ok(())
+    }
+}
+
+impl EventEmitter {
+    /// Returns a handle that other threads can use to queue emits for this emitter.
+    ///
+    /// Queued emits are delivered when the owner of the emitter calls `process_pending`, in the order they were
+    /// queued across all senders.
+    ///
+    /// # Returns
+    ///
+    /// A sender queuing emits for this emitter, which can be cloned and moved to other threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::thread;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("job.done", |job: u32| println!("Job {} done", job));
+    ///
+    /// let sender = event_emitter.sender();
+    /// thread::spawn(move || sender.send("job.done", 7_u32).unwrap())
+    ///     .join()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(1, event_emitter.process_pending());
+    /// ```
+    pub fn sender(&self) -> EmitterSender {
+        EmitterSender {
+            pending: Arc::downgrade(&self.pending),
+        }
+    }
+
+    /// Delivers the emits queued by the senders of this emitter, in the order they were queued.
+    ///
+    /// Each queued emit is delivered like `emit`. Emits queued while processing, including by the listeners of
+    /// the processed emits, are left for the next call.
+    ///
+    /// # Returns
+    ///
+    /// The number of queued emits processed.
+    pub fn process_pending(&mut self) -> usize {
+        let pending =
+            std::mem::take(&mut *self.pending.lock().unwrap_or_else(PoisonError::into_inner));
+        let processed = pending.len();
+        for (event, bytes) in pending {
+            if let Err(e) = self.emit_encoded(&event, &[], || Ok(bytes)) {
+                report_emit_error(&event, &e);
+            }
+        }
+
+        processed
+    }
+}
@@ -0,0 +1,74 @@
+//! The end of an emitter's lifecycle, for a graceful teardown.
+//!
+//! Shutting an emitter down waits for the callbacks still running on worker threads, then removes every
+//! listener. From then on, emits fail with `EmitError::ShutDown` and new listeners are dropped, so that a late
+//! emit or registration is reported instead of silently delivering to a half torn down application.
+
+use crate::error::EmitError;
+use crate::EventEmitter;
+
+impl EventEmitter {
+    /// Shuts the emitter down: waits for the callbacks in flight, then removes every listener.
+    ///
+    /// Afterwards, `try_emit` and the other `Result`-returning emit methods fail with `EmitError::ShutDown`, and
+    /// `try_on` fails with it too; the other emit and registration methods report it on stderr and do nothing.
+    /// Callbacks running on worker threads, such as those of keyed listeners, are waited for without timeout, so
+    /// they must not wait for the emitter themselves. On wasm, pooled callbacks not started yet are skipped.
+    /// Shutting down again does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::error::EmitError;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on("app.closing", |_: ()| println!("Saving state"));
+    /// event_emitter.sync_emit("app.closing", ());
+    /// event_emitter.shutdown();
+    ///
+    /// assert_eq!(0, event_emitter.listener_count("app.closing"));
+    /// assert_eq!(Err(EmitError::ShutDown), event_emitter.try_emit("app.closing", ()));
+    /// ```
+    pub fn shutdown(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.in_flight.wait_all();
+
+        let ids: Vec<_> = self
+            .listeners
+            .values()
+            .flatten()
+            .map(|listener| listener.id)
+            .collect();
+        for id in ids {
+            self.remove_listener(&id);
+        }
+        self.any_listeners.clear();
+        self.responders.clear();
+        self.async_repliers.clear();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.keyed.clear();
+    }
+
+    /// Returns whether the emitter was shut down with `shutdown`.
+    pub fn is_shut_down(&self) -> bool {
+        self.shut_down
+    }
+
+    /// Returns whether listeners are refused because the emitter was shut down, reporting the refusal.
+    pub(crate) fn refuses_listeners(&self, event: &str) -> bool {
+        if self.shut_down {
+            eprintln!(
+                "Failed to add listener to {}: {}",
+                event,
+                EmitError::ShutDown
+            );
+        }
+        self.shut_down
+    }
+}
@@ -0,0 +1,279 @@
+//! The extension point running pooled callbacks.
+//!
+//! Native emitters run each pooled callback on its own worker thread and wasm emitters schedule it on the JS
+//! event loop. An injected `Spawner` replaces both, so that tests can drive the callbacks with a manual
+//! executor and exercise limits and removal on every target, without threads or a browser.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::EventEmitter;
+#[cfg(test)]
+use std::sync::Arc;
+
+/// Runs the pooled callbacks of an emitter's dispatches.
+pub(crate) trait Spawner: Send + Sync {
+    /// Schedules a pooled callback.
+    fn spawn(&self, run: Box<dyn FnOnce() + Send + 'static>);
+}
+
+impl EventEmitter {
+    /// Creates an emitter whose pooled callbacks are run by `spawner` instead of worker threads or the JS
+    /// event loop. `wait_until_idle` does not wait for them.
+    #[cfg(test)]
+    pub(crate) fn with_spawner(spawner: Arc<dyn Spawner>) -> Self {
+        let mut event_emitter = Self::new();
+        event_emitter.spawner = Some(spawner);
+        event_emitter
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::Spawner;
+    use crate::EventEmitter;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    type Run = Box<dyn FnOnce() + Send + 'static>;
+
+    /// Queues pooled callbacks until they are drained, like an executor between two turns.
+    #[derive(Default)]
+    struct QueuedSpawner(Mutex<VecDeque<Run>>);
+
+    impl QueuedSpawner {
+        /// Runs the queued callbacks in order, including those queued while draining, and returns their number.
+        fn drain(&self) -> usize {
+            let mut ran = 0;
+            while let Some(run) = self.pop() {
+                run();
+                ran += 1;
+            }
+            ran
+        }
+
+        fn pop(&self) -> Option<Run> {
+            self.0.lock().unwrap().pop_front()
+        }
+    }
+
+    impl Spawner for QueuedSpawner {
+        fn spawn(&self, run: Run) {
+            self.0.lock().unwrap().push_back(run);
+        }
+    }
+
+    /// Runs pooled callbacks right away.
+    struct ImmediateSpawner;
+
+    impl Spawner for ImmediateSpawner {
+        fn spawn(&self, run: Run) {
+            run();
+        }
+    }
+
+    fn queued_emitter() -> (EventEmitter, Arc<QueuedSpawner>) {
+        let spawner = Arc::new(QueuedSpawner::default());
+        (EventEmitter::with_spawner(spawner.clone()), spawner)
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_limit_with_queued_spawner() {
+        let (mut event_emitter, spawner) = queued_emitter();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let cloned_received = Arc::clone(&received);
+        event_emitter.on_limited("tick", Some(2), move |value: u32| {
+            cloned_received.lock().unwrap().push(value);
+        });
+
+        for value in 1..=3_u32 {
+            event_emitter.emit("tick", value);
+        }
+        assert!(
+            received.lock().unwrap().is_empty(),
+            "Pooled callbacks should wait for the spawner"
+        );
+        assert_eq!(
+            0,
+            event_emitter.listener_count("tick"),
+            "The limit should be consumed at emit"
+        );
+
+        assert_eq!(2, spawner.drain());
+        assert_eq!(vec![1, 2], *received.lock().unwrap());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_removal_with_queued_spawner() {
+        let (mut event_emitter, spawner) = queued_emitter();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let cloned_received = Arc::clone(&received);
+        let listener_id = event_emitter.on("tick", move |value: u32| {
+            cloned_received.lock().unwrap().push(value);
+        });
+
+        event_emitter.emit("tick", 1_u32);
+        event_emitter.remove_listener(&listener_id);
+        event_emitter.emit("tick", 2_u32);
+
+        assert_eq!(
+            1,
+            spawner.drain(),
+            "Only the emit before removal should be queued"
+        );
+        assert!(
+            received.lock().unwrap().is_empty(),
+            "A callback queued before removal should be skipped"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_order_with_queued_spawner() {
+        let (mut event_emitter, spawner) = queued_emitter();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        for name in ["first", "second"] {
+            let cloned_received = Arc::clone(&received);
+            event_emitter.on("tick", move |value: u32| {
+                cloned_received
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", name, value));
+            });
+        }
+
+        event_emitter.emit("tick", 1_u32);
+        event_emitter.emit("tick", 2_u32);
+        spawner.drain();
+
+        assert_eq!(
+            vec!["first 1", "second 1", "first 2", "second 2"],
+            *received.lock().unwrap(),
+            "Callbacks should run in emit order, then registration order"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_immediate_spawner() {
+        let mut event_emitter = EventEmitter::with_spawner(Arc::new(ImmediateSpawner));
+        let received = Arc::new(Mutex::new(0));
+        let cloned_received = Arc::clone(&received);
+        event_emitter.on_limited("tick", Some(1), move |value: u32| {
+            *cloned_received.lock().unwrap() += value;
+        });
+
+        event_emitter.emit("tick", 5_u32);
+        event_emitter.emit("tick", 7_u32);
+        assert_eq!(5, *received.lock().unwrap());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_on_limited_with_queued_spawner() {
+        let (mut event_emitter, spawner) = queued_emitter();
+        let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+
+        let cloned_counter = Arc::clone(&counter);
+        let listener_id = event_emitter.on_limited("Set", Some(2), move |value: u32| {
+            *cloned_counter.lock().unwrap() = value;
+        });
+        assert_eq!(
+            Some(Some(2)),
+            event_emitter.remaining_limit(listener_id),
+            "Listener should have been added with a limit of 2 calls"
+        );
+
+        event_emitter.emit("Set", 10_u32);
+        assert_eq!(
+            Some(Some(1)),
+            event_emitter.remaining_limit(listener_id),
+            "Listener limit should have been reduced by 1 before its callback runs"
+        );
+        assert_eq!(1, spawner.drain());
+        assert_eq!(
+            10,
+            *counter.lock().unwrap(),
+            "Counter should have been set to the emitted value"
+        );
+
+        event_emitter.emit("Set", 20_u32);
+        event_emitter.emit("Set", 30_u32);
+        assert_eq!(
+            0,
+            event_emitter.listener_count("Set"),
+            "Listener should have been removed after reaching its limit"
+        );
+        assert_eq!(1, spawner.drain());
+        assert_eq!(
+            20,
+            *counter.lock().unwrap(),
+            "Counter should not have been changed after the last call"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_once_with_queued_spawner() {
+        let (mut event_emitter, spawner) = queued_emitter();
+        let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+
+        let cloned_counter = Arc::clone(&counter);
+        event_emitter.once("Set Once", move |value: u32| {
+            *cloned_counter.lock().unwrap() = value;
+        });
+
+        event_emitter.emit("Set Once", 10_u32);
+        event_emitter.emit("Set Once", 20_u32);
+        assert_eq!(
+            1,
+            spawner.drain(),
+            "Only the first emit should have been queued"
+        );
+        assert_eq!(
+            10,
+            *counter.lock().unwrap(),
+            "Counter should not have been changed after the first call"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_remove_listener_with_queued_spawner() {
+        let (mut event_emitter, spawner) = queued_emitter();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let cloned_received = Arc::clone(&received);
+        let listener_id = event_emitter.on("Hello rust!", move |value: String| {
+            cloned_received.lock().unwrap().push(value);
+        });
+
+        event_emitter.emit("Hello rust!", "first".to_string());
+        assert_eq!(
+            None,
+            event_emitter.remove_listener(&crate::id::ListenerId(u64::MAX)),
+            "Should not have removed listener"
+        );
+        assert_eq!(1, spawner.drain());
+
+        event_emitter.emit("Hello rust!", "second".to_string());
+        assert_eq!(
+            Some(listener_id),
+            event_emitter.remove_listener(&listener_id)
+        );
+        assert_eq!(
+            0,
+            event_emitter.listener_count("Hello rust!"),
+            "Should have removed listener"
+        );
+        spawner.drain();
+        assert_eq!(
+            vec!["first".to_string()],
+            *received.lock().unwrap(),
+            "The callback queued before removal should have been skipped"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! State machines driven by the events of a shared emitter.
+//!
+//! A `StateMachine` holds a state and the transitions declared with `allow`. Emitting the event of a
+//! transition on the emitter moves the machine from the transition's source state to its target state, then
+//! emits `state.exited.<from>` and `state.entered.<to>` with a `TransitionInfo`. Emitting it in any other
+//! state leaves the state unchanged and emits `state.rejected` with a `RejectedTransition` instead. States
+//! are named in these events by their serialized form, such as `Idle` for a unit enum variant.
+//!
+//! Transitions are applied under the machine's lock, one at a time, so concurrent emits never observe or
+//! produce a half-applied transition. The resulting events are emitted once the dispatch of the triggering
+//! emit completes.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::deferred::Deferred;
+use crate::event_emitter::{
+    report_emit_error, to_json, Callback, Execution, Listener, SharedEventEmitter,
+};
+use crate::removal::ListenerRemover;
+use crate::EventEmitter;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, PoisonError, TryLockError, Weak};
+
+/// The event emitted when a machine rejects a transition.
+pub const REJECTED_EVENT: &str = "state.rejected";
+
+/// The payload of `state.exited.<from>` and `state.entered.<to>`, describing a transition that happened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitionInfo<S> {
+    /// The state the machine left.
+    pub from: S,
+    /// The state the machine entered.
+    pub to: S,
+    /// The event that triggered the transition.
+    pub event: String,
+}
+
+/// The payload of `state.rejected`, describing an event that has no transition from the current state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedTransition<S> {
+    /// The state the machine stayed in.
+    pub state: S,
+    /// The event that triggered no transition.
+    pub event: String,
+}
+
+/// The state and transitions of a machine, shared with the listeners of its events.
+struct Machine<S> {
+    state: Mutex<S>,
+    /// The target state of each transition, by event and source state.
+    transitions: Mutex<HashMap<String, HashMap<S, S>>>,
+    emitter: Weak<Mutex<EventEmitter>>,
+    deferred: Arc<Deferred>,
+}
+
+impl<S> Machine<S>
+where
+    S: Eq + Hash + Clone + Serialize + Send + 'static,
+{
+    /// Applies the transition of `event` from the current state, then emits its outcome.
+    fn fire(&self, event: &str) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let to = self
+            .transitions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(event)
+            .and_then(|targets| targets.get(&*state))
+            .cloned();
+
+        let Some(to) = to else {
+            let rejected = RejectedTransition {
+                state: state.clone(),
+                event: event.to_string(),
+            };
+            drop(state);
+            self.emit(REJECTED_EVENT, &rejected);
+            return;
+        };
+
+        let from = std::mem::replace(&mut *state, to.clone());
+        drop(state);
+        let info = TransitionInfo {
+            from,
+            to,
+            event: event.to_string(),
+        };
+        self.emit(&format!("state.exited.{}", state_name(&info.from)), &info);
+        self.emit(&format!("state.entered.{}", state_name(&info.to)), &info);
+    }
+
+    /// Emits an outcome after the dispatch in progress, or right away if the emitter is not dispatching.
+    fn emit<T>(&self, event: &str, value: &T)
+    where
+        T: Serialize,
+    {
+        let bytes = match to_json(value) {
+            Ok(bytes) => bytes,
+            Err(e) => return report_emit_error(event, &e),
+        };
+        let Err(bytes) = self.deferred.defer(event, bytes) else {
+            return;
+        };
+        let Some(emitter) = self.emitter.upgrade() else {
+            return;
+        };
+
+        let mut emitter = match emitter.try_lock() {
+            Ok(emitter) => emitter,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                eprintln!(
+                    "Failed to emit {}: the emitter is locked outside of a dispatch",
+                    event
+                );
+                return;
+            }
+        };
+        if let Err(e) = emitter.emit_encoded(event, &[], || Ok(bytes)) {
+            report_emit_error(event, &e);
+        }
+    }
+}
+
+/// Names a state in event names by its serialized form, without quotes for strings and unit variants.
+fn state_name<S>(state: &S) -> String
+where
+    S: Serialize,
+{
+    match serde_json::to_value(state) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(value) => value.to_string(),
+        Err(e) => e.to_string(),
+    }
+}
+
+/// A state machine whose transitions are triggered by the events of a shared emitter.
+///
+/// Dropping the machine removes the listeners of its events.
+pub struct StateMachine<S> {
+    machine: Arc<Machine<S>>,
+    emitter: SharedEventEmitter,
+    removers: Vec<ListenerRemover>,
+}
+
+impl<S> StateMachine<S>
+where
+    S: Eq + Hash + Clone + Serialize + Send + 'static,
+{
+    /// Creates a machine in the `initial` state, driven by the events of `emitter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `emitter` - The emitter whose events trigger the transitions, and on which their outcome is emitted.
+    /// * `initial` - The state of the machine before any transition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::state::{StateMachine, TransitionInfo};
+    /// use emitter_rs::EventEmitter;
+    /// use serde::{Deserialize, Serialize};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    /// enum Door {
+    ///     Closed,
+    ///     Open,
+    /// }
+    ///
+    /// let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    /// let mut door = StateMachine::new(&event_emitter, Door::Closed);
+    /// door.allow(Door::Closed, "door.push", Door::Open);
+    /// door.allow(Door::Open, "door.pull", Door::Closed);
+    ///
+    /// event_emitter.lock().unwrap().on("state.entered.Open", |info: TransitionInfo<Door>| {
+    ///     println!("Opened by {}", info.event);
+    /// });
+    /// event_emitter.lock().unwrap().emit("door.push", ());
+    /// assert_eq!(Door::Open, door.current());
+    /// ```
+    pub fn new(emitter: &SharedEventEmitter, initial: S) -> Self {
+        let deferred = Arc::clone(
+            &emitter
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .deferred,
+        );
+        Self {
+            machine: Arc::new(Machine {
+                state: Mutex::new(initial),
+                transitions: Mutex::default(),
+                emitter: Arc::downgrade(emitter),
+                deferred,
+            }),
+            emitter: Arc::clone(emitter),
+            removers: Vec::new(),
+        }
+    }
+
+    /// Declares that emitting `event` in the `from` state moves the machine to the `to` state.
+    ///
+    /// Declaring another transition from the same state on the same event replaces it.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The state the transition starts from.
+    /// * `event` - The event triggering the transition.
+    /// * `to` - The state the transition leads to.
+    pub fn allow(&mut self, from: S, event: &str, to: S) {
+        let first_of_event = {
+            let mut transitions = self
+                .machine
+                .transitions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let first_of_event = !transitions.contains_key(event);
+            transitions
+                .entry(event.to_string())
+                .or_default()
+                .insert(from, to);
+            first_of_event
+        };
+        if !first_of_event {
+            return;
+        }
+
+        let machine = Arc::clone(&self.machine);
+        let trigger = event.to_string();
+        let callback: Callback = Arc::new(move |_: Vec<u8>| machine.fire(&trigger));
+        let mut emitter = self.emitter.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut listener = Listener::new(emitter.next_listener_id(), None, callback);
+        listener.execution = Execution::Inline;
+        let id = emitter.add_listener(event, listener);
+        self.removers.extend(emitter.remover(id));
+    }
+
+    /// Returns the current state of the machine.
+    pub fn current(&self) -> S {
+        self.machine
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl<S> Drop for StateMachine<S> {
+    fn drop(&mut self) {
+        for remover in &self.removers {
+            remover.remove();
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Opt-in counters of the activity of an emitter over its lifetime.
+//!
+//! Statistics are collected once enabled with `EventEmitter::collect_stats`, and cost a flag check per emit
+//! otherwise. They count the emits of the emitter itself, not those of a `FastEventEmitter`.
+
+use crate::EventEmitter;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Counters of the emits of an emitter, collected while enabled with `collect_stats`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct EmitterStats {
+    #[serde(skip)]
+    enabled: bool,
+    emits: u64,
+    invocations: u64,
+    bytes_serialized: u64,
+    expired: u64,
+    compression_saved: u64,
+    sampled_out: u64,
+    event_emits: HashMap<String, u64>,
+}
+
+impl EmitterStats {
+    /// Returns whether the counters are being updated.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the number of emits, across all events, including emits of events without listeners.
+    pub fn emits(&self) -> u64 {
+        self.emits
+    }
+
+    /// Returns the number of listener invocations dispatched by emits.
+    pub fn invocations(&self) -> u64 {
+        self.invocations
+    }
+
+    /// Returns the total size of the serialized payloads of the emits.
+    ///
+    /// A payload shared by several emits, as with `emit_shared`, counts once per emit. Emits whose value is
+    /// never serialized, because no listener needs it, count no bytes.
+    pub fn bytes_serialized(&self) -> u64 {
+        self.bytes_serialized
+    }
+
+    /// Returns the number of buffered payloads dropped because they outlived the buffer TTL of their event.
+    pub fn expired(&self) -> u64 {
+        self.expired
+    }
+
+    /// Returns the number of bytes saved by compressing payloads with `set_compression`.
+    pub fn compression_saved(&self) -> u64 {
+        self.compression_saved
+    }
+
+    /// Returns the number of emits dropped by the sampling policies of their event, set with `set_sampling`.
+    ///
+    /// Sampled-out emits are not counted by `emits`.
+    pub fn sampled_out(&self) -> u64 {
+        self.sampled_out
+    }
+
+    /// Returns the number of emits of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    pub fn event_emits(&self, event: &str) -> u64 {
+        self.event_emits.get(event).copied().unwrap_or(0)
+    }
+
+    /// Returns the emitted events with their number of emits, busiest first, ties in name order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.collect_stats(true);
+    /// event_emitter.sync_emit("tick", 1_u32);
+    /// event_emitter.sync_emit("tick", 2_u32);
+    /// event_emitter.sync_emit("tock", 1_u32);
+    /// assert_eq!(vec![("tick", 2), ("tock", 1)], event_emitter.stats().busiest_events());
+    /// ```
+    pub fn busiest_events(&self) -> Vec<(&str, u64)> {
+        let mut events: Vec<(&str, u64)> = self
+            .event_emits
+            .iter()
+            .map(|(event, count)| (event.as_str(), *count))
+            .collect();
+        events.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        events
+    }
+
+    /// Counts an emit of `event`.
+    pub(crate) fn record_emit(&mut self, event: &str) {
+        if !self.enabled {
+            return;
+        }
+
+        self.emits += 1;
+        match self.event_emits.get_mut(event) {
+            Some(count) => *count += 1,
+            None => {
+                self.event_emits.insert(event.to_string(), 1);
+            }
+        }
+    }
+
+    /// Forgets the emit count of an event, pruned by `prune_unused_event_names`.
+    pub(crate) fn forget_event(&mut self, event: &str) {
+        self.event_emits.remove(event);
+    }
+
+    /// Counts the listener invocations dispatched by an emit.
+    pub(crate) fn record_invocations(&mut self, invocations: usize) {
+        if self.enabled {
+            self.invocations += invocations as u64;
+        }
+    }
+
+    /// Counts the buffered payloads dropped by their buffer TTL.
+    pub(crate) fn record_expired(&mut self, expired: usize) {
+        if self.enabled {
+            self.expired += expired as u64;
+        }
+    }
+
+    /// Counts the bytes saved by compressing a payload.
+    #[cfg_attr(not(feature = "compress"), allow(dead_code))]
+    pub(crate) fn record_compression(&mut self, saved: usize) {
+        if self.enabled {
+            self.compression_saved += saved as u64;
+        }
+    }
+
+    /// Counts an emit dropped by the sampling policy of its event.
+    pub(crate) fn record_sampled_out(&mut self) {
+        if self.enabled {
+            self.sampled_out += 1;
+        }
+    }
+
+    /// Counts a payload serialized for an emit.
+    pub(crate) fn record_bytes(&mut self, len: usize) {
+        if self.enabled {
+            self.bytes_serialized += len as u64;
+        }
+    }
+}
+
+impl EventEmitter {
+    /// Enables or disables the collection of emit statistics, returned by `stats`.
+    ///
+    /// Collection is disabled by default. Disabling it keeps the counts collected so far, and enabling it again
+    /// resumes counting from them.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether statistics are collected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("some_event", |value: String| println!("{}", value));
+    ///
+    /// event_emitter.collect_stats(true);
+    /// event_emitter.sync_emit("some_event", "Hello, world!".to_string());
+    ///
+    /// assert_eq!(1, event_emitter.stats().emits());
+    /// assert_eq!(1, event_emitter.stats().invocations());
+    /// assert_eq!(15, event_emitter.stats().bytes_serialized());
+    /// ```
+    pub fn collect_stats(&mut self, enabled: bool) {
+        self.stats.enabled = enabled;
+    }
+
+    /// Returns the emit statistics collected so far.
+    pub fn stats(&self) -> &EmitterStats {
+        &self.stats
+    }
+
+    /// Clears the emit statistics, without changing whether they are collected.
+    pub fn reset_stats(&mut self) {
+        self.stats = EmitterStats {
+            enabled: self.stats.enabled,
+            ..EmitterStats::default()
+        };
+    }
+}
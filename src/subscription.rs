@@ -0,0 +1,99 @@
+//! Listener handles remembering the type of value their listener expects.
+//!
+//! `subscribe` adds a listener like `on` but returns a `TypedSubscription<T>` instead of a bare
+//! `ListenerId`, so that values delivered to that listener alone with `emit_to_subscription` are checked
+//! against its type at compile time rather than failing to decode at runtime.
+
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The handle of a listener expecting values of type `T`, returned by `subscribe`.
+pub struct TypedSubscription<T> {
+    id: ListenerId,
+    value_type: PhantomData<fn(T)>,
+}
+
+impl<T> TypedSubscription<T> {
+    /// Returns the ID of the listener, for `remove_listener` and the other methods taking one.
+    pub fn id(&self) -> ListenerId {
+        self.id
+    }
+}
+
+impl<T> Clone for TypedSubscription<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TypedSubscription<T> {}
+
+impl<T> fmt::Debug for TypedSubscription<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedSubscription")
+            .field("id", &self.id)
+            .field("value_type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T> From<TypedSubscription<T>> for ListenerId {
+    fn from(subscription: TypedSubscription<T>) -> Self {
+        subscription.id
+    }
+}
+
+impl EventEmitter {
+    /// Adds an event listener like `on`, returning a handle that remembers the type of value it expects.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The subscription of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let subscription = event_emitter.subscribe("inbox", |message: String| println!("{}", message));
+    /// assert!(event_emitter.emit_to_subscription(&subscription, "Hello!".to_string()));
+    /// ```
+    pub fn subscribe<F, T>(&mut self, event: &str, callback: F) -> TypedSubscription<T>
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        TypedSubscription {
+            id: self.on(event, callback),
+            value_type: PhantomData,
+        }
+    }
+
+    /// Delivers a value to the listener of a subscription only, like `emit_to_id`.
+    ///
+    /// The value must have the type the listener expects, so it always decodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `subscription` - The subscription of the listener.
+    /// * `value` - The value to pass to the listener.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the listener was invoked, `false` if it was removed or could not be invoked.
+    pub fn emit_to_subscription<T>(&mut self, subscription: &TypedSubscription<T>, value: T) -> bool
+    where
+        T: Serialize,
+    {
+        self.emit_to_id(subscription.id, value)
+    }
+}
@@ -0,0 +1,251 @@
+//! Views of a shared emitter confined to the events of one tenant, such as a plugin.
+//!
+//! A `TenantEmitter` prefixes every event name it is given with its tenant's prefix and a dot, so a tenant
+//! can only listen to and emit its own events, and only remove the listeners it added. The host keeps the
+//! shared emitter itself, on which it sees every tenant's events under their full names, and can remove all
+//! the listeners of a tenant at once with `revoke_tenant`.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use crate::error::EmitError;
+use crate::event_emitter::{Listener, SharedEventEmitter};
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, MutexGuard, PoisonError};
+
+/// An error returned when a tenant tries to reach beyond its own events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantError {
+    /// The event name is empty or contains `..`.
+    InvalidEvent(String),
+    /// The event name starts with the prefix of another tenant.
+    ForeignPrefix(String),
+    /// The tenant prefix is empty, contains `..`, or starts or ends with a dot.
+    InvalidPrefix(String),
+    /// The tenant prefix is nested in the prefix of another tenant, or the other way around.
+    OverlappingPrefix(String),
+    /// The tenant was revoked by the host.
+    Revoked,
+    /// The emitter refused the listener, for example because it is shut down.
+    Refused(EmitError),
+}
+
+impl fmt::Display for TenantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TenantError::InvalidEvent(event) => write!(f, "invalid event name {:?}", event),
+            TenantError::ForeignPrefix(event) => {
+                write!(f, "{} belongs to another tenant", event)
+            }
+            TenantError::InvalidPrefix(prefix) => write!(f, "invalid tenant prefix {:?}", prefix),
+            TenantError::OverlappingPrefix(prefix) => {
+                write!(f, "{} overlaps the prefix of another tenant", prefix)
+            }
+            TenantError::Revoked => write!(f, "the tenant was revoked"),
+            TenantError::Refused(error) => write!(f, "the listener was refused: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for TenantError {}
+
+/// A view of a shared emitter confined to the events under its prefix, returned by `EventEmitter::tenant`.
+#[derive(Clone)]
+pub struct TenantEmitter {
+    emitter: SharedEventEmitter,
+    prefix: String,
+}
+
+impl TenantEmitter {
+    /// Returns the prefix of the tenant, without the separating dot.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Locks the shared emitter, checking that the tenant may use `event`, and returns its full name.
+    ///
+    /// The full name is checked against the other prefixes, so that a tenant cannot reach the events of a
+    /// tenant whose prefix would be nested in its own.
+    fn scope(&self, event: &str) -> Result<(MutexGuard<'_, EventEmitter>, String), TenantError> {
+        let full_event = format!("{}.{}", self.prefix, event);
+        if event.is_empty() || full_event.contains("..") {
+            return Err(TenantError::InvalidEvent(event.to_string()));
+        }
+
+        let emitter = self.emitter.lock().unwrap_or_else(PoisonError::into_inner);
+        if !emitter.tenants.contains(&self.prefix) {
+            return Err(TenantError::Revoked);
+        }
+        let foreign = emitter
+            .tenants
+            .iter()
+            .any(|prefix| prefix != &self.prefix && is_within(&full_event, prefix));
+        if foreign {
+            return Err(TenantError::ForeignPrefix(event.to_string()));
+        }
+
+        Ok((emitter, full_event))
+    }
+
+    /// Adds an event listener to an event of the tenant, like `EventEmitter::on`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event within the tenant, without the prefix.
+    /// * `callback` - The callback function to execute when the event is emitted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener, or the error if the tenant may not use the event or the emitter
+    /// refused the listener.
+    pub fn on<F, T>(&self, event: &str, callback: F) -> Result<ListenerId, TenantError>
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let (mut emitter, event) = self.scope(event)?;
+        let id = emitter.next_listener_id();
+        let callback = emitter.decoding_callback(&event, id, callback);
+        let listener = Listener {
+            tenant: Some(self.prefix.clone()),
+            ..Listener::new(id, None, callback)
+        };
+        emitter
+            .try_attach(&event, listener)
+            .map_err(TenantError::Refused)
+    }
+
+    /// Emits an event of the tenant, like `EventEmitter::emit`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event within the tenant, without the prefix.
+    /// * `value` - The value to pass to the event listeners.
+    ///
+    /// # Returns
+    ///
+    /// The sequence number of the emit, or the error if the tenant may not use the event.
+    pub fn emit<T>(&self, event: &str, value: T) -> Result<u64, TenantError>
+    where
+        T: Serialize,
+    {
+        let (mut emitter, event) = self.scope(event)?;
+        Ok(emitter.emit(event, value))
+    }
+
+    /// Removes a listener added by this tenant.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the listener to remove.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the removed listener, or `None` if no listener of this tenant has this ID.
+    pub fn remove_listener(&self, id: ListenerId) -> Option<ListenerId> {
+        let mut emitter = self.emitter.lock().unwrap_or_else(PoisonError::into_inner);
+        let owned = emitter
+            .listener(id)
+            .is_some_and(|listener| listener.tenant.as_deref() == Some(self.prefix.as_str()));
+        if !owned {
+            return None;
+        }
+
+        emitter.remove_listener(&id)
+    }
+}
+
+/// Returns whether `name` is `prefix` itself or lies under `prefix.`.
+fn is_within(name: &str, prefix: &str) -> bool {
+    name.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+impl fmt::Debug for TenantEmitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantEmitter")
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl EventEmitter {
+    /// Returns a view of a shared emitter confined to the events under `prefix.`, to hand to a tenant.
+    ///
+    /// The view prefixes the event names it is given, and rejects names containing `..` or whose full name
+    /// lies under the prefix of another tenant. Views of the same prefix share the tenant's listeners, but a
+    /// prefix nested in the prefix of another tenant, or the other way around, is refused.
+    ///
+    /// # Arguments
+    ///
+    /// * `emitter` - The emitter shared by the host and its tenants.
+    /// * `prefix` - The prefix of the tenant's events, without the separating dot.
+    ///
+    /// # Returns
+    ///
+    /// The view of the tenant, or the error if the prefix is empty, contains `..`, starts or ends with a dot,
+    /// or overlaps the prefix of another tenant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::sync::{Arc, Mutex};
+    /// let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    ///
+    /// let plugin = EventEmitter::tenant(&event_emitter, "plugin1").unwrap();
+    /// plugin.on("ready", |_: ()| println!("Plugin ready")).unwrap();
+    /// plugin.emit("ready", ()).unwrap();
+    /// assert_eq!(1, event_emitter.lock().unwrap().listener_count("plugin1.ready"));
+    /// ```
+    pub fn tenant(
+        emitter: &SharedEventEmitter,
+        prefix: &str,
+    ) -> Result<TenantEmitter, TenantError> {
+        if prefix.is_empty()
+            || prefix.contains("..")
+            || prefix.starts_with('.')
+            || prefix.ends_with('.')
+        {
+            return Err(TenantError::InvalidPrefix(prefix.to_string()));
+        }
+
+        let mut locked = emitter.lock().unwrap_or_else(PoisonError::into_inner);
+        let overlapping = locked
+            .tenants
+            .iter()
+            .any(|other| other != prefix && (is_within(prefix, other) || is_within(other, prefix)));
+        if overlapping {
+            return Err(TenantError::OverlappingPrefix(prefix.to_string()));
+        }
+
+        locked.tenants.insert(prefix.to_string());
+        Ok(TenantEmitter {
+            emitter: Arc::clone(emitter),
+            prefix: prefix.to_string(),
+        })
+    }
+
+    /// Removes every listener added by a tenant and revokes its views, which fail from then on.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The prefix of the tenant.
+    ///
+    /// # Returns
+    ///
+    /// The number of listeners removed.
+    pub fn revoke_tenant(&mut self, prefix: &str) -> usize {
+        self.tenants.remove(prefix);
+        let ids: Vec<ListenerId> = self
+            .listeners
+            .values()
+            .flatten()
+            .filter(|listener| listener.tenant.as_deref() == Some(prefix))
+            .map(|listener| listener.id)
+            .collect();
+        self.remove_listeners(&ids)
+    }
+}
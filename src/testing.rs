@@ -0,0 +1,335 @@
+//! Helpers asserting on the emits of an emitter from downstream test suites, enabled by the `testing` feature.
+//!
+//! `expect_emit` records the emits of one event through an inline listener, removed when the `Expectation` is
+//! dropped. `with_recording_scope` records every emit made while a closure runs, without adding listeners.
+//! Both record before `emit` returns, whether listeners run on worker threads or not, so assertions need no
+//! waiting. Failed assertions panic with the event name and the payloads involved.
+
+use crate::event_emitter::{Callback, Execution, Listener};
+use crate::removal::ListenerRemover;
+use crate::EventEmitter;
+use serde::Deserialize;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The emits of an event recorded by `expect_emit`, decoded as `T` for assertions.
+///
+/// Dropping the expectation removes its listener.
+pub struct Expectation<T> {
+    event: String,
+    payloads: Arc<Mutex<Vec<Vec<u8>>>>,
+    remover: Option<ListenerRemover>,
+    value_type: PhantomData<fn() -> T>,
+}
+
+impl<T> Expectation<T> {
+    /// Returns the number of emits recorded so far.
+    pub fn count(&self) -> usize {
+        self.payloads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Asserts that exactly `count` emits were recorded.
+    ///
+    /// # Panics
+    ///
+    /// If another number of emits was recorded.
+    #[track_caller]
+    pub fn assert_count(&self, count: usize) {
+        let actual = self.count();
+        assert!(
+            actual == count,
+            "expected {} emit(s) of {}, got {}: {}",
+            count,
+            self.event,
+            actual,
+            self.describe()
+        );
+    }
+
+    /// Asserts that no emit was recorded.
+    ///
+    /// # Panics
+    ///
+    /// If any emit was recorded.
+    #[track_caller]
+    pub fn assert_none(&self) {
+        assert!(
+            self.count() == 0,
+            "expected no emit of {}, got {}: {}",
+            self.event,
+            self.count(),
+            self.describe()
+        );
+    }
+
+    /// Lists the recorded payloads as text, for failure messages.
+    fn describe(&self) -> String {
+        let payloads = self.payloads.lock().unwrap_or_else(PoisonError::into_inner);
+        let payloads: Vec<String> = payloads
+            .iter()
+            .map(|payload| String::from_utf8_lossy(payload).into_owned())
+            .collect();
+        format!("[{}]", payloads.join(", "))
+    }
+}
+
+impl<T> Expectation<T>
+where
+    for<'de> T: Deserialize<'de>,
+{
+    /// Returns the recorded payloads decoded as `T`, in emission order.
+    ///
+    /// # Panics
+    ///
+    /// If a payload cannot be decoded as `T`.
+    #[track_caller]
+    pub fn payloads(&self) -> Vec<T> {
+        let payloads = self.payloads.lock().unwrap_or_else(PoisonError::into_inner);
+        payloads
+            .iter()
+            .enumerate()
+            .map(|(index, payload)| {
+                serde_json::from_slice(payload).unwrap_or_else(|e| {
+                    panic!(
+                        "emit {} of {} is not a {}: {} ({})",
+                        index,
+                        self.event,
+                        std::any::type_name::<T>(),
+                        String::from_utf8_lossy(payload),
+                        e
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+impl<T> Expectation<T>
+where
+    for<'de> T: Deserialize<'de>,
+    T: PartialEq + Debug,
+{
+    /// Asserts that the recorded payloads are `expected`, in emission order.
+    ///
+    /// # Panics
+    ///
+    /// If the payloads differ, or one cannot be decoded as `T`.
+    #[track_caller]
+    pub fn assert_payloads_eq(&self, expected: &[T]) {
+        let actual = self.payloads();
+        assert!(
+            actual == expected,
+            "payloads of {} differ\n  expected: {:?}\n    actual: {:?}",
+            self.event,
+            expected,
+            actual
+        );
+    }
+}
+
+impl<T> Drop for Expectation<T> {
+    fn drop(&mut self) {
+        if let Some(remover) = &self.remover {
+            remover.remove();
+        }
+    }
+}
+
+/// An emit recorded by `with_recording_scope`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedEmit {
+    /// The name of the emitted event.
+    pub event: String,
+    /// The serialized payload of the emit.
+    pub payload: Vec<u8>,
+}
+
+/// The emits recorded by `with_recording_scope`, in emission order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EmitLog {
+    entries: Vec<LoggedEmit>,
+}
+
+impl EmitLog {
+    /// Returns the recorded emits, in emission order.
+    pub fn entries(&self) -> &[LoggedEmit] {
+        &self.entries
+    }
+
+    /// Returns the names of the emitted events, in emission order.
+    pub fn events(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.event.as_str())
+            .collect()
+    }
+
+    /// Returns the number of recorded emits of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    pub fn count(&self, event: &str) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.event == event)
+            .count()
+    }
+
+    /// Returns the payloads of the recorded emits of an event decoded as `T`, in emission order.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    ///
+    /// # Panics
+    ///
+    /// If a payload cannot be decoded as `T`.
+    #[track_caller]
+    pub fn payloads<T>(&self, event: &str) -> Vec<T>
+    where
+        for<'de> T: Deserialize<'de>,
+    {
+        self.entries
+            .iter()
+            .filter(|entry| entry.event == event)
+            .map(|entry| {
+                serde_json::from_slice(&entry.payload).unwrap_or_else(|e| {
+                    panic!(
+                        "an emit of {} is not a {}: {} ({})",
+                        event,
+                        std::any::type_name::<T>(),
+                        String::from_utf8_lossy(&entry.payload),
+                        e
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Asserts that an event was emitted at least once.
+    ///
+    /// # Panics
+    ///
+    /// If the event was not emitted.
+    #[track_caller]
+    pub fn assert_emitted(&self, event: &str) {
+        assert!(
+            self.count(event) > 0,
+            "expected an emit of {}, got only {:?}",
+            event,
+            self.events()
+        );
+    }
+
+    /// Asserts that an event was never emitted.
+    ///
+    /// # Panics
+    ///
+    /// If the event was emitted.
+    #[track_caller]
+    pub fn assert_not_emitted(&self, event: &str) {
+        assert!(
+            self.count(event) == 0,
+            "expected no emit of {}, got {} in {:?}",
+            event,
+            self.count(event),
+            self.events()
+        );
+    }
+}
+
+impl EventEmitter {
+    /// Records the emits of an event from now on, for assertions on their number and payloads.
+    ///
+    /// The recording listener runs inline and counts like any other listener of the event; it is removed when
+    /// the expectation is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let placed = event_emitter.expect_emit::<u32>("order.placed");
+    /// event_emitter.emit("order.placed", 7_u32);
+    /// event_emitter.emit("order.placed", 8_u32);
+    ///
+    /// placed.assert_count(2);
+    /// placed.assert_payloads_eq(&[7, 8]);
+    /// ```
+    pub fn expect_emit<T>(&mut self, event: &str) -> Expectation<T>
+    where
+        for<'de> T: Deserialize<'de>,
+    {
+        let payloads: Arc<Mutex<Vec<Vec<u8>>>> = Arc::default();
+        let recorded = Arc::clone(&payloads);
+        let callback: Callback = Arc::new(move |bytes: Vec<u8>| {
+            recorded
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(bytes);
+        });
+
+        let mut listener = Listener::new(self.next_listener_id(), None, callback);
+        listener.execution = Execution::Inline;
+        let id = self.add_listener(event, listener);
+        Expectation {
+            event: event.to_string(),
+            payloads,
+            remover: self.remover(id),
+            value_type: PhantomData,
+        }
+    }
+
+    /// Runs `scope` and returns every emit the emitter made while it ran, whatever their listeners.
+    ///
+    /// Payloads are recorded as the emits are stamped, before being held, captured or delivered, so the emits
+    /// of events without listeners are recorded too, and every payload is serialized during the scope. Emits
+    /// dropped by sampling are not recorded. The emits of a nested scope are also recorded by the outer one.
+    ///
+    /// # Arguments
+    ///
+    /// * `scope` - The code whose emits are recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let log = event_emitter.with_recording_scope(|event_emitter| {
+    ///     event_emitter.sync_emit("user.created", "ada");
+    ///     event_emitter.emit("mail.sent", 1_u32);
+    /// });
+    ///
+    /// assert_eq!(vec!["user.created", "mail.sent"], log.events());
+    /// log.assert_not_emitted("user.deleted");
+    /// ```
+    pub fn with_recording_scope<F>(&mut self, scope: F) -> EmitLog
+    where
+        F: FnOnce(&mut Self),
+    {
+        let outer = self.recording.replace(Vec::new());
+        scope(self);
+        let entries = std::mem::replace(&mut self.recording, outer).unwrap_or_default();
+        if let Some(outer) = self.recording.as_mut() {
+            outer.extend(entries.iter().cloned());
+        }
+
+        EmitLog {
+            entries: entries
+                .into_iter()
+                .map(|(event, payload)| LoggedEmit { event, payload })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! A snapshot of the events of an emitter, their listeners and the bridges forwarding them, for rendering event
+//! flow diagrams.
+
+use crate::EventEmitter;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// An event with at least one live listener.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EventNode {
+    /// The name of the event.
+    pub event: String,
+    /// The number of listeners that can still be invoked, leaving out removed and exhausted listeners.
+    pub listener_count: usize,
+    /// The tags that listeners of the event include or exclude, sorted and without duplicates.
+    pub tags: Vec<String>,
+}
+
+/// What forwards the emits along an `EventEdge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum EdgeKind {
+    /// A bridge added with `attach_receiver` or `attach_crossbeam_receiver`, emitting the values of a channel.
+    Bridge,
+    /// A `WorkerBridge` posting the emits of an event to the other side of a web worker boundary.
+    Worker,
+}
+
+impl EdgeKind {
+    fn label(self) -> &'static str {
+        match self {
+            EdgeKind::Bridge => "bridge",
+            EdgeKind::Worker => "worker",
+        }
+    }
+}
+
+/// A bridge forwarding values into the emitter or emits out of it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct EventEdge {
+    /// Where the values come from: the channel of a bridge, named after the bridge, or the forwarded event.
+    pub from: String,
+    /// Where the values go: the event a bridge emits, or the other side of a worker boundary.
+    pub to: String,
+    /// What forwards the values.
+    pub kind: EdgeKind,
+}
+
+/// The events of an emitter, as returned by `EventEmitter::topology`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct Topology {
+    /// The events with live listeners, sorted by name.
+    pub nodes: Vec<EventNode>,
+    /// The bridges still forwarding, sorted by source then destination.
+    pub edges: Vec<EventEdge>,
+}
+
+impl Topology {
+    /// Renders the topology as a Graphviz DOT graph, with one node per event labelled with its listener count
+    /// and tags, and one edge per bridge labelled with its kind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("user.created", |name: String| println!("{}", name));
+    ///
+    /// let dot = event_emitter.topology().to_dot();
+    /// assert!(dot.starts_with("digraph emitter {"));
+    /// assert!(dot.contains("\"user.created\" [label=\"user.created\\n1 listener\"];"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph emitter {\n");
+        for node in &self.nodes {
+            let mut label = format!(
+                "{}\\n{} listener{}",
+                escape(&node.event),
+                node.listener_count,
+                if node.listener_count == 1 { "" } else { "s" }
+            );
+            if !node.tags.is_empty() {
+                let tags: Vec<String> = node.tags.iter().map(|tag| escape(tag)).collect();
+                let _ = write!(label, "\\n[{}]", tags.join(", "));
+            }
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\"];",
+                escape(&node.event),
+                label
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape(&edge.from),
+                escape(&edge.to),
+                edge.kind.label()
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Escapes a string for use inside a quoted DOT identifier or label.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl EventEmitter {
+    /// Returns a snapshot of the events that have live listeners and of the bridges forwarding values into the
+    /// emitter or emits out of it.
+    ///
+    /// Listeners removed through a `ListenerRemover` but not yet swept, and listeners whose limit is exhausted,
+    /// are left out, so the snapshot reflects what emits can still reach. Bridges that were stopped or whose
+    /// channel disconnected are left out as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("user.created", |name: String| println!("{}", name));
+    /// event_emitter.once("user.deleted", |name: String| println!("{}", name));
+    /// event_emitter.sync_emit("user.deleted", "ada".to_string());
+    ///
+    /// let topology = event_emitter.topology();
+    /// assert_eq!(1, topology.nodes.len());
+    /// println!("{}", serde_json::to_string(&topology).unwrap());
+    /// ```
+    pub fn topology(&self) -> Topology {
+        let mut events = BTreeMap::new();
+        for (event, listeners) in &self.listeners {
+            let live: Vec<_> = listeners
+                .iter()
+                .filter(|listener| !listener.is_removed() && listener.limit != Some(0))
+                .collect();
+            if live.is_empty() {
+                continue;
+            }
+
+            let tags: BTreeSet<String> = live
+                .iter()
+                .flat_map(|listener| listener.include_tags.iter().chain(&listener.exclude_tags))
+                .cloned()
+                .collect();
+            events.insert(
+                event.clone(),
+                EventNode {
+                    event: event.clone(),
+                    listener_count: live.len(),
+                    tags: tags.into_iter().collect(),
+                },
+            );
+        }
+
+        let mut edges: Vec<EventEdge> = self
+            .edges
+            .iter()
+            .filter(|(_, live)| live.load(Ordering::Acquire))
+            .map(|(edge, _)| edge.clone())
+            .collect();
+        edges.sort();
+
+        Topology {
+            nodes: events.into_values().collect(),
+            edges,
+        }
+    }
+
+    /// Registers the edge of a bridge, which `topology` lists while the returned flag is set.
+    pub(crate) fn add_edge(&mut self, edge: EventEdge) -> Arc<AtomicBool> {
+        self.edges.retain(|(_, live)| live.load(Ordering::Acquire));
+        let live = Arc::new(AtomicBool::new(true));
+        self.edges.push((edge, Arc::clone(&live)));
+        live
+    }
+
+    /// Returns the registered event keys whose listeners an untagged emit of `event` would trigger, without
+    /// emitting.
+    ///
+    /// The result holds `event` itself if it has a listener the emit can still reach, then each pattern
+    /// matching `event` that has such a listener, in the order their listeners were added. Disabled listeners,
+    /// listeners whose limit is exhausted and listeners that only accept tagged emits are left out, as by
+    /// `effective_listener_count`; filters depend on the emitted value and are not evaluated.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event that would be emitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// event_emitter.on("user.created", |name: String| println!("{}", name));
+    ///
+    /// assert_eq!(vec!["user.created".to_string()], event_emitter.matching_events("user.created"));
+    /// assert!(event_emitter.matching_events("user").is_empty());
+    ///
+    /// event_emitter.on_pattern("user.*", |name: String| println!("{}", name));
+    /// assert_eq!(
+    ///     vec!["user.created".to_string(), "user.*".to_string()],
+    ///     event_emitter.matching_events("user.created")
+    /// );
+    /// ```
+    pub fn matching_events(&self, event: &str) -> Vec<String> {
+        let mut events = Vec::new();
+        let exact = self
+            .listeners
+            .get(event)
+            .is_some_and(|listeners| listeners.iter().any(|listener| listener.is_reachable()));
+        if exact {
+            events.push(event.to_string());
+        }
+
+        for (pattern, listener) in self.pattern_listeners(event) {
+            if listener.is_reachable() && !events.iter().any(|known| known == pattern) {
+                events.push(pattern.to_string());
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,214 @@
+//! Emit sequence numbers, and opt-in tracking of the most recent emit of each event for staleness monitoring.
+//!
+//! Every emit is assigned the next sequence number of its emitter, across all events, so listeners of
+//! different events can tell in which order a series of emits happened. The emits the emitter makes on its own
+//! behalf, the emits deferred by callbacks and the dead letters of failed callbacks, also record the emit they
+//! derive from, which `on_emitted` hooks receive to follow a chain of emits.
+
+use crate::error::EmitError;
+use crate::event_emitter::ListenerOptions;
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use web_time::SystemTime;
+
+/// The sequence number of an emit and of the emit it derives from, passed to `on_emitted` hooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmitMeta {
+    /// The sequence number of the emit, as returned by `emit`.
+    pub seq: u64,
+    /// The sequence number of the emit whose dispatch caused this one, such as the emit a callback deferred
+    /// an emit from or the emit a dead letter reports a failure of, or `None` for an emit made by the caller.
+    pub parent: Option<u64>,
+}
+
+/// When an event was last emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmitStamp {
+    /// The wall-clock time of the emit.
+    pub at: SystemTime,
+    /// The sequence number of the emit, as returned by `emit`.
+    pub seq: u64,
+}
+
+impl EventEmitter {
+    /// Enables or disables the tracking of the most recent emit of each event.
+    ///
+    /// Every emit is tracked, including emits of events without listeners. Tracking is disabled by default,
+    /// and disabling it forgets the tracked emits.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether tracking is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.track_last_emit(true);
+    /// event_emitter.emit("heartbeat.worker1", ());
+    /// assert!(event_emitter.last_emit("heartbeat.worker1").is_some());
+    /// ```
+    pub fn track_last_emit(&mut self, enabled: bool) {
+        match (enabled, &self.last_emits) {
+            (true, None) => self.last_emits = Some(HashMap::new()),
+            (false, Some(_)) => self.last_emits = None,
+            _ => {}
+        }
+    }
+
+    /// Returns when the given event was last emitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event.
+    ///
+    /// # Returns
+    ///
+    /// The stamp of the most recent emit, or `None` if the event was not emitted since tracking was enabled.
+    pub fn last_emit(&self, event: &str) -> Option<EmitStamp> {
+        self.last_emits.as_ref()?.get(event).copied()
+    }
+
+    /// Returns the tracked events that were last emitted longer than `older_than` ago, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `older_than` - How long an event can go without being emitted before it is stale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// use std::time::Duration;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.track_last_emit(true);
+    /// event_emitter.emit("heartbeat.worker1", ());
+    /// assert!(event_emitter.stale_events(Duration::from_secs(30)).is_empty());
+    /// ```
+    pub fn stale_events(&self, older_than: Duration) -> Vec<(String, EmitStamp)> {
+        let Some(last_emits) = &self.last_emits else {
+            return Vec::new();
+        };
+
+        let now = SystemTime::now();
+        let mut stale: Vec<(String, EmitStamp)> = last_emits
+            .iter()
+            .filter(|(_, stamp)| {
+                now.duration_since(stamp.at)
+                    .is_ok_and(|elapsed| elapsed > older_than)
+            })
+            .map(|(event, stamp)| (event.clone(), *stamp))
+            .collect();
+        stale.sort_by_key(|(_, stamp)| stamp.seq);
+        stale
+    }
+
+    /// Adds an event listener that also receives the sequence number of each emit it is invoked for.
+    ///
+    /// Sequence numbers are assigned per emitter, across all events, starting from 1, so that 0 stands for an
+    /// emit that was given none, such as a re-entrant or sampled out emit. A held emit keeps the number it was
+    /// given when emitted, and a coalesced delivery carries the number of the emit completing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `callback` - The callback function to execute with the sequence number and the value.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// event_emitter.on_seq("order.validated", |seq: u64, order_id: u32| println!("#{} validated {}", seq, order_id));
+    /// event_emitter.on_seq("order.persisted", |seq: u64, order_id: u32| println!("#{} persisted {}", seq, order_id));
+    ///
+    /// let validated = event_emitter.sync_emit("order.validated", 7_u32);
+    /// let persisted = event_emitter.sync_emit("order.persisted", 7_u32);
+    /// assert!(validated < persisted);
+    /// ```
+    pub fn on_seq<F, T>(&mut self, event: &str, callback: F) -> ListenerId
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(u64, T) + 'static + Sync + Send,
+    {
+        let id = self.ids.next_id();
+        let decode_errors = self.hooks.decode_errors.clone();
+        let listener_event = event.to_string();
+        let seq_callback = Arc::new(move |seq: u64, bytes: Vec<u8>| {
+            if let Some(value) = decode_errors.decode(&listener_event, id, &bytes) {
+                callback(seq, value);
+            }
+        });
+
+        let parsed_callback = Arc::new(|_: Vec<u8>| {});
+        self.insert_listener(event, id, ListenerOptions::default(), parsed_callback, None);
+        if let Some(listener) = self.listener_mut(id) {
+            listener.seq_callback = Some(seq_callback);
+        }
+
+        id
+    }
+
+    /// Assigns the next sequence number, making it the number of the emit being dispatched.
+    pub(crate) fn next_seq(&mut self) -> u64 {
+        self.current_seq = self.emit_count.fetch_add(1, Ordering::Relaxed) + 1;
+        self.current_seq
+    }
+
+    /// Assigns the next sequence number to an emit of `event` admitted by `admit_emit`, recording the emit if
+    /// tracking is enabled.
+    ///
+    /// Returns the sequence number of the emit.
+    pub(crate) fn stamp_emit(&mut self, event: &str) -> u64 {
+        let seq = self.next_seq();
+        self.stats.record_emit(event);
+        let parent = self.emit_parent.take();
+        self.hooks.fire_emitted(event, &EmitMeta { seq, parent });
+        let Some(last_emits) = &mut self.last_emits else {
+            return seq;
+        };
+
+        let stamp = EmitStamp {
+            at: SystemTime::now(),
+            seq,
+        };
+        match last_emits.get_mut(event) {
+            Some(last_emit) => *last_emit = stamp,
+            None => {
+                last_emits.insert(event.to_string(), stamp);
+            }
+        }
+
+        seq
+    }
+
+    /// Emits serialized bytes like `emit_encoded`, as derived from the emit numbered `parent`, or as an emit of
+    /// its own if `parent` is 0.
+    pub(crate) fn emit_derived<E>(
+        &mut self,
+        parent: u64,
+        event: &str,
+        encode: E,
+    ) -> Result<(u64, usize), EmitError>
+    where
+        E: FnOnce() -> Result<Vec<u8>, EmitError>,
+    {
+        self.emit_parent = (parent != 0).then_some(parent);
+        let emitted = self.emit_encoded(event, &[], encode);
+        self.emit_parent = None;
+        emitted
+    }
+}
@@ -0,0 +1,165 @@
+//! Wiring of listeners from a JS-side configuration object on WebAssembly.
+//!
+//! A [`HandlerRegistry`] maps handler names to Rust callbacks. A JS configuration such as
+//! `{"user.created": ["logUser", "notify"]}` then decides which handlers listen to which events.
+
+use crate::event_emitter::{Callback, ListenerOptions};
+use crate::id::ListenerId;
+use crate::EventEmitter;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use wasm_bindgen::JsValue;
+
+/// Maps handler names to callbacks that can be wired to events by `configure_from_js`.
+#[derive(Default)]
+pub struct HandlerRegistry {
+    handlers: HashMap<String, Callback>,
+}
+
+impl HandlerRegistry {
+    /// Creates an empty `HandlerRegistry`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a callback under the given handler name, replacing any previous handler of that name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name used to refer to the handler from the JS configuration.
+    /// * `callback` - The callback function to execute when a configured event is emitted.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use emitter_rs::wasm_config::HandlerRegistry;
+    /// let mut registry = HandlerRegistry::new();
+    ///
+    /// registry.register("logUser", |name: String| {
+    ///     println!("User created: {}", name);
+    /// });
+    /// ```
+    pub fn register<F, T>(&mut self, name: &str, callback: F)
+    where
+        for<'de> T: Deserialize<'de>,
+        F: Fn(T) + 'static + Sync + Send,
+    {
+        let handler_name = name.to_string();
+        let parsed_callback = move |bytes: Vec<u8>| match serde_json::from_slice::<T>(&bytes) {
+            Ok(value) => callback(value),
+            Err(e) => eprintln!("Handler {} could not decode the value: {}", handler_name, e),
+        };
+
+        self.handlers
+            .insert(name.to_string(), Arc::new(parsed_callback));
+    }
+}
+
+/// An error raised while applying a JS listener configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The configuration is not an object mapping event names to lists of handler names.
+    InvalidConfig(String),
+    /// The configuration refers to handler names missing from the registry.
+    UnknownHandlers(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidConfig(reason) => write!(f, "invalid listener config: {}", reason),
+            ConfigError::UnknownHandlers(names) => {
+                write!(f, "unknown handlers: {}", names.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl EventEmitter {
+    /// Adds listeners described by a JS configuration object mapping event names to handler names.
+    ///
+    /// The configuration is validated before any listener is added, so an unknown handler name
+    /// leaves the emitter unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - A JS object such as `{"user.created": ["logUser", "notify"]}`.
+    /// * `registry` - The registry resolving handler names to callbacks.
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the newly added listeners, or a `ConfigError` describing why the configuration was rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use emitter_rs::wasm_config::HandlerRegistry;
+    /// use emitter_rs::EventEmitter;
+    /// let mut event_emitter = EventEmitter::new();
+    /// let mut registry = HandlerRegistry::new();
+    /// registry.register("logUser", |name: String| println!("User created: {}", name));
+    ///
+    /// let config = js_sys::JSON::parse(r#"{"user.created": ["logUser"]}"#).unwrap();
+    /// let listener_ids = event_emitter.configure_from_js(config, &registry).unwrap();
+    /// ```
+    pub fn configure_from_js(
+        &mut self,
+        config: JsValue,
+        registry: &HandlerRegistry,
+    ) -> Result<Vec<ListenerId>, ConfigError> {
+        let config: BTreeMap<String, Vec<String>> = serde_wasm_bindgen::from_value(config)
+            .map_err(|e| ConfigError::InvalidConfig(e.to_string()))?;
+
+        let unknown_handlers: Vec<String> = config
+            .values()
+            .flatten()
+            .filter(|name| !registry.handlers.contains_key(*name))
+            .cloned()
+            .collect();
+        if !unknown_handlers.is_empty() {
+            return Err(ConfigError::UnknownHandlers(unknown_handlers));
+        }
+
+        let mut listener_ids = Vec::new();
+        for (event, names) in config {
+            for name in names {
+                let callback = Arc::clone(&registry.handlers[&name]);
+                let id = self.ids.next_id();
+                self.insert_listener(&event, id, ListenerOptions::default(), callback, None);
+                self.handler_names.insert(id, name);
+                listener_ids.push(id);
+            }
+        }
+
+        Ok(listener_ids)
+    }
+
+    /// Exports the listeners added by `configure_from_js` as a JS object mapping event names to handler names.
+    ///
+    /// Listeners that have since been removed are left out.
+    ///
+    /// # Returns
+    ///
+    /// A JS object in the same shape accepted by `configure_from_js`.
+    pub fn export_config_js(&self) -> JsValue {
+        let mut config: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (event, listeners) in &self.listeners {
+            let names: Vec<&str> = listeners
+                .iter()
+                .filter(|listener| !listener.is_removed())
+                .filter_map(|listener| self.handler_names.get(&listener.id))
+                .map(String::as_str)
+                .collect();
+            if !names.is_empty() {
+                config.insert(event, names);
+            }
+        }
+
+        let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+        serde::Serialize::serialize(&config, &serializer).unwrap_or(JsValue::NULL)
+    }
+}
@@ -0,0 +1,346 @@
+//! Offloading of CPU-heavy listeners to web workers on WebAssembly, enabled by the `wasm-workers` feature.
+//!
+//! Callbacks cannot be moved between wasm instances, so a worker runs its own copy of the application's
+//! wasm module: it calls [`serve_worker`] from its entry point with the callback to run, and the main
+//! thread adds a listener with `EventEmitter::on_worker` that posts the serialized value of each emit to it.
+//!
+//! To share events both ways instead, each side wraps its own emitter in a [`WorkerBridge`] and forwards
+//! the events the other side should see. Forwarded emits cross the boundary as `{ event, payload }`
+//! messages, the payload being the serialized value in a transferred `Uint8Array`.
+
+use crate::event_emitter::report_emit_error;
+use crate::id::ListenerId;
+use crate::topology::{EdgeKind, EventEdge};
+use crate::EventEmitter;
+use js_sys::{Array, Object, Reflect, Uint8Array};
+use serde::Deserialize;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{DedicatedWorkerGlobalScope, EventTarget, MessageEvent, Worker};
+
+impl EventEmitter {
+    /// Adds an event listener posting the serialized value of each emit to a web worker.
+    ///
+    /// The value is posted as a `Uint8Array` holding its JSON serialization, which `serve_worker` decodes
+    /// inside the worker. Posting does not wait for the worker to handle the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to listen for.
+    /// * `worker` - The worker receiving the values.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the newly added listener.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use emitter_rs::EventEmitter;
+    /// use web_sys::Worker;
+    /// let mut event_emitter = EventEmitter::new();
+    ///
+    /// let worker = Worker::new("./worker.js").unwrap();
+    /// event_emitter.on_worker("image.resize", worker);
+    /// ```
+    pub fn on_worker(&mut self, event: &str, worker: Worker) -> ListenerId {
+        let id = self.ids.next_id();
+        let post = move |bytes: Vec<u8>| {
+            if let Err(e) = worker.post_message(&Uint8Array::from(bytes.as_slice())) {
+                eprintln!("Worker error: {:?}", e);
+            }
+        };
+
+        self.add_local_callback(event, id, Box::new(post))
+    }
+}
+
+/// Runs `callback` inside the current web worker on every value posted by `on_worker` listeners.
+///
+/// Call this once from the worker's entry point. Messages that are not values posted by `on_worker`
+/// are ignored with an error printed.
+///
+/// # Arguments
+///
+/// * `callback` - The callback function to execute for each posted value.
+///
+/// # Examples
+///
+/// ```ignore
+/// use emitter_rs::wasm_workers::serve_worker;
+///
+/// serve_worker(|(width, height): (u32, u32)| {
+///     println!("Resizing to {}x{}", width, height);
+/// });
+/// ```
+pub fn serve_worker<F, T>(callback: F)
+where
+    for<'de> T: Deserialize<'de>,
+    F: Fn(T) + 'static,
+{
+    let onmessage = Closure::<dyn Fn(MessageEvent)>::new(move |message: MessageEvent| {
+        let Some(bytes) = message
+            .data()
+            .dyn_ref::<Uint8Array>()
+            .map(Uint8Array::to_vec)
+        else {
+            eprintln!("Worker error: unexpected message {:?}", message.data());
+            return;
+        };
+
+        match serde_json::from_slice(&bytes) {
+            Ok(value) => callback(value),
+            Err(e) => eprintln!("Worker error: {}", e),
+        }
+    });
+
+    let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+    scope.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
+}
+
+/// The side of the boundary a `WorkerBridge` posts to.
+#[derive(Clone)]
+enum Endpoint {
+    /// The main thread, posting to a worker it created.
+    Main(Worker),
+    /// A worker, posting to the main thread through its global scope.
+    Worker(DedicatedWorkerGlobalScope),
+}
+
+impl Endpoint {
+    /// Names the side posted to, as the destination of the forwarded events in the topology.
+    fn name(&self) -> &'static str {
+        match self {
+            Endpoint::Main(_) => "worker",
+            Endpoint::Worker(_) => "main thread",
+        }
+    }
+
+    fn target(&self) -> &EventTarget {
+        match self {
+            Endpoint::Main(worker) => worker.as_ref(),
+            Endpoint::Worker(scope) => scope.as_ref(),
+        }
+    }
+
+    /// Posts a forwarded emit to the other side, transferring the payload's buffer.
+    fn post(&self, event: &str, bytes: &[u8]) -> Result<(), JsValue> {
+        let payload = Uint8Array::from(bytes);
+        let message = Object::new();
+        Reflect::set(
+            &message,
+            &JsValue::from_str("event"),
+            &JsValue::from_str(event),
+        )?;
+        Reflect::set(&message, &JsValue::from_str("payload"), &payload)?;
+        let transfer = Array::of1(&payload.buffer());
+
+        match self {
+            Endpoint::Main(worker) => worker.post_message_with_transfer(&message, &transfer),
+            Endpoint::Worker(scope) => scope.post_message_with_transfer(&message, &transfer),
+        }
+    }
+}
+
+/// Reads the event and payload of a message posted by the `WorkerBridge` of the other side.
+fn forwarded_emit(data: &JsValue) -> Option<(String, Vec<u8>)> {
+    let event = Reflect::get(data, &JsValue::from_str("event"))
+        .ok()?
+        .as_string()?;
+    let payload = Reflect::get(data, &JsValue::from_str("payload"))
+        .ok()?
+        .dyn_into::<Uint8Array>()
+        .ok()?;
+    Some((event, payload.to_vec()))
+}
+
+/// Forwards emits between the main thread and a web worker, each side running its own emitter.
+///
+/// Events passed to `forward` are posted to the other side whenever they are emitted locally, and messages
+/// posted by the other side's bridge are emitted on the local emitter with their serialized payload, for the
+/// listeners there to decode. A received emit is never forwarded back, so an event forwarded by both sides
+/// does not bounce between them. Other messages, such as the values posted by `on_worker`, are ignored.
+///
+/// Each forwarded event is listed by `EventEmitter::topology` as an edge to the other side. Dropping the
+/// bridge stops forwarding in both directions.
+pub struct WorkerBridge {
+    emitter: Arc<Mutex<EventEmitter>>,
+    endpoint: Endpoint,
+    /// Set while a received emit is dispatched, so the forwarding listeners skip it.
+    receiving: Rc<Cell<bool>>,
+    /// The forwarding listener of each forwarded event, with the flag listing its edge in the topology.
+    forwarded: HashMap<String, (ListenerId, Arc<AtomicBool>)>,
+    onmessage: Closure<dyn Fn(MessageEvent)>,
+}
+
+impl WorkerBridge {
+    /// Bridges an emitter of the main thread with a worker it created.
+    ///
+    /// # Arguments
+    ///
+    /// * `emitter` - The emitter of the main thread.
+    /// * `worker` - The worker running the other side of the bridge.
+    ///
+    /// # Returns
+    ///
+    /// The bridge, forwarding no event yet.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use emitter_rs::wasm_workers::WorkerBridge;
+    /// use emitter_rs::EventEmitter;
+    /// use std::sync::{Arc, Mutex};
+    /// use web_sys::Worker;
+    /// let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    ///
+    /// let worker = Worker::new("./worker.js").unwrap();
+    /// let mut bridge = WorkerBridge::main(&event_emitter, worker);
+    /// bridge.forward("image.resize");
+    /// ```
+    pub fn main(emitter: &Arc<Mutex<EventEmitter>>, worker: Worker) -> Self {
+        Self::new(emitter, Endpoint::Main(worker))
+    }
+
+    /// Bridges an emitter of the current worker with the main thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `emitter` - The emitter of the worker.
+    ///
+    /// # Returns
+    ///
+    /// The bridge, forwarding no event yet.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use emitter_rs::wasm_workers::WorkerBridge;
+    /// use emitter_rs::EventEmitter;
+    /// use std::sync::{Arc, Mutex};
+    /// let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    ///
+    /// let mut bridge = WorkerBridge::worker(&event_emitter);
+    /// bridge.forward("image.resized");
+    /// ```
+    pub fn worker(emitter: &Arc<Mutex<EventEmitter>>) -> Self {
+        Self::new(emitter, Endpoint::Worker(js_sys::global().unchecked_into()))
+    }
+
+    fn new(emitter: &Arc<Mutex<EventEmitter>>, endpoint: Endpoint) -> Self {
+        let receiving = Rc::new(Cell::new(false));
+        let cloned_receiving = Rc::clone(&receiving);
+        let cloned_emitter = Arc::clone(emitter);
+        let onmessage = Closure::<dyn Fn(MessageEvent)>::new(move |message: MessageEvent| {
+            let Some((event, bytes)) = forwarded_emit(&message.data()) else {
+                return;
+            };
+
+            let mut emitter = cloned_emitter
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            cloned_receiving.set(true);
+            if let Err(e) = emitter.emit_encoded(&event, &[], || Ok(bytes)) {
+                report_emit_error(&event, &e);
+            }
+            cloned_receiving.set(false);
+        });
+
+        if let Err(e) = endpoint
+            .target()
+            .add_event_listener_with_callback("message", onmessage.as_ref().unchecked_ref())
+        {
+            eprintln!("Worker error: {:?}", e);
+        }
+
+        Self {
+            emitter: Arc::clone(emitter),
+            endpoint,
+            receiving,
+            forwarded: HashMap::new(),
+            onmessage,
+        }
+    }
+
+    /// Posts the emits of an event to the other side of the bridge from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the event to forward.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the event was not forwarded yet, otherwise `false`.
+    pub fn forward(&mut self, event: &str) -> bool {
+        if self.forwarded.contains_key(event) {
+            return false;
+        }
+
+        let endpoint = self.endpoint.clone();
+        let receiving = Rc::clone(&self.receiving);
+        let listener_event = event.to_string();
+        let post = move |bytes: Vec<u8>| {
+            if receiving.get() {
+                return;
+            }
+
+            if let Err(e) = endpoint.post(&listener_event, &bytes) {
+                eprintln!("Worker error: {:?}", e);
+            }
+        };
+
+        let mut emitter = self.emitter.lock().unwrap_or_else(PoisonError::into_inner);
+        let id = emitter.ids.next_id();
+        emitter.add_local_callback(event, id, Box::new(post));
+        let live = emitter.add_edge(EventEdge {
+            from: event.to_string(),
+            to: self.endpoint.name().to_string(),
+            kind: EdgeKind::Worker,
+        });
+        self.forwarded.insert(event.to_string(), (id, live));
+        true
+    }
+
+    /// Stops posting the emits of an event to the other side of the bridge.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The name of the forwarded event.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the event was forwarded, otherwise `false`.
+    pub fn stop_forwarding(&mut self, event: &str) -> bool {
+        let Some((id, live)) = self.forwarded.remove(event) else {
+            return false;
+        };
+
+        live.store(false, Ordering::Release);
+        self.emitter
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove_listener(&id);
+        true
+    }
+}
+
+impl Drop for WorkerBridge {
+    fn drop(&mut self) {
+        let mut emitter = self.emitter.lock().unwrap_or_else(PoisonError::into_inner);
+        for (_, (id, live)) in self.forwarded.drain() {
+            live.store(false, Ordering::Release);
+            emitter.remove_listener(&id);
+        }
+
+        let _ = self.endpoint.target().remove_event_listener_with_callback(
+            "message",
+            self.onmessage.as_ref().unchecked_ref(),
+        );
+    }
+}
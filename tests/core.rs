@@ -1,3 +1,6 @@
+use emitter_rs::id::ListenerId;
+#[cfg(not(target_arch = "wasm32"))]
+use emitter_rs::retry::ListenerOutcome;
 use emitter_rs::EventEmitter;
 use lazy_static::lazy_static;
 use std::sync::{Arc, Mutex};
@@ -21,7 +24,7 @@ fn test_on() {
         *cloned_counter.lock().unwrap() = value;
     });
 
-    event_emitter.emit("Set", 10 as u32);
+    event_emitter.emit("Set", 10_u32);
 
     assert_eq!(
         10,
@@ -38,14 +41,14 @@ fn test_on() {
     let cloned_container = Arc::clone(&container);
     event_emitter.on("Add Value To List", move |value: String| {
         let mut container = cloned_container.lock().unwrap();
-        (*container).list.push(value);
+        container.list.push(value);
     });
 
     event_emitter.emit("Add Value To List", "hello".to_string());
 
     assert_eq!(
         vec!["hello".to_string()],
-        (*container.lock().unwrap()).list,
+        container.lock().unwrap().list,
         "'hello' should have been pushed to the list after the 'Add Value To List' event was called with 'hello'"
     );
 }
@@ -70,11 +73,34 @@ fn test_remove_listener() {
     event_emitter.remove_listener(&listener_id);
     assert_eq!(
         0,
-        event_emitter.listeners.get("Hello rust!").unwrap().len(),
+        event_emitter.listener_count("Hello rust!"),
         "Should have removed listener"
     );
 }
 
+#[test]
+fn test_remove_listener_by_listener_id() {
+    let mut event_emitter = EventEmitter::new();
+    let first_id = event_emitter.on("Hello rust!", |_: String| {});
+    let second_id = event_emitter.on("Hello rust!", |_: String| {});
+    let third_id = event_emitter.on("Hello rust!", |_: String| {});
+
+    assert_eq!(None, event_emitter.remove_listener(&ListenerId(u64::MAX)));
+    assert_eq!(Some(first_id), event_emitter.remove_listener(&first_id));
+    assert_eq!(
+        None,
+        event_emitter.remove_listener(&first_id),
+        "A removed listener should not be removed twice"
+    );
+    assert_eq!(Some(second_id), event_emitter.remove_listener(&second_id));
+    assert_eq!(
+        Some(third_id),
+        event_emitter.remove_listener(&third_id.to_string()),
+        "The string form of an id should still be accepted"
+    );
+    assert_eq!(0, event_emitter.listener_count("Hello rust!"));
+}
+
 #[test]
 fn test_on_limited() {
     let mut event_emitter = EventEmitter::new();
@@ -97,7 +123,7 @@ fn test_on_limited() {
         "Listener should have been added with a limit of 2 calls"
     );
 
-    event_emitter.emit("Set", 10 as u32);
+    event_emitter.emit("Set", 10_u32);
     assert_eq!(
         1,
         event_emitter
@@ -111,7 +137,7 @@ fn test_on_limited() {
         "Listener limit should have been reduced by 1"
     );
 
-    event_emitter.emit("Set", 20 as u32);
+    event_emitter.emit("Set", 20_u32);
     assert_eq!(
         0,
         event_emitter
@@ -130,14 +156,96 @@ fn test_on_limited() {
         "Counter should have been set to the emitted value"
     );
 
-    event_emitter.emit("Set", 30 as u32);
+    event_emitter.emit("Set", 30_u32);
     assert_eq!(
         0,
-        event_emitter.listeners.get("Set").unwrap().len(),
+        event_emitter.listener_count("Set"),
         "Listener should have been removed after reaching its limit"
     );
 }
 
+#[test]
+fn test_on_limited_removed_by_exhausting_emit() {
+    let mut event_emitter = EventEmitter::new();
+    let removed: Arc<Mutex<Vec<usize>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_removed = Arc::clone(&removed);
+    event_emitter.on_listener_removed(move |_, info| {
+        cloned_removed.lock().unwrap().push(info.listener_count);
+    });
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let cloned_calls = Arc::clone(&calls);
+    let listener_id = event_emitter.on_limited("Set", Some(2), move |value: u32| {
+        cloned_calls.lock().unwrap().push(value);
+    });
+
+    event_emitter.sync_emit("Set", 10_u32);
+    assert!(removed.lock().unwrap().is_empty());
+
+    event_emitter.sync_emit("Set", 20_u32);
+    assert_eq!(
+        vec![0],
+        *removed.lock().unwrap(),
+        "The emit using the last call should fire the removal hook"
+    );
+    assert_eq!(0, event_emitter.listener_count("Set"));
+    assert!(event_emitter.event_names().is_empty());
+    assert_eq!(None, event_emitter.remaining_limit(listener_id));
+    assert_eq!(
+        None,
+        event_emitter.remove_listener(&listener_id),
+        "The exhausted listener should already be removed"
+    );
+
+    event_emitter.sync_emit("Set", 30_u32);
+    assert!(
+        !event_emitter.listeners.contains_key("Set"),
+        "The next emit should sweep the exhausted listener"
+    );
+    assert_eq!(
+        vec![0],
+        *removed.lock().unwrap(),
+        "Sweeping the exhausted listener should not fire the hook again"
+    );
+    assert_eq!(vec![10, 20], *calls.lock().unwrap());
+}
+
+#[test]
+fn test_on_with_id_reuses_exhausted_id() {
+    let mut event_emitter = EventEmitter::new();
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let subscription = ListenerId(0x5eed);
+
+    let cloned_calls = Arc::clone(&calls);
+    event_emitter
+        .on_with_id("Set", subscription, move |value: u32| {
+            cloned_calls.lock().unwrap().push(value);
+        })
+        .unwrap();
+    event_emitter.reset_limit(subscription, Some(1));
+    event_emitter.sync_emit("Set", 10_u32);
+
+    let cloned_calls = Arc::clone(&calls);
+    event_emitter
+        .on_with_id("Set", subscription, move |value: u32| {
+            cloned_calls.lock().unwrap().push(value + 1);
+        })
+        .unwrap();
+    event_emitter.sync_emit("Set", 20_u32);
+    event_emitter.sync_emit("Set", 30_u32);
+
+    assert_eq!(
+        vec![10, 21, 31],
+        *calls.lock().unwrap(),
+        "Sweeping the exhausted listener should keep the one added again under its ID"
+    );
+    assert_eq!(1, event_emitter.listeners["Set"].len());
+    assert_eq!(
+        Some(subscription),
+        event_emitter.remove_listener(&subscription)
+    );
+}
+
 #[test]
 fn test_once() {
     let mut event_emitter = EventEmitter::new();
@@ -148,14 +256,14 @@ fn test_once() {
         *cloned_counter.lock().unwrap() = value;
     });
 
-    event_emitter.emit("Set Once", 10 as u32);
+    event_emitter.emit("Set Once", 10_u32);
     assert_eq!(
         10,
         *counter.lock().unwrap(),
         "Counter should have been set to the emitted value"
     );
 
-    event_emitter.emit("Set Once", 20 as u32);
+    event_emitter.emit("Set Once", 20_u32);
     assert_eq!(
         10,
         *counter.lock().unwrap(),
@@ -195,3 +303,5733 @@ fn test_on_and_emit_wasm() {
         assert_eq!(*result.lock().unwrap(), "Hello, world!");
     });
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emit_thread_names() {
+    let mut event_emitter = EventEmitter::new();
+    let thread_name: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+
+    let cloned_thread_name = Arc::clone(&thread_name);
+    let listener_id = event_emitter.on("order.placed", move |_: ()| {
+        *cloned_thread_name.lock().unwrap() = std::thread::current().name().map(String::from);
+    });
+
+    event_emitter.emit("order.placed", ());
+
+    assert_eq!(
+        Some(format!(
+            "emit-order.placed-{}",
+            listener_id.to_string().chars().take(8).collect::<String>()
+        )),
+        *thread_name.lock().unwrap(),
+        "Callback thread should be named after the event and listener id"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emit_thread_names_sanitized() {
+    let mut event_emitter = EventEmitter::new();
+    let thread_names: Arc<Mutex<Vec<Option<String>>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let long_event = "x".repeat(1000);
+    for event in ["nul\0event", long_event.as_str()] {
+        let cloned_thread_names = Arc::clone(&thread_names);
+        event_emitter.on(event, move |_: ()| {
+            cloned_thread_names
+                .lock()
+                .unwrap()
+                .push(std::thread::current().name().map(String::from));
+        });
+        event_emitter.emit(event, ());
+    }
+
+    let thread_names = thread_names.lock().unwrap();
+    assert_eq!(2, thread_names.len(), "Both callbacks should have run");
+    let nul_name = thread_names[0].as_deref().unwrap();
+    assert!(
+        nul_name.starts_with("emit-nulevent-"),
+        "NUL characters should be dropped from the thread name, got {}",
+        nul_name
+    );
+    let long_name = thread_names[1].as_deref().unwrap();
+    assert!(
+        long_name.len() < 100,
+        "Long event names should be cut in the thread name, got {} bytes",
+        long_name.len()
+    );
+}
+
+#[test]
+fn test_global_emitter_survives_panicking_listener() {
+    use emitter_rs::event_emitter_file::EVENT_EMITTER;
+    use emitter_rs::global;
+
+    global::on("Global Panic", |_: ()| panic!("listener failure"));
+    let result = std::panic::catch_unwind(|| global::sync_emit("Global Panic", ()));
+    assert!(result.is_err(), "Listener panic should reach the caller");
+    assert!(
+        !EVENT_EMITTER.is_poisoned(),
+        "A panicking listener should not poison the global emitter"
+    );
+
+    let _ = std::thread::spawn(|| {
+        let _guard = EVENT_EMITTER.lock().unwrap();
+        panic!("poisoning the global emitter");
+    })
+    .join();
+
+    let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_counter = Arc::clone(&counter);
+    global::on("Global Set", move |value: u32| {
+        *cloned_counter.lock().unwrap() = value;
+    });
+    global::emit("Global Set", 10_u32);
+
+    assert_eq!(
+        10,
+        *counter.lock().unwrap(),
+        "Global emitter should keep working after its mutex was poisoned"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_on_with_options_execution() {
+    use emitter_rs::event_emitter::{Execution, ListenerOptions};
+    use std::thread::{self, ThreadId};
+
+    let mut event_emitter = EventEmitter::new();
+    let inline_thread: Arc<Mutex<Option<ThreadId>>> = Arc::new(Mutex::new(None));
+    let pooled_thread: Arc<Mutex<Option<ThreadId>>> = Arc::new(Mutex::new(None));
+
+    let cloned_inline_thread = Arc::clone(&inline_thread);
+    event_emitter.on_with_options(
+        "Affinity",
+        ListenerOptions {
+            execution: Execution::Inline,
+            limit: Some(1),
+            ..ListenerOptions::default()
+        },
+        move |_: ()| {
+            *cloned_inline_thread.lock().unwrap() = Some(thread::current().id());
+        },
+    );
+    let cloned_pooled_thread = Arc::clone(&pooled_thread);
+    event_emitter.on_with_options("Affinity", ListenerOptions::default(), move |_: ()| {
+        *cloned_pooled_thread.lock().unwrap() = Some(thread::current().id());
+    });
+
+    event_emitter.emit("Affinity", ());
+
+    assert_eq!(
+        Some(thread::current().id()),
+        *inline_thread.lock().unwrap(),
+        "Inline listener should run on the emitting thread"
+    );
+    assert_ne!(
+        Some(thread::current().id()),
+        *pooled_thread.lock().unwrap(),
+        "Pooled listener should run on a worker thread"
+    );
+
+    event_emitter.emit("Affinity", ());
+    assert_eq!(
+        1,
+        event_emitter.listeners.get("Affinity").unwrap().len(),
+        "Inline listener should have been removed after reaching its limit"
+    );
+}
+
+#[test]
+fn test_map_payload_key_order() {
+    let mut event_emitter = EventEmitter::new();
+    let keys: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_keys = Arc::clone(&keys);
+    event_emitter.on(
+        "Map",
+        move |map: serde_json::Map<String, serde_json::Value>| {
+            *cloned_keys.lock().unwrap() = map.keys().cloned().collect();
+        },
+    );
+
+    let mut map = serde_json::Map::new();
+    map.insert("b".to_string(), 1.into());
+    map.insert("a".to_string(), 2.into());
+    event_emitter.emit("Map", map);
+
+    let expected = if cfg!(feature = "preserve_order") {
+        vec!["b".to_string(), "a".to_string()]
+    } else {
+        vec!["a".to_string(), "b".to_string()]
+    };
+    assert_eq!(
+        expected,
+        *keys.lock().unwrap(),
+        "Map keys should be delivered in a deterministic order"
+    );
+}
+
+#[test]
+fn test_on_distinct_limited() {
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_distinct_limited("State", Some(3), move |state: String| {
+        cloned_received.lock().unwrap().push(state);
+    });
+
+    for state in [
+        "idle", "idle", "busy", "busy", "busy", "idle", "done", "idle",
+    ] {
+        event_emitter.emit("State", state.to_string());
+    }
+
+    assert_eq!(
+        vec!["idle".to_string(), "busy".to_string(), "idle".to_string()],
+        *received.lock().unwrap(),
+        "Only the first 3 distinct consecutive values should have been delivered"
+    );
+    assert_eq!(
+        0,
+        event_emitter.listener_count("State"),
+        "Listener should have been removed after reaching its limit"
+    );
+}
+
+#[test]
+fn test_on_distinct_limited_dry_emit() {
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_distinct_limited("State", Some(2), move |state: String| {
+        cloned_received.lock().unwrap().push(state);
+    });
+
+    assert_eq!(1, event_emitter.dry_emit("State", "idle".to_string()));
+    event_emitter.sync_emit("State", "idle".to_string());
+    event_emitter.sync_emit("State", "idle".to_string());
+    assert_eq!(0, event_emitter.dry_emit("State", "idle".to_string()));
+    assert_eq!(1, event_emitter.dry_emit("State", "busy".to_string()));
+    event_emitter.sync_emit("State", "busy".to_string());
+
+    assert_eq!(
+        vec![
+            "idle".to_string(),
+            "idle".to_string(),
+            "busy".to_string(),
+            "busy".to_string()
+        ],
+        *received.lock().unwrap(),
+        "Dry runs should not count as the previously delivered value"
+    );
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+fn test_configure_from_js_wasm() {
+    use emitter_rs::wasm_config::HandlerRegistry;
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let mut registry = HandlerRegistry::new();
+    let cloned_received = Arc::clone(&received);
+    registry.register("logUser", move |name: String| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("log {}", name));
+    });
+    let cloned_received = Arc::clone(&received);
+    registry.register("notify", move |name: String| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("notify {}", name));
+    });
+
+    let config =
+        js_sys::JSON::parse(r#"{"user.created": ["logUser"], "user.deleted": ["notify"]}"#)
+            .unwrap();
+    let listener_ids = event_emitter.configure_from_js(config, &registry).unwrap();
+    assert_eq!(
+        2,
+        listener_ids.len(),
+        "Both handlers should have been wired"
+    );
+
+    event_emitter.sync_emit("user.created", "ada".to_string());
+    event_emitter.sync_emit("user.deleted", "bob".to_string());
+    assert_eq!(
+        vec!["log ada".to_string(), "notify bob".to_string()],
+        *received.lock().unwrap(),
+        "Both configured handlers should have fired"
+    );
+
+    let unknown = js_sys::JSON::parse(r#"{"user.created": ["missing"]}"#).unwrap();
+    assert!(
+        event_emitter.configure_from_js(unknown, &registry).is_err(),
+        "Unknown handler names should be rejected"
+    );
+
+    let exported = js_sys::JSON::stringify(&event_emitter.export_config_js()).unwrap();
+    assert_eq!(
+        r#"{"user.created":["logUser"],"user.deleted":["notify"]}"#,
+        String::from(exported)
+    );
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-workers"))]
+#[wasm_bindgen_test]
+async fn test_on_worker_wasm() {
+    use wasm_bindgen::{JsCast, JsValue};
+    use web_sys::{MessageEvent, Worker};
+
+    let worker =
+        Worker::new("data:text/javascript,onmessage=(event)=>postMessage(event.data)").unwrap();
+    let echoed = js_sys::Promise::new(&mut |resolve, _| {
+        let onmessage = wasm_bindgen::closure::Closure::once_into_js(move |event: MessageEvent| {
+            resolve.call1(&JsValue::NULL, &event.data()).unwrap();
+        });
+        worker.set_onmessage(Some(onmessage.unchecked_ref()));
+    });
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.on_worker("Heavy", worker.clone());
+    event_emitter.emit("Heavy", vec![1, 2, 3]);
+
+    let echoed = wasm_bindgen_futures::JsFuture::from(echoed).await.unwrap();
+    assert_eq!(
+        b"[1,2,3]".to_vec(),
+        js_sys::Uint8Array::new(&echoed).to_vec(),
+        "The worker should have received the serialized value"
+    );
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-workers"))]
+#[wasm_bindgen_test]
+async fn test_worker_bridge_wasm() {
+    use emitter_rs::wasm_workers::WorkerBridge;
+    use web_sys::Worker;
+
+    let echo_worker =
+        Worker::new("data:text/javascript,onmessage=(event)=>postMessage(event.data)").unwrap();
+    let greeting_worker = Worker::new(
+        "data:text/javascript,postMessage({event:'Hello',payload:new Uint8Array([34,104,105,34])})",
+    )
+    .unwrap();
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    for event in ["Ping", "Local", "Hello"] {
+        let cloned_received = Arc::clone(&received);
+        event_emitter
+            .lock()
+            .unwrap()
+            .on(event, move |value: String| {
+                cloned_received.lock().unwrap().push(value);
+            });
+    }
+
+    let mut echo_bridge = WorkerBridge::main(&event_emitter, echo_worker);
+    assert!(echo_bridge.forward("Ping"));
+    assert!(!echo_bridge.forward("Ping"));
+    let _greeting_bridge = WorkerBridge::main(&event_emitter, greeting_worker);
+
+    event_emitter.lock().unwrap().emit("Ping", "ping");
+    event_emitter.lock().unwrap().emit("Local", "local");
+
+    let settled = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 200)
+            .unwrap();
+    });
+    wasm_bindgen_futures::JsFuture::from(settled).await.unwrap();
+
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    assert_eq!(
+        vec!["hi", "local", "ping", "ping"],
+        received,
+        "Ping should come back once from the echo worker, Local should stay local, and Hello should arrive from the worker"
+    );
+}
+
+#[test]
+fn test_coalescing() {
+    use emitter_rs::coalesce::Coalesce;
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on("Sample", move |value: u32| {
+        cloned_received.lock().unwrap().push(value);
+    });
+    event_emitter.set_coalescing("Sample", Duration::from_millis(50), Coalesce::KeepLatest);
+
+    for value in 0..100_u32 {
+        event_emitter.emit("Sample", value);
+    }
+    event_emitter.flush_coalesced("Sample");
+
+    let received = received.lock().unwrap();
+    assert!(
+        received.len() < 100,
+        "Rapid emits should have been merged into fewer deliveries"
+    );
+    assert_eq!(
+        Some(&99),
+        received.last(),
+        "The last delivery should carry the final emitted value"
+    );
+
+    let first: Arc<Mutex<Option<u32>>> = Arc::new(Mutex::new(None));
+    let cloned_first = Arc::clone(&first);
+    event_emitter.on("First", move |value: u32| {
+        *cloned_first.lock().unwrap() = Some(value);
+    });
+    event_emitter.set_coalescing("First", Duration::from_secs(60), Coalesce::KeepFirst);
+    event_emitter.emit("First", 1_u32);
+    event_emitter.emit("First", 2_u32);
+    assert_eq!(
+        None,
+        *first.lock().unwrap(),
+        "Emits should have been buffered"
+    );
+
+    event_emitter.clear_coalescing("First");
+    assert_eq!(
+        Some(1),
+        *first.lock().unwrap(),
+        "Clearing coalescing should deliver the first buffered payload"
+    );
+
+    let synced: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_synced = Arc::clone(&synced);
+    event_emitter.on_limited("Synced", Some(2), move |value: u32| {
+        cloned_synced.lock().unwrap().push(value);
+    });
+    event_emitter.set_coalescing("Synced", Duration::from_secs(60), Coalesce::KeepLatest);
+    for value in 0..10_u32 {
+        event_emitter.sync_emit("Synced", value);
+    }
+    assert!(
+        synced.lock().unwrap().is_empty(),
+        "sync_emit should buffer coalesced emits too"
+    );
+
+    assert!(event_emitter.flush_coalesced("Synced"));
+    assert_eq!(
+        vec![9],
+        *synced.lock().unwrap(),
+        "The coalesced sync emits should be delivered once, consuming a single call"
+    );
+    assert_eq!(1, event_emitter.listeners["Synced"][0].limit.unwrap());
+}
+
+#[test]
+fn test_emit_typed() {
+    use serde::{Deserialize, Serialize, Serializer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static SERIALIZATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Clone, Deserialize)]
+    struct Counted(u32);
+
+    impl Serialize for Counted {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            SERIALIZATIONS.fetch_add(1, Ordering::SeqCst);
+            serializer.serialize_u32(self.0)
+        }
+    }
+
+    let mut event_emitter = EventEmitter::new();
+    let total: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+
+    for _ in 0..2 {
+        let cloned_total = Arc::clone(&total);
+        event_emitter.on_typed("Typed", move |value: Counted| {
+            *cloned_total.lock().unwrap() += value.0;
+        });
+    }
+
+    event_emitter.emit_typed("Typed", Counted(5));
+    assert_eq!(
+        10,
+        *total.lock().unwrap(),
+        "Both typed listeners should run"
+    );
+    assert_eq!(
+        0,
+        SERIALIZATIONS.load(Ordering::SeqCst),
+        "Typed listeners should not require serialization"
+    );
+
+    let cloned_total = Arc::clone(&total);
+    event_emitter.on("Typed", move |value: u32| {
+        *cloned_total.lock().unwrap() += value;
+    });
+
+    event_emitter.emit_typed("Typed", Counted(1));
+    assert_eq!(13, *total.lock().unwrap(), "All listeners should run");
+    assert_eq!(
+        1,
+        SERIALIZATIONS.load(Ordering::SeqCst),
+        "Untyped listeners should cause a single serialization"
+    );
+}
+
+#[test]
+fn test_emit_typed_mixed_listeners() {
+    use serde::{Deserialize, Serialize, Serializer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static SERIALIZATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Clone, Deserialize)]
+    struct Counted(u32);
+
+    impl Serialize for Counted {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            SERIALIZATIONS.fetch_add(1, Ordering::SeqCst);
+            serializer.serialize_u32(self.0)
+        }
+    }
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_typed("Mixed", move |value: Counted| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("typed {}", value.0))
+    });
+    let cloned_received = Arc::clone(&received);
+    event_emitter.once("Mixed", move |value: u32| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("bytes {}", value))
+    });
+    assert!(
+        event_emitter.needs_bytes::<Counted>("Mixed"),
+        "A serde listener should require serialization"
+    );
+
+    event_emitter.emit_typed("Mixed", Counted(1));
+    assert_eq!(1, SERIALIZATIONS.load(Ordering::SeqCst));
+
+    assert!(
+        !event_emitter.needs_bytes::<Counted>("Mixed"),
+        "An exhausted serde listener should no longer require serialization"
+    );
+    event_emitter.emit_typed("Mixed", Counted(2));
+    assert_eq!(
+        1,
+        SERIALIZATIONS.load(Ordering::SeqCst),
+        "Only typed listeners remain, so no serialization should happen"
+    );
+
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_typed("Mixed", move |value: u64| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("u64 {}", value))
+    });
+    assert!(
+        event_emitter.needs_bytes::<Counted>("Mixed"),
+        "A typed listener of another type should require serialization"
+    );
+    event_emitter.emit_typed("Mixed", Counted(3));
+    assert_eq!(2, SERIALIZATIONS.load(Ordering::SeqCst));
+
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    assert_eq!(
+        vec!["bytes 1", "typed 1", "typed 2", "typed 3", "u64 3"],
+        received,
+        "Every listener should receive the value in its own type"
+    );
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.set_max_event_names(Some(1));
+    event_emitter.on_typed("Count", |_: u32| {});
+    event_emitter.on_typed("Other", |_: u64| {});
+    assert_eq!(1, event_emitter.listener_count("Count"));
+    assert_eq!(0, event_emitter.listener_count("Other"));
+    assert!(
+        !event_emitter.needs_bytes::<u32>("Count"),
+        "A refused typed listener should leave the other listeners untouched"
+    );
+}
+
+#[test]
+fn test_global_macros() {
+    use emitter_rs::{emit, on};
+
+    let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+
+    let cloned_counter = Arc::clone(&counter);
+    on!("Macro Set", move |value: u32| {
+        *cloned_counter.lock().unwrap() = value;
+    });
+    emit!("Macro Set", 7_u32);
+
+    assert_eq!(
+        7,
+        *counter.lock().unwrap(),
+        "Counter should have been set through the global macros"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_request() {
+    use emitter_rs::request::{Envelope, RequestError};
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let replier = event_emitter.replier();
+    event_emitter.on("query.weather", move |request: Envelope<String>| {
+        replier.respond(
+            &request.correlation_id,
+            format!("sunny in {}", request.payload),
+        );
+    });
+
+    let forecast: Result<String, RequestError> = event_emitter.request(
+        "query.weather",
+        "weather.result",
+        "Paris".to_string(),
+        Duration::from_secs(1),
+    );
+    assert_eq!(
+        Ok("sunny in Paris".to_string()),
+        forecast,
+        "Matching reply should resolve"
+    );
+
+    let replier = event_emitter.replier();
+    event_emitter.on("query.mismatched", move |_: Envelope<()>| {
+        assert!(
+            !replier.respond("some other id", "wrong"),
+            "Replies to unknown correlation ids should be rejected"
+        );
+    });
+    let result: Result<String, RequestError> = event_emitter.request(
+        "query.mismatched",
+        "mismatched.result",
+        (),
+        Duration::from_millis(50),
+    );
+    assert_eq!(
+        Err(RequestError::Timeout),
+        result,
+        "Request without matching reply should time out"
+    );
+
+    let replier = event_emitter.replier();
+    let responded: Arc<Mutex<Vec<bool>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_responded = Arc::clone(&responded);
+    event_emitter.on("query.unencodable", move |request: Envelope<()>| {
+        let unencodable = std::collections::HashMap::from([((1_u32, 2_u32), 3_u32)]);
+        let sent = replier.respond(&request.correlation_id, unencodable);
+        let retried = replier.respond(&request.correlation_id, "fallback");
+        cloned_responded.lock().unwrap().extend([sent, retried]);
+    });
+    event_emitter.enable_history("unencodable.result", 10);
+    let result: Result<String, RequestError> = event_emitter.request(
+        "query.unencodable",
+        "unencodable.result",
+        (),
+        Duration::from_secs(1),
+    );
+    assert_eq!(
+        vec![false, true],
+        *responded.lock().unwrap(),
+        "A response that cannot be serialized should not be sent"
+    );
+    assert_eq!(Ok("fallback".to_string()), result);
+    assert_eq!(
+        1,
+        event_emitter.history("unencodable.result").len(),
+        "Replies should be emitted like any other emit"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_request_interleaved_replies() {
+    use emitter_rs::request::{Envelope, RequestError};
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let replier = event_emitter.replier();
+    event_emitter.on("query.slow", move |request: Envelope<u32>| {
+        let replier = replier.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100 * u64::from(request.payload)));
+            replier.respond(&request.correlation_id, request.payload * 10);
+        });
+    });
+
+    let first: Result<u32, RequestError> =
+        event_emitter.request("query.slow", "slow.result", 1, Duration::from_millis(20));
+    assert_eq!(Err(RequestError::Timeout), first);
+
+    let second: Result<u32, RequestError> =
+        event_emitter.request("query.slow", "slow.result", 2, Duration::from_secs(2));
+    assert_eq!(
+        Ok(20),
+        second,
+        "The late reply to the first request should not answer the second one"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_on_with_execution() {
+    use emitter_rs::event_emitter::Execution;
+    use std::thread::{self, ThreadId};
+
+    let mut event_emitter = EventEmitter::new();
+    let threads: Arc<Mutex<Vec<(Execution, ThreadId)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    for execution in [Execution::Inline, Execution::Pooled] {
+        let cloned_threads = Arc::clone(&threads);
+        event_emitter.on_with_execution("Execution", execution, move |_: ()| {
+            cloned_threads
+                .lock()
+                .unwrap()
+                .push((execution, thread::current().id()));
+        });
+    }
+
+    event_emitter.emit("Execution", ());
+
+    let threads = threads.lock().unwrap();
+    assert_eq!(2, threads.len(), "Both listeners should have run");
+    for (execution, thread_id) in threads.iter() {
+        assert_eq!(
+            *execution == Execution::Inline,
+            *thread_id == thread::current().id(),
+            "Only the inline listener should run on the emitting thread"
+        );
+    }
+}
+
+#[test]
+fn test_listener_lifecycle_hooks() {
+    let mut event_emitter = EventEmitter::new();
+    let changes: Arc<Mutex<Vec<(String, String, usize)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_changes = Arc::clone(&changes);
+    event_emitter.on_listener_added(move |event, info| {
+        cloned_changes.lock().unwrap().push((
+            "added".to_string(),
+            event.to_string(),
+            info.listener_count,
+        ));
+    });
+    let cloned_changes = Arc::clone(&changes);
+    let removed_hook = event_emitter.on_listener_removed(move |event, info| {
+        cloned_changes.lock().unwrap().push((
+            "removed".to_string(),
+            event.to_string(),
+            info.listener_count,
+        ));
+    });
+
+    let listener_id = event_emitter.on("Prices", |_: u32| {});
+    event_emitter.once("Prices", |_: u32| {});
+    event_emitter.on_limited("Prices", Some(1), |_: u32| {});
+    event_emitter.emit("Prices", 1_u32);
+
+    let change = |kind: &str, count: usize| (kind.to_string(), "Prices".to_string(), count);
+    assert_eq!(
+        vec![
+            change("added", 1),
+            change("added", 2),
+            change("added", 3),
+            change("removed", 2),
+            change("removed", 1),
+        ],
+        *changes.lock().unwrap(),
+        "The exhausted listeners should be removed by the emit using their last call"
+    );
+
+    event_emitter.emit("Prices", 2_u32);
+    event_emitter.remove_listener(&listener_id);
+    assert_eq!(
+        vec![
+            change("added", 1),
+            change("added", 2),
+            change("added", 3),
+            change("removed", 2),
+            change("removed", 1),
+            change("removed", 0),
+        ],
+        *changes.lock().unwrap(),
+        "Every registration and removal path should fire the hooks with the new count"
+    );
+
+    assert!(event_emitter.remove_hook(&removed_hook));
+    assert!(!event_emitter.remove_hook(&removed_hook));
+    let listener_id = event_emitter.on("Prices", |_: u32| {});
+    event_emitter.remove_listener(&listener_id);
+    assert_eq!(
+        Some(&change("added", 1)),
+        changes.lock().unwrap().last(),
+        "Removed hooks should no longer be called"
+    );
+}
+
+#[test]
+fn test_remove_listeners() {
+    let mut event_emitter = EventEmitter::new();
+    let first_id = event_emitter.on("First", |_: ()| {});
+    let second_id = event_emitter.on("First", |_: ()| {});
+    let third_id = event_emitter.on("Second", |_: ()| {});
+    event_emitter.on("Second", |_: ()| {});
+
+    assert_eq!(
+        3,
+        event_emitter.remove_listeners(&[first_id, second_id, third_id, ListenerId(u64::MAX)]),
+        "Only the existing listeners should have been counted"
+    );
+    assert_eq!(0, event_emitter.listener_count("First"));
+    assert_eq!(1, event_emitter.listeners.get("Second").unwrap().len());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_remove_listeners_cancels_retries() {
+    use emitter_rs::retry::{Backoff, RetryPolicy, RetryableError};
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let policy = RetryPolicy {
+        max_attempts: 5,
+        backoff: Backoff::Fixed(Duration::from_millis(20)),
+    };
+    let attempts: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_attempts = Arc::clone(&attempts);
+    let retrying_id = event_emitter.on_with_retry("Refund Issued", policy, move |_: u32| {
+        *cloned_attempts.lock().unwrap() += 1;
+        Err(RetryableError::new("service unavailable"))
+    });
+    let values: Arc<Mutex<Vec<serde_json::Value>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_values = Arc::clone(&values);
+    let value_id = event_emitter.on_any_value("Refund Issued", move |value| {
+        cloned_values.lock().unwrap().push(value);
+    });
+
+    event_emitter.emit("Refund Issued", 7_u32);
+    while *attempts.lock().unwrap() == 0 {
+        std::thread::yield_now();
+    }
+    assert_eq!(
+        2,
+        event_emitter.remove_listeners(&[retrying_id, value_id, retrying_id]),
+        "Both kinds of listeners should have been removed once"
+    );
+    assert!(event_emitter.wait_until_idle(Duration::from_secs(5)));
+    assert_eq!(
+        1,
+        *attempts.lock().unwrap(),
+        "The pending retries should have been cancelled"
+    );
+
+    event_emitter.sync_emit("Refund Issued", 8_u32);
+    assert!(
+        !values.lock().unwrap().contains(&serde_json::json!(8)),
+        "The value listener should not receive later emits"
+    );
+}
+
+#[test]
+fn test_any_value_listeners() {
+    use serde_json::{json, Value};
+
+    let mut event_emitter = EventEmitter::new();
+    let values: Arc<Mutex<Vec<Value>>> = Arc::new(Mutex::new(Vec::new()));
+    let numbers: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let decode_errors: Arc<Mutex<Vec<ListenerId>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_values = Arc::clone(&values);
+    event_emitter.on_any_value("Dynamic", move |value| {
+        cloned_values.lock().unwrap().push(value);
+    });
+    let cloned_numbers = Arc::clone(&numbers);
+    let number_listener = event_emitter.on("Dynamic", move |value: u32| {
+        cloned_numbers.lock().unwrap().push(value);
+    });
+    let cloned_decode_errors = Arc::clone(&decode_errors);
+    event_emitter.on_decode_error(move |error| {
+        cloned_decode_errors.lock().unwrap().push(error.listener_id);
+    });
+
+    event_emitter.emit("Dynamic", 3_u32);
+    event_emitter.sync_emit_value("Dynamic", json!(4));
+    event_emitter.sync_emit_value("Dynamic", json!({"not": "a number"}));
+
+    assert_eq!(
+        vec![json!(3), json!(4), json!({"not": "a number"})],
+        *values.lock().unwrap(),
+        "The value listener should receive every emitted value"
+    );
+    assert_eq!(
+        vec![3, 4],
+        *numbers.lock().unwrap(),
+        "The typed listener should receive compatible values only"
+    );
+    assert_eq!(
+        vec![number_listener],
+        *decode_errors.lock().unwrap(),
+        "The incompatible value should have been reported for the typed listener"
+    );
+}
+
+#[test]
+fn test_sync_emit_honors_limits() {
+    let mut event_emitter = EventEmitter::new();
+    let once_counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let limited_counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+
+    let cloned_once_counter = Arc::clone(&once_counter);
+    event_emitter.once("Limited", move |_: ()| {
+        *cloned_once_counter.lock().unwrap() += 1;
+    });
+    let cloned_limited_counter = Arc::clone(&limited_counter);
+    event_emitter.on_limited("Limited", Some(3), move |_: ()| {
+        *cloned_limited_counter.lock().unwrap() += 1;
+    });
+
+    event_emitter.sync_emit("Limited", ());
+    event_emitter.sync_emit("Limited", ());
+    assert_eq!(
+        1,
+        *once_counter.lock().unwrap(),
+        "Once listener should not fire a second time through sync_emit"
+    );
+
+    event_emitter.emit("Limited", ());
+    event_emitter.sync_emit("Limited", ());
+    event_emitter.emit("Limited", ());
+    assert_eq!(
+        3,
+        *limited_counter.lock().unwrap(),
+        "Limit should be shared between emit and sync_emit"
+    );
+    assert_eq!(
+        0,
+        event_emitter.listener_count("Limited"),
+        "Exhausted listeners should have been removed"
+    );
+}
+
+#[test]
+fn test_strict_emit() {
+    use emitter_rs::error::EmitError;
+
+    let mut event_emitter = EventEmitter::new();
+    assert_eq!(
+        Ok(()),
+        event_emitter.strict_emit("Unknown", ()),
+        "Unknown events should be accepted outside strict mode"
+    );
+
+    event_emitter.set_strict(true);
+    assert_eq!(
+        Err(EmitError::NoSuchEvent("Unknown".to_string())),
+        event_emitter.strict_emit("Unknown", ()),
+        "Unknown events should be rejected in strict mode"
+    );
+
+    let listener_id = event_emitter.on("Known", |_: ()| {});
+    event_emitter.remove_listener(&listener_id);
+    assert_eq!(
+        Ok(()),
+        event_emitter.strict_emit("Known", ()),
+        "Events that had listeners should stay known"
+    );
+}
+
+#[test]
+fn test_id_generators() {
+    use emitter_rs::id::{IdGenerator, SequentialGenerator};
+    use std::collections::HashSet;
+
+    fn concurrent_ids(generator: Arc<dyn IdGenerator>) -> HashSet<ListenerId> {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let generator = Arc::clone(&generator);
+                std::thread::spawn(move || {
+                    (0..250).map(|_| generator.next_id()).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    }
+
+    assert_eq!(
+        1000,
+        concurrent_ids(Arc::new(SequentialGenerator::default())).len(),
+        "Sequential ids should be unique"
+    );
+    #[cfg(feature = "uuid")]
+    assert_eq!(
+        1000,
+        concurrent_ids(Arc::new(emitter_rs::id::UuidGenerator)).len(),
+        "Uuid ids should be unique"
+    );
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::with_id_generator(
+        SequentialGenerator::default(),
+    )));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let event_emitter = Arc::clone(&event_emitter);
+            std::thread::spawn(move || {
+                (0..25)
+                    .map(|_| event_emitter.lock().unwrap().on("Ids", |_: ()| {}))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let ids: HashSet<ListenerId> = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect();
+    assert_eq!(
+        100,
+        ids.len(),
+        "Concurrent registrations should get unique ids"
+    );
+
+    let mut event_emitter = event_emitter.lock().unwrap();
+    assert_eq!(
+        Some(ListenerId(1)),
+        event_emitter.remove_listener(&ListenerId(1))
+    );
+    assert_eq!(99, event_emitter.listeners.get("Ids").unwrap().len());
+
+    assert_eq!(
+        Some(ListenerId(2)),
+        event_emitter.remove_listener("2"),
+        "String ids should still be accepted"
+    );
+}
+
+#[test]
+fn test_remove_preserves_listener_order() {
+    let mut event_emitter = EventEmitter::new();
+    let calls: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let ids: Vec<ListenerId> = (1..=5)
+        .map(|position| {
+            let cloned_calls = Arc::clone(&calls);
+            event_emitter.on("Ordered", move |_: ()| {
+                cloned_calls.lock().unwrap().push(position);
+            })
+        })
+        .collect();
+
+    event_emitter.remove_listener(&ids[2]);
+    event_emitter.sync_emit("Ordered", ());
+    assert_eq!(
+        vec![1, 2, 4, 5],
+        *calls.lock().unwrap(),
+        "The remaining listeners should fire in their original relative order"
+    );
+
+    calls.lock().unwrap().clear();
+    event_emitter.remove_listeners(&[ids[0], ids[3]]);
+    event_emitter.sync_emit("Ordered", ());
+    assert_eq!(
+        vec![2, 5],
+        *calls.lock().unwrap(),
+        "Batch removal should preserve the relative order as well"
+    );
+}
+
+#[test]
+fn test_buffer_limit() {
+    use emitter_rs::coalesce::Coalesce;
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let calls: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    for event in ["First", "Second"] {
+        let cloned_calls = Arc::clone(&calls);
+        event_emitter.on(event, move |value: String| {
+            cloned_calls.lock().unwrap().push(value);
+        });
+        event_emitter.set_coalescing(event, Duration::from_secs(60), Coalesce::KeepLatest);
+    }
+
+    event_emitter.emit("First", "aaaa".to_string());
+    event_emitter.emit("Second", "bbbb".to_string());
+    assert_eq!(12, event_emitter.buffered_bytes());
+
+    event_emitter.set_buffer_limit(Some(10));
+    assert_eq!(
+        6,
+        event_emitter.buffered_bytes(),
+        "The least recently buffered event should have been evicted"
+    );
+    assert!(!event_emitter.flush_coalesced("First"));
+
+    event_emitter.emit("First", "cccc".to_string());
+    assert_eq!(
+        6,
+        event_emitter.buffered_bytes(),
+        "Buffering a new payload should evict the older event"
+    );
+    assert!(!event_emitter.flush_coalesced("Second"));
+    assert!(event_emitter.flush_coalesced("First"));
+
+    assert_eq!(vec!["cccc".to_string()], *calls.lock().unwrap());
+    assert_eq!(0, event_emitter.buffered_bytes());
+}
+
+#[test]
+fn test_capture() {
+    use emitter_rs::capture::CaptureError;
+
+    let mut event_emitter = EventEmitter::new();
+    let calls: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_calls = Arc::clone(&calls);
+    event_emitter.on("Captured", move |value: u32| {
+        cloned_calls.lock().unwrap().push(value);
+    });
+
+    let capture = event_emitter.capture("Captured").unwrap();
+    assert_eq!(
+        Some(CaptureError::AlreadyCaptured("Captured".to_string())),
+        event_emitter.capture("Captured").err(),
+        "Captures should not nest"
+    );
+
+    event_emitter.emit("Captured", 1_u32);
+    event_emitter.sync_emit("Captured", 2_u32);
+    event_emitter.emit_typed("Captured", 3_u32);
+    assert!(
+        calls.lock().unwrap().is_empty(),
+        "Listeners should not run while the event is captured"
+    );
+
+    let emits = capture.drain();
+    assert_eq!(
+        vec![0, 1, 2],
+        emits.iter().map(|emit| emit.sequence).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![1, 2, 3],
+        emits
+            .iter()
+            .map(|emit| emit.value::<u32>().unwrap())
+            .collect::<Vec<_>>(),
+        "The drained emits should match the emitted values"
+    );
+    assert!(capture.drain().is_empty());
+
+    event_emitter.emit("Captured", 4_u32);
+    event_emitter.emit("Captured", 5_u32);
+    assert_eq!(2, capture.forward(&mut event_emitter));
+    assert_eq!(
+        vec![4, 5],
+        *calls.lock().unwrap(),
+        "Forwarded emits should reach the listeners in order"
+    );
+
+    drop(capture);
+    event_emitter.emit("Captured", 6_u32);
+    assert_eq!(
+        vec![4, 5, 6],
+        *calls.lock().unwrap(),
+        "Dropping the handle should restore delivery"
+    );
+    assert!(event_emitter.capture("Captured").is_ok());
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn test_cbor_codec() {
+    use emitter_rs::cbor::to_canonical_vec;
+    use serde::ser::SerializeMap;
+    use serde::{Deserialize, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    /// A map serialized in insertion order, like a `HashMap` whose iteration order differs.
+    struct InsertionOrdered(Vec<(&'static str, u32)>);
+
+    impl Serialize for InsertionOrdered {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+            for (key, value) in &self.0 {
+                map.serialize_entry(key, value)?;
+            }
+            map.end()
+        }
+    }
+
+    let first = InsertionOrdered(vec![("alpha", 1), ("beta", 2), ("gamma", 3)]);
+    let second = InsertionOrdered(vec![("gamma", 3), ("alpha", 1), ("beta", 2)]);
+    assert_ne!(
+        serde_json::to_vec(&first).unwrap(),
+        serde_json::to_vec(&second).unwrap(),
+        "JSON keeps the insertion order of maps"
+    );
+    assert_eq!(
+        to_canonical_vec(&first).unwrap(),
+        to_canonical_vec(&second).unwrap(),
+        "Canonical CBOR should not depend on the insertion order of maps"
+    );
+
+    struct Unencodable;
+
+    impl Serialize for Unencodable {
+        fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("not encodable"))
+        }
+    }
+
+    assert!(matches!(
+        to_canonical_vec(&Unencodable),
+        Err(emitter_rs::error::EmitError::Serialize(_))
+    ));
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Audit {
+        user: String,
+        counts: HashMap<String, u32>,
+    }
+
+    let audit = Audit {
+        user: "ada".to_string(),
+        counts: HashMap::from([("login".to_string(), 2), ("logout".to_string(), 1)]),
+    };
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<Audit>>> = Arc::new(Mutex::new(Vec::new()));
+    let decode_errors: Arc<Mutex<Vec<ListenerId>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_cbor("Audit", move |value: Audit| {
+        cloned_received.lock().unwrap().push(value);
+    });
+    let json_listener = event_emitter.on("Audit", |_: Audit| {
+        panic!("A JSON listener should not decode a CBOR payload");
+    });
+    let cloned_decode_errors = Arc::clone(&decode_errors);
+    event_emitter.on_decode_error(move |error| {
+        cloned_decode_errors.lock().unwrap().push(error.listener_id);
+    });
+
+    event_emitter.emit_cbor("Audit", audit.clone());
+    assert_eq!(
+        vec![audit],
+        *received.lock().unwrap(),
+        "The CBOR listener should receive the value"
+    );
+    assert_eq!(
+        vec![json_listener],
+        *decode_errors.lock().unwrap(),
+        "The JSON listener should report a decode error"
+    );
+}
+
+#[test]
+fn test_dry_emit() {
+    let mut event_emitter = EventEmitter::new();
+    let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+
+    let cloned_counter = Arc::clone(&counter);
+    event_emitter.once("Probe", move |value: u32| {
+        *cloned_counter.lock().unwrap() += value;
+    });
+    let cloned_counter = Arc::clone(&counter);
+    event_emitter.on_limited("Probe", Some(2), move |value: u32| {
+        *cloned_counter.lock().unwrap() += value * 10;
+    });
+
+    for _ in 0..3 {
+        assert_eq!(2, event_emitter.dry_emit("Probe", 1_u32));
+    }
+    assert_eq!(33, *counter.lock().unwrap());
+    assert_eq!(
+        vec![Some(1), Some(2)],
+        event_emitter.listeners["Probe"]
+            .iter()
+            .map(|listener| listener.limit)
+            .collect::<Vec<_>>(),
+        "Dry emits should not consume limits"
+    );
+
+    event_emitter.sync_emit("Probe", 1_u32);
+    assert_eq!(44, *counter.lock().unwrap());
+    assert_eq!(
+        1,
+        event_emitter.dry_emit("Probe", 1_u32),
+        "Exhausted listeners should be skipped"
+    );
+    assert_eq!(0, event_emitter.dry_emit("Unknown", 1_u32));
+}
+
+#[test]
+fn test_emit_each() {
+    use emitter_rs::event_emitter::Execution;
+
+    let mut event_emitter = EventEmitter::new();
+    let values: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let limited_calls: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+
+    let cloned_values = Arc::clone(&values);
+    event_emitter.on_with_execution("Batch", Execution::Inline, move |value: u32| {
+        cloned_values.lock().unwrap().push(value);
+    });
+    let cloned_limited_calls = Arc::clone(&limited_calls);
+    event_emitter.on_limited("Batch", Some(2), move |_: u32| {
+        *cloned_limited_calls.lock().unwrap() += 1;
+    });
+
+    assert_eq!(
+        5,
+        event_emitter.emit_each("Batch", vec![1_u32, 2, 3]),
+        "Every value should reach the unlimited listener and two should reach the limited one"
+    );
+    assert_eq!(
+        vec![1, 2, 3],
+        *values.lock().unwrap(),
+        "Values should be delivered as separate emits, in order"
+    );
+    assert_eq!(2, *limited_calls.lock().unwrap());
+    assert_eq!(0, event_emitter.emit_each("Unknown", [1_u32, 2]));
+}
+
+#[test]
+fn test_hold_and_release() {
+    use emitter_rs::event_emitter::Execution;
+
+    let mut event_emitter = EventEmitter::new();
+    let calls: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    assert!(event_emitter.hold());
+    assert!(event_emitter.is_holding());
+    event_emitter.emit("First", "a".to_string());
+    event_emitter.sync_emit("Second", "b".to_string());
+    event_emitter.emit("Third", "c".to_string());
+    event_emitter.emit("First", "d".to_string());
+
+    for event in ["First", "Second", "Third"] {
+        let cloned_calls = Arc::clone(&calls);
+        let event_name = event.to_string();
+        event_emitter.on_with_execution(event, Execution::Inline, move |value: String| {
+            cloned_calls
+                .lock()
+                .unwrap()
+                .push(format!("{}:{}", event_name, value));
+        });
+    }
+    assert!(calls.lock().unwrap().is_empty());
+
+    assert_eq!(4, event_emitter.release());
+    assert!(!event_emitter.is_holding());
+    assert_eq!(
+        vec!["First:a", "Second:b", "Third:c", "First:d"],
+        *calls.lock().unwrap(),
+        "Held emits should be delivered in emission order across events"
+    );
+
+    event_emitter.emit("Second", "e".to_string());
+    assert_eq!(Some(&"Second:e".to_string()), calls.lock().unwrap().last());
+    assert!(
+        !event_emitter.hold(),
+        "The emitter should not hold again after release"
+    );
+}
+
+#[test]
+fn test_hold_buffer_limit() {
+    let mut event_emitter = EventEmitter::new();
+    let calls: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_calls = Arc::clone(&calls);
+    event_emitter.on("Held", move |value: u32| {
+        cloned_calls.lock().unwrap().push(value);
+    });
+
+    event_emitter.set_buffer_limit(Some(2));
+    event_emitter.hold();
+    for value in 1..=4_u32 {
+        event_emitter.emit("Held", value);
+    }
+    assert_eq!(2, event_emitter.buffered_bytes());
+
+    assert_eq!(2, event_emitter.release());
+    assert_eq!(
+        vec![3, 4],
+        *calls.lock().unwrap(),
+        "The oldest held emits should have been dropped"
+    );
+}
+
+#[test]
+fn test_detach_and_attach() {
+    let mut event_emitter = EventEmitter::new();
+    let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+
+    let cloned_counter = Arc::clone(&counter);
+    let listener_id = event_emitter.on_limited("First", Some(3), move |value: u32| {
+        *cloned_counter.lock().unwrap() += value;
+    });
+
+    event_emitter.sync_emit("First", 1_u32);
+    let listener = event_emitter.detach(listener_id).unwrap();
+    assert_eq!(
+        Some(2),
+        listener.limit,
+        "The remaining limit should be kept"
+    );
+    assert!(event_emitter.detach(listener_id).is_none());
+
+    event_emitter.sync_emit("First", 10_u32);
+    assert_eq!(
+        1,
+        *counter.lock().unwrap(),
+        "A detached listener should not fire"
+    );
+
+    assert_eq!(listener_id, event_emitter.attach("Second", listener));
+    for _ in 0..3 {
+        event_emitter.sync_emit("Second", 100_u32);
+    }
+    assert_eq!(
+        201,
+        *counter.lock().unwrap(),
+        "The attached listener should fire on its new event until its limit is exhausted"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_local_listeners() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Flags when the local callback owning it is dropped.
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let totals: Arc<Mutex<Vec<(usize, String)>>> = Arc::new(Mutex::new(Vec::new()));
+    let removed_dropped = Arc::new(AtomicBool::new(false));
+    let remaining_dropped = Arc::new(AtomicBool::new(false));
+
+    let cloned_totals = Arc::clone(&totals);
+    let cloned_removed_dropped = Arc::clone(&removed_dropped);
+    let removed_id = event_emitter
+        .lock()
+        .unwrap()
+        .on_local_with("Local", move || {
+            let cache: Rc<RefCell<Vec<u32>>> = Rc::new(RefCell::new(Vec::new()));
+            let flag = DropFlag(cloned_removed_dropped);
+            move |value: u32| {
+                let _ = &flag;
+                cache.borrow_mut().push(value);
+                let thread_name = std::thread::current()
+                    .name()
+                    .unwrap_or_default()
+                    .to_string();
+                cloned_totals
+                    .lock()
+                    .unwrap()
+                    .push((cache.borrow().len(), thread_name));
+            }
+        });
+    let cloned_remaining_dropped = Arc::clone(&remaining_dropped);
+    event_emitter
+        .lock()
+        .unwrap()
+        .on_local_with("Other", move || {
+            let flag = Rc::new(DropFlag(cloned_remaining_dropped));
+            move |_: ()| {
+                let _ = &flag;
+            }
+        });
+
+    let handles: Vec<_> = (0..4_u32)
+        .map(|value| {
+            let event_emitter = Arc::clone(&event_emitter);
+            std::thread::spawn(move || event_emitter.lock().unwrap().emit("Local", value))
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(
+        vec![1, 2, 3, 4],
+        totals
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(count, _)| *count)
+            .collect::<Vec<_>>(),
+        "The Rc cache should have received every emit"
+    );
+    assert!(totals
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|(_, thread_name)| thread_name == "emit-local-dispatcher"));
+
+    let mut event_emitter = Arc::try_unwrap(event_emitter)
+        .ok()
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    event_emitter.remove_listener(&removed_id);
+    event_emitter.sync_emit("Other", ());
+    assert!(
+        removed_dropped.load(Ordering::SeqCst),
+        "Removing the listener should drop its local callback"
+    );
+    assert!(!remaining_dropped.load(Ordering::SeqCst));
+
+    drop(event_emitter);
+    assert!(
+        remaining_dropped.load(Ordering::SeqCst),
+        "Dropping the emitter should join the dispatcher thread"
+    );
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_emitter_logger() {
+    use emitter_rs::logger::{EmitterLogger, LogEvent};
+    use log::{Level, LevelFilter, Log, Record};
+
+    let handle = Arc::new(Mutex::new(EventEmitter::new()));
+    let logger =
+        Arc::new(EmitterLogger::new(Arc::clone(&handle), LevelFilter::Warn).with_target("app"));
+    let errors: Arc<Mutex<Vec<LogEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let records: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_errors = Arc::clone(&errors);
+    handle
+        .lock()
+        .unwrap()
+        .on("log.error", move |event: LogEvent| {
+            cloned_errors.lock().unwrap().push(event);
+        });
+    let cloned_records = Arc::clone(&records);
+    let cloned_logger = Arc::clone(&logger);
+    handle
+        .lock()
+        .unwrap()
+        .on("log.record", move |event: LogEvent| {
+            cloned_records.lock().unwrap().push(event.message);
+            cloned_logger.log(
+                &Record::builder()
+                    .level(Level::Error)
+                    .target("app")
+                    .args(format_args!("logged by a listener"))
+                    .build(),
+            );
+        });
+
+    let log = |level: Level, target: &str, message: &str| {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .target(target)
+                .args(format_args!("{}", message))
+                .module_path(Some("app::db"))
+                .file(Some("src/db.rs"))
+                .line(Some(42))
+                .build(),
+        );
+    };
+
+    log(Level::Info, "app", "too verbose");
+    log(Level::Error, "other", "wrong target");
+    log(Level::Warn, "app::db", "slow query");
+    log(Level::Error, "app::db", "connection lost");
+
+    assert_eq!(
+        vec!["slow query".to_string(), "connection lost".to_string()],
+        *records.lock().unwrap(),
+        "Only records passing the level and target filters should be emitted, without recursion"
+    );
+    assert_eq!(
+        vec![LogEvent {
+            level: "error".to_string(),
+            target: "app::db".to_string(),
+            message: "connection lost".to_string(),
+            module_path: Some("app::db".to_string()),
+            file: Some("src/db.rs".to_string()),
+            line: Some(42),
+        }],
+        *errors.lock().unwrap(),
+        "Errors should be emitted on log.error"
+    );
+
+    let guard = handle.lock().unwrap();
+    log(Level::Warn, "app", "while busy");
+    drop(guard);
+    assert_eq!(2, records.lock().unwrap().len());
+    logger.flush();
+    assert_eq!(
+        Some(&"while busy".to_string()),
+        records.lock().unwrap().last(),
+        "Records logged while the emitter is locked should be emitted on flush"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emit_orderings() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Barrier;
+    use std::thread;
+
+    let calling_thread = thread::current().id();
+    let mut event_emitter = EventEmitter::new();
+    let calls: Arc<Mutex<Vec<(u32, bool)>>> = Arc::new(Mutex::new(Vec::new()));
+    let running = Arc::new(AtomicBool::new(false));
+
+    for position in 1..=3 {
+        let cloned_calls = Arc::clone(&calls);
+        let cloned_running = Arc::clone(&running);
+        event_emitter.on("Ordered", move |_: ()| {
+            assert!(
+                !cloned_running.swap(true, Ordering::SeqCst),
+                "Callbacks should not overlap"
+            );
+            thread::sleep(std::time::Duration::from_millis(10));
+            let on_calling_thread = thread::current().id() == calling_thread;
+            cloned_calls
+                .lock()
+                .unwrap()
+                .push((position, on_calling_thread));
+            cloned_running.store(false, Ordering::SeqCst);
+        });
+    }
+
+    event_emitter.sync_emit("Ordered", ());
+    assert_eq!(
+        vec![(1, true), (2, true), (3, true)],
+        *calls.lock().unwrap(),
+        "sync_emit should run callbacks in order on the calling thread"
+    );
+
+    calls.lock().unwrap().clear();
+    event_emitter.emit_sequential("Ordered", ());
+    assert_eq!(
+        vec![(1, false), (2, false), (3, false)],
+        *calls.lock().unwrap(),
+        "emit_sequential should run callbacks in order, one at a time, on other threads"
+    );
+
+    // emit runs pooled callbacks concurrently: it only returns if both callbacks meet at the barrier.
+    let barrier = Arc::new(Barrier::new(2));
+    for _ in 0..2 {
+        let cloned_barrier = Arc::clone(&barrier);
+        event_emitter.on("Concurrent", move |_: ()| {
+            cloned_barrier.wait();
+        });
+    }
+    event_emitter.emit("Concurrent", ());
+}
+
+#[test]
+fn test_fast_emitter() {
+    use emitter_rs::fast::FastEmitter;
+
+    let mut fast_emitter = FastEmitter::new();
+    let calls: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_calls = Arc::clone(&calls);
+    let first_id = fast_emitter.on("Fast", move |value: u32| {
+        cloned_calls.lock().unwrap().push(format!("u32 {}", value));
+    });
+    let cloned_calls = Arc::clone(&calls);
+    fast_emitter.on("Fast", move |value: u64| {
+        cloned_calls.lock().unwrap().push(format!("u64 {}", value));
+    });
+    let cloned_calls = Arc::clone(&calls);
+    let other_id = fast_emitter.on("Other", move |value: u32| {
+        cloned_calls
+            .lock()
+            .unwrap()
+            .push(format!("other {}", value));
+    });
+
+    fast_emitter.sync_emit("Fast", 7_u32);
+    assert_eq!(
+        vec!["u32 7".to_string(), "u64 7".to_string()],
+        *calls.lock().unwrap(),
+        "Listeners of matching and compatible types should both receive the value in order"
+    );
+
+    assert_eq!(Some(first_id), fast_emitter.remove_listener(first_id));
+    assert_eq!(None, fast_emitter.remove_listener(first_id));
+    calls.lock().unwrap().clear();
+    fast_emitter.sync_emit("Fast", 8_u32);
+    fast_emitter.sync_emit("Missing", 9_u32);
+    fast_emitter.sync_emit("Other", "not a number".to_string());
+    assert_eq!(
+        vec!["u64 8".to_string()],
+        *calls.lock().unwrap(),
+        "Removed listeners and undecodable values should be skipped"
+    );
+
+    assert_eq!(Some(other_id), fast_emitter.remove_listener(other_id));
+    let cloned_calls = Arc::clone(&calls);
+    fast_emitter.on("Other", move |value: u32| {
+        cloned_calls
+            .lock()
+            .unwrap()
+            .push(format!("again {}", value));
+    });
+    calls.lock().unwrap().clear();
+    fast_emitter.sync_emit("Other", 10_u32);
+    fast_emitter.sync_emit("Fast", 11_u32);
+    assert_eq!(
+        vec!["again 10".to_string(), "u64 11".to_string()],
+        *calls.lock().unwrap(),
+        "An event should be listened to again after its last listener was removed"
+    );
+}
+
+#[test]
+fn test_last_emit_tracking() {
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.emit("heartbeat.worker1", ());
+    assert_eq!(None, event_emitter.last_emit("heartbeat.worker1"));
+
+    event_emitter.track_last_emit(true);
+    event_emitter.emit("heartbeat.worker1", ());
+    let first = event_emitter.last_emit("heartbeat.worker1").unwrap();
+    event_emitter.sync_emit("heartbeat.worker1", ());
+    let second = event_emitter.last_emit("heartbeat.worker1").unwrap();
+    assert!(
+        second.seq > first.seq,
+        "The stamp should update on each emit"
+    );
+    assert!(second.at >= first.at);
+    assert_eq!(None, event_emitter.last_emit("heartbeat.unknown"));
+
+    std::thread::sleep(Duration::from_millis(60));
+    event_emitter.emit("heartbeat.worker2", ());
+    let stale: Vec<String> = event_emitter
+        .stale_events(Duration::from_millis(50))
+        .into_iter()
+        .map(|(event, _)| event)
+        .collect();
+    assert_eq!(
+        vec!["heartbeat.worker1".to_string()],
+        stale,
+        "Only the event emitted before the sleep should be stale"
+    );
+
+    event_emitter.track_last_emit(false);
+    assert_eq!(None, event_emitter.last_emit("heartbeat.worker2"));
+    assert!(event_emitter.stale_events(Duration::ZERO).is_empty());
+}
+
+#[test]
+fn test_swap_listeners() {
+    let mut event_emitter = EventEmitter::new();
+    let calls: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_calls = Arc::clone(&calls);
+    let blue_id = event_emitter.on("Config", move |value: u32| {
+        cloned_calls.lock().unwrap().push(format!("blue {}", value));
+    });
+
+    let mut staging = EventEmitter::new();
+    let cloned_calls = Arc::clone(&calls);
+    staging.on("Config", move |value: u32| {
+        cloned_calls
+            .lock()
+            .unwrap()
+            .push(format!("green {}", value));
+    });
+    let cloned_calls = Arc::clone(&calls);
+    staging.on("Reloaded", move |_: ()| {
+        cloned_calls.lock().unwrap().push("reloaded".to_string());
+    });
+
+    let blue = event_emitter.swap_listeners(staging.take_listeners());
+    assert!(staging.listeners.is_empty());
+    assert_eq!(blue_id, blue["Config"][0].id);
+
+    event_emitter.sync_emit("Config", 1_u32);
+    event_emitter.sync_emit("Reloaded", ());
+    assert_eq!(
+        vec!["green 1".to_string(), "reloaded".to_string()],
+        *calls.lock().unwrap(),
+        "Only the swapped-in listeners should fire"
+    );
+
+    event_emitter.set_listeners(blue);
+    calls.lock().unwrap().clear();
+    event_emitter.sync_emit("Config", 2_u32);
+    event_emitter.sync_emit("Reloaded", ());
+    assert_eq!(vec!["blue 2".to_string()], *calls.lock().unwrap());
+}
+
+#[test]
+fn test_swap_listeners_keeps_index_and_patterns() {
+    let mut event_emitter = EventEmitter::new();
+    let calls: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_calls = Arc::clone(&calls);
+    event_emitter.on_pattern("order.*", move |value: u32| {
+        cloned_calls.lock().unwrap().push(format!("blue {}", value));
+    });
+
+    let mut staging = EventEmitter::new();
+    let cloned_calls = Arc::clone(&calls);
+    let green_id = staging.on_pattern("order.**", move |value: u32| {
+        cloned_calls
+            .lock()
+            .unwrap()
+            .push(format!("green {}", value));
+    });
+
+    let blue = event_emitter.swap_listeners(staging.take_listeners());
+    event_emitter.sync_emit("order.item.added", 1_u32);
+    assert_eq!(
+        vec!["green 1".to_string()],
+        *calls.lock().unwrap(),
+        "Only the swapped-in pattern listener should fire"
+    );
+    assert_eq!(Some(green_id), event_emitter.remove_listener(&green_id));
+    assert_eq!(0, event_emitter.listener_count("order.**"));
+
+    event_emitter.set_listeners(blue);
+    calls.lock().unwrap().clear();
+    event_emitter.sync_emit("order.placed", 2_u32);
+    assert_eq!(vec!["blue 2".to_string()], *calls.lock().unwrap());
+
+    let mut capped = EventEmitter::new();
+    capped.set_max_event_names(Some(1));
+    let mut staging = EventEmitter::new();
+    staging.on("First", |_: ()| {});
+    staging.on("Second", |_: ()| {});
+    capped.set_listeners(staging.take_listeners());
+    assert_eq!(
+        1,
+        capped.listener_count("First") + capped.listener_count("Second"),
+        "The listeners of a name beyond the cap should have been refused"
+    );
+
+    capped.shutdown();
+    let mut staging = EventEmitter::new();
+    staging.on("Third", |_: ()| {});
+    capped.set_listeners(staging.take_listeners());
+    assert_eq!(
+        0,
+        capped.listener_count("Third"),
+        "The listeners should have been refused after shutdown"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_take_listeners_cancels_retries() {
+    use emitter_rs::retry::{Backoff, RetryPolicy, RetryableError};
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let policy = RetryPolicy {
+        max_attempts: 5,
+        backoff: Backoff::Fixed(Duration::from_millis(20)),
+    };
+    let attempts: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_attempts = Arc::clone(&attempts);
+    event_emitter.on_with_retry("Refund Issued", policy, move |_: u32| {
+        *cloned_attempts.lock().unwrap() += 1;
+        Err(RetryableError::new("service unavailable"))
+    });
+
+    event_emitter.emit("Refund Issued", 7_u32);
+    while *attempts.lock().unwrap() == 0 {
+        std::thread::yield_now();
+    }
+    let taken = event_emitter.take_listeners();
+    assert!(event_emitter.wait_until_idle(Duration::from_secs(5)));
+    assert_eq!(
+        1,
+        *attempts.lock().unwrap(),
+        "The pending retries should have been cancelled"
+    );
+
+    event_emitter.set_listeners(taken);
+    event_emitter.emit("Refund Issued", 8_u32);
+    assert!(event_emitter.wait_until_idle(Duration::from_secs(5)));
+    assert_eq!(
+        6,
+        *attempts.lock().unwrap(),
+        "The listener should be live again once set back"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_request_all() {
+    use std::time::{Duration, Instant};
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.on_async_replier("price.quote", |item: String| async move {
+        item.len() as u32 * 10
+    });
+    let slow_id = event_emitter.on_async_replier("price.quote", |_: String| async move {
+        std::future::pending::<u32>().await
+    });
+    event_emitter.on_async_replier("price.quote", |_: String| async move { 25_u32 });
+    event_emitter.on_async_replier("price.quote", |_: String| async move { "free" });
+
+    let requests: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_requests = Arc::clone(&requests);
+    event_emitter.on_with_execution(
+        "price.quote",
+        emitter_rs::event_emitter::Execution::Inline,
+        move |item: String| cloned_requests.lock().unwrap().push(item),
+    );
+
+    let started = Instant::now();
+    let quotes: Vec<u32> = futures::executor::block_on(event_emitter.request_all(
+        "price.quote",
+        "apple".to_string(),
+        Duration::from_millis(100),
+    ));
+    assert_eq!(
+        vec![50, 25],
+        quotes,
+        "Replies that arrived in time should be gathered in registration order, skipping undecodable ones"
+    );
+    assert!(
+        started.elapsed() >= Duration::from_millis(100),
+        "The request should have waited for the slow replier until the timeout"
+    );
+    assert_eq!(
+        vec!["apple".to_string()],
+        *requests.lock().unwrap(),
+        "Regular listeners should have received the request"
+    );
+
+    assert_eq!(Some(slow_id), event_emitter.remove_listener(&slow_id));
+    let started = Instant::now();
+    let quotes: Vec<u32> = futures::executor::block_on(event_emitter.request_all(
+        "price.quote",
+        "kiwi".to_string(),
+        Duration::from_secs(10),
+    ));
+    assert_eq!(vec![40, 25], quotes, "Every replier should have answered");
+    assert!(
+        started.elapsed() < Duration::from_secs(10),
+        "The request should resolve as soon as every replier has answered"
+    );
+
+    let unencodable = event_emitter.on_async_replier("price.quote", |_: String| async move {
+        std::collections::HashMap::from([((1_u32, 2_u32), 3_u32)])
+    });
+    let quotes: Vec<u32> = futures::executor::block_on(event_emitter.request_all(
+        "price.quote",
+        "fig".to_string(),
+        Duration::from_secs(10),
+    ));
+    assert_eq!(
+        vec![30, 25],
+        quotes,
+        "A reply that cannot be serialized should be dropped"
+    );
+    assert_eq!(
+        Some(unencodable),
+        event_emitter.remove_listener(&unencodable)
+    );
+
+    requests.lock().unwrap().clear();
+    let quotes: Vec<u32> = futures::executor::block_on(event_emitter.request_all(
+        "price.quote",
+        std::collections::HashMap::from([((1_u32, 2_u32), 3_u32)]),
+        Duration::from_secs(10),
+    ));
+    assert!(
+        quotes.is_empty(),
+        "A request that cannot be serialized should reach no replier"
+    );
+
+    event_emitter.shutdown();
+    let quotes: Vec<u32> = futures::executor::block_on(event_emitter.request_all(
+        "price.quote",
+        "pear".to_string(),
+        Duration::from_secs(10),
+    ));
+    assert!(
+        quotes.is_empty(),
+        "A shut down emitter should refuse requests"
+    );
+    assert!(requests.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_responders() {
+    let mut event_emitter = EventEmitter::new();
+    let invoked: Arc<Mutex<Vec<&str>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_invoked = Arc::clone(&invoked);
+    event_emitter.on_responder("file.handler", move |extension: String| {
+        cloned_invoked.lock().unwrap().push("image");
+        (extension == "png").then(|| "image viewer".to_string())
+    });
+    let cloned_invoked = Arc::clone(&invoked);
+    event_emitter.on_responder("file.handler", move |_: String| {
+        cloned_invoked.lock().unwrap().push("size");
+        42_u32
+    });
+    let cloned_invoked = Arc::clone(&invoked);
+    event_emitter.on_responder("file.handler", move |_: String| {
+        cloned_invoked.lock().unwrap().push("hex");
+        Some("hex viewer".to_string())
+    });
+    let cloned_invoked = Arc::clone(&invoked);
+    event_emitter.on_with_execution(
+        "file.handler",
+        emitter_rs::event_emitter::Execution::Inline,
+        move |_: String| cloned_invoked.lock().unwrap().push("listener"),
+    );
+
+    let handlers: Vec<Option<String>> = event_emitter.sync_query("file.handler", "txt".to_string());
+    assert_eq!(
+        vec![None, Some("hex viewer".to_string())],
+        handlers,
+        "Answers should be aggregated in registration order, skipping the one that fails to decode"
+    );
+    assert_eq!(
+        vec!["listener", "image", "size", "hex"],
+        *invoked.lock().unwrap(),
+        "The regular listener should fire, then every responder"
+    );
+
+    invoked.lock().unwrap().clear();
+    let handler: Option<String> = event_emitter.sync_query_first("file.handler", "png".to_string());
+    assert_eq!(Some("image viewer".to_string()), handler);
+    assert_eq!(
+        vec!["listener", "image"],
+        *invoked.lock().unwrap(),
+        "The query should stop at the first responder returning a value"
+    );
+
+    invoked.lock().unwrap().clear();
+    let handler: Option<String> = event_emitter.sync_query_first("file.handler", "txt".to_string());
+    assert_eq!(
+        Some("hex viewer".to_string()),
+        handler,
+        "Declining and undecodable answers should be skipped"
+    );
+    assert_eq!(
+        vec!["listener", "image", "size", "hex"],
+        *invoked.lock().unwrap()
+    );
+
+    let decode_errors: Arc<Mutex<Vec<ListenerId>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_decode_errors = Arc::clone(&decode_errors);
+    event_emitter.on_decode_error(move |error| {
+        cloned_decode_errors.lock().unwrap().push(error.listener_id);
+    });
+    let unencodable = event_emitter.on_responder("file.sizes", |_: String| {
+        std::collections::HashMap::from([((1_u32, 2_u32), 3_u32)])
+    });
+    event_emitter.on_responder("file.sizes", |_: String| 42_u32);
+    let sizes: Vec<u32> = event_emitter.sync_query("file.sizes", "txt".to_string());
+    assert_eq!(
+        vec![42],
+        sizes,
+        "An answer that cannot be serialized should count as no answer"
+    );
+    assert_eq!(vec![unencodable], *decode_errors.lock().unwrap());
+
+    let sizes: Vec<u32> = event_emitter.sync_query(
+        "file.sizes",
+        std::collections::HashMap::from([((1_u32, 2_u32), 3_u32)]),
+    );
+    assert!(
+        sizes.is_empty(),
+        "A query that cannot be serialized should get no answer"
+    );
+}
+
+#[test]
+fn test_on_many() {
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    let listener_ids =
+        event_emitter.on_many(&["user.created", "user.updated"], move |name: String| {
+            cloned_received.lock().unwrap().push(name);
+        });
+    assert_eq!(
+        2,
+        listener_ids.len(),
+        "One listener should be added per event"
+    );
+
+    event_emitter.sync_emit("user.created", "alice".to_string());
+    event_emitter.sync_emit("user.updated", "bob".to_string());
+    assert_eq!(
+        vec!["alice".to_string(), "bob".to_string()],
+        *received.lock().unwrap(),
+        "The shared callback should fire for every event"
+    );
+
+    event_emitter.remove_listener(&listener_ids[0]);
+    event_emitter.sync_emit("user.created", "carol".to_string());
+    event_emitter.sync_emit("user.updated", "dave".to_string());
+    assert_eq!(
+        vec!["alice".to_string(), "bob".to_string(), "dave".to_string()],
+        *received.lock().unwrap(),
+        "Removing one listener should leave the others in place"
+    );
+}
+
+#[test]
+fn test_emit_tagged() {
+    use emitter_rs::event_emitter::{Execution, ListenerOptions};
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    let not_self = ListenerOptions {
+        execution: Execution::Inline,
+        limit: Some(2),
+        exclude_tags: vec!["self".to_string()],
+        ..ListenerOptions::default()
+    };
+    let not_self_id = event_emitter.on_with_options("changed", not_self, move |value: String| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("not self: {}", value));
+    });
+
+    let cloned_received = Arc::clone(&received);
+    let only_ui = ListenerOptions {
+        execution: Execution::Inline,
+        include_tags: vec!["ui".to_string()],
+        ..ListenerOptions::default()
+    };
+    event_emitter.on_with_options("changed", only_ui, move |value: String| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("ui: {}", value));
+    });
+
+    event_emitter.emit_tagged("changed", "a".to_string(), &["self"]);
+    event_emitter.emit_tagged("changed", "b".to_string(), &["self", "ui"]);
+    event_emitter.emit("changed", "c".to_string());
+    event_emitter.emit_tagged("changed", "d".to_string(), &["ui"]);
+
+    assert_eq!(
+        vec![
+            "ui: b".to_string(),
+            "not self: c".to_string(),
+            "not self: d".to_string(),
+            "ui: d".to_string()
+        ],
+        *received.lock().unwrap(),
+        "Excluded tags should be skipped and included tags required"
+    );
+    assert_eq!(
+        None,
+        event_emitter.remaining_limit(not_self_id),
+        "Filtered-out deliveries should not count toward the limit, used up by the last delivery"
+    );
+}
+
+#[test]
+fn test_debug_and_display() {
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.on("user.created", |_: String| {});
+    let limited_id = event_emitter.on_limited("user.created", Some(2), |_: String| {});
+    event_emitter.on("user.deleted", |_: String| {});
+
+    assert_eq!(
+        "EventEmitter{2 events, 3 listeners}",
+        event_emitter.to_string(),
+        "Display should summarize the events and listeners"
+    );
+
+    let debug = format!("{:?}", event_emitter);
+    assert!(
+        debug.contains("user.created") && debug.contains("user.deleted"),
+        "Debug should list the event names: {}",
+        debug
+    );
+    assert!(
+        debug.contains(&format!("{:?}", limited_id)) && debug.contains("limit: Some(2)"),
+        "Debug should list the listener IDs and limits: {}",
+        debug
+    );
+    assert!(
+        debug.find("user.created") < debug.find("user.deleted"),
+        "Debug should list the events in name order"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_deferred_removal_stress() {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let self_removing_calls = Arc::new(AtomicUsize::new(0));
+    let remover_slot: Arc<Mutex<Option<emitter_rs::removal::ListenerRemover>>> =
+        Arc::new(Mutex::new(None));
+    {
+        let mut event_emitter = event_emitter.lock().unwrap();
+        event_emitter.on("tick", |_: u32| {});
+
+        let cloned_calls = Arc::clone(&self_removing_calls);
+        let cloned_slot = Arc::clone(&remover_slot);
+        let id = event_emitter.on("tick", move |_: u32| {
+            cloned_calls.fetch_add(1, Ordering::SeqCst);
+            if let Some(remover) = cloned_slot.lock().unwrap().as_ref() {
+                remover.remove();
+            }
+        });
+        *remover_slot.lock().unwrap() = event_emitter.remover(id);
+    }
+
+    let cloned_emitter = Arc::clone(&event_emitter);
+    let cloned_stop = Arc::clone(&stop);
+    let emitting = thread::spawn(move || {
+        while !cloned_stop.load(Ordering::SeqCst) {
+            cloned_emitter.lock().unwrap().sync_emit("tick", 1_u32);
+        }
+    });
+
+    let mut removed_listeners = Vec::new();
+    for _ in 0..50 {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cloned_calls = Arc::clone(&calls);
+        let remover = {
+            let mut event_emitter = event_emitter.lock().unwrap();
+            let id = event_emitter.on("tick", move |_: u32| {
+                cloned_calls.fetch_add(1, Ordering::SeqCst);
+            });
+            event_emitter.remover(id).unwrap()
+        };
+        thread::sleep(Duration::from_micros(200));
+
+        assert!(remover.remove(), "The listener should have been live");
+        // Any emit in progress when `remove` returned has finished once the lock is acquired.
+        drop(event_emitter.lock().unwrap());
+        removed_listeners.push((calls.load(Ordering::SeqCst), calls));
+    }
+
+    thread::sleep(Duration::from_millis(20));
+    stop.store(true, Ordering::SeqCst);
+    emitting.join().unwrap();
+
+    for (calls_at_removal, calls) in removed_listeners {
+        assert_eq!(
+            calls_at_removal,
+            calls.load(Ordering::SeqCst),
+            "No delivery should start after removal returns"
+        );
+    }
+    assert_eq!(
+        1,
+        self_removing_calls.load(Ordering::SeqCst),
+        "A listener removing itself from its callback should not be called again"
+    );
+
+    let mut event_emitter = event_emitter.lock().unwrap();
+    assert_eq!(1, event_emitter.listener_count("tick"));
+    event_emitter.compact();
+    assert_eq!(
+        1,
+        event_emitter.listeners["tick"].len(),
+        "Removed listeners should eventually be physically dropped"
+    );
+}
+
+#[test]
+fn test_emit_shared() {
+    use emitter_rs::error::EmitError;
+    use emitter_rs::event_emitter::Execution;
+    use std::collections::BTreeMap;
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    for event in ["config.changed", "config.changed.db", "config.changed.net"] {
+        let cloned_received = Arc::clone(&received);
+        event_emitter.on_with_execution(event, Execution::Inline, move |value: Vec<u32>| {
+            cloned_received
+                .lock()
+                .unwrap()
+                .push(format!("{}: {:?}", event, value));
+        });
+    }
+    event_emitter.on("config.changed.db", |_: Vec<u32>| {});
+
+    let counts = event_emitter
+        .emit_shared(
+            &[
+                "config.changed",
+                "config.changed.db",
+                "config.changed.net",
+                "config.changed",
+            ],
+            vec![1_u32, 2, 3],
+        )
+        .unwrap();
+    assert_eq!(
+        vec![1, 2, 1, 1],
+        counts,
+        "Each event should report its own delivery count"
+    );
+    assert_eq!(
+        vec![
+            "config.changed: [1, 2, 3]".to_string(),
+            "config.changed.db: [1, 2, 3]".to_string(),
+            "config.changed.net: [1, 2, 3]".to_string(),
+            "config.changed: [1, 2, 3]".to_string(),
+        ],
+        *received.lock().unwrap(),
+        "Every event should receive the same value, in slice order"
+    );
+
+    received.lock().unwrap().clear();
+    let unserializable: BTreeMap<(u32, u32), u32> = BTreeMap::from([((1, 2), 3)]);
+    let result =
+        event_emitter.emit_shared(&["config.changed", "config.changed.db"], unserializable);
+    assert!(
+        matches!(result, Err(EmitError::Serialize(_))),
+        "A serialization failure should be reported"
+    );
+    assert!(
+        received.lock().unwrap().is_empty(),
+        "A serialization failure should deliver to none of the events"
+    );
+}
+
+#[test]
+fn test_emit_shared_serializes_once() {
+    use emitter_rs::event_emitter::Execution;
+    use serde::{Serialize, Serializer};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct Counted(Arc<AtomicU32>);
+
+    impl Serialize for Counted {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            serializer.serialize_u32(7)
+        }
+    }
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    for event in ["config.changed", "config.changed.db"] {
+        for _ in 0..3 {
+            let cloned_received = Arc::clone(&received);
+            event_emitter.on_with_execution(event, Execution::Inline, move |value: u32| {
+                cloned_received.lock().unwrap().push(value);
+            });
+        }
+    }
+    event_emitter.enable_history("config.changed.db", 5);
+
+    let serializations = Arc::new(AtomicU32::new(0));
+    let counts = event_emitter
+        .emit_shared(
+            &["config.changed", "config.changed.db", "config.changed.net"],
+            Counted(Arc::clone(&serializations)),
+        )
+        .unwrap();
+    assert_eq!(vec![3, 3, 0], counts);
+    assert_eq!(vec![7; 6], *received.lock().unwrap());
+    assert_eq!(
+        1,
+        serializations.load(Ordering::SeqCst),
+        "The value should be serialized once for every event and listener"
+    );
+}
+
+#[test]
+fn test_emit_shared_refused_delivers_nothing() {
+    use emitter_rs::error::EmitError;
+    use emitter_rs::event_emitter::Execution;
+    use emitter_rs::guard::GuardError;
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    for event in ["config.changed", "config.changed.db"] {
+        let cloned_received = Arc::clone(&received);
+        event_emitter.on_with_execution(event, Execution::Inline, move |value: u32| {
+            cloned_received
+                .lock()
+                .unwrap()
+                .push(format!("{}: {}", event, value));
+        });
+    }
+    event_emitter.add_guard("config.changed.db", |_, _| {
+        Err(GuardError::new("the database config is frozen"))
+    });
+
+    let result = event_emitter.emit_shared(&["config.changed", "config.changed.db"], 7_u32);
+    assert!(
+        matches!(result, Err(EmitError::Rejected { .. })),
+        "The guard rejection of the second event should be reported"
+    );
+    assert!(
+        received.lock().unwrap().is_empty(),
+        "A refused event should keep the events before it from being delivered"
+    );
+
+    let counts = event_emitter
+        .emit_shared(&["config.changed"], 7_u32)
+        .unwrap();
+    assert_eq!(vec![1], counts);
+    assert_eq!(
+        vec!["config.changed: 7".to_string()],
+        *received.lock().unwrap()
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emit_with_retry() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::{Duration, Instant};
+
+    let mut event_emitter = EventEmitter::new();
+
+    let flaky_calls = Arc::new(AtomicU32::new(0));
+    let cloned_flaky_calls = Arc::clone(&flaky_calls);
+    let flaky_id = event_emitter.on_result("sync", move |_: u32| {
+        match cloned_flaky_calls.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => Err("connection reset"),
+            _ => Ok(()),
+        }
+    });
+
+    let broken_calls = Arc::new(AtomicU32::new(0));
+    let cloned_broken_calls = Arc::clone(&broken_calls);
+    let broken_id = event_emitter.on_result("sync", move |_: u32| {
+        cloned_broken_calls.fetch_add(1, Ordering::SeqCst);
+        Err::<(), _>("server down")
+    });
+
+    let plain_calls = Arc::new(AtomicU32::new(0));
+    let cloned_plain_calls = Arc::clone(&plain_calls);
+    let plain_id = event_emitter.on("sync", move |_: u32| {
+        cloned_plain_calls.fetch_add(1, Ordering::SeqCst);
+    });
+
+    let started = Instant::now();
+    let outcomes = event_emitter.emit_with_retry("sync", 1_u32, 3, Duration::from_millis(5));
+    assert_eq!(
+        vec![
+            ListenerOutcome {
+                listener_id: flaky_id,
+                attempts: 3,
+                result: Ok(()),
+            },
+            ListenerOutcome {
+                listener_id: broken_id,
+                attempts: 4,
+                result: Err("server down".to_string()),
+            },
+            ListenerOutcome {
+                listener_id: plain_id,
+                attempts: 1,
+                result: Ok(()),
+            },
+        ],
+        outcomes,
+        "Failing listeners should be retried until they succeed or run out of retries"
+    );
+    assert_eq!(3, flaky_calls.load(Ordering::SeqCst));
+    assert_eq!(4, broken_calls.load(Ordering::SeqCst));
+    assert_eq!(
+        1,
+        plain_calls.load(Ordering::SeqCst),
+        "Plain listeners should not be retried"
+    );
+    assert!(
+        started.elapsed() >= Duration::from_millis(25),
+        "Each retry should wait for the backoff"
+    );
+
+    event_emitter.emit("sync", 2_u32);
+    assert_eq!(
+        5,
+        broken_calls.load(Ordering::SeqCst),
+        "A plain emit should call failing listeners once"
+    );
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.set_max_event_names(Some(1));
+    event_emitter.on("sync", |_: u32| {});
+    event_emitter.on_result("other", |_: u32| Err::<(), _>("refused"));
+    assert_eq!(
+        0,
+        event_emitter.listener_count("other"),
+        "A refused on_result listener should not be added"
+    );
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_macrotask_scheduling_wasm() {
+    use emitter_rs::scheduling::Scheduling;
+    use wasm_bindgen::JsCast;
+
+    let document = web_sys::window().unwrap().document().unwrap();
+    let body = document.body().unwrap();
+    body.remove_attribute("data-turned").unwrap();
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.set_wasm_scheduling(Scheduling::Macrotask);
+    let observed: Arc<Mutex<Vec<Option<String>>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_observed = Arc::clone(&observed);
+    event_emitter.on_limited("frame", Some(2), move |_: u32| {
+        let body = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .body()
+            .unwrap();
+        cloned_observed
+            .lock()
+            .unwrap()
+            .push(body.get_attribute("data-turned"));
+    });
+
+    event_emitter.emit("frame", 1_u32);
+    let mutation = wasm_bindgen::closure::Closure::once_into_js(move || {
+        body.set_attribute("data-turned", "yes").unwrap();
+    });
+    web_sys::window()
+        .unwrap()
+        .set_timeout_with_callback(mutation.unchecked_ref())
+        .unwrap();
+    event_emitter.emit("frame", 2_u32);
+    event_emitter.emit("frame", 3_u32);
+
+    let settled = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 50)
+            .unwrap();
+    });
+    wasm_bindgen_futures::JsFuture::from(settled).await.unwrap();
+
+    assert_eq!(
+        vec![None, Some("yes".to_string())],
+        *observed.lock().unwrap(),
+        "The second emit should observe the DOM mutation queued before it, and the limit should hold"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_limit_on_success() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    let mut event_emitter = EventEmitter::new();
+
+    let success_calls = Arc::new(AtomicU32::new(0));
+    let cloned_success_calls = Arc::clone(&success_calls);
+    event_emitter.on_limited_on_success("migrate", 1, move |should_panic: bool| {
+        cloned_success_calls.fetch_add(1, Ordering::SeqCst);
+        if should_panic {
+            panic!("migration failed");
+        }
+    });
+
+    let plain_calls = Arc::new(AtomicU32::new(0));
+    let cloned_plain_calls = Arc::clone(&plain_calls);
+    event_emitter.on_limited("migrate", Some(1), move |should_panic: bool| {
+        cloned_plain_calls.fetch_add(1, Ordering::SeqCst);
+        if should_panic {
+            panic!("migration failed");
+        }
+    });
+
+    event_emitter.emit("migrate", true);
+    event_emitter.emit("migrate", false);
+    event_emitter.emit("migrate", false);
+
+    assert_eq!(
+        2,
+        success_calls.load(Ordering::SeqCst),
+        "A panicking call should not consume the budget of a success-limited listener"
+    );
+    assert_eq!(
+        1,
+        plain_calls.load(Ordering::SeqCst),
+        "A panicking call should consume the budget of a plain limited listener"
+    );
+    assert_eq!(
+        0,
+        event_emitter.listener_count("migrate"),
+        "Both listeners should be removed once their budget is spent"
+    );
+}
+
+#[test]
+fn test_effective_listener_count() {
+    use emitter_rs::event_emitter::ListenerOptions;
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.on("user.created", |_: String| {});
+    event_emitter.on_limited("user.created", Some(1), |_: String| {});
+    let only_admin = ListenerOptions {
+        include_tags: vec!["admin".to_string()],
+        ..ListenerOptions::default()
+    };
+    event_emitter.on_with_options("user.created", only_admin, |_: String| {});
+    let removed_id = event_emitter.on("user.created", |_: String| {});
+    event_emitter.remover(removed_id).unwrap().remove();
+
+    assert_eq!(3, event_emitter.listener_count("user.created"));
+    assert_eq!(
+        2,
+        event_emitter.effective_listener_count("user.created"),
+        "Tag-restricted and removed listeners would not fire on a plain emit"
+    );
+
+    event_emitter.sync_emit("user.created", "ada".to_string());
+    assert_eq!(
+        1,
+        event_emitter.effective_listener_count("user.created"),
+        "An exhausted listener would not fire"
+    );
+    assert_eq!(0, event_emitter.effective_listener_count("user.deleted"));
+}
+
+#[test]
+fn test_dispatch_order() {
+    use emitter_rs::order::DispatchOrder;
+
+    fn dispatched(order: DispatchOrder) -> Vec<u32> {
+        let mut event_emitter = EventEmitter::new();
+        event_emitter.set_dispatch_order(order);
+        let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+        for index in 0..8 {
+            let cloned_received = Arc::clone(&received);
+            event_emitter.on("tick", move |_: ()| {
+                cloned_received.lock().unwrap().push(index)
+            });
+        }
+
+        event_emitter.sync_emit("tick", ());
+        let order = received.lock().unwrap().clone();
+        order
+    }
+
+    let insertion: Vec<u32> = (0..8).collect();
+    assert_eq!(insertion, dispatched(DispatchOrder::Insertion));
+    assert_eq!(
+        insertion.iter().rev().copied().collect::<Vec<u32>>(),
+        dispatched(DispatchOrder::Reverse)
+    );
+
+    let shuffled = dispatched(DispatchOrder::Shuffled { seed: 7 });
+    assert_ne!(
+        insertion, shuffled,
+        "A shuffled order should differ from insertion order"
+    );
+    let mut sorted = shuffled.clone();
+    sorted.sort();
+    assert_eq!(
+        insertion, sorted,
+        "Every listener should still be invoked once"
+    );
+    assert_eq!(
+        shuffled,
+        dispatched(DispatchOrder::Shuffled { seed: 7 }),
+        "The same seed should give the same order"
+    );
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.set_dispatch_order(DispatchOrder::Reverse);
+    assert_eq!(DispatchOrder::Reverse, event_emitter.dispatch_order());
+    let received: Arc<Mutex<Vec<&str>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_limited("tick", Some(1), move |_: ()| {
+        cloned_received.lock().unwrap().push("once")
+    });
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_limited("tick", Some(2), move |_: ()| {
+        cloned_received.lock().unwrap().push("twice")
+    });
+
+    for _ in 0..3 {
+        event_emitter.sync_emit("tick", ());
+    }
+    assert_eq!(
+        vec!["twice", "once", "twice"],
+        *received.lock().unwrap(),
+        "Limits should expire correctly in reverse order"
+    );
+    assert_eq!(0, event_emitter.listener_count("tick"));
+}
+
+#[test]
+fn test_emit_failures_do_not_panic() {
+    use emitter_rs::error::EmitError;
+    use std::collections::BTreeMap;
+
+    let mut event_emitter = EventEmitter::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on("grid", move |value: u32| {
+        cloned_received.lock().unwrap().push(value)
+    });
+    let decode_errors = Arc::new(Mutex::new(0));
+    let cloned_decode_errors = Arc::clone(&decode_errors);
+    event_emitter.on_decode_error(move |_| *cloned_decode_errors.lock().unwrap() += 1);
+
+    // JSON object keys must be strings, so this map cannot be serialized.
+    let grid = BTreeMap::from([((0_u32, 0_u32), 1_u32)]);
+    assert!(
+        matches!(
+            event_emitter.try_emit("grid", grid.clone()),
+            Err(EmitError::Serialize(_))
+        ),
+        "try_emit should return the serialization error"
+    );
+    assert!(
+        matches!(
+            event_emitter.try_sync_emit("grid", grid.clone()),
+            Err(EmitError::Serialize(_))
+        ),
+        "try_sync_emit should return the serialization error"
+    );
+    event_emitter.emit("grid", grid.clone());
+    event_emitter.sync_emit("grid", grid);
+
+    event_emitter.sync_emit("grid", "not a number".to_string());
+    event_emitter.emit("grid", "not a number".to_string());
+    assert_eq!(
+        2,
+        *decode_errors.lock().unwrap(),
+        "Mismatched payloads should be reported to the decode hooks"
+    );
+
+    event_emitter.sync_emit("grid", 7_u32);
+    assert_eq!(
+        vec![7],
+        *received.lock().unwrap(),
+        "Only the valid payload should reach the listener"
+    );
+}
+
+#[test]
+fn test_add_built_listener() {
+    use emitter_rs::event_emitter::{Callback, Listener};
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    let callback: Callback = Arc::new(move |bytes: Vec<u8>| {
+        let value: String = serde_json::from_slice(&bytes).unwrap();
+        cloned_received.lock().unwrap().push(value)
+    });
+    let cloned_received = Arc::clone(&received);
+    let wrapped: Callback = Arc::new(move |bytes: Vec<u8>| {
+        cloned_received.lock().unwrap().push("wrapped".to_string());
+        callback(bytes)
+    });
+
+    let id = event_emitter.next_listener_id();
+    let listener = Listener::new(id, Some(2), wrapped);
+    assert_eq!(id, event_emitter.add_listener("Built", listener));
+    assert_eq!(1, event_emitter.listener_count("Built"));
+
+    for value in ["a", "b", "c"] {
+        event_emitter.sync_emit("Built", value.to_string());
+    }
+    assert_eq!(
+        vec!["wrapped", "a", "wrapped", "b"],
+        *received.lock().unwrap(),
+        "The built listener should honor its limit and run the wrapped callback"
+    );
+    assert_eq!(0, event_emitter.listener_count("Built"));
+
+    let listener = Listener::new(event_emitter.next_listener_id(), None, Arc::new(|_| {}));
+    let id = event_emitter.add_listener("Built", listener);
+    assert!(event_emitter.remove_listener(&id).is_some());
+}
+
+#[test]
+fn test_topology() {
+    use emitter_rs::event_emitter::ListenerOptions;
+    use emitter_rs::topology::EventNode;
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.on("user.created", |_: String| {});
+    event_emitter.on("user.created", |_: String| {});
+    let options = ListenerOptions {
+        exclude_tags: vec!["sync".to_string()],
+        ..ListenerOptions::default()
+    };
+    event_emitter.on_with_options("settings.changed", options, |_: String| {});
+    event_emitter.once("user.deleted", |_: String| {});
+    event_emitter.sync_emit("user.deleted", "ada".to_string());
+    let removed = event_emitter.on("user.updated", |_: String| {});
+    event_emitter.remover(removed).unwrap().remove();
+
+    let topology = event_emitter.topology();
+    assert_eq!(
+        vec![
+            EventNode {
+                event: "settings.changed".to_string(),
+                listener_count: 1,
+                tags: vec!["sync".to_string()],
+            },
+            EventNode {
+                event: "user.created".to_string(),
+                listener_count: 2,
+                tags: Vec::new(),
+            },
+        ],
+        topology.nodes,
+        "Only events with live listeners should be nodes"
+    );
+
+    let dot = topology.to_dot();
+    let mut lines: Vec<&str> = dot.lines().collect();
+    assert_eq!(Some("digraph emitter {"), lines.first().copied());
+    assert_eq!(Some("}"), lines.last().copied());
+    lines.sort();
+    assert_eq!(
+        vec![
+            "    \"settings.changed\" [label=\"settings.changed\\n1 listener\\n[sync]\"];",
+            "    \"user.created\" [label=\"user.created\\n2 listeners\"];",
+            "digraph emitter {",
+            "}",
+        ],
+        lines,
+        "The DOT output should have one statement per event"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_topology_bridge_edges() {
+    use emitter_rs::topology::EdgeKind;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    event_emitter.lock().unwrap().on("job.done", |_: u32| {});
+
+    let (sender, receiver) = mpsc::channel::<u32>();
+    let bridge = EventEmitter::attach_receiver(&event_emitter, "job.done", receiver);
+    let (_, other_receiver) = mpsc::channel::<u32>();
+    let mut other_bridge =
+        EventEmitter::attach_receiver(&event_emitter, "job.failed", other_receiver);
+
+    let topology = event_emitter.lock().unwrap().topology();
+    let mut edges: Vec<(&str, EdgeKind)> = topology
+        .edges
+        .iter()
+        .map(|edge| (edge.to.as_str(), edge.kind))
+        .collect();
+    edges.sort();
+    assert_eq!(
+        vec![
+            ("job.done", EdgeKind::Bridge),
+            ("job.failed", EdgeKind::Bridge)
+        ],
+        edges,
+        "Each bridge should be an edge from its channel to its event"
+    );
+    assert!(topology
+        .edges
+        .iter()
+        .all(|edge| edge.from.starts_with("channel ")));
+    assert_ne!(topology.edges[0].from, topology.edges[1].from);
+
+    let dot = topology.to_dot();
+    let job_done = topology
+        .edges
+        .iter()
+        .find(|edge| edge.to == "job.done")
+        .unwrap();
+    let edge_line = format!(
+        "    \"{}\" -> \"job.done\" [label=\"bridge\"];",
+        job_done.from
+    );
+    assert!(dot.lines().any(|line| line == edge_line), "{}", dot);
+
+    other_bridge.stop();
+    drop(sender);
+    while !bridge.is_finished() {
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert!(
+        event_emitter.lock().unwrap().topology().edges.is_empty(),
+        "Stopped and disconnected bridges should be left out"
+    );
+}
+
+#[test]
+fn test_listener_control() {
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    let first = event_emitter.on("tick", move |value: u32| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("first {}", value))
+    });
+    let cloned_received = Arc::clone(&received);
+    let once = event_emitter.once("tick", move |value: u32| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("once {}", value))
+    });
+    let cloned_received = Arc::clone(&received);
+    let last = event_emitter.on_limited("tick", Some(5), move |value: u32| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("last {}", value))
+    });
+
+    assert!(event_emitter.set_enabled(last, false));
+    event_emitter.sync_emit("tick", 1_u32);
+    assert_eq!(
+        Some(Some(5)),
+        event_emitter.remaining_limit(last),
+        "A disabled listener should not consume its limit"
+    );
+    assert_eq!(
+        None,
+        event_emitter.remaining_limit(once),
+        "An exhausted listener should be dropped by the emit using its last call"
+    );
+    assert!(!event_emitter.reset_limit(once, Some(1)));
+
+    assert!(event_emitter.reset_limit(first, Some(1)));
+    assert!(event_emitter.set_enabled(last, true));
+    event_emitter.sync_emit("tick", 2_u32);
+    assert_eq!(
+        vec!["first 1", "once 1", "first 2", "last 2"],
+        *received.lock().unwrap(),
+        "Reset and re-enabled listeners should fire in their original positions"
+    );
+
+    event_emitter.sync_emit("tick", 3_u32);
+    assert_eq!(None, event_emitter.remaining_limit(first));
+    assert_eq!(Some(Some(3)), event_emitter.remaining_limit(last));
+
+    let listener = event_emitter.detach(last).unwrap();
+    event_emitter.attach("tock", listener);
+    assert!(event_emitter.set_enabled(last, false));
+    event_emitter.sync_emit("tock", 4_u32);
+    assert_eq!(
+        Some(Some(3)),
+        event_emitter.remaining_limit(last),
+        "The index should follow a listener moved to another event"
+    );
+}
+
+#[test]
+fn test_emit_to_id() {
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    let alice = event_emitter.once("inbox", move |message: String| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("alice: {}", message))
+    });
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on("inbox", move |message: String| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("bob: {}", message))
+    });
+
+    assert!(event_emitter.emit_to_id(alice, "hi".to_string()));
+    assert!(
+        !event_emitter.emit_to_id(alice, "again".to_string()),
+        "An exhausted listener should not be invoked"
+    );
+    assert_eq!(
+        vec!["alice: hi"],
+        *received.lock().unwrap(),
+        "Only the addressed listener should receive the value"
+    );
+
+    event_emitter.remove_listener(&alice);
+    assert!(!event_emitter.emit_to_id(alice, "gone".to_string()));
+    assert!(!event_emitter.emit_to_id(ListenerId(0), "nobody".to_string()));
+}
+
+#[test]
+fn test_closed_channel_removal() {
+    let mut event_emitter = EventEmitter::new();
+    let removed: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_removed = Arc::clone(&removed);
+    event_emitter.on_listener_removed(move |event, _| {
+        cloned_removed.lock().unwrap().push(event.to_string())
+    });
+
+    let (kept_id, kept) = event_emitter.on_channel::<u32>("tick");
+    let (dropped_id, dropped) = event_emitter.on_channel::<u32>("tick");
+    drop(dropped);
+
+    event_emitter.emit("tick", 1_u32);
+    assert_eq!(1, kept.recv().unwrap());
+    assert_eq!(
+        1,
+        event_emitter.listener_count("tick"),
+        "The listener should be gone once its receiver is found dropped"
+    );
+    assert_eq!(vec![dropped_id], event_emitter.take_closed_channels());
+    assert!(event_emitter.take_closed_channels().is_empty());
+
+    event_emitter.emit("tick", 2_u32);
+    assert_eq!(2, kept.recv().unwrap());
+    assert_eq!(
+        vec![kept_id],
+        event_emitter.listeners["tick"]
+            .iter()
+            .map(|listener| listener.id)
+            .collect::<Vec<_>>(),
+        "The next emit should drop the closed listener"
+    );
+    assert_eq!(vec!["tick"], *removed.lock().unwrap());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emit_sequence() {
+    use std::thread;
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let observed: Arc<Mutex<Vec<(String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+    for event in ["validate", "persist"] {
+        let cloned_observed = Arc::clone(&observed);
+        event_emitter
+            .lock()
+            .unwrap()
+            .on_seq(event, move |seq: u64, value: String| {
+                cloned_observed.lock().unwrap().push((value, seq))
+            });
+    }
+
+    let handles: Vec<_> = ["validate", "persist"]
+        .into_iter()
+        .map(|event| {
+            let cloned_event_emitter = Arc::clone(&event_emitter);
+            thread::spawn(move || {
+                (0..50)
+                    .map(|index| {
+                        let value = format!("{}-{}", event, index);
+                        let seq = cloned_event_emitter
+                            .lock()
+                            .unwrap()
+                            .sync_emit(event, value.clone());
+                        (value, seq)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let mut returned: Vec<(String, u64)> = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect();
+
+    let mut observed = observed.lock().unwrap().clone();
+    returned.sort();
+    observed.sort();
+    assert_eq!(
+        returned, observed,
+        "Listeners should observe the sequence number returned by sync_emit"
+    );
+
+    let mut seqs: Vec<u64> = returned.iter().map(|(_, seq)| *seq).collect();
+    seqs.sort();
+    assert_eq!(
+        (1..=100).collect::<Vec<u64>>(),
+        seqs,
+        "Every emit should get its own sequence number, starting from 1"
+    );
+    for event in ["validate", "persist"] {
+        let event_seqs: Vec<u64> = (0..50)
+            .map(|index| {
+                let value = format!("{}-{}", event, index);
+                returned.iter().find(|(v, _)| *v == value).unwrap().1
+            })
+            .collect();
+        assert!(
+            event_seqs.windows(2).all(|pair| pair[0] < pair[1]),
+            "Sequence numbers should increase within each thread"
+        );
+    }
+
+    let mut event_emitter = event_emitter.lock().unwrap();
+    let capture = event_emitter.capture("persist").unwrap();
+    let held_seq = event_emitter.emit("persist", "captured".to_string());
+    assert_eq!(held_seq, capture.drain()[0].emit_seq);
+}
+
+#[test]
+fn test_emit_sequence_with_dead_letters() {
+    use emitter_rs::event_emitter::Execution;
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.enable_dead_letter();
+    event_emitter.on("deadletter", |_: serde_json::Value| {});
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let cloned_observed = Arc::clone(&observed);
+    event_emitter.on_seq("order.placed", move |seq: u64, _: u32| {
+        cloned_observed.lock().unwrap().push(seq)
+    });
+    event_emitter.on_with_execution("order.placed", Execution::Inline, |_: u32| {
+        panic!("order rejected")
+    });
+
+    let first = event_emitter.emit("order.placed", 1_u32);
+    let second = event_emitter.sync_emit("order.placed", 2_u32);
+    assert_eq!(
+        vec![first, second],
+        *observed.lock().unwrap(),
+        "Emits should return their own sequence number, not that of the dead letters they caused"
+    );
+    assert!(first > 0 && second > first + 1);
+}
+
+#[cfg(all(feature = "schema", not(target_arch = "wasm32")))]
+#[test]
+fn test_schema_validation() {
+    use emitter_rs::error::EmitError;
+    use emitter_rs::schema::Validation;
+    use serde_json::json;
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<serde_json::Value>>> = Arc::new(Mutex::new(Vec::new()));
+    for event in ["user.created", "user.deleted"] {
+        let cloned_received = Arc::clone(&received);
+        event_emitter.on(event, move |value: serde_json::Value| {
+            cloned_received.lock().unwrap().push(value)
+        });
+    }
+
+    assert!(event_emitter
+        .register_schema("user.created", json!({ "type": "invalid" }))
+        .is_err());
+    event_emitter
+        .register_schema("user.created", json!({ "type": "string" }))
+        .unwrap();
+
+    event_emitter.sync_emit("user.created", 1);
+    assert_eq!(
+        vec![json!(1)],
+        *received.lock().unwrap(),
+        "Nothing should be validated while validation is off"
+    );
+
+    event_emitter.set_validation(Validation::Warn);
+    assert!(event_emitter.try_sync_emit("user.created", 2).is_ok());
+    assert_eq!(
+        json!(2),
+        received.lock().unwrap()[1],
+        "Warn mode should still deliver invalid payloads"
+    );
+
+    event_emitter.set_validation(Validation::Enforce);
+    assert!(matches!(
+        event_emitter.try_sync_emit("user.created", 3),
+        Err(EmitError::SchemaViolation(ref event, _)) if event == "user.created"
+    ));
+    assert!(event_emitter.try_emit("user.created", 3).is_err());
+    assert!(event_emitter.try_sync_emit("user.created", "ada").is_ok());
+    assert!(
+        event_emitter.try_sync_emit("user.deleted", 4).is_ok(),
+        "Events without a schema should be unaffected"
+    );
+
+    event_emitter
+        .register_schema("user.created", json!({ "type": "integer" }))
+        .unwrap();
+    assert!(event_emitter.try_sync_emit("user.created", 5).is_ok());
+    assert!(
+        event_emitter.try_sync_emit("user.created", "bob").is_err(),
+        "A replaced schema should apply from the next emit"
+    );
+
+    assert_eq!(
+        vec![json!(1), json!(2), json!("ada"), json!(4), json!(5)],
+        *received.lock().unwrap()
+    );
+}
+
+#[test]
+fn test_emitter_stats() {
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.on("tick", |_: u32| {});
+    event_emitter.on("tick", |_: u32| {});
+    event_emitter.on("tock", |_: u32| {});
+
+    event_emitter.sync_emit("tick", 1_u32);
+    assert_eq!(
+        0,
+        event_emitter.stats().emits(),
+        "Nothing should be counted until collection is enabled"
+    );
+
+    event_emitter.collect_stats(true);
+    event_emitter.sync_emit("tick", 10_u32);
+    event_emitter.sync_emit("tick", 20_u32);
+    event_emitter.sync_emit("tock", 30_u32);
+    event_emitter.sync_emit("unheard", 40_u32);
+
+    let stats = event_emitter.stats();
+    assert_eq!(4, stats.emits());
+    assert_eq!(5, stats.invocations(), "Every listener call should count");
+    assert_eq!(
+        6,
+        stats.bytes_serialized(),
+        "Unheard emits should not be serialized"
+    );
+    assert_eq!(2, stats.event_emits("tick"));
+    assert_eq!(
+        vec![("tick", 2), ("tock", 1), ("unheard", 1)],
+        stats.busiest_events()
+    );
+
+    event_emitter.collect_stats(false);
+    event_emitter.sync_emit("tock", 50_u32);
+    assert_eq!(
+        4,
+        event_emitter.stats().emits(),
+        "Counts should be kept but not updated while disabled"
+    );
+
+    event_emitter.reset_stats();
+    assert_eq!(0, event_emitter.stats().emits());
+    assert!(event_emitter.stats().busiest_events().is_empty());
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_wait_until_idle() {
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let finished: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_finished = Arc::clone(&finished);
+    event_emitter.on("slow", move |value: u32| {
+        std::thread::sleep(Duration::from_millis(20));
+        cloned_finished.lock().unwrap().push(value);
+    });
+    event_emitter.on("slow", |_: u32| panic!("listener failure"));
+
+    assert!(event_emitter.wait_until_idle(Duration::ZERO));
+    event_emitter.emit("slow", 1_u32);
+    assert!(
+        event_emitter.wait_until_idle(Duration::from_millis(10)),
+        "Completed and panicked callbacks should no longer be in flight"
+    );
+    assert_eq!(vec![1], *finished.lock().unwrap());
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_buffer_ttl() {
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    for event in ["price.update", "app.started", "user.login"] {
+        let cloned_received = Arc::clone(&received);
+        event_emitter.on(event, move |value: String| {
+            cloned_received.lock().unwrap().push(value);
+        });
+    }
+
+    event_emitter.collect_stats(true);
+    event_emitter.set_buffer_ttl("price.update", Some(Duration::from_millis(30)));
+    event_emitter.set_buffer_ttl("user.login", Some(Duration::from_secs(60)));
+    event_emitter.set_buffer_ttl("app.started", Some(Duration::from_millis(30)));
+    event_emitter.set_buffer_ttl("app.started", None);
+
+    event_emitter.hold();
+    event_emitter.sync_emit("price.update", "stale price".to_string());
+    event_emitter.sync_emit("app.started", "started".to_string());
+    std::thread::sleep(Duration::from_millis(50));
+    event_emitter.sync_emit("user.login", "ada".to_string());
+    event_emitter.sync_emit("price.update", "fresh price".to_string());
+
+    assert_eq!(3, event_emitter.release());
+    assert_eq!(
+        vec!["started", "ada", "fresh price"],
+        *received.lock().unwrap(),
+        "Only the expired payload should be dropped, the others keeping their order"
+    );
+    assert_eq!(1, event_emitter.stats().expired());
+}
+
+#[test]
+fn test_emit_borrowed_payload() {
+    #[derive(serde::Serialize)]
+    struct Report {
+        lines: Vec<String>,
+    }
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<Vec<String>>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on("report.daily", move |lines: serde_json::Value| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(serde_json::from_value(lines["lines"].clone()).unwrap());
+    });
+
+    let report = Report {
+        lines: vec!["ok".to_string()],
+    };
+    let owned_name = "report.daily".to_string();
+    let shared_name: Arc<str> = Arc::from("report.daily");
+
+    event_emitter.sync_emit(&owned_name, &report);
+    event_emitter.sync_emit(owned_name.clone(), &report);
+    event_emitter.sync_emit(Arc::clone(&shared_name), &report);
+    event_emitter.sync_emit("report.daily", &report);
+
+    assert_eq!(1, report.lines.len(), "The payload should still be usable");
+    assert_eq!(4, received.lock().unwrap().len());
+}
+
+#[test]
+fn test_suspend_and_resume() {
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    let listener_id = event_emitter.on_limited("tick", Some(2), move |value: u32| {
+        cloned_received.lock().unwrap().push(value);
+    });
+
+    event_emitter.sync_emit("tick", 1_u32);
+    let token = event_emitter.suspend(listener_id).unwrap();
+    assert_eq!("tick", token.event());
+    assert_eq!(Some(1), token.limit());
+    assert!(event_emitter.suspend(listener_id).is_none());
+
+    event_emitter.sync_emit("tick", 2_u32);
+    let resumed_id = event_emitter.resume(token);
+    assert_ne!(
+        listener_id, resumed_id,
+        "A resumed listener should get a fresh ID"
+    );
+    event_emitter.sync_emit("tick", 3_u32);
+    event_emitter.sync_emit("tick", 4_u32);
+
+    assert_eq!(
+        vec![1, 3],
+        *received.lock().unwrap(),
+        "The listener should miss emits while suspended and keep its remaining limit"
+    );
+    assert!(event_emitter.remove_listener(&listener_id).is_none());
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_emit_keyed() {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let processed: Arc<Mutex<HashMap<u32, Vec<u32>>>> = Arc::new(Mutex::new(HashMap::new()));
+    let unkeyed: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+
+    let cloned_processed = Arc::clone(&processed);
+    let keyed_id = event_emitter.on_keyed("order.updated", 3, move |(order, step): (u32, u32)| {
+        std::thread::sleep(Duration::from_millis(u64::from(3 - step)));
+        cloned_processed
+            .lock()
+            .unwrap()
+            .entry(order)
+            .or_default()
+            .push(step);
+    });
+    let cloned_unkeyed = Arc::clone(&unkeyed);
+    event_emitter.on("order.updated", move |_: (u32, u32)| {
+        *cloned_unkeyed.lock().unwrap() += 1;
+    });
+
+    for step in 0..3_u32 {
+        for order in 0..4_u32 {
+            event_emitter.emit_keyed("order.updated", order, (order, step));
+        }
+    }
+    assert!(event_emitter.wait_until_idle(Duration::from_secs(5)));
+
+    let processed = processed.lock().unwrap();
+    for order in 0..4_u32 {
+        assert_eq!(
+            vec![0, 1, 2],
+            processed[&order],
+            "Emits with the same key should be processed in order"
+        );
+    }
+    assert_eq!(
+        12,
+        *unkeyed.lock().unwrap(),
+        "Listeners that don't care about keys should receive every emit"
+    );
+
+    assert_eq!(Some(keyed_id), event_emitter.remove_listener(&keyed_id));
+    assert!(event_emitter.remove_listener(&keyed_id).is_none());
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_attach_receiver() {
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter
+        .lock()
+        .unwrap()
+        .on("job.done", move |job: u32| {
+            cloned_received.lock().unwrap().push(job)
+        });
+
+    let (sender, receiver) = mpsc::channel();
+    let bridge = EventEmitter::attach_receiver(&event_emitter, "job.done", receiver);
+    for job in 0..5_u32 {
+        sender.send(job).unwrap();
+    }
+    drop(sender);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !bridge.is_finished() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert!(bridge.is_finished(), "Disconnecting should end the bridge");
+    assert_eq!(vec![0, 1, 2, 3, 4], *received.lock().unwrap());
+
+    let (_sender, receiver) = mpsc::channel::<u32>();
+    let mut bridge = EventEmitter::attach_receiver(&event_emitter, "job.done", receiver);
+    let started = Instant::now();
+    bridge.stop();
+    bridge.stop();
+    assert!(bridge.is_finished());
+    assert!(
+        started.elapsed() < Duration::from_secs(1),
+        "Stopping should join the bridge promptly"
+    );
+}
+
+#[test]
+#[cfg(feature = "compress")]
+fn test_compression() {
+    use emitter_rs::compress::{self, FrameError};
+    use emitter_rs::event_emitter::Callback;
+    use emitter_rs::event_emitter::Listener;
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<usize>>> = Arc::new(Mutex::new(Vec::new()));
+    let raw_received: Arc<Mutex<Vec<Vec<u8>>>> = Arc::new(Mutex::new(Vec::new()));
+
+    event_emitter.collect_stats(true);
+    event_emitter.set_compression("report.generated", 1024);
+    for event in ["report.generated", "report.uncompressed"] {
+        let cloned_received = Arc::clone(&received);
+        event_emitter.on(event, move |report: String| {
+            cloned_received.lock().unwrap().push(report.len());
+        });
+    }
+    let cloned_raw_received = Arc::clone(&raw_received);
+    let callback: Callback = Arc::new(move |bytes: Vec<u8>| {
+        cloned_raw_received.lock().unwrap().push(bytes);
+    });
+    let listener = Listener::new(event_emitter.next_listener_id(), None, callback);
+    event_emitter.add_listener("report.generated", listener);
+
+    event_emitter.emit("report.generated", "x".repeat(100_000));
+    event_emitter.emit("report.generated", "small".to_string());
+    event_emitter.emit("report.uncompressed", "y".repeat(100_000));
+
+    let mut received = received.lock().unwrap().clone();
+    received.sort_unstable();
+    assert_eq!(vec![5, 100_000, 100_000], received);
+    let raw_received = raw_received.lock().unwrap();
+    assert_eq!(
+        serde_json::to_vec(&"x".repeat(100_000)).unwrap(),
+        raw_received[0],
+        "Raw listeners should receive the decompressed payload"
+    );
+    assert_eq!(b"\"small\"".to_vec(), raw_received[1]);
+    assert!(event_emitter.stats().compression_saved() > 90_000);
+
+    let frame = compress::frame(&raw_received[0]).unwrap();
+    let mut tampered = frame.clone();
+    tampered[10] ^= 0xAA;
+    tampered.truncate(tampered.len() - 5);
+    assert!(compress::unframe(&tampered).is_err());
+    assert_eq!(Err(FrameError::Truncated), compress::unframe(&frame[..3]));
+    let mut forged = frame.clone();
+    forged[2..6].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        compress::unframe(&forged),
+        Err(FrameError::Corrupt(_))
+    ));
+
+    let synced: Arc<Mutex<Vec<Vec<u8>>>> = Arc::new(Mutex::new(Vec::new()));
+    event_emitter.set_compression("report.synced", 1024);
+    for _ in 0..2 {
+        let cloned_synced = Arc::clone(&synced);
+        let callback: Callback = Arc::new(move |bytes: Vec<u8>| {
+            cloned_synced.lock().unwrap().push(bytes);
+        });
+        let listener = Listener::new(event_emitter.next_listener_id(), None, callback);
+        event_emitter.add_listener("report.synced", listener);
+    }
+    let saved = event_emitter.stats().compression_saved();
+    event_emitter.sync_emit("report.synced", "z".repeat(100_000));
+
+    let expected = serde_json::to_vec(&"z".repeat(100_000)).unwrap();
+    assert_eq!(
+        vec![expected.clone(), expected],
+        *synced.lock().unwrap(),
+        "sync_emit listeners should receive the decompressed payload"
+    );
+    assert!(
+        event_emitter.stats().compression_saved() - saved > 90_000,
+        "sync_emit should compress payloads above the threshold"
+    );
+}
+
+#[test]
+fn test_node_aliases() {
+    let mut event_emitter = EventEmitter::new();
+    let order: Arc<Mutex<Vec<&str>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_order = Arc::clone(&order);
+    event_emitter.on("some_event", move |_: ()| {
+        cloned_order.lock().unwrap().push("on")
+    });
+    let cloned_order = Arc::clone(&order);
+    event_emitter.prepend_listener("some_event", move |_: ()| {
+        cloned_order.lock().unwrap().push("prepended")
+    });
+    let cloned_order = Arc::clone(&order);
+    event_emitter.prepend_once_listener("some_event", move |_: ()| {
+        cloned_order.lock().unwrap().push("prepended once")
+    });
+    let off_id = event_emitter.on("other_event", |_: ()| {});
+
+    event_emitter.sync_emit("some_event", ());
+    event_emitter.sync_emit("some_event", ());
+    assert_eq!(
+        vec!["prepended once", "prepended", "on", "prepended", "on"],
+        *order.lock().unwrap()
+    );
+
+    assert_eq!(
+        vec!["other_event", "some_event"],
+        event_emitter.event_names()
+    );
+    assert_eq!(Some(off_id), event_emitter.off(off_id));
+    assert_eq!(vec!["some_event"], event_emitter.event_names());
+
+    assert_eq!(0, event_emitter.get_max_listeners());
+    event_emitter.set_max_listeners(1);
+    assert_eq!(1, event_emitter.get_max_listeners());
+}
+
+#[test]
+fn test_emit_expect() {
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.on("payment.captured", |_: u32| {});
+    event_emitter.on("payment.captured", |_: u32| {});
+
+    assert_eq!(2, event_emitter.emit_expect("payment.captured", 100_u32, 2));
+
+    let shortfall = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        event_emitter.emit_expect("payment.refunded", 100_u32, 1)
+    }));
+    assert_eq!(
+        cfg!(debug_assertions),
+        shortfall.is_err(),
+        "A shortfall should only panic in debug builds"
+    );
+}
+
+#[test]
+fn test_emit_deferred_from_drop() {
+    use emitter_rs::global;
+
+    struct Resource;
+
+    impl Drop for Resource {
+        fn drop(&mut self) {
+            global::emit_deferred("Global Resource Released", "socket");
+        }
+    }
+
+    let log: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_log = Arc::clone(&log);
+    global::on("Global Job Done", move |_: ()| {
+        cloned_log.lock().unwrap().push("first".to_string());
+        drop(Resource);
+    });
+    let cloned_log = Arc::clone(&log);
+    global::on("Global Job Done", move |_: ()| {
+        cloned_log.lock().unwrap().push("second".to_string());
+    });
+    let cloned_log = Arc::clone(&log);
+    global::on("Global Resource Released", move |name: String| {
+        cloned_log.lock().unwrap().push(name);
+    });
+
+    global::sync_emit("Global Job Done", ());
+    assert_eq!(
+        vec!["first", "second", "socket"],
+        *log.lock().unwrap(),
+        "The deferred emit should run once, after every listener of the triggering emit"
+    );
+
+    log.lock().unwrap().clear();
+    assert!(global::emit_deferred("Global Resource Released", "file").is_some());
+    assert_eq!(
+        vec!["file"],
+        *log.lock().unwrap(),
+        "Outside of a dispatch, emit_deferred should emit right away"
+    );
+}
+
+#[test]
+fn test_on_emitted_traces_deferred_emits() {
+    use emitter_rs::global;
+
+    type Trace = Vec<(String, u64, Option<u64>)>;
+
+    let trace: Arc<Mutex<Trace>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_trace = Arc::clone(&trace);
+    let hook_id = global::lock().on_emitted(move |event, meta| {
+        if event.starts_with("Global Trace") {
+            cloned_trace
+                .lock()
+                .unwrap()
+                .push((event.to_string(), meta.seq, meta.parent));
+        }
+    });
+    global::on("Global Trace Job Done", |_: ()| {
+        global::emit_deferred("Global Trace Report Sent", ());
+    });
+
+    let seq = global::sync_emit("Global Trace Job Done", ());
+    let later = global::sync_emit("Global Trace Idle", ());
+    let trace = std::mem::take(&mut *trace.lock().unwrap());
+    assert_eq!(3, trace.len());
+    assert_eq!(("Global Trace Job Done".to_string(), seq, None), trace[0]);
+    assert_eq!(
+        ("Global Trace Report Sent", Some(seq)),
+        (trace[1].0.as_str(), trace[1].2),
+        "The deferred emit should name the emit it was deferred from as its parent"
+    );
+    assert_eq!(("Global Trace Idle".to_string(), later, None), trace[2]);
+    assert!(global::lock().remove_hook(&hook_id));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_on_with_retry() {
+    use emitter_rs::retry::{Backoff, RetryPolicy, RetryableError};
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        backoff: Backoff::Fixed(Duration::from_millis(10)),
+    };
+    let failures: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_failures = Arc::clone(&failures);
+    event_emitter.on_listener_failure(move |failure| {
+        cloned_failures.lock().unwrap().push(failure.attempts);
+    });
+
+    let attempts: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_attempts = Arc::clone(&attempts);
+    let flaky_id = event_emitter.on_with_retry("Order Placed", policy, move |_: u32| {
+        let mut attempts = cloned_attempts.lock().unwrap();
+        *attempts += 1;
+        if *attempts < 3 {
+            return Err(RetryableError::new("connection reset"));
+        }
+        Ok(())
+    });
+    event_emitter.reset_limit(flaky_id, Some(2));
+
+    event_emitter.emit("Order Placed", 7_u32);
+    assert!(event_emitter.wait_until_idle(Duration::from_secs(5)));
+    assert_eq!(
+        3,
+        *attempts.lock().unwrap(),
+        "Should succeed on the third attempt"
+    );
+    assert_eq!(
+        Some(Some(1)),
+        event_emitter.remaining_limit(flaky_id),
+        "Retries should not consume the limit"
+    );
+    assert!(failures.lock().unwrap().is_empty());
+
+    let broken_attempts: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_broken_attempts = Arc::clone(&broken_attempts);
+    event_emitter.on_with_retry("Invoice Sent", policy, move |_: u32| {
+        *cloned_broken_attempts.lock().unwrap() += 1;
+        Err(RetryableError::new("service unavailable"))
+    });
+
+    event_emitter.emit("Invoice Sent", 7_u32);
+    assert!(event_emitter.wait_until_idle(Duration::from_secs(5)));
+    assert_eq!(
+        3,
+        *broken_attempts.lock().unwrap(),
+        "Should exhaust the policy"
+    );
+    assert_eq!(
+        vec![3],
+        *failures.lock().unwrap(),
+        "The exhausted delivery should be reported once with its attempts"
+    );
+
+    let cancelled_attempts: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_cancelled_attempts = Arc::clone(&cancelled_attempts);
+    let slow_policy = RetryPolicy {
+        max_attempts: 3,
+        backoff: Backoff::Fixed(Duration::from_millis(100)),
+    };
+    let cancelled_id = event_emitter.on_with_retry("Refund Issued", slow_policy, move |_: u32| {
+        *cloned_cancelled_attempts.lock().unwrap() += 1;
+        Err(RetryableError::new("service unavailable"))
+    });
+
+    event_emitter.emit("Refund Issued", 7_u32);
+    event_emitter.remove_listener(&cancelled_id);
+    assert!(event_emitter.wait_until_idle(Duration::from_secs(5)));
+    assert_eq!(
+        1,
+        *cancelled_attempts.lock().unwrap(),
+        "Removing the listener should cancel its pending retries"
+    );
+    assert_eq!(vec![3], *failures.lock().unwrap());
+}
+
+#[test]
+fn test_emit_raw_from() {
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on("User Renamed", move |name: String| {
+        cloned_received.lock().unwrap().push(name);
+    });
+
+    event_emitter.sync_emit("User Renamed", "ada");
+    let seq = event_emitter.emit_raw_from("User Renamed", "grace", |name| {
+        serde_json::to_vec_pretty(&name.to_uppercase()).unwrap()
+    });
+
+    assert_eq!(2, seq, "Should be numbered like any emit");
+    assert_eq!(
+        vec!["ada", "GRACE"],
+        *received.lock().unwrap(),
+        "Listeners should receive the bytes of the custom encoder"
+    );
+}
+
+#[test]
+fn test_last_listener_removal_prunes_event() {
+    let mut event_emitter = EventEmitter::new();
+    let first_id = event_emitter.on("Order Placed", |_: u32| {});
+    let second_id = event_emitter.on("Order Placed", |_: u32| {});
+    event_emitter.once("Order Shipped", |_: u32| {});
+    event_emitter.on("User Created", |_: u32| {});
+
+    event_emitter.remove_listener(&first_id);
+    assert!(event_emitter.listeners.contains_key("Order Placed"));
+    event_emitter.remove_listener(&second_id);
+    event_emitter.sync_emit("Order Shipped", 1_u32);
+    event_emitter.sync_emit("Order Shipped", 2_u32);
+
+    assert_eq!(vec!["User Created"], event_emitter.event_names());
+    assert_eq!(0, event_emitter.listener_count("Order Placed"));
+    assert!(
+        !event_emitter.listeners.contains_key("Order Placed")
+            && !event_emitter.listeners.contains_key("Order Shipped"),
+        "Events should be dropped with their last listener"
+    );
+}
+
+#[test]
+fn test_emit_history() {
+    let mut event_emitter = EventEmitter::new();
+    let listener_id = event_emitter.on("Order State", |_: String| {});
+    event_emitter.enable_history("Order State", 2);
+
+    event_emitter.emit("Order State", "placed");
+    event_emitter.sync_emit("Order State", "paid");
+    event_emitter.remove_listener(&listener_id);
+    event_emitter.emit("Order State", "shipped");
+    event_emitter.emit("Other", "ignored");
+
+    let history = event_emitter.history("Order State");
+    assert_eq!(
+        vec![serde_json::json!("paid"), serde_json::json!("shipped")],
+        history
+            .iter()
+            .map(|entry| entry.value().unwrap())
+            .collect::<Vec<_>>(),
+        "Only the latest emits should be kept, oldest first, with or without listeners"
+    );
+    assert_eq!(
+        vec![2, 3],
+        history.iter().map(|entry| entry.seq()).collect::<Vec<_>>()
+    );
+    assert_eq!(b"\"shipped\"", history[1].bytes());
+    assert!(history[0].at() <= history[1].at());
+    assert!(event_emitter.history("Other").is_empty());
+
+    event_emitter.disable_history("Order State");
+    assert!(event_emitter.history("Order State").is_empty());
+    event_emitter.emit("Order State", "delivered");
+    assert!(event_emitter.history("Order State").is_empty());
+}
+
+#[test]
+fn test_typed_subscription() {
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    let subscription = event_emitter.subscribe("Score", move |score: u32| {
+        cloned_received.lock().unwrap().push(score);
+    });
+    event_emitter.on("Score", |_: u32| {
+        panic!("only the subscribed listener should run")
+    });
+
+    assert!(event_emitter.emit_to_subscription(&subscription, 7));
+    assert_eq!(vec![7], *received.lock().unwrap());
+
+    event_emitter.remove_listener(&subscription.id());
+    assert!(
+        !event_emitter.emit_to_subscription(&subscription, 8),
+        "A removed subscription should not be invoked"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emitter_sender() {
+    use emitter_rs::error::EmitError;
+    use std::thread;
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on("Job Done", move |job: u32| {
+        cloned_received.lock().unwrap().push(job);
+    });
+
+    let sender = event_emitter.sender();
+    let cloned_sender = sender.clone();
+    thread::spawn(move || {
+        for job in 0..3_u32 {
+            cloned_sender.send("Job Done", job).unwrap();
+        }
+    })
+    .join()
+    .unwrap();
+
+    assert!(
+        received.lock().unwrap().is_empty(),
+        "Queued emits should wait for process_pending"
+    );
+    assert_eq!(3, event_emitter.process_pending());
+    assert_eq!(
+        vec![0, 1, 2],
+        *received.lock().unwrap(),
+        "Emits should keep their order"
+    );
+    assert_eq!(0, event_emitter.process_pending());
+
+    drop(event_emitter);
+    assert_eq!(Err(EmitError::Disconnected), sender.send("Job Done", 3_u32));
+}
+
+#[test]
+fn test_tenant_isolation() {
+    use emitter_rs::tenant::TenantError;
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let plugin1 = EventEmitter::tenant(&event_emitter, "plugin1").unwrap();
+    let plugin2 = EventEmitter::tenant(&event_emitter, "plugin2").unwrap();
+
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    let id1 = plugin1
+        .on("ready", move |value: String| {
+            cloned_received.lock().unwrap().push(value);
+        })
+        .unwrap();
+    let id2 = plugin2.on("ready", |_: String| {}).unwrap();
+
+    plugin2.emit("ready", "plugin2".to_string()).unwrap();
+    plugin1.emit("ready", "plugin1".to_string()).unwrap();
+    event_emitter
+        .lock()
+        .unwrap()
+        .emit("plugin1.ready", "host".to_string());
+    assert_eq!(
+        vec!["plugin1".to_string(), "host".to_string()],
+        *received.lock().unwrap(),
+        "Tenants should not receive each other's events"
+    );
+
+    assert!(
+        plugin1.emit("plugin2.ready", ()).is_ok(),
+        "An event named after another tenant should stay under the tenant's own prefix"
+    );
+    assert_eq!(
+        Err(TenantError::InvalidEvent("..ready".to_string())),
+        plugin1.emit("..ready", ())
+    );
+    assert_eq!(
+        Err(TenantError::InvalidEvent(".ready".to_string())),
+        plugin1.emit(".ready", ())
+    );
+    for prefix in ["", "plugin..3", ".plugin3", "plugin3."] {
+        assert_eq!(
+            Err(TenantError::InvalidPrefix(prefix.to_string())),
+            EventEmitter::tenant(&event_emitter, prefix).map(|_| ())
+        );
+    }
+    assert_eq!(
+        "plugin1",
+        EventEmitter::tenant(&event_emitter, "plugin1")
+            .unwrap()
+            .prefix(),
+        "Views of the same prefix should be allowed"
+    );
+    assert_eq!(
+        None,
+        plugin1.remove_listener(id2),
+        "A tenant should not remove another tenant's listener"
+    );
+
+    assert_eq!(1, event_emitter.lock().unwrap().revoke_tenant("plugin1"));
+    assert_eq!(None, plugin1.remove_listener(id1));
+    assert_eq!(Err(TenantError::Revoked), plugin1.on("ready", |_: ()| {}));
+    assert_eq!(Some(id2), plugin2.remove_listener(id2));
+
+    event_emitter.lock().unwrap().shutdown();
+    assert!(matches!(
+        plugin2.on("ready", |_: ()| {}),
+        Err(TenantError::Refused(_))
+    ));
+}
+
+#[test]
+fn test_tenant_nested_prefixes() {
+    use emitter_rs::tenant::TenantError;
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let outer = EventEmitter::tenant(&event_emitter, "a").unwrap();
+    assert_eq!(
+        Err(TenantError::OverlappingPrefix("a.b".to_string())),
+        EventEmitter::tenant(&event_emitter, "a.b").map(|_| ())
+    );
+    assert!(EventEmitter::tenant(&event_emitter, "ab").is_ok());
+
+    let nested_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let nested = EventEmitter::tenant(&nested_emitter, "a.b").unwrap();
+    assert_eq!(
+        Err(TenantError::OverlappingPrefix("a".to_string())),
+        EventEmitter::tenant(&nested_emitter, "a").map(|_| ())
+    );
+
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    let id = outer
+        .on("b.x", move |value: u32| {
+            cloned_received.lock().unwrap().push(value);
+        })
+        .unwrap();
+    outer.emit("b.x", 1_u32).unwrap();
+    assert_eq!(
+        vec![1],
+        *received.lock().unwrap(),
+        "Without a nested tenant, a.b.x is the outer tenant's own event"
+    );
+    assert_eq!(Some(id), outer.remove_listener(id));
+
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    nested
+        .on("x", move |value: u32| {
+            cloned_received.lock().unwrap().push(value);
+        })
+        .unwrap();
+    nested.emit("x", 2_u32).unwrap();
+    assert_eq!(vec![2], *received.lock().unwrap());
+}
+
+#[test]
+fn test_once_removal_with_reentrant_emits() {
+    use emitter_rs::global;
+
+    let calls: Arc<Mutex<Vec<&str>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_calls = Arc::clone(&calls);
+    global::lock().once("Global Reentrant Outer", move |_: ()| {
+        cloned_calls.lock().unwrap().push("outer once");
+        global::emit_deferred("Global Reentrant Outer", ());
+        global::emit_deferred("Global Reentrant Inner", ());
+        global::emit_deferred("Global Reentrant Inner", ());
+    });
+    let cloned_calls = Arc::clone(&calls);
+    global::on("Global Reentrant Outer", move |_: ()| {
+        cloned_calls.lock().unwrap().push("outer");
+    });
+    let cloned_calls = Arc::clone(&calls);
+    global::lock().once("Global Reentrant Inner", move |_: ()| {
+        cloned_calls.lock().unwrap().push("inner once");
+    });
+    let cloned_calls = Arc::clone(&calls);
+    global::on("Global Reentrant Inner", move |_: ()| {
+        cloned_calls.lock().unwrap().push("inner");
+    });
+
+    global::sync_emit("Global Reentrant Outer", ());
+    assert_eq!(
+        vec![
+            "outer once",
+            "outer",
+            "outer",
+            "inner once",
+            "inner",
+            "inner"
+        ],
+        *calls.lock().unwrap(),
+        "Once listeners should run once, and the other listeners should stay"
+    );
+    assert_eq!(1, global::lock().listener_count("Global Reentrant Outer"));
+    assert_eq!(1, global::lock().listener_count("Global Reentrant Inner"));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_max_event_names() {
+    use emitter_rs::error::EmitError;
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.track_last_emit(true);
+    event_emitter.set_max_event_names(Some(3));
+
+    let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_counter = Arc::clone(&counter);
+    event_emitter.on("user.updated", move |_: u32| {
+        *cloned_counter.lock().unwrap() += 1;
+    });
+    let listener_id = event_emitter.on("user.12345.updated", |_: ()| {});
+    event_emitter.emit("user.created", ());
+    assert_eq!(3, event_emitter.event_name_count());
+
+    assert_eq!(
+        Err(EmitError::TooManyEventNames {
+            current: 3,
+            limit: 3
+        }),
+        event_emitter.try_emit("user.67890.updated", ()),
+        "The fourth distinct name should be rejected"
+    );
+    assert!(event_emitter
+        .try_on("user.67890.updated", |_: ()| {})
+        .is_err());
+    assert_eq!(0, event_emitter.listener_count("user.67890.updated"));
+    assert!(event_emitter.last_emit("user.67890.updated").is_none());
+
+    assert!(event_emitter.try_emit("user.updated", 1_u32).is_ok());
+    assert_eq!(
+        1,
+        *counter.lock().unwrap(),
+        "Existing names should keep working"
+    );
+
+    event_emitter.emit("user.12345.updated", ());
+    event_emitter.remove_listener(&listener_id);
+    event_emitter.enable_history("user.created", 5);
+    assert_eq!(
+        0,
+        event_emitter.prune_unused_event_names(Duration::from_secs(60)),
+        "Recently emitted names should not be pruned"
+    );
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(
+        1,
+        event_emitter.prune_unused_event_names(Duration::from_millis(1)),
+        "Only the name without listeners or history should be pruned"
+    );
+    assert!(event_emitter.last_emit("user.created").is_some());
+    assert_eq!(2, event_emitter.event_name_count());
+    assert!(event_emitter.try_emit("user.67890.updated", ()).is_ok());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_dead_letter() {
+    use emitter_rs::deadletter::DeadLetter;
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.enable_dead_letter();
+    let letters: Arc<Mutex<Vec<DeadLetter>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_letters = Arc::clone(&letters);
+    event_emitter.on("deadletter", move |letter: DeadLetter| {
+        cloned_letters.lock().unwrap().push(letter);
+    });
+    let parents: Arc<Mutex<Vec<Option<u64>>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_parents = Arc::clone(&parents);
+    event_emitter.on_emitted(move |event, meta| {
+        if event == "deadletter" {
+            cloned_parents.lock().unwrap().push(meta.parent);
+        }
+    });
+
+    let panicking_id = event_emitter.on("Order Placed", |order_id: u32| {
+        panic!("order {} is invalid", order_id);
+    });
+    let failing_id = event_emitter.on_result("Order Placed", |order_id: u32| {
+        Err(format!("order {} is out of stock", order_id))
+    });
+    let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_counter = Arc::clone(&counter);
+    event_emitter.on("Order Placed", move |_: u32| {
+        *cloned_counter.lock().unwrap() += 1;
+    });
+
+    event_emitter.emit("Order Placed", 7_u32);
+    assert_eq!(
+        1,
+        *counter.lock().unwrap(),
+        "Other listeners should still run"
+    );
+
+    let mut emitted = std::mem::take(&mut *letters.lock().unwrap());
+    emitted.sort_by_key(|letter| letter.error.clone());
+    assert_eq!(
+        vec![
+            DeadLetter {
+                event: "Order Placed".to_string(),
+                listener_id: panicking_id,
+                seq: 1,
+                payload: b"7".to_vec(),
+                error: "order 7 is invalid".to_string(),
+            },
+            DeadLetter {
+                event: "Order Placed".to_string(),
+                listener_id: failing_id,
+                seq: 1,
+                payload: b"7".to_vec(),
+                error: "order 7 is out of stock".to_string(),
+            },
+        ],
+        emitted,
+        "Both failures should be emitted on deadletter"
+    );
+    assert_eq!(
+        vec![Some(1), Some(1)],
+        std::mem::take(&mut *parents.lock().unwrap()),
+        "The dead letters should be traced back to the failed emit"
+    );
+
+    event_emitter.sync_emit("Order Placed", 8_u32);
+    event_emitter.emit_each("Order Placed", vec![9_u32, 10]);
+    event_emitter.emit_sequential("Order Placed", 11_u32);
+    assert_eq!(5, *counter.lock().unwrap());
+
+    let emitted = std::mem::take(&mut *letters.lock().unwrap());
+    for (listener_id, kind) in [(panicking_id, "invalid"), (failing_id, "out of stock")] {
+        let mut errors: Vec<String> = emitted
+            .iter()
+            .filter(|letter| letter.listener_id == listener_id)
+            .map(|letter| letter.error.clone())
+            .collect();
+        errors.sort();
+        let mut expected: Vec<String> = (8..=11)
+            .map(|order_id| format!("order {} is {}", order_id, kind))
+            .collect();
+        expected.sort();
+        assert_eq!(
+            expected, errors,
+            "Failures should be emitted on deadletter by sync_emit, emit_each and emit_sequential too"
+        );
+    }
+}
+
+#[test]
+fn test_emit_guards() {
+    use emitter_rs::error::EmitError;
+    use emitter_rs::guard::GuardError;
+
+    let mut event_emitter = EventEmitter::new();
+    let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_counter = Arc::clone(&counter);
+    event_emitter.on_limited("Order Placed", Some(1), move |amount: u32| {
+        *cloned_counter.lock().unwrap() += amount;
+    });
+
+    let guard_calls: Arc<Mutex<Vec<&str>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_guard_calls = Arc::clone(&guard_calls);
+    event_emitter.add_global_guard(move |_, _| {
+        cloned_guard_calls.lock().unwrap().push("global");
+        Ok(())
+    });
+    let cloned_guard_calls = Arc::clone(&guard_calls);
+    let limit_guard = event_emitter.add_guard("Order Placed", move |_, payload| {
+        cloned_guard_calls.lock().unwrap().push("limit");
+        let amount: u32 = serde_json::from_slice(payload).map_err(GuardError::new)?;
+        if amount > 100 {
+            return Err(GuardError::new("amount above 100"));
+        }
+        Ok(())
+    });
+    let cloned_guard_calls = Arc::clone(&guard_calls);
+    event_emitter.add_guard("Order Placed", move |_, _| {
+        cloned_guard_calls.lock().unwrap().push("last");
+        Ok(())
+    });
+
+    assert_eq!(
+        Err(EmitError::Rejected {
+            guard_index: 1,
+            source: GuardError::new("amount above 100")
+        }),
+        event_emitter.try_sync_emit("Order Placed", 500_u32)
+    );
+    assert_eq!(
+        vec!["global", "limit"],
+        *guard_calls.lock().unwrap(),
+        "The first rejecting guard should stop the later ones"
+    );
+    event_emitter.emit("Order Placed", 500_u32);
+    assert_eq!(
+        0,
+        *counter.lock().unwrap(),
+        "Rejected emits should not be delivered"
+    );
+
+    guard_calls.lock().unwrap().clear();
+    assert!(event_emitter.try_emit("Order Placed", 50_u32).is_ok());
+    assert_eq!(
+        vec!["global", "limit", "last"],
+        *guard_calls.lock().unwrap()
+    );
+    assert_eq!(
+        50,
+        *counter.lock().unwrap(),
+        "Rejected emits should not consume the listener limit"
+    );
+
+    assert!(event_emitter.remove_guard(&limit_guard));
+    assert!(!event_emitter.remove_guard(&limit_guard));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emit_any() {
+    use emitter_rs::hooks::DecodeError;
+    use futures::channel::oneshot;
+
+    let mut event_emitter = EventEmitter::new();
+    let decode_errors: Arc<Mutex<Vec<DecodeError>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_decode_errors = Arc::clone(&decode_errors);
+    event_emitter.on_decode_error(move |error| {
+        cloned_decode_errors.lock().unwrap().push(error.clone());
+    });
+
+    let mismatched_id = event_emitter.on_any("Reply Channel", |_: String| {
+        panic!("A String listener should not receive a sender");
+    });
+    let served: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_served = Arc::clone(&served);
+    event_emitter.once_any("Reply Channel", move |sender: oneshot::Sender<u32>| {
+        *cloned_served.lock().unwrap() += 1;
+        sender.send(42).unwrap();
+    });
+    let cloned_served = Arc::clone(&served);
+    event_emitter.on_any("Reply Channel", move |sender: oneshot::Sender<u32>| {
+        *cloned_served.lock().unwrap() += 10;
+        sender.send(7).unwrap();
+    });
+    event_emitter.on("Reply Channel", |_: serde_json::Value| {
+        panic!("A serde listener should not receive an Any emit");
+    });
+
+    let (sender, mut receiver) = oneshot::channel::<u32>();
+    assert!(event_emitter.emit_any("Reply Channel", Box::new(sender)));
+    assert_eq!(Ok(Some(42)), receiver.try_recv().map_err(drop));
+    assert_eq!(
+        1,
+        *served.lock().unwrap(),
+        "Only one listener should consume the value"
+    );
+    assert_eq!(1, decode_errors.lock().unwrap().len());
+    assert_eq!(mismatched_id, decode_errors.lock().unwrap()[0].listener_id);
+
+    let (sender, mut receiver) = oneshot::channel::<u32>();
+    assert!(event_emitter.emit_any("Reply Channel", Box::new(sender)));
+    assert_eq!(Ok(Some(7)), receiver.try_recv().map_err(drop));
+    assert_eq!(
+        11,
+        *served.lock().unwrap(),
+        "The once listener should not receive a second value"
+    );
+
+    assert_eq!(
+        Some(mismatched_id),
+        event_emitter.remove_listener(&mismatched_id)
+    );
+    assert!(!event_emitter.emit_any("Reply Channel", Box::new(1_u8)));
+}
+
+#[test]
+fn test_emit_iter() {
+    let mut event_emitter = EventEmitter::new();
+    let invoked: Arc<Mutex<Vec<&str>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_invoked = Arc::clone(&invoked);
+    event_emitter.on_responder("Find Handler", move |extension: String| {
+        cloned_invoked.lock().unwrap().push("image");
+        (extension == "png").then(|| "image viewer".to_string())
+    });
+    let cloned_invoked = Arc::clone(&invoked);
+    event_emitter.on_responder("Find Handler", move |_: String| {
+        cloned_invoked.lock().unwrap().push("text");
+        Some("text editor".to_string())
+    });
+    let cloned_invoked = Arc::clone(&invoked);
+    event_emitter.on_responder("Find Handler", move |_: String| {
+        cloned_invoked.lock().unwrap().push("hex");
+        Some("hex viewer".to_string())
+    });
+
+    let mut answers =
+        event_emitter.emit_iter::<_, Option<String>>("Find Handler", "txt".to_string());
+    assert!(
+        invoked.lock().unwrap().is_empty(),
+        "No responder should run before the iterator is advanced"
+    );
+    assert_eq!(
+        Some("text editor".to_string()),
+        answers.by_ref().flatten().next()
+    );
+    drop(answers);
+    assert_eq!(
+        vec!["image", "text"],
+        *invoked.lock().unwrap(),
+        "Responders after the first answer should not run"
+    );
+
+    let handlers: Vec<Option<String>> = event_emitter
+        .emit_iter("Find Handler", "png".to_string())
+        .collect();
+    assert_eq!(
+        vec![
+            Some("image viewer".to_string()),
+            Some("text editor".to_string()),
+            Some("hex viewer".to_string())
+        ],
+        handlers
+    );
+}
+
+#[test]
+fn test_serialize_failure_policy() {
+    use emitter_rs::error::{EmitError, SerializeFailure};
+    use std::collections::BTreeMap;
+
+    let mut event_emitter = EventEmitter::new();
+    let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_counter = Arc::clone(&counter);
+    event_emitter.on("Grid Updated", move |_: serde_json::Value| {
+        *cloned_counter.lock().unwrap() += 1;
+    });
+
+    // JSON object keys must be strings.
+    let grid = BTreeMap::from([((0_u32, 0_u32), 1_u32)]);
+    assert_eq!(SerializeFailure::Abort, event_emitter.serialize_failure());
+    assert!(matches!(
+        event_emitter.try_sync_emit("Grid Updated", grid.clone()),
+        Err(EmitError::Serialize(_))
+    ));
+
+    event_emitter.set_serialize_failure(SerializeFailure::Skip);
+    assert!(event_emitter.try_emit("Grid Updated", grid.clone()).is_ok());
+    assert!(event_emitter
+        .try_sync_emit("Grid Updated", grid.clone())
+        .is_ok());
+    assert!(event_emitter.strict_emit("Grid Updated", grid).is_ok());
+    assert_eq!(
+        0,
+        *counter.lock().unwrap(),
+        "Skipped emits should deliver nothing"
+    );
+
+    assert!(event_emitter.try_emit("Grid Updated", 1_u32).is_ok());
+    assert_eq!(1, *counter.lock().unwrap());
+}
+
+#[test]
+fn test_emit_lazy() {
+    let mut event_emitter = EventEmitter::new();
+    assert_eq!(
+        0,
+        event_emitter.emit_lazy("Diagnostics", || -> u32 {
+            panic!("The value should not be built without listeners")
+        })
+    );
+
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    for _ in 0..3 {
+        let cloned_received = Arc::clone(&received);
+        event_emitter.on("Diagnostics", move |value: u32| {
+            cloned_received.lock().unwrap().push(value);
+        });
+    }
+    let spent_id = event_emitter.on("Spent", |_: u32| {});
+    event_emitter.reset_limit(spent_id, Some(0));
+    assert_eq!(1, event_emitter.listener_count("Spent"));
+    assert_eq!(
+        0,
+        event_emitter.sync_emit_lazy("Spent", || -> u32 {
+            panic!("Exhausted listeners should not count")
+        })
+    );
+
+    let built: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_built = Arc::clone(&built);
+    let invoked = event_emitter.emit_lazy("Diagnostics", move || {
+        *cloned_built.lock().unwrap() += 1;
+        7_u32
+    });
+    assert_eq!(3, invoked);
+    let cloned_built = Arc::clone(&built);
+    let invoked = event_emitter.sync_emit_lazy("Diagnostics", move || {
+        *cloned_built.lock().unwrap() += 1;
+        8_u32
+    });
+    assert_eq!(3, invoked);
+    assert_eq!(
+        2,
+        *built.lock().unwrap(),
+        "The value should be built once per emit"
+    );
+    assert_eq!(vec![7, 7, 7, 8, 8, 8], *received.lock().unwrap());
+}
+
+#[test]
+fn test_state_machine() {
+    use emitter_rs::event_emitter::{Execution, ListenerOptions};
+    use emitter_rs::state::{RejectedTransition, StateMachine, TransitionInfo};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    enum Phase {
+        Idle,
+        Running,
+        Done,
+    }
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let mut machine = StateMachine::new(&event_emitter, Phase::Idle);
+    machine.allow(Phase::Idle, "job.start", Phase::Running);
+    machine.allow(Phase::Running, "job.finish", Phase::Done);
+
+    let seen: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let rejected: Arc<Mutex<Vec<RejectedTransition<Phase>>>> = Arc::new(Mutex::new(Vec::new()));
+    {
+        let mut emitter = event_emitter.lock().unwrap();
+        for event in [
+            "state.exited.Idle",
+            "state.entered.Running",
+            "state.exited.Running",
+            "state.entered.Done",
+        ] {
+            let cloned_seen = Arc::clone(&seen);
+            let name = event.to_string();
+            emitter.on_with_options(
+                event,
+                ListenerOptions {
+                    execution: Execution::Inline,
+                    ..ListenerOptions::default()
+                },
+                move |info: TransitionInfo<Phase>| {
+                    cloned_seen.lock().unwrap().push(format!(
+                        "{} {:?}->{:?} by {}",
+                        name, info.from, info.to, info.event
+                    ));
+                },
+            );
+        }
+        let cloned_rejected = Arc::clone(&rejected);
+        emitter.on_with_options(
+            "state.rejected",
+            ListenerOptions {
+                execution: Execution::Inline,
+                ..ListenerOptions::default()
+            },
+            move |transition: RejectedTransition<Phase>| {
+                cloned_rejected.lock().unwrap().push(transition);
+            },
+        );
+    }
+
+    event_emitter.lock().unwrap().sync_emit("job.finish", ());
+    assert_eq!(
+        Phase::Idle,
+        machine.current(),
+        "Illegal transitions should leave the state unchanged"
+    );
+    assert_eq!(
+        vec![RejectedTransition {
+            state: Phase::Idle,
+            event: "job.finish".to_string()
+        }],
+        *rejected.lock().unwrap()
+    );
+
+    event_emitter.lock().unwrap().sync_emit("job.start", ());
+    event_emitter.lock().unwrap().sync_emit("job.finish", ());
+    assert_eq!(Phase::Done, machine.current());
+    assert_eq!(
+        vec![
+            "state.exited.Idle Idle->Running by job.start".to_string(),
+            "state.entered.Running Idle->Running by job.start".to_string(),
+            "state.exited.Running Running->Done by job.finish".to_string(),
+            "state.entered.Done Running->Done by job.finish".to_string(),
+        ],
+        *seen.lock().unwrap(),
+        "Transitions should exit the old state before entering the new one"
+    );
+
+    drop(machine);
+    assert_eq!(0, event_emitter.lock().unwrap().listener_count("job.start"));
+}
+
+#[test]
+fn test_on_or_default() {
+    let mut event_emitter = EventEmitter::new();
+    let decode_errors: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_decode_errors = Arc::clone(&decode_errors);
+    event_emitter.on_decode_error(move |error| {
+        cloned_decode_errors
+            .lock()
+            .unwrap()
+            .push(error.event.clone());
+    });
+
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_or_default("Count", move |count: u32| {
+        cloned_received.lock().unwrap().push(count);
+    });
+
+    event_emitter.sync_emit("Count", 5_u32);
+    event_emitter.sync_emit("Count", "five");
+    assert_eq!(
+        vec![5, 0],
+        *received.lock().unwrap(),
+        "Undecodable values should be replaced by the default"
+    );
+    assert_eq!(vec!["Count".to_string()], *decode_errors.lock().unwrap());
+}
+
+#[test]
+fn test_matching_events() {
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.on("user.created", |_: String| {});
+    event_emitter.once("user.deleted", |_: String| {});
+    event_emitter.sync_emit("user.deleted", "ada".to_string());
+
+    assert_eq!(
+        vec!["user.created".to_string()],
+        event_emitter.matching_events("user.created")
+    );
+    assert!(
+        event_emitter.matching_events("user.deleted").is_empty(),
+        "Exhausted listeners should not match"
+    );
+    assert!(
+        event_emitter.matching_events("user").is_empty(),
+        "Events should only match their exact name"
+    );
+}
+
+#[test]
+fn test_sampling() {
+    use emitter_rs::sampling::Sampling;
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.collect_stats(true);
+    let received: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_with_options(
+        "trace.span",
+        emitter_rs::event_emitter::ListenerOptions {
+            execution: emitter_rs::event_emitter::Execution::Inline,
+            ..Default::default()
+        },
+        move |value: u32| {
+            cloned_received.lock().unwrap().push(value);
+        },
+    );
+
+    event_emitter.set_sampling("trace.span", Sampling::OneIn(10));
+    for value in 1..=30_u32 {
+        event_emitter.emit("trace.span", value);
+    }
+    assert_eq!(
+        vec![10, 20, 30],
+        *received.lock().unwrap(),
+        "OneIn(10) should deliver exactly every 10th emit"
+    );
+    assert_eq!(27, event_emitter.stats().sampled_out());
+
+    received.lock().unwrap().clear();
+    event_emitter.set_sampling_seed(7);
+    event_emitter.set_sampling("trace.span", Sampling::Ratio(0.0));
+    for value in 0..100_u32 {
+        event_emitter.sync_emit("trace.span", value);
+    }
+    assert!(received.lock().unwrap().is_empty());
+    event_emitter.set_sampling("trace.span", Sampling::Ratio(1.0));
+    for value in 0..100_u32 {
+        event_emitter.sync_emit("trace.span", value);
+    }
+    assert_eq!(100, received.lock().unwrap().len());
+    assert_eq!(127, event_emitter.stats().sampled_out());
+
+    received.lock().unwrap().clear();
+    event_emitter.set_sampling("trace.span", Sampling::Ratio(0.5));
+    for value in 0..100_u32 {
+        event_emitter.sync_emit("trace.span", value);
+    }
+    let first_draw = received.lock().unwrap().clone();
+    received.lock().unwrap().clear();
+    event_emitter.set_sampling("trace.span", Sampling::Ratio(0.5));
+    for value in 0..100_u32 {
+        event_emitter.sync_emit("trace.span", value);
+    }
+    assert_eq!(
+        first_draw,
+        *received.lock().unwrap(),
+        "The same seed should sample the same emits"
+    );
+    assert_eq!(
+        127 + 2 * (100 - first_draw.len() as u64),
+        event_emitter.stats().sampled_out()
+    );
+
+    received.lock().unwrap().clear();
+    let sampled_out = event_emitter.stats().sampled_out();
+    event_emitter.set_sampling("trace.span", Sampling::PerSecond(5));
+    for value in 0..50_u32 {
+        event_emitter.sync_emit("trace.span", value);
+    }
+    assert_eq!(
+        vec![0, 1, 2, 3, 4],
+        *received.lock().unwrap(),
+        "PerSecond should cap a burst"
+    );
+    assert_eq!(sampled_out + 45, event_emitter.stats().sampled_out());
+
+    received.lock().unwrap().clear();
+    event_emitter.clear_sampling("trace.span");
+    for value in 0..10_u32 {
+        event_emitter.sync_emit("trace.span", value);
+    }
+    assert_eq!(10, received.lock().unwrap().len());
+    assert_eq!(sampled_out + 45, event_emitter.stats().sampled_out());
+}
+
+#[test]
+fn test_bind_method() {
+    struct Service {
+        received: Mutex<Vec<String>>,
+    }
+
+    impl Service {
+        fn created(&self, name: String) {
+            self.received
+                .lock()
+                .unwrap()
+                .push(format!("created {}", name));
+        }
+
+        fn deleted(&self, name: String) {
+            self.received
+                .lock()
+                .unwrap()
+                .push(format!("deleted {}", name));
+        }
+    }
+
+    let mut event_emitter = EventEmitter::new();
+    let service = Arc::new(Service {
+        received: Mutex::new(Vec::new()),
+    });
+    let group = event_emitter
+        .binder(&service)
+        .on("user.created", Service::created)
+        .on("user.deleted", Service::deleted)
+        .group();
+    assert_eq!(2, group.ids().len());
+
+    event_emitter.sync_emit("user.created", "ada".to_string());
+    event_emitter.sync_emit("user.deleted", "bob".to_string());
+    assert_eq!(
+        vec!["created ada".to_string(), "deleted bob".to_string()],
+        *service.received.lock().unwrap(),
+        "Both bound methods should fire"
+    );
+
+    assert_eq!(2, group.remove());
+    assert_eq!(0, event_emitter.listener_count("user.created"));
+    assert_eq!(0, event_emitter.listener_count("user.deleted"));
+
+    event_emitter.bind_method("user.created", &service, Service::created);
+    event_emitter.sync_emit("user.created", "eve".to_string());
+    assert_eq!(3, service.received.lock().unwrap().len());
+
+    drop(service);
+    event_emitter.sync_emit("user.created", "mallory".to_string());
+    assert_eq!(
+        0,
+        event_emitter.listener_count("user.created"),
+        "A weak binding should remove itself once its service is dropped"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emit_bytes() {
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<Vec<u8>>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_bytes("Frame", move |bytes| {
+        cloned_received.lock().unwrap().push(bytes);
+    });
+
+    let frame = vec![0x08, 0x96, 0x01, 0xff];
+    event_emitter.emit_bytes("Frame", frame.clone());
+    event_emitter.wait_until_idle(std::time::Duration::from_secs(1));
+    event_emitter.sync_emit("Frame", vec![1_u8, 2]);
+    assert_eq!(
+        vec![frame, b"[1,2]".to_vec()],
+        *received.lock().unwrap(),
+        "Bytes should be delivered verbatim, and serialized values as JSON"
+    );
+}
+
+#[test]
+fn test_emit_and_prune() {
+    let mut event_emitter = EventEmitter::new();
+    let removed_hooks: Arc<Mutex<Vec<ListenerId>>> = Arc::new(Mutex::new(Vec::new()));
+    let cloned_removed_hooks = Arc::clone(&removed_hooks);
+    event_emitter.on_listener_removed(move |_, info| {
+        cloned_removed_hooks.lock().unwrap().push(info.id);
+    });
+
+    let kept = event_emitter.on("Job", |_: u32| {});
+    let spent = event_emitter.once("Job", |_: u32| {});
+    let tagged = event_emitter.on_with_options(
+        "Job",
+        emitter_rs::event_emitter::ListenerOptions {
+            exclude_tags: vec!["audit".to_string()],
+            ..Default::default()
+        },
+        |_: u32| {},
+    );
+
+    let removed = event_emitter.emit_and_prune("Job", 1_u32, |listener| {
+        listener.limit == Some(0) || listener.exclude_tags.contains(&"audit".to_string())
+    });
+    assert_eq!(
+        vec![tagged],
+        removed,
+        "The listener exhausted by the emit should already be gone"
+    );
+    assert_eq!(vec![spent, tagged], *removed_hooks.lock().unwrap());
+    assert_eq!(1, event_emitter.listener_count("Job"));
+
+    let removed = event_emitter.emit_and_prune("Job", 2_u32, |listener| listener.id == kept);
+    assert_eq!(vec![kept], removed);
+    assert_eq!(0, event_emitter.listener_count("Job"));
+}
+
+/// Emits from inside a listener of a shared emitter, returning the order in which the listeners ran.
+fn run_reentrant_emit(policy: emitter_rs::reentrancy::ReentrancyPolicy) -> Vec<String> {
+    use emitter_rs::reentrancy;
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let log: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let weak_emitter = Arc::downgrade(&event_emitter);
+    let cloned_log = Arc::clone(&log);
+    let mut emitter = event_emitter.lock().unwrap();
+    emitter.set_reentrancy_policy(policy);
+    emitter.on("Outer", move |value: u32| {
+        cloned_log.lock().unwrap().push("outer start".to_string());
+        if let Some(event_emitter) = weak_emitter.upgrade() {
+            assert_eq!(0, reentrancy::sync_emit(&event_emitter, "Inner", value + 1));
+        }
+        cloned_log.lock().unwrap().push("outer end".to_string());
+    });
+    let cloned_log = Arc::clone(&log);
+    emitter.on("Inner", move |value: u32| {
+        cloned_log.lock().unwrap().push(format!("inner {}", value));
+    });
+    drop(emitter);
+
+    reentrancy::sync_emit(&event_emitter, "Outer", 1_u32);
+    let order = log.lock().unwrap().clone();
+    order
+}
+
+#[test]
+fn test_reentrancy_policy() {
+    use emitter_rs::reentrancy::ReentrancyPolicy;
+
+    assert_eq!(
+        vec!["outer start", "outer end", "inner 2"],
+        run_reentrant_emit(ReentrancyPolicy::Defer),
+        "A deferred nested emit should run after the outer dispatch"
+    );
+    assert_eq!(
+        vec!["outer start", "inner 2", "outer end"],
+        run_reentrant_emit(ReentrancyPolicy::Snapshot),
+        "A snapshot nested emit should run right away"
+    );
+}
+
+#[test]
+fn test_reentrancy_snapshot_patterns() {
+    use emitter_rs::reentrancy::{self, ReentrancyPolicy};
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let log: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let weak_emitter = Arc::downgrade(&event_emitter);
+    let mut emitter = event_emitter.lock().unwrap();
+    emitter.set_reentrancy_policy(ReentrancyPolicy::Snapshot);
+    emitter.on("Order Placed", move |id: u32| {
+        if let Some(event_emitter) = weak_emitter.upgrade() {
+            reentrancy::sync_emit(&event_emitter, "user.updated", id);
+        }
+    });
+    let cloned_log = Arc::clone(&log);
+    emitter.on("user.updated", move |id: u32| {
+        cloned_log.lock().unwrap().push(format!("exact {}", id));
+    });
+    let cloned_log = Arc::clone(&log);
+    emitter.on_pattern("user.*", move |id: u32| {
+        cloned_log.lock().unwrap().push(format!("pattern {}", id));
+    });
+    let cloned_log = Arc::clone(&log);
+    emitter.on_pattern("order.*", move |id: u32| {
+        cloned_log.lock().unwrap().push(format!("order {}", id));
+    });
+    drop(emitter);
+
+    reentrancy::sync_emit(&event_emitter, "Order Placed", 1_u32);
+    assert_eq!(
+        vec!["exact 1", "pattern 1"],
+        *log.lock().unwrap(),
+        "A snapshot nested emit should reach the pattern listeners matching it"
+    );
+
+    log.lock().unwrap().clear();
+    let cloned_log = Arc::clone(&log);
+    let added = event_emitter
+        .lock()
+        .unwrap()
+        .on_pattern("user.**", move |id: u32| {
+            cloned_log.lock().unwrap().push(format!("added {}", id));
+        });
+    reentrancy::sync_emit(&event_emitter, "Order Placed", 2_u32);
+    assert_eq!(
+        vec!["exact 2", "pattern 2", "added 2"],
+        *log.lock().unwrap(),
+        "The snapshot should be taken again once the listeners change"
+    );
+
+    log.lock().unwrap().clear();
+    event_emitter.lock().unwrap().remove_listener(&added);
+    reentrancy::sync_emit(&event_emitter, "Order Placed", 3_u32);
+    assert_eq!(vec!["exact 3", "pattern 3"], *log.lock().unwrap());
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+fn test_reentrancy_policy_wasm() {
+    use emitter_rs::reentrancy::ReentrancyPolicy;
+
+    assert_eq!(
+        vec!["outer start", "outer end", "inner 2"],
+        run_reentrant_emit(ReentrancyPolicy::Defer)
+    );
+    assert_eq!(
+        vec!["outer start", "inner 2", "outer end"],
+        run_reentrant_emit(ReentrancyPolicy::Snapshot)
+    );
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_testing_helpers() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    fn panic_message(run: impl FnOnce()) -> String {
+        let payload = panic::catch_unwind(AssertUnwindSafe(run)).unwrap_err();
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.on("Order", |_: u32| {});
+    let orders = event_emitter.expect_emit::<u32>("Order");
+    let refunds = event_emitter.expect_emit::<u32>("Refund");
+    event_emitter.emit("Order", 7_u32);
+    event_emitter.sync_emit("Order", 8_u32);
+
+    orders.assert_count(2);
+    orders.assert_payloads_eq(&[7, 8]);
+    refunds.assert_none();
+    assert_eq!(
+        "expected 1 emit(s) of Order, got 2: [7, 8]",
+        panic_message(|| orders.assert_count(1))
+    );
+    assert_eq!(
+        "payloads of Order differ\n  expected: [8, 7]\n    actual: [7, 8]",
+        panic_message(|| orders.assert_payloads_eq(&[8, 7]))
+    );
+    assert_eq!(
+        "expected no emit of Order, got 2: [7, 8]",
+        panic_message(|| orders.assert_none())
+    );
+
+    assert_eq!(2, event_emitter.listener_count("Order"));
+    drop(orders);
+    drop(refunds);
+    assert_eq!(
+        1,
+        event_emitter.listener_count("Order"),
+        "Dropped expectations should remove their listeners"
+    );
+    assert_eq!(0, event_emitter.listener_count("Refund"));
+
+    let log = event_emitter.with_recording_scope(|event_emitter| {
+        event_emitter.emit("Order", 9_u32);
+        event_emitter.sync_emit("Shipped", "box");
+    });
+    assert_eq!(vec!["Order", "Shipped"], log.events());
+    assert_eq!(vec![9_u32], log.payloads::<u32>("Order"));
+    log.assert_emitted("Shipped");
+    log.assert_not_emitted("Refund");
+    assert_eq!(
+        "expected an emit of Refund, got only [\"Order\", \"Shipped\"]",
+        panic_message(|| log.assert_emitted("Refund"))
+    );
+    assert_eq!(
+        "expected no emit of Order, got 1 in [\"Order\", \"Shipped\"]",
+        panic_message(|| log.assert_not_emitted("Order"))
+    );
+
+    event_emitter.sync_emit("Order", 10_u32);
+    assert_eq!(
+        2,
+        log.entries().len(),
+        "Emits after the scope should not be recorded"
+    );
+}
+
+#[test]
+fn test_on_with_id() {
+    use emitter_rs::id::{DuplicateId, ListenerIdTaken};
+
+    let mut event_emitter = EventEmitter::new();
+    let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let subscription = ListenerId(0x5eed);
+
+    let cloned_received = Arc::clone(&received);
+    assert_eq!(
+        Ok(subscription),
+        event_emitter.on_with_id("User", subscription, move |name: String| {
+            cloned_received
+                .lock()
+                .unwrap()
+                .push(format!("first {}", name));
+        })
+    );
+    let cloned_received = Arc::clone(&received);
+    assert_eq!(
+        Err(ListenerIdTaken(subscription)),
+        event_emitter.on_with_id("User", subscription, move |name: String| {
+            cloned_received
+                .lock()
+                .unwrap()
+                .push(format!("second {}", name));
+        }),
+        "Duplicate IDs should be rejected by default"
+    );
+
+    event_emitter.set_duplicate_id(DuplicateId::Replace);
+    let cloned_received = Arc::clone(&received);
+    assert_eq!(
+        Ok(subscription),
+        event_emitter.on_with_id("User", subscription, move |name: String| {
+            cloned_received
+                .lock()
+                .unwrap()
+                .push(format!("third {}", name));
+        })
+    );
+    assert_eq!(1, event_emitter.listener_count("User"));
+
+    event_emitter.sync_emit("User", "ada");
+    assert_eq!(vec!["third ada".to_string()], *received.lock().unwrap());
+    assert_eq!(
+        Some(subscription),
+        event_emitter.remove_listener(&subscription)
+    );
+}
+
+#[test]
+fn test_with_persistent() {
+    let mut event_emitter = EventEmitter::new();
+    let counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+    let cloned_counter = Arc::clone(&counter);
+    let listener_id = event_emitter.once("Tick", move |_: ()| {
+        *cloned_counter.lock().unwrap() += 1;
+    });
+
+    let result = event_emitter.with_persistent(listener_id, |event_emitter| {
+        for _ in 0..3 {
+            event_emitter.sync_emit("Tick", ());
+        }
+        "done"
+    });
+    assert_eq!(Some("done"), result);
+    assert_eq!(
+        3,
+        *counter.lock().unwrap(),
+        "The once listener should keep firing in the scope"
+    );
+    assert_eq!(Some(Some(1)), event_emitter.remaining_limit(listener_id));
+
+    event_emitter.sync_emit("Tick", ());
+    event_emitter.sync_emit("Tick", ());
+    assert_eq!(
+        4,
+        *counter.lock().unwrap(),
+        "The limit should be restored after the scope"
+    );
+    assert_eq!(
+        None,
+        event_emitter.with_persistent(listener_id, |_| unreachable!(
+            "Unknown listeners should not run the scope"
+        ))
+    );
+}
+
+#[test]
+fn test_declared_events() {
+    mod names {
+        emitter_rs::events! {
+            pub ORDER_EVENTS = {
+                pub const PLACED = "order.placed";
+                pub const SHIPPED = "order.shipped";
+            }
+        }
+
+        emitter_rs::events! {
+            pub const AUDITED = "order.audited";
+        }
+    }
+
+    assert_eq!("order.placed", names::PLACED);
+    assert_eq!(&["order.placed", "order.shipped"], names::ORDER_EVENTS);
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.set_strict(true);
+    event_emitter.on(names::AUDITED, |_: u32| {});
+    assert!(event_emitter.strict_emit(names::AUDITED, 1_u32).is_ok());
+
+    event_emitter.declare_events(names::ORDER_EVENTS);
+    assert!(
+        event_emitter.strict_emit(names::SHIPPED, 1_u32).is_ok(),
+        "Declared events should be accepted without listeners"
+    );
+    assert_eq!(
+        Err(emitter_rs::error::EmitError::NoSuchEvent(
+            names::AUDITED.to_string()
+        )),
+        event_emitter.strict_emit(names::AUDITED, 1_u32),
+        "Undeclared events should be rejected once names are declared"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_max_json_depth() {
+    use emitter_rs::event_emitter::{Execution, ListenerOptions};
+
+    let deep = |depth: usize| ["[".repeat(depth), "]".repeat(depth)].concat().into_bytes();
+
+    let mut event_emitter = EventEmitter::new();
+    let decode_errors = Arc::new(Mutex::new(0));
+    let cloned_decode_errors = Arc::clone(&decode_errors);
+    event_emitter.on_decode_error(move |_| *cloned_decode_errors.lock().unwrap() += 1);
+
+    let received = Arc::new(Mutex::new(0));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_with_options(
+        "payload.received",
+        ListenerOptions {
+            execution: Execution::Inline,
+            ..ListenerOptions::default()
+        },
+        move |_: serde_json::Value| *cloned_received.lock().unwrap() += 1,
+    );
+
+    event_emitter.emit_bytes("payload.received", deep(10_000));
+    assert_eq!(
+        1,
+        *decode_errors.lock().unwrap(),
+        "A too deep payload should be reported to the decode error hooks without a limit"
+    );
+
+    event_emitter.set_max_json_depth(Some(64));
+    event_emitter.emit_bytes("payload.received", deep(10_000));
+    assert_eq!(
+        1,
+        *decode_errors.lock().unwrap(),
+        "A too deep payload should be rejected before any listener decodes it"
+    );
+    let mut nested = serde_json::json!(1);
+    for _ in 0..65 {
+        nested = serde_json::json!([nested]);
+    }
+    assert_eq!(
+        Err(emitter_rs::error::EmitError::PayloadTooDeep { limit: 64 }),
+        event_emitter.try_emit("payload.received", nested),
+    );
+
+    let result = event_emitter.try_emit(
+        "payload.received",
+        serde_json::json!({ "text": "[[[[{{{{" }),
+    );
+    assert!(result.is_ok(), "Brackets in strings should not count");
+    assert_eq!(
+        1,
+        *received.lock().unwrap(),
+        "Normal payloads should be delivered"
+    );
+}
+
+#[test]
+fn test_max_payload_bytes() {
+    let mut event_emitter = EventEmitter::new();
+    let received = Arc::new(Mutex::new(0));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on("upload.received", move |_: String| {
+        *cloned_received.lock().unwrap() += 1
+    });
+
+    event_emitter.set_max_payload_bytes(Some(1024));
+    assert_eq!(
+        Err(emitter_rs::error::EmitError::PayloadTooLarge {
+            size: 2050,
+            limit: 1024
+        }),
+        event_emitter.try_emit("upload.received", "x".repeat(2048)),
+        "An oversized payload should be rejected"
+    );
+    assert!(
+        matches!(
+            event_emitter.try_sync_emit("upload.received", vec![0_u8; 1024]),
+            Err(emitter_rs::error::EmitError::PayloadTooLarge { limit: 1024, .. })
+        ),
+        "An oversized payload should be rejected by synchronous emits"
+    );
+    assert_eq!(
+        0,
+        *received.lock().unwrap(),
+        "Rejected payloads should not be delivered"
+    );
+
+    event_emitter.set_max_payload_bytes(None);
+    event_emitter.sync_emit("upload.received", "x".repeat(2048));
+    assert_eq!(
+        1,
+        *received.lock().unwrap(),
+        "Payloads should be unlimited by default"
+    );
+}
+
+#[test]
+fn test_once_per_key() {
+    let mut event_emitter = EventEmitter::new();
+    let welcomed = Arc::new(Mutex::new(Vec::new()));
+    let cloned_welcomed = Arc::clone(&welcomed);
+    let listener_id = event_emitter.once_per_key_bounded(
+        "user.seen",
+        Some(2),
+        |user_id: &u32| *user_id,
+        move |user_id: u32| cloned_welcomed.lock().unwrap().push(user_id),
+    );
+
+    for user_id in [1_u32, 2, 1, 2, 1] {
+        event_emitter.sync_emit("user.seen", user_id);
+    }
+    assert_eq!(
+        vec![1, 2],
+        *welcomed.lock().unwrap(),
+        "Repeated keys should be skipped"
+    );
+    assert_eq!(Some(2), event_emitter.seen_key_count(listener_id));
+
+    // 2 is now the least recently seen key, so 3 evicts it.
+    event_emitter.sync_emit("user.seen", 3_u32);
+    event_emitter.sync_emit("user.seen", 1_u32);
+    event_emitter.sync_emit("user.seen", 2_u32);
+    assert_eq!(
+        vec![1, 2, 3, 2],
+        *welcomed.lock().unwrap(),
+        "An evicted key should be delivered again"
+    );
+
+    assert!(event_emitter.reset_keys(listener_id));
+    assert_eq!(Some(0), event_emitter.seen_key_count(listener_id));
+    event_emitter.sync_emit("user.seen", 2_u32);
+    event_emitter.sync_emit("user.seen", 3_u32);
+    assert_eq!(
+        vec![1, 2, 3, 2, 2, 3],
+        *welcomed.lock().unwrap(),
+        "Every key should be delivered again after a reset"
+    );
+
+    let plain_id = event_emitter.on("user.seen", |_: u32| {});
+    assert!(!event_emitter.reset_keys(plain_id));
+    assert_eq!(None, event_emitter.seen_key_count(plain_id));
+}
+
+#[test]
+fn test_once_per_key_dry_emit() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DECODED: AtomicUsize = AtomicUsize::new(0);
+
+    struct UserId(u32);
+
+    impl<'de> serde::Deserialize<'de> for UserId {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            DECODED.fetch_add(1, Ordering::SeqCst);
+            u32::deserialize(deserializer).map(UserId)
+        }
+    }
+
+    let mut event_emitter = EventEmitter::new();
+    let welcomed = Arc::new(Mutex::new(Vec::new()));
+    let cloned_welcomed = Arc::clone(&welcomed);
+    let listener_id = event_emitter.once_per_key(
+        "user.seen",
+        |user_id: &UserId| user_id.0,
+        move |user_id: UserId| cloned_welcomed.lock().unwrap().push(user_id.0),
+    );
+
+    assert_eq!(1, event_emitter.dry_emit("user.seen", 7_u32));
+    assert_eq!(
+        Some(0),
+        event_emitter.seen_key_count(listener_id),
+        "A dry run should not remember the key"
+    );
+
+    DECODED.store(0, Ordering::SeqCst);
+    event_emitter.sync_emit("user.seen", 7_u32);
+    assert_eq!(
+        vec![7, 7],
+        *welcomed.lock().unwrap(),
+        "A key probed by a dry run should still be delivered"
+    );
+    assert_eq!(
+        1,
+        DECODED.load(Ordering::SeqCst),
+        "A delivered value should be decoded once"
+    );
+    assert_eq!(0, event_emitter.dry_emit("user.seen", 7_u32));
+}
+
+#[test]
+fn test_on_filter_map() {
+    let mut event_emitter = EventEmitter::new();
+    let decode_errors = Arc::new(Mutex::new(0));
+    let cloned_decode_errors = Arc::clone(&decode_errors);
+    event_emitter.on_decode_error(move |_| *cloned_decode_errors.lock().unwrap() += 1);
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_filter_map(
+        "order.placed",
+        |total: u32| (total >= 100).then(|| format!("{} cents", total)),
+        move |label: String| cloned_received.lock().unwrap().push(label),
+    );
+
+    event_emitter.sync_emit("order.placed", 250_u32);
+    event_emitter.sync_emit("order.placed", 50_u32);
+    event_emitter.sync_emit("order.placed", "not a total");
+
+    assert_eq!(
+        vec!["250 cents".to_string()],
+        *received.lock().unwrap(),
+        "Only transformed values should reach the callback"
+    );
+    assert_eq!(
+        1,
+        *decode_errors.lock().unwrap(),
+        "Undecodable values should be reported and skipped"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_shutdown() {
+    use emitter_rs::error::EmitError;
+
+    let mut event_emitter = EventEmitter::new();
+    let saved = Arc::new(Mutex::new(false));
+    let cloned_saved = Arc::clone(&saved);
+    event_emitter.on_keyed("session.closing", 1, move |_: ()| {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        *cloned_saved.lock().unwrap() = true;
+    });
+    event_emitter.on("session.closed", |_: ()| {});
+
+    event_emitter.emit_keyed("session.closing", 1_u32, ());
+    event_emitter.shutdown();
+    assert!(
+        *saved.lock().unwrap(),
+        "Shutdown should wait for the callbacks in flight"
+    );
+    assert!(event_emitter.is_shut_down());
+    assert_eq!(0, event_emitter.listener_count("session.closed"));
+
+    assert_eq!(
+        Err(EmitError::ShutDown),
+        event_emitter.try_emit("session.closed", ())
+    );
+    assert_eq!(
+        Err(EmitError::ShutDown),
+        event_emitter.try_sync_emit("session.closed", ())
+    );
+    assert_eq!(
+        Err(EmitError::ShutDown),
+        event_emitter.try_on("session.closed", |_: ()| {})
+    );
+    event_emitter.on("session.closed", |_: ()| {});
+    assert_eq!(
+        0,
+        event_emitter.listener_count("session.closed"),
+        "Listeners should be refused after shutdown"
+    );
+}
+
+#[test]
+fn test_refused_listeners() {
+    use emitter_rs::error::EmitError;
+    use emitter_rs::event_emitter::{Callback, Listener};
+
+    let callback: Callback = Arc::new(|_: Vec<u8>| {});
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.set_max_event_names(Some(1));
+    event_emitter.on("user.updated", |_: u32| {});
+
+    let refused_id = event_emitter.on("user.created", |_: u32| {});
+    assert_eq!(0, event_emitter.listener_count("user.created"));
+    assert_eq!(
+        None,
+        event_emitter.remove_listener(&refused_id),
+        "The ID of a refused listener should not name any listener"
+    );
+    let listener = Listener::new(
+        event_emitter.next_listener_id(),
+        None,
+        Arc::clone(&callback),
+    );
+    assert_eq!(
+        Err(EmitError::TooManyEventNames {
+            current: 1,
+            limit: 1
+        }),
+        event_emitter.try_attach("user.created", listener)
+    );
+
+    event_emitter.shutdown();
+    let listener = Listener::new(event_emitter.next_listener_id(), None, callback);
+    let listener_id = listener.id;
+    assert_eq!(
+        listener_id,
+        event_emitter.add_listener("user.updated", listener)
+    );
+    assert_eq!(
+        0,
+        event_emitter.listener_count("user.updated"),
+        "The listener should have been refused after shutdown"
+    );
+    let listener = Listener::new(
+        event_emitter.next_listener_id(),
+        None,
+        Arc::new(|_: Vec<u8>| {}),
+    );
+    assert_eq!(
+        Err(EmitError::ShutDown),
+        event_emitter.try_attach("user.updated", listener)
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_every_emit_refused_after_shutdown() {
+    use std::time::Duration;
+
+    let mut event_emitter = EventEmitter::new();
+    event_emitter.collect_stats(true);
+    let listener_id = event_emitter.on("session.closed", |_: u32| {});
+    event_emitter.shutdown();
+
+    event_emitter.emit("session.closed", 1_u32);
+    event_emitter.sync_emit("session.closed", 1_u32);
+    event_emitter.emit_typed("session.closed", 1_u32);
+    event_emitter.emit_sequential("session.closed", 1_u32);
+    assert_eq!(
+        0,
+        event_emitter.emit_each("session.closed", vec![1_u32, 2, 3])
+    );
+    assert!(!event_emitter.emit_to_id(listener_id, 1_u32));
+    assert!(event_emitter
+        .emit_with_retry("session.closed", 1_u32, 3, Duration::from_millis(1))
+        .is_empty());
+    assert!(!event_emitter.emit_any("session.closed", Box::new(1_u32)));
+    assert_eq!(
+        0,
+        event_emitter.stats().emits(),
+        "No emit should have been admitted after shutdown"
+    );
+}
+
+#[test]
+fn test_pattern_trie_matches_brute_force() {
+    use emitter_rs::id::ListenerId;
+    use emitter_rs::pattern::{self, PatternTrie};
+
+    // A xorshift generator, so that failures replay with the same patterns and names.
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+    let mut name = |segments: &[&str]| {
+        (0..1 + next(4))
+            .map(|_| segments[next(segments.len())])
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+
+    for round in 0..50 {
+        let mut trie = PatternTrie::new();
+        let mut patterns = Vec::new();
+        for index in 0..40 {
+            let pattern = name(&["a", "b", "c", "*", "**"]);
+            trie.insert(&pattern, ListenerId(index));
+            patterns.push((ListenerId(index), pattern));
+        }
+        for (id, _) in patterns.iter().filter(|(id, _)| id.0 % 5 == round % 5) {
+            assert!(trie.remove(*id));
+        }
+        patterns.retain(|(id, _)| id.0 % 5 != round % 5);
+
+        for _ in 0..40 {
+            let event = name(&["a", "b", "c"]);
+            let expected: Vec<ListenerId> = patterns
+                .iter()
+                .filter(|(_, pattern)| pattern::matches(pattern, &event))
+                .map(|(id, _)| *id)
+                .collect();
+            assert_eq!(
+                expected,
+                trie.matches(&event),
+                "The trie should match {} like the brute force matcher",
+                event
+            );
+        }
+    }
+}
+
+#[test]
+fn test_on_pattern() {
+    let mut event_emitter = EventEmitter::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on("order.placed", move |id: u32| {
+        cloned_received
+            .lock()
+            .unwrap()
+            .push(format!("exact {}", id));
+    });
+    let cloned_received = Arc::clone(&received);
+    let one_id = event_emitter.on_pattern("order.*", move |id: u32| {
+        cloned_received.lock().unwrap().push(format!("one {}", id));
+    });
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_pattern("order.**", move |id: u32| {
+        cloned_received.lock().unwrap().push(format!("any {}", id));
+    });
+
+    event_emitter.sync_emit("order.placed", 1_u32);
+    event_emitter.sync_emit("order.item.added", 2_u32);
+    event_emitter.sync_emit("invoice.sent", 3_u32);
+    event_emitter.remove_listener(&one_id);
+    event_emitter.sync_emit("order.shipped", 4_u32);
+
+    assert_eq!(
+        vec!["exact 1", "one 1", "any 1", "any 2", "any 4"],
+        *received.lock().unwrap(),
+        "Pattern listeners should receive the matching emits after the exact listeners"
+    );
+    assert_eq!(1, event_emitter.listener_count("order.**"));
+}
+
+#[test]
+fn test_pattern_delivery_order() {
+    let mut event_emitter = EventEmitter::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    for (name, pattern) in [("first", "**"), ("second", "order.*"), ("third", "**")] {
+        let cloned_received = Arc::clone(&received);
+        event_emitter.on_pattern(pattern, move |_: u32| {
+            cloned_received.lock().unwrap().push(name)
+        });
+    }
+
+    for _ in 0..5 {
+        event_emitter.sync_emit("order.placed", 1_u32);
+    }
+    assert_eq!(
+        ["first", "second", "third"].repeat(5),
+        *received.lock().unwrap(),
+        "Pattern listeners should run in registration order across patterns"
+    );
+}
+
+#[test]
+fn test_pattern_listeners_inspected_without_emitting() {
+    let mut event_emitter = EventEmitter::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let cloned_received = Arc::clone(&received);
+    let listener_id = event_emitter.on_pattern("order.*", move |id: u32| {
+        cloned_received.lock().unwrap().push(id);
+    });
+
+    assert_eq!(1, event_emitter.effective_listener_count("order.placed"));
+    assert_eq!(
+        vec!["order.*".to_string()],
+        event_emitter.matching_events("order.placed")
+    );
+    assert!(event_emitter.matching_events("invoice.sent").is_empty());
+
+    assert_eq!(1, event_emitter.dry_emit("order.placed", 1_u32));
+    assert_eq!(
+        1,
+        event_emitter.sync_emit_lazy("order.placed", || 2_u32),
+        "Lazy emits should build values for pattern listeners"
+    );
+    assert_eq!(
+        0,
+        event_emitter.sync_emit_lazy("invoice.sent", || -> u32 { unreachable!() })
+    );
+    assert_eq!(vec![1, 2], *received.lock().unwrap());
+
+    event_emitter.set_enabled(listener_id, false);
+    assert!(
+        event_emitter.matching_events("order.placed").is_empty(),
+        "Disabled pattern listeners should be left out"
+    );
+    assert_eq!(0, event_emitter.dry_emit("order.placed", 3_u32));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emit_lazy_with_pattern_listener() {
+    let mut event_emitter = EventEmitter::new();
+    let built = Arc::new(Mutex::new(false));
+    event_emitter.on_pattern("order.**", |_: u32| {});
+
+    let cloned_built = Arc::clone(&built);
+    let invoked = event_emitter.emit_lazy("order.item.added", move || {
+        *cloned_built.lock().unwrap() = true;
+        7_u32
+    });
+    assert_eq!(1, invoked);
+    assert!(
+        *built.lock().unwrap(),
+        "emit_lazy should build values for pattern listeners"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_emit_typed_with_pattern_listener() {
+    let mut event_emitter = EventEmitter::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    event_emitter.on_pattern("order.*", move |value: u32| {
+        cloned_received.lock().unwrap().push(value);
+    });
+    assert!(
+        event_emitter.needs_bytes::<u32>("order.placed"),
+        "pattern listeners should be served from the serialized value"
+    );
+
+    event_emitter.emit_typed("order.placed", 2_u32);
+    assert_eq!(vec![2], *received.lock().unwrap());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_pattern_listener_detach_attach_round_trip() {
+    let mut event_emitter = EventEmitter::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let cloned_received = Arc::clone(&received);
+    let listener_id = event_emitter.on_pattern("order.*", move |value: u32| {
+        cloned_received.lock().unwrap().push(value);
+    });
+
+    let listener = event_emitter.detach(listener_id).unwrap();
+    event_emitter.sync_emit("order.placed", 1_u32);
+    assert!(received.lock().unwrap().is_empty());
+
+    assert_eq!(listener_id, event_emitter.attach("order.*", listener));
+    event_emitter.sync_emit("order.placed", 2_u32);
+    assert_eq!(vec![2], *received.lock().unwrap());
+}
+
+#[test]
+fn test_reentrancy_snapshot_guards() {
+    use emitter_rs::guard::GuardError;
+    use emitter_rs::reentrancy::{self, ReentrancyPolicy};
+
+    let event_emitter = Arc::new(Mutex::new(EventEmitter::new()));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let weak_emitter = Arc::downgrade(&event_emitter);
+    let mut emitter = event_emitter.lock().unwrap();
+    emitter.set_reentrancy_policy(ReentrancyPolicy::Snapshot);
+    emitter.add_guard("order.audited", |_, payload| {
+        let id: u32 = serde_json::from_slice(payload).map_err(GuardError::new)?;
+        if id > 100 {
+            return Err(GuardError::new("id above 100"));
+        }
+        Ok(())
+    });
+    emitter.on("order.placed", move |id: u32| {
+        if let Some(event_emitter) = weak_emitter.upgrade() {
+            reentrancy::sync_emit(&event_emitter, "order.audited", id);
+        }
+    });
+    let cloned_received = Arc::clone(&received);
+    emitter.on("order.audited", move |id: u32| {
+        cloned_received.lock().unwrap().push(id);
+    });
+    drop(emitter);
+
+    reentrancy::sync_emit(&event_emitter, "order.placed", 7_u32);
+    reentrancy::sync_emit(&event_emitter, "order.placed", 700_u32);
+    assert_eq!(
+        vec![7],
+        *received.lock().unwrap(),
+        "A snapshot nested emit should be rejected by the guards of its event"
+    );
+}